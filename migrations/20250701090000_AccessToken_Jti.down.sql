@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_access_tokens_active_expires_at;
+DROP INDEX IF EXISTS idx_access_tokens_jti;
+
+ALTER TABLE access_tokens DROP COLUMN IF EXISTS jti;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE access_tokens ADD COLUMN jti TEXT NOT NULL DEFAULT gen_random_uuid()::text;
+
+CREATE UNIQUE INDEX idx_access_tokens_jti ON access_tokens(jti);
+CREATE INDEX idx_access_tokens_active_expires_at ON access_tokens(active, expires_at);
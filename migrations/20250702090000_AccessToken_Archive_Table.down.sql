@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_access_tokens_expires_at;
+DROP INDEX IF EXISTS idx_access_tokens_archive_project_access_id;
+
+DROP TABLE IF EXISTS access_tokens_archive;
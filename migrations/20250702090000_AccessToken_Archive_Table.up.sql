@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE TABLE access_tokens_archive (
+    id UUID PRIMARY KEY,
+    jti TEXT NOT NULL,
+    project_access_id UUID NOT NULL,
+    algorithm TEXT NOT NULL,
+    token TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    active BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_access_tokens_archive_project_access_id ON access_tokens_archive(project_access_id);
+CREATE INDEX idx_access_tokens_expires_at ON access_tokens(expires_at);
@@ -0,0 +1,9 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_access_tokens_refresh_token;
+ALTER TABLE access_tokens_archive DROP COLUMN IF EXISTS token_use;
+ALTER TABLE access_tokens DROP COLUMN IF EXISTS token_use;
+
+DROP INDEX IF EXISTS idx_token_policies_project_id_environment_id;
+DROP INDEX IF EXISTS idx_token_policies_project_id;
+
+DROP TABLE IF EXISTS token_policies;
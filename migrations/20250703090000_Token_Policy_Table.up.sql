@@ -0,0 +1,22 @@
+-- Add up migration script here
+CREATE TABLE token_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL REFERENCES projects(id),
+    environment_id UUID REFERENCES environment(id),
+    max_ttl_seconds BIGINT,
+    default_ttl_seconds BIGINT,
+    audience TEXT[],
+    issuer TEXT,
+    allowed_algorithms TEXT[],
+    allow_refresh_tokens BOOLEAN,
+    refresh_token_ttl_seconds BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX idx_token_policies_project_id ON token_policies(project_id) WHERE environment_id IS NULL;
+CREATE UNIQUE INDEX idx_token_policies_project_id_environment_id ON token_policies(project_id, environment_id) WHERE environment_id IS NOT NULL;
+
+ALTER TABLE access_tokens ADD COLUMN token_use TEXT NOT NULL DEFAULT 'access';
+ALTER TABLE access_tokens_archive ADD COLUMN token_use TEXT NOT NULL DEFAULT 'access';
+CREATE INDEX idx_access_tokens_refresh_token ON access_tokens(token) WHERE token_use = 'refresh';
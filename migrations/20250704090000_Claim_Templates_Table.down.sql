@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_claim_templates_project_access_id;
+DROP INDEX IF EXISTS idx_claim_templates_level_claim;
+
+DROP TABLE IF EXISTS claim_templates;
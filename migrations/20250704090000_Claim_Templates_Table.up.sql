@@ -0,0 +1,20 @@
+-- Add up migration script here
+CREATE TABLE claim_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL REFERENCES projects(id),
+    environment_id UUID REFERENCES environment(id),
+    project_access_id UUID REFERENCES project_access(id),
+    claim TEXT NOT NULL,
+    template TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT claim_templates_single_level CHECK (environment_id IS NULL OR project_access_id IS NULL)
+);
+
+CREATE UNIQUE INDEX idx_claim_templates_level_claim ON claim_templates(
+    project_id,
+    COALESCE(environment_id, '00000000-0000-0000-0000-000000000000'),
+    COALESCE(project_access_id, '00000000-0000-0000-0000-000000000000'),
+    claim
+);
+CREATE INDEX idx_claim_templates_project_access_id ON claim_templates(project_access_id);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_client_assertion_jtis_expires_at;
+DROP TABLE IF EXISTS client_assertion_jtis;
+
+DROP INDEX IF EXISTS idx_service_account_keys_service_account_id_name;
+DROP TABLE IF EXISTS service_account_keys;
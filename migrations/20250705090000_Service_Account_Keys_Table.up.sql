@@ -0,0 +1,24 @@
+-- Add up migration script here
+CREATE TABLE service_account_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    service_account_id UUID NOT NULL REFERENCES service_account(id),
+    name TEXT NOT NULL,
+    algorithm TEXT NOT NULL,
+    public_key TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX idx_service_account_keys_service_account_id_name ON service_account_keys(service_account_id, name);
+
+-- Client assertion IDs already used, kept until the assertion expires to reject replays
+CREATE TABLE client_assertion_jtis (
+    service_account_id UUID NOT NULL REFERENCES service_account(id),
+    jti TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (service_account_id, jti)
+);
+
+CREATE INDEX idx_client_assertion_jtis_expires_at ON client_assertion_jtis(expires_at);
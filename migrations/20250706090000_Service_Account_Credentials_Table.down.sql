@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_service_account_credentials_secret_hash;
+DROP INDEX IF EXISTS idx_service_account_credentials_service_account_id;
+DROP TABLE IF EXISTS service_account_credentials;
@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TABLE service_account_credentials (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    service_account_id UUID NOT NULL REFERENCES service_account(id),
+    label TEXT NOT NULL,
+    secret_hash TEXT NOT NULL,
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_service_account_credentials_service_account_id ON service_account_credentials(service_account_id);
+CREATE UNIQUE INDEX idx_service_account_credentials_secret_hash ON service_account_credentials(secret_hash);
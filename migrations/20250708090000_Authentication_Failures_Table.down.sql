@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_audit_events_created_at;
+DROP INDEX IF EXISTS idx_audit_events_subject;
+DROP TABLE IF EXISTS audit_events;
+
+DROP INDEX IF EXISTS idx_authentication_failures_last_failure_at;
+DROP TABLE IF EXISTS authentication_failures;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+-- Failed client authentications per service account or source IP
+CREATE TABLE authentication_failures (
+    subject_type TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    failure_count INTEGER NOT NULL DEFAULT 0,
+    last_failure_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    blocked_until TIMESTAMPTZ,
+    locked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (subject_type, subject)
+);
+
+CREATE INDEX idx_authentication_failures_last_failure_at ON authentication_failures(last_failure_at);
+
+CREATE TABLE audit_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_type TEXT NOT NULL,
+    subject_type TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    details TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_audit_events_subject ON audit_events(subject_type, subject);
+CREATE INDEX idx_audit_events_created_at ON audit_events(created_at);
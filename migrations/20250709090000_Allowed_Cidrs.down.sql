@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE project_access DROP COLUMN IF EXISTS allowed_cidrs;
+ALTER TABLE service_account DROP COLUMN IF EXISTS allowed_cidrs;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- An empty list allows tokens to be requested from any address
+ALTER TABLE service_account ADD COLUMN allowed_cidrs TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE project_access ADD COLUMN allowed_cidrs TEXT[] NOT NULL DEFAULT '{}';
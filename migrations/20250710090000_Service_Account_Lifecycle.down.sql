@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_service_account_expires_at;
+ALTER TABLE service_account DROP COLUMN IF EXISTS inactivity_warned_at;
+ALTER TABLE service_account DROP COLUMN IF EXISTS enabled_at;
+ALTER TABLE service_account DROP COLUMN IF EXISTS last_token_issued_at;
+ALTER TABLE service_account DROP COLUMN IF EXISTS expires_at;
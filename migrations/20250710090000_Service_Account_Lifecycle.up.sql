@@ -0,0 +1,19 @@
+-- Add up migration script here
+ALTER TABLE service_account ADD COLUMN expires_at TIMESTAMPTZ NULL;
+ALTER TABLE service_account ADD COLUMN last_token_issued_at TIMESTAMPTZ NULL;
+-- Set whenever a disabled service account is enabled again, restarting its inactivity period
+ALTER TABLE service_account ADD COLUMN enabled_at TIMESTAMPTZ NULL;
+-- Set once the service account was warned of its upcoming inactivity disablement
+ALTER TABLE service_account ADD COLUMN inactivity_warned_at TIMESTAMPTZ NULL;
+
+UPDATE service_account
+SET last_token_issued_at = issued.last_issued_at
+FROM (
+    SELECT project_access.service_account_id, MAX(access_tokens.created_at) AS last_issued_at
+    FROM access_tokens
+    INNER JOIN project_access ON project_access.id = access_tokens.project_access_id
+    GROUP BY project_access.service_account_id
+) AS issued
+WHERE service_account.id = issued.service_account_id;
+
+CREATE INDEX idx_service_account_expires_at ON service_account (expires_at) WHERE expires_at IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_environment_key_next_rotation_at;
+ALTER TABLE environment_key DROP COLUMN IF EXISTS next_rotation_at;
+ALTER TABLE environment_key DROP COLUMN IF EXISTS rotation_interval_days;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+-- Days between two automatic rotations of the key, NULL when it is only rotated on demand
+ALTER TABLE environment_key ADD COLUMN rotation_interval_days INTEGER NULL CHECK (rotation_interval_days > 0);
+ALTER TABLE environment_key ADD COLUMN next_rotation_at TIMESTAMPTZ NULL;
+
+CREATE INDEX idx_environment_key_next_rotation_at ON environment_key (next_rotation_at) WHERE next_rotation_at IS NOT NULL;
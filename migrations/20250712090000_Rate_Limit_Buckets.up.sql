@@ -0,0 +1,11 @@
+-- Add up migration script here
+-- Token buckets of the rate limiter, shared by every replica
+CREATE TABLE rate_limit_buckets (
+    key TEXT PRIMARY KEY,
+    tokens DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- When the bucket is full again, after which the row can be deleted
+    full_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_rate_limit_buckets_full_at ON rate_limit_buckets(full_at);
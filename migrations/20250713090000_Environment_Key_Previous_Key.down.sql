@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_environment_key_previous_key_expires_at;
+ALTER TABLE environment_key DROP COLUMN IF EXISTS previous_key_expires_at;
+ALTER TABLE environment_key DROP COLUMN IF EXISTS previous_key;
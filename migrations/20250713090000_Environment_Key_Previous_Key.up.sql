@@ -0,0 +1,6 @@
+-- Add up migration script here
+-- Key replaced by the last rotation, still verifying the tokens it signed until previous_key_expires_at
+ALTER TABLE environment_key ADD COLUMN previous_key TEXT NULL;
+ALTER TABLE environment_key ADD COLUMN previous_key_expires_at TIMESTAMPTZ NULL;
+
+CREATE INDEX idx_environment_key_previous_key_expires_at ON environment_key (previous_key_expires_at) WHERE previous_key_expires_at IS NOT NULL;
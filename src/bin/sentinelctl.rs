@@ -0,0 +1,55 @@
+use clap::Parser;
+use sentinel_guard::cli::{
+    self, Cli, CliContext, Command, ConfigCommand, EXIT_UNAVAILABLE, OutputFormat,
+};
+use sentinel_guard::config::AppConfig;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Usage errors exit with code 2, see `cli::EXIT_USAGE`
+    let cli = Cli::parse();
+
+    let config = match AppConfig::load(cli.config.as_deref(), Some(true)) {
+        Ok(config) => config,
+        Err(error) => return fail(EXIT_UNAVAILABLE, &error),
+    };
+    if let (Command::Config(ConfigCommand::Export), OutputFormat::Table) =
+        (&cli.command, cli.output)
+    {
+        return match config.to_redacted_toml() {
+            Ok(toml) => {
+                print!("{}", toml);
+                ExitCode::SUCCESS
+            }
+            Err(error) => fail(cli::EXIT_FAILURE, &error),
+        };
+    }
+    let pool = match config
+        .database
+        .pool_options()
+        .connect(&config.database.uri)
+        .await
+    {
+        Ok(pool) => Arc::new(pool),
+        Err(error) => return fail(EXIT_UNAVAILABLE, &error.into()),
+    };
+    let context = match CliContext::new(pool, config) {
+        Ok(context) => context,
+        Err(error) => return fail(EXIT_UNAVAILABLE, &error),
+    };
+
+    match cli::execute(cli.command, &context).await {
+        Ok(value) => {
+            println!("{}", cli::render(cli.output, &value));
+            ExitCode::SUCCESS
+        }
+        Err(error) => fail(cli::exit_code(&error), &error),
+    }
+}
+
+fn fail(code: u8, error: &anyhow::Error) -> ExitCode {
+    eprintln!("error: {}", error);
+    ExitCode::from(code)
+}
@@ -0,0 +1,641 @@
+//! `sentinelctl`, the administrative command line driving the repositories directly.
+//!
+//! Commands print the created or listed records as a table, or as JSON for scripts, and the
+//! binary exits with one of the `EXIT_*` codes.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Error;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::postgres::PgPool;
+use tabled::builder::Builder;
+use tabled::settings::Style;
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::models::environment::{EnvironmentCreatePayload, EnvironmentFilter};
+use crate::models::environment_key::{EnvironmentKeyCreatePayload, EnvironmentKeyFilter};
+use crate::models::project::{ProjectCreatePayload, ProjectFilter};
+use crate::models::project_access::ProjectAccessCreatePayload;
+use crate::models::project_access_scopes::ProjectAccessScopeCreatePayload;
+use crate::models::project_scope::{ProjectScopeCreatePayload, ProjectScopeFilter};
+use crate::models::service_account::{ServiceAccountCreatePayload, ServiceAccountFilter};
+use crate::models::token::{GRANT_TYPE_CLIENT_CREDENTIALS, TokenRequest};
+use crate::repositories::base::Repository;
+use crate::repositories::environment_key_repository::EnvironmentKeyRepository;
+use crate::repositories::environment_repository::EnvironmentRepository;
+use crate::repositories::project_access_repository::ProjectAccessRepository;
+use crate::repositories::project_access_scopes_repository::ProjectAccessScopesRepository;
+use crate::repositories::project_repository::ProjectRepository;
+use crate::repositories::project_scope_repository::ProjectScopeRepository;
+use crate::repositories::service_account_repository::ServiceAccountRepository;
+use crate::services::token_service::TokenService;
+use crate::utils::key_store::{self, KeyStore};
+use crate::utils::security::{SecretsManager, generate_opaque_token};
+
+/// The command succeeded
+pub const EXIT_SUCCESS: u8 = 0;
+/// The command was rejected, e.g. by validation or a database constraint
+pub const EXIT_FAILURE: u8 = 1;
+/// The command line could not be parsed, as reported by clap
+pub const EXIT_USAGE: u8 = 2;
+/// The configuration is invalid or the database cannot be reached
+pub const EXIT_UNAVAILABLE: u8 = 3;
+/// A record the command refers to does not exist
+pub const EXIT_NOT_FOUND: u8 = 4;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "sentinelctl",
+    version,
+    about = "Administers a SentinelGuard database",
+    after_help = "Exit codes: 0 success, 1 rejected, 2 usage, 3 configuration or database unavailable, 4 not found"
+)]
+pub struct Cli {
+    /// Configuration file, read like the server does with `SENTINEL_GUARD_*` overrides
+    #[arg(long, global = true, env = "SENTINEL_GUARD_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    #[arg(long, short, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Manage projects
+    #[command(subcommand)]
+    Project(ProjectCommand),
+    /// Manage the environments of projects
+    #[command(subcommand)]
+    Environment(EnvironmentCommand),
+    /// Manage service accounts
+    #[command(subcommand)]
+    ServiceAccount(ServiceAccountCommand),
+    /// Manage the scopes of projects
+    #[command(subcommand)]
+    Scope(ScopeCommand),
+    /// Grant service accounts access to environments
+    #[command(subcommand)]
+    Access(AccessCommand),
+    /// Manage the signing keys of environments
+    #[command(subcommand)]
+    Key(KeyCommand),
+    /// Issue tokens
+    #[command(subcommand)]
+    Token(TokenCommand),
+    /// Inspect the configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProjectCommand {
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        description: String,
+        /// Create the project disabled
+        #[arg(long)]
+        disabled: bool,
+    },
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EnvironmentCommand {
+    Create {
+        #[arg(long)]
+        project_id: Uuid,
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        description: String,
+        #[arg(long)]
+        disabled: bool,
+    },
+    List {
+        #[arg(long)]
+        project_id: Option<Uuid>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ServiceAccountCommand {
+    /// Create a service account, printing its secret once
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        email: String,
+        #[arg(long, default_value = "")]
+        description: String,
+        /// Client secret, generated when omitted
+        #[arg(long, env = "SENTINELCTL_CLIENT_SECRET", hide_env_values = true)]
+        secret: Option<String>,
+        #[arg(long)]
+        disabled: bool,
+    },
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ScopeCommand {
+    Create {
+        #[arg(long)]
+        project_id: Uuid,
+        #[arg(long)]
+        scope: String,
+        #[arg(long, default_value = "")]
+        description: String,
+    },
+    List {
+        #[arg(long)]
+        project_id: Option<Uuid>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AccessCommand {
+    /// Grant a service account access to an environment, with project scopes
+    Grant(GrantArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct GrantArgs {
+    #[arg(long)]
+    pub project_id: Uuid,
+    #[arg(long)]
+    pub service_account_id: Uuid,
+    #[arg(long)]
+    pub environment_id: Uuid,
+    /// Name of a project scope to grant, may be repeated
+    #[arg(long = "scope")]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KeyCommand {
+    /// Create the active signing key of an environment
+    Create {
+        #[arg(long)]
+        environment_id: Uuid,
+        #[arg(long, default_value = "RS256")]
+        algorithm: String,
+        #[arg(long)]
+        rotation_interval_days: Option<i32>,
+    },
+    /// Replace a key with a new one of the same algorithm
+    Rotate { id: Uuid },
+    List {
+        #[arg(long)]
+        environment_id: Option<Uuid>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TokenCommand {
+    /// Issue an access token with the client credentials grant, to test a setup
+    Mint {
+        #[arg(long)]
+        client_id: Uuid,
+        #[arg(long, env = "SENTINELCTL_CLIENT_SECRET", hide_env_values = true)]
+        client_secret: String,
+        #[arg(long)]
+        environment_id: Uuid,
+        /// Space separated scopes, every granted scope when omitted
+        #[arg(long)]
+        scope: Option<String>,
+        #[arg(long)]
+        algorithm: Option<String>,
+        #[arg(long)]
+        expires_in: Option<i64>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the effective configuration, secrets redacted
+    Export,
+}
+
+/// What commands run against
+#[derive(Clone)]
+pub struct CliContext {
+    pub pool: Arc<PgPool>,
+    pub config: AppConfig,
+    pub secrets_manager: SecretsManager,
+    pub key_store: Arc<dyn KeyStore>,
+}
+
+impl CliContext {
+    pub fn new(pool: Arc<PgPool>, config: AppConfig) -> Result<Self, Error> {
+        let secrets_manager = config.security.secrets_manager()?;
+        let key_store =
+            key_store::from_config(&config.security.key_store, secrets_manager.clone())?;
+        Ok(Self {
+            pool,
+            config,
+            secrets_manager,
+            key_store,
+        })
+    }
+}
+
+/// Runs a command, returning the records it created or found
+pub async fn execute(command: Command, context: &CliContext) -> Result<Value, Error> {
+    let pool = context.pool.clone();
+    match command {
+        Command::Project(ProjectCommand::Create {
+            name,
+            description,
+            disabled,
+        }) => {
+            let project = ProjectRepository::new(pool)
+                .create(ProjectCreatePayload {
+                    name,
+                    description,
+                    enabled: !disabled,
+                })
+                .await?;
+            to_value(project)
+        }
+        Command::Project(ProjectCommand::List) => {
+            let projects = ProjectRepository::new(pool)
+                .find(ProjectFilter::default(), None, None)
+                .await?;
+            to_value(projects)
+        }
+        Command::Environment(EnvironmentCommand::Create {
+            project_id,
+            name,
+            description,
+            disabled,
+        }) => {
+            let environment = EnvironmentRepository::new(pool)
+                .create(EnvironmentCreatePayload {
+                    project_id: project_id.to_string(),
+                    name,
+                    description,
+                    enabled: !disabled,
+                })
+                .await?;
+            to_value(environment)
+        }
+        Command::Environment(EnvironmentCommand::List { project_id }) => {
+            let environments = EnvironmentRepository::new(pool)
+                .find(
+                    EnvironmentFilter {
+                        project_id: project_id.map(|id| id.to_string()),
+                        ..Default::default()
+                    },
+                    None,
+                    None,
+                )
+                .await?;
+            to_value(environments)
+        }
+        Command::ServiceAccount(ServiceAccountCommand::Create {
+            name,
+            email,
+            description,
+            secret,
+            disabled,
+        }) => {
+            let secret = secret.unwrap_or_else(generate_opaque_token);
+            let mut service_account =
+                ServiceAccountRepository::new(pool, context.secrets_manager.clone())
+                    .create(ServiceAccountCreatePayload {
+                        name,
+                        email,
+                        secret: secret.clone(),
+                        description,
+                        enabled: !disabled,
+                        allowed_cidrs: None,
+                        expires_at: None,
+                    })
+                    .await?;
+            // Only ever shown here, the stored secret is encrypted
+            service_account.secret = secret;
+            to_value(service_account)
+        }
+        Command::ServiceAccount(ServiceAccountCommand::List) => {
+            let service_accounts =
+                ServiceAccountRepository::new(pool, context.secrets_manager.clone())
+                    .find(ServiceAccountFilter::default(), None, None)
+                    .await?;
+            let mut service_accounts = to_value(service_accounts)?;
+            if let Value::Array(service_accounts) = &mut service_accounts {
+                for service_account in service_accounts {
+                    if let Value::Object(service_account) = service_account {
+                        service_account.remove("secret");
+                    }
+                }
+            }
+            Ok(service_accounts)
+        }
+        Command::Scope(ScopeCommand::Create {
+            project_id,
+            scope,
+            description,
+        }) => {
+            let scope = ProjectScopeRepository::new(pool)
+                .create(ProjectScopeCreatePayload {
+                    project_id: project_id.to_string(),
+                    scope,
+                    description,
+                    enabled: true,
+                })
+                .await?;
+            to_value(scope)
+        }
+        Command::Scope(ScopeCommand::List { project_id }) => {
+            let scopes = ProjectScopeRepository::new(pool)
+                .find(
+                    ProjectScopeFilter {
+                        project_id: project_id.map(|id| id.to_string()),
+                        ..Default::default()
+                    },
+                    None,
+                    None,
+                )
+                .await?;
+            to_value(scopes)
+        }
+        Command::Access(AccessCommand::Grant(grant)) => grant_access(grant, pool).await,
+        Command::Key(KeyCommand::Create {
+            environment_id,
+            algorithm,
+            rotation_interval_days,
+        }) => {
+            let key = environment_key_repository(context)
+                .create(EnvironmentKeyCreatePayload {
+                    environment_id: environment_id.to_string(),
+                    algorithm,
+                    active: true,
+                    rotation_interval_days,
+                })
+                .await?;
+            to_value(key)
+        }
+        Command::Key(KeyCommand::Rotate { id }) => {
+            let key = environment_key_repository(context).rotate_key(id).await?;
+            to_value(key)
+        }
+        Command::Key(KeyCommand::List { environment_id }) => {
+            let keys = environment_key_repository(context)
+                .find(
+                    EnvironmentKeyFilter {
+                        environment_id: environment_id.map(|id| id.to_string()),
+                        ..Default::default()
+                    },
+                    None,
+                    None,
+                )
+                .await?;
+            to_value(keys)
+        }
+        Command::Token(TokenCommand::Mint {
+            client_id,
+            client_secret,
+            environment_id,
+            scope,
+            algorithm,
+            expires_in,
+        }) => {
+            let token_service = TokenService::new(pool, context.secrets_manager.clone())
+                .with_key_store(context.key_store.clone())
+                .with_lockout(context.config.security.lockout.clone())
+                .with_token_defaults(context.config.tokens.policy());
+            let request = TokenRequest {
+                grant_type: GRANT_TYPE_CLIENT_CREDENTIALS.to_string(),
+                client_id: Some(client_id.to_string()),
+                client_secret: Some(client_secret),
+                environment_id: Some(environment_id.to_string()),
+                scope,
+                algorithm,
+                expires_in,
+                ..Default::default()
+            };
+            let response = token_service
+                .issue(request, None, None)
+                .await
+                .map_err(|error| Error::msg(error.to_string()))?;
+            to_value(response)
+        }
+        Command::Config(ConfigCommand::Export) => to_value(context.config.redacted()),
+    }
+}
+
+/// Creates the project access and grants it the named project scopes
+async fn grant_access(grant: GrantArgs, pool: Arc<PgPool>) -> Result<Value, Error> {
+    let scopes = ProjectScopeRepository::new(pool.clone())
+        .find(
+            ProjectScopeFilter {
+                project_id: Some(grant.project_id.to_string()),
+                ..Default::default()
+            },
+            None,
+            None,
+        )
+        .await?;
+    let scope_ids = grant
+        .scopes
+        .iter()
+        .map(|name| {
+            scopes
+                .iter()
+                .find(|scope| &scope.scope == name)
+                .and_then(|scope| scope.id)
+                .ok_or_else(|| Error::msg(format!("Project scope not found: {}", name)))
+        })
+        .collect::<Result<Vec<Uuid>, Error>>()?;
+
+    // The access and its scopes are written together so a failing scope leaves no
+    // project access granting nothing behind
+    let mut transaction = pool.begin().await?;
+    let project_access = ProjectAccessRepository::insert(
+        &mut *transaction,
+        ProjectAccessCreatePayload {
+            project_id: grant.project_id.to_string(),
+            service_account_id: grant.service_account_id.to_string(),
+            environment_id: grant.environment_id.to_string(),
+            enabled: true,
+            allowed_cidrs: None,
+        },
+    )
+    .await?;
+    let project_access_id = project_access
+        .id
+        .ok_or_else(|| Error::msg("Project access not found"))?;
+
+    for scope_id in scope_ids {
+        ProjectAccessScopesRepository::insert(
+            &mut *transaction,
+            ProjectAccessScopeCreatePayload {
+                project_access_id: project_access_id.to_string(),
+                scope_id: scope_id.to_string(),
+            },
+        )
+        .await?;
+    }
+    transaction.commit().await?;
+
+    let mut value = to_value(project_access)?;
+    value["scopes"] = to_value(&grant.scopes)?;
+    Ok(value)
+}
+
+fn environment_key_repository(context: &CliContext) -> EnvironmentKeyRepository {
+    EnvironmentKeyRepository::new(context.pool.clone(), context.secrets_manager.clone())
+        .with_key_store(context.key_store.clone())
+}
+
+fn to_value(value: impl Serialize) -> Result<Value, Error> {
+    Ok(serde_json::to_value(value)?)
+}
+
+/// Exit code of a failed command
+pub fn exit_code(error: &Error) -> u8 {
+    if error.to_string().to_lowercase().contains("not found") {
+        EXIT_NOT_FOUND
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+/// Formats the output of a command.
+///
+/// Tables have one row per record and one column per field, single records are shown as
+/// field and value pairs.
+pub fn render(format: OutputFormat, value: &Value) -> String {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(value).unwrap_or_default(),
+        OutputFormat::Table => render_table(value),
+    }
+}
+
+fn render_table(value: &Value) -> String {
+    let mut builder = Builder::default();
+    match value {
+        Value::Array(records) => {
+            let Some(Value::Object(first)) = records.first() else {
+                return "No records".to_string();
+            };
+            let columns: Vec<String> = first.keys().cloned().collect();
+            builder.push_record(columns.iter().map(|column| column.to_uppercase()));
+            for record in records {
+                builder.push_record(columns.iter().map(|column| cell(&record[column])));
+            }
+        }
+        Value::Object(record) => {
+            builder.push_record(["FIELD", "VALUE"]);
+            for (field, value) in record {
+                builder.push_record([field.clone(), cell(value)]);
+            }
+        }
+        value => return cell(value),
+    }
+    builder.build().with(Style::sharp()).to_string()
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        Value::Array(values) => values.iter().map(cell).collect::<Vec<_>>().join(", "),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_table() {
+        let records = json!([
+            {"id": "1", "name": "Billing", "allowed_cidrs": ["10.0.0.0/8", "192.168.0.0/16"]},
+            {"id": "2", "name": "Payroll", "allowed_cidrs": []},
+        ]);
+        let table = render(OutputFormat::Table, &records);
+        let header = table.lines().nth(1).unwrap();
+        assert!(header.contains("ALLOWED_CIDRS") && header.contains("NAME"));
+        assert!(table.contains("10.0.0.0/8, 192.168.0.0/16"));
+        assert!(table.contains("Payroll"));
+
+        let record = render(OutputFormat::Table, &json!({"id": "1", "expires_at": null}));
+        assert!(record.contains("FIELD") && record.contains("expires_at"));
+
+        assert_eq!(render(OutputFormat::Table, &json!([])), "No records");
+    }
+
+    #[test]
+    fn test_render_json() {
+        let record = json!({"id": "1", "enabled": true});
+        let output = render(OutputFormat::Json, &record);
+        assert_eq!(serde_json::from_str::<Value>(&output).unwrap(), record);
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let cli = Cli::try_parse_from([
+            "sentinelctl",
+            "access",
+            "grant",
+            "--project-id",
+            "123e4567-e89b-12d3-a456-426614174000",
+            "--service-account-id",
+            "123e4567-e89b-12d3-a456-426614174001",
+            "--environment-id",
+            "123e4567-e89b-12d3-a456-426614174002",
+            "--scope",
+            "read",
+            "--scope",
+            "write",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        assert_eq!(cli.output, OutputFormat::Json);
+        match cli.command {
+            Command::Access(AccessCommand::Grant(grant)) => {
+                assert_eq!(grant.scopes, vec!["read", "write"])
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+
+        let error = Cli::try_parse_from(["sentinelctl", "project", "create"]).unwrap_err();
+        assert_eq!(error.exit_code(), EXIT_USAGE as i32);
+        let error =
+            Cli::try_parse_from(["sentinelctl", "key", "rotate", "not-a-uuid"]).unwrap_err();
+        assert_eq!(error.exit_code(), EXIT_USAGE as i32);
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(
+            exit_code(&Error::msg("Environment key not found")),
+            EXIT_NOT_FOUND
+        );
+        assert_eq!(
+            exit_code(&Error::msg(
+                "duplicate key value violates unique constraint"
+            )),
+            EXIT_FAILURE
+        );
+    }
+}
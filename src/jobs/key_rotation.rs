@@ -0,0 +1,83 @@
+//! Background job rotating environment keys once their rotation schedule is due.
+
+use std::time::Duration;
+
+use anyhow::Error;
+use tokio::task::JoinHandle;
+
+use crate::config::KeyRotationConfig;
+use crate::models::environment_key::EnvironmentKey;
+use crate::repositories::environment_key_repository::EnvironmentKeyRepository;
+use crate::utils::metrics::METRICS;
+
+/// Job label of the runs recorded in `METRICS.job_runs_total`
+pub const JOB_NAME: &str = "key_rotation";
+
+#[derive(Clone)]
+pub struct KeyRotationJob {
+    repository: EnvironmentKeyRepository,
+    config: KeyRotationConfig,
+}
+
+impl KeyRotationJob {
+    pub fn new(repository: EnvironmentKeyRepository, config: KeyRotationConfig) -> Self {
+        let repository = repository.with_rotation_jitter(config.jitter_seconds);
+        Self { repository, config }
+    }
+
+    /// Rotates the keys due for rotation, in batches until none is left, after discarding
+    /// the replaced keys no longer needed to verify tokens
+    pub async fn run_once(&self) -> Result<Vec<EnvironmentKey>, Error> {
+        if self.config.batch_size <= 0 {
+            return Err(Error::msg("Key rotation batch size must be positive"));
+        }
+
+        let rotated = self.run().await;
+        METRICS.record_job_run(JOB_NAME, rotated.is_ok());
+        rotated
+    }
+
+    async fn run(&self) -> Result<Vec<EnvironmentKey>, Error> {
+        let discarded = self.repository.discard_expired_previous_keys().await?;
+        if discarded > 0 {
+            tracing::info!(discarded, "Discarded expired previous environment keys");
+        }
+
+        let mut rotated = Vec::new();
+        loop {
+            let batch = self
+                .repository
+                .rotate_due_keys(self.config.batch_size)
+                .await?;
+            let done = (batch.len() as i64) < self.config.batch_size;
+            rotated.extend(batch);
+            if done {
+                return Ok(rotated);
+            }
+        }
+    }
+
+    /// Starts a tokio task checking for keys due for rotation every `interval_seconds`
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.interval_seconds.max(1)));
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(rotated) => {
+                        for key in &rotated {
+                            tracing::info!(
+                                environment_key_id = %key.id.unwrap_or_default(),
+                                algorithm = ?key.algorithm,
+                                environment_id = %key.environment_id,
+                                "Rotated environment key"
+                            );
+                        }
+                    }
+                    Err(error) => tracing::error!(error = %error, "Key rotation failed"),
+                }
+            }
+        })
+    }
+}
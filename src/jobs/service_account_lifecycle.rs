@@ -0,0 +1,130 @@
+//! Background job disabling expired service accounts and those no token was issued to for
+//! longer than the inactivity policy allows.
+
+use std::time::Duration;
+
+use anyhow::Error;
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::config::ServiceAccountLifecycleConfig;
+use crate::repositories::service_account_repository::ServiceAccountRepository;
+use crate::utils::metrics::METRICS;
+
+/// Job label of the runs recorded in `METRICS.job_runs_total`
+pub const JOB_NAME: &str = "service_account_lifecycle";
+
+/// Outcome of a single lifecycle run
+#[derive(Debug, Clone, Default)]
+pub struct ServiceAccountLifecycleReport {
+    /// Service accounts disabled because they expired
+    pub expired: Vec<Uuid>,
+    /// Service accounts disabled for inactivity
+    pub inactive: Vec<Uuid>,
+    /// Service accounts warned of their upcoming inactivity disablement
+    pub warned: Vec<Uuid>,
+}
+
+#[derive(Clone)]
+pub struct ServiceAccountLifecycleJob {
+    repository: ServiceAccountRepository,
+    config: ServiceAccountLifecycleConfig,
+}
+
+impl ServiceAccountLifecycleJob {
+    pub fn new(
+        repository: ServiceAccountRepository,
+        config: ServiceAccountLifecycleConfig,
+    ) -> Self {
+        Self { repository, config }
+    }
+
+    /// Disables expired service accounts, warns those about to become inactive, then
+    /// disables inactive ones warned at least `warning_days` ago. Every change is recorded
+    /// as an audit event.
+    pub async fn run_once(&self) -> Result<ServiceAccountLifecycleReport, Error> {
+        if self.config.inactivity_days < 0 {
+            return Err(Error::msg(
+                "Service account inactivity days must not be negative",
+            ));
+        }
+        if self.config.warning_days < 0 {
+            return Err(Error::msg(
+                "Service account inactivity warning days must not be negative",
+            ));
+        }
+
+        let report = self.run().await;
+        METRICS.record_job_run(JOB_NAME, report.is_ok());
+        if let Ok(report) = &report {
+            METRICS
+                .service_accounts_disabled_total
+                .inc_by(&["expired"], report.expired.len() as u64);
+            METRICS
+                .service_accounts_disabled_total
+                .inc_by(&["inactive"], report.inactive.len() as u64);
+            METRICS
+                .service_account_inactivity_warnings_total
+                .inc_by(&[], report.warned.len() as u64);
+        }
+
+        report
+    }
+
+    async fn run(&self) -> Result<ServiceAccountLifecycleReport, Error> {
+        let mut report = ServiceAccountLifecycleReport {
+            expired: self.repository.disable_expired().await?,
+            ..Default::default()
+        };
+
+        if self.config.inactivity_days > 0 {
+            let inactivity_period = chrono::Duration::days(self.config.inactivity_days);
+            let warning_period = chrono::Duration::days(self.config.warning_days);
+            let cutoff = Utc::now() - inactivity_period;
+            report.warned = self
+                .repository
+                .warn_inactive(cutoff + warning_period, inactivity_period, warning_period)
+                .await?;
+
+            // Accounts are only disabled once their warning has been out for the whole
+            // warning period, even those already past the inactivity cutoff
+            report.inactive = self
+                .repository
+                .disable_inactive(cutoff, Utc::now() - warning_period)
+                .await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Starts a tokio task running the lifecycle checks every `interval_seconds`
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.interval_seconds.max(1)));
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(report) => {
+                        for id in &report.warned {
+                            tracing::warn!(
+                                service_account_id = %id,
+                                "Service account will be disabled for inactivity unless a token is issued to it"
+                            );
+                        }
+                        tracing::info!(
+                            expired = report.expired.len(),
+                            inactive = report.inactive.len(),
+                            warned = report.warned.len(),
+                            "Service account lifecycle completed"
+                        );
+                    }
+                    Err(error) => {
+                        tracing::error!(error = %error, "Service account lifecycle failed")
+                    }
+                }
+            }
+        })
+    }
+}
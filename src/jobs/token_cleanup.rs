@@ -0,0 +1,148 @@
+//! Background job removing access tokens once their retention window has passed, along
+//! with the authentication failures that no longer count towards a lockout.
+
+use std::time::Duration;
+
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use tokio::task::JoinHandle;
+
+use crate::config::{TokenCleanupConfig, TokenCleanupMode};
+use crate::repositories::access_token_repository::AccessTokenRepository;
+use crate::repositories::authentication_failure_repository::AuthenticationFailureRepository;
+use crate::utils::metrics::METRICS;
+
+/// Job label of the runs recorded in `METRICS.job_runs_total`
+pub const JOB_NAME: &str = "token_cleanup";
+
+/// Outcome of a single cleanup run
+#[derive(Debug, Clone)]
+pub struct TokenCleanupReport {
+    /// Tokens expired before this instant were removed
+    pub cutoff: DateTime<Utc>,
+    /// Number of tokens deleted or archived
+    pub removed: u64,
+    /// Number of batches executed
+    pub batches: u64,
+    pub mode: TokenCleanupMode,
+    /// Number of subjects whose stale authentication failures were removed
+    pub authentication_failures_removed: u64,
+}
+
+#[derive(Clone)]
+pub struct TokenCleanupJob {
+    repository: AccessTokenRepository,
+    config: TokenCleanupConfig,
+    /// Repository and lockout window of the authentication failures to prune, if any
+    authentication_failures: Option<(AuthenticationFailureRepository, i64)>,
+}
+
+impl TokenCleanupJob {
+    pub fn new(repository: AccessTokenRepository, config: TokenCleanupConfig) -> Self {
+        Self {
+            repository,
+            config,
+            authentication_failures: None,
+        }
+    }
+
+    /// Also removes authentication failures older than `failure_window_seconds` whose
+    /// subject is no longer blocked, which client authentication leaves behind
+    pub fn with_authentication_failures(
+        mut self,
+        repository: AuthenticationFailureRepository,
+        failure_window_seconds: i64,
+    ) -> Self {
+        self.authentication_failures = Some((repository, failure_window_seconds));
+        self
+    }
+
+    /// Removes every token that expired before the retention window, one batch at a time
+    pub async fn run_once(&self) -> Result<TokenCleanupReport, Error> {
+        if self.config.batch_size <= 0 {
+            return Err(Error::msg("Token cleanup batch size must be positive"));
+        }
+        if self.config.retention_days < 0 {
+            return Err(Error::msg("Token retention days must not be negative"));
+        }
+
+        let cutoff = Utc::now() - chrono::Duration::days(self.config.retention_days);
+        let mut report = TokenCleanupReport {
+            cutoff,
+            removed: 0,
+            batches: 0,
+            mode: self.config.mode,
+            authentication_failures_removed: 0,
+        };
+
+        loop {
+            let removed = match self
+                .repository
+                .purge_expired(cutoff, self.config.batch_size, self.config.mode)
+                .await
+            {
+                Ok(removed) => removed,
+                Err(error) => {
+                    METRICS.record_job_run(JOB_NAME, false);
+                    return Err(error);
+                }
+            };
+
+            report.batches += 1;
+            report.removed += removed;
+            METRICS
+                .access_tokens_removed_total
+                .inc_by(&[self.config.mode.as_str()], removed);
+
+            if removed < self.config.batch_size as u64 {
+                break;
+            }
+        }
+
+        if let Some((repository, failure_window_seconds)) = &self.authentication_failures {
+            let window_start = Utc::now() - chrono::Duration::seconds(*failure_window_seconds);
+            loop {
+                let removed = match repository
+                    .purge_stale(window_start, self.config.batch_size)
+                    .await
+                {
+                    Ok(removed) => removed,
+                    Err(error) => {
+                        METRICS.record_job_run(JOB_NAME, false);
+                        return Err(error);
+                    }
+                };
+
+                report.authentication_failures_removed += removed;
+                if removed < self.config.batch_size as u64 {
+                    break;
+                }
+            }
+        }
+
+        METRICS.record_job_run(JOB_NAME, true);
+        Ok(report)
+    }
+
+    /// Starts a tokio task running the cleanup every `interval_seconds`
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.interval_seconds.max(1)));
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(report) => tracing::info!(
+                        removed = report.removed,
+                        mode = ?report.mode,
+                        batches = report.batches,
+                        cutoff = %report.cutoff,
+                        authentication_failures_removed = report.authentication_failures_removed,
+                        "Token cleanup completed"
+                    ),
+                    Err(error) => tracing::error!(error = %error, "Token cleanup failed"),
+                }
+            }
+        })
+    }
+}
@@ -1,6 +1,13 @@
+pub mod cli;
 pub mod config;
+pub mod jobs;
+pub mod middleware;
 pub mod models;
 pub mod repositories;
 pub mod routes;
 pub mod serializers;
+pub mod services;
 pub mod utils;
+
+/// Migrations of the `migrations` directory, embedded at build time
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!();
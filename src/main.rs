@@ -1,53 +1,352 @@
-use actix_web::HttpServer;
+use actix_web::{HttpServer, middleware, web};
+use clap::{Parser, Subcommand};
 use sentinel_guard::config::AppConfig;
-use sentinel_guard::routes::register::register_routes;
+use sentinel_guard::jobs::key_rotation::KeyRotationJob;
+use sentinel_guard::jobs::service_account_lifecycle::ServiceAccountLifecycleJob;
+use sentinel_guard::jobs::token_cleanup::TokenCleanupJob;
+use sentinel_guard::middleware::cors::cors;
+use sentinel_guard::middleware::rate_limit::rate_limit;
+use sentinel_guard::middleware::request_id::request_id;
+use sentinel_guard::middleware::request_metrics::request_metrics;
+use sentinel_guard::middleware::security_headers::{SecurityHeaders, security_headers};
+use sentinel_guard::repositories::access_token_repository::AccessTokenRepository;
+use sentinel_guard::repositories::authentication_failure_repository::AuthenticationFailureRepository;
+use sentinel_guard::repositories::environment_key_repository::EnvironmentKeyRepository;
 use sentinel_guard::repositories::register::register_repositories;
+use sentinel_guard::repositories::service_account_repository::ServiceAccountRepository;
+use sentinel_guard::routes::register::register_routes;
+use sentinel_guard::services::health_service::HealthService;
+use sentinel_guard::services::migration_service::MigrationService;
+use sentinel_guard::services::register::register_services;
+use sentinel_guard::utils::rate_limit::{self, RateLimiter};
 use sentinel_guard::utils::swagger::get_swagger_ui;
-use sqlx::postgres::PgPool;
+use sentinel_guard::utils::tls::{self, TlsReloader};
+use sentinel_guard::utils::{key_store, logging};
+use std::path::PathBuf;
 use std::{sync::Arc, time::Duration};
 use tokio::signal;
 
+/// Command line of the server
+#[derive(Debug, Parser)]
+#[command(
+    name = "sentinel-guard",
+    version,
+    about = "Runs the SentinelGuard token service"
+)]
+struct Arguments {
+    /// Configuration file, overridden by `SENTINEL_GUARD_*` variables
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Print the effective configuration with secrets redacted and exit
+    #[arg(long)]
+    print_config: bool,
+
+    /// Apply pending database migrations before starting
+    #[arg(long)]
+    migrate: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// One-shot commands, run and exiting without starting the server
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List the applied and pending migrations
+    MigrationStatus,
+    /// Remove expired access tokens
+    CleanupTokens,
+    /// Disable expired and inactive service accounts
+    ServiceAccountLifecycle,
+    /// Rotate the environment keys due for rotation
+    RotateKeys,
+}
+
 #[actix_web::main]
 async fn main() -> Result<(), anyhow::Error> {
-    let config = AppConfig::from_env(Some(true))?;
+    let arguments = Arguments::parse();
+    let config = AppConfig::load(arguments.config.as_deref(), Some(true))?;
+    if arguments.print_config {
+        print!("{}", config.to_redacted_toml()?);
+        return Ok(());
+    }
+    let tracer_provider = logging::init(&config.logging, &config.tracing)?;
 
-    let pool = Arc::new(PgPool::connect(&config.database_uri).await?);
-    let host = config.host;
-    let port = config.port;
+    let pool = Arc::new(
+        config
+            .database
+            .pool_options()
+            .connect(&config.database.uri)
+            .await?,
+    );
 
+    let migration_service = MigrationService::new(pool.clone());
+    if matches!(arguments.command, Some(Command::MigrationStatus)) {
+        let statuses = migration_service.status().await?;
+        println!(
+            "{:<16} {:<8} {:<26} DESCRIPTION",
+            "VERSION", "STATE", "INSTALLED ON"
+        );
+        for status in &statuses {
+            println!(
+                "{:<16} {:<8} {:<26} {}{}",
+                status.version,
+                status.state.as_str(),
+                status
+                    .installed_on
+                    .map(|installed_on| installed_on.to_rfc3339())
+                    .unwrap_or_default(),
+                status.description,
+                if status.checksum_mismatch {
+                    " (checksum mismatch)"
+                } else {
+                    ""
+                }
+            );
+        }
+        return Ok(());
+    }
+    if arguments.migrate || config.database.migrate_on_startup {
+        let applied = migration_service.migrate().await?;
+        tracing::info!(applied = applied.len(), "Database migrations applied");
+        for status in applied {
+            tracing::info!(
+                version = status.version,
+                description = status.description,
+                "Applied migration"
+            );
+        }
+    }
+    // A newer binary may have changed the schema in ways this one cannot serve, and queries
+    // of this one fail against a schema missing its migrations
+    migration_service.ensure_migrated().await?;
+
+    let secrets_manager = config.security.secrets_manager()?;
+    let key_store = key_store::from_config(&config.security.key_store, secrets_manager.clone())?;
+    // Keys left in another store could neither sign nor be rotated
+    EnvironmentKeyRepository::new(pool.clone(), secrets_manager.clone())
+        .with_key_store(key_store.clone())
+        .ensure_key_store_holds_keys()
+        .await?;
+    let token_cleanup_job = TokenCleanupJob::new(
+        AccessTokenRepository::new(pool.clone()),
+        config.jobs.token_cleanup.clone(),
+    )
+    .with_authentication_failures(
+        AuthenticationFailureRepository::new(pool.clone()),
+        config.security.lockout.failure_window_seconds,
+    );
+    let service_account_lifecycle_job = ServiceAccountLifecycleJob::new(
+        ServiceAccountRepository::new(pool.clone(), secrets_manager.clone()),
+        config.jobs.service_account_lifecycle.clone(),
+    );
+    let key_rotation_job = KeyRotationJob::new(
+        EnvironmentKeyRepository::new(pool.clone(), secrets_manager.clone())
+            .with_key_store(key_store.clone())
+            .with_token_defaults(config.tokens.policy()),
+        config.jobs.key_rotation.clone(),
+    );
+
+    // One-shot commands run and exit without starting the server
+    match arguments.command {
+        Some(Command::CleanupTokens) => {
+            let report = token_cleanup_job.run_once().await?;
+            println!(
+                "Token cleanup removed {} expired tokens ({:?}, {} batches, cutoff {}) and the stale authentication failures of {} subjects",
+                report.removed,
+                report.mode,
+                report.batches,
+                report.cutoff,
+                report.authentication_failures_removed
+            );
+            return Ok(());
+        }
+        Some(Command::ServiceAccountLifecycle) => {
+            let report = service_account_lifecycle_job.run_once().await?;
+            println!(
+                "Service account lifecycle disabled {} expired and {} inactive accounts, warned {}",
+                report.expired.len(),
+                report.inactive.len(),
+                report.warned.len()
+            );
+            return Ok(());
+        }
+        Some(Command::RotateKeys) => {
+            let rotated = key_rotation_job.run_once().await?;
+            println!("Key rotation rotated {} environment keys", rotated.len());
+            return Ok(());
+        }
+        Some(Command::MigrationStatus) | None => {}
+    }
+
+    let health_service = HealthService::new(pool.clone(), secrets_manager.clone());
+    let service_config = config.clone();
+    let host = config.server.host.clone();
+    let port = config.server.port;
+
+    let token_cleanup_handle = config
+        .jobs
+        .token_cleanup
+        .enabled
+        .then(|| token_cleanup_job.spawn());
+    let service_account_lifecycle_handle = config
+        .jobs
+        .service_account_lifecycle
+        .enabled
+        .then(|| service_account_lifecycle_job.spawn());
+    let key_rotation_handle = config
+        .jobs
+        .key_rotation
+        .enabled
+        .then(|| key_rotation_job.spawn());
+
+    let tls_reloader = config
+        .server
+        .tls
+        .is_enabled()
+        .then(|| TlsReloader::load(&config.server.tls))
+        .transpose()?;
+
+    // Shared by the workers, so they take from the same buckets
+    let rate_limiter = config.security.rate_limit.enabled.then(|| {
+        RateLimiter::new(config.security.rate_limit.clone())
+            .with_store(rate_limit::from_config(
+                &config.security.rate_limit,
+                pool.clone(),
+            ))
+            .with_trusted_proxies(config.server.trusted_proxies.clone())
+    });
+
+    let cors_config = config.server.cors.clone();
+    let security_headers_config = config.server.security_headers.enabled.then(|| {
+        SecurityHeaders::new(config.server.security_headers.clone())
+            .with_trusted_proxies(config.server.trusted_proxies.clone())
+    });
+
+    let readiness = health_service.clone();
     let server = HttpServer::new(move || {
         let app = actix_web::App::new();
+        let app = match &rate_limiter {
+            Some(rate_limiter) => app.app_data(web::Data::new(rate_limiter.clone())),
+            None => app,
+        };
+        let app = match &security_headers_config {
+            Some(config) => app.app_data(web::Data::new(config.clone())),
+            None => app,
+        };
 
-        let app = register_repositories(app, pool.clone());
+        let app = register_repositories(
+            app,
+            pool.clone(),
+            secrets_manager.clone(),
+            key_store.clone(),
+            service_config.tokens.policy(),
+            service_config.jobs.key_rotation.jitter_seconds,
+        );
+        let app = register_services(
+            app,
+            pool.clone(),
+            secrets_manager.clone(),
+            key_store.clone(),
+            health_service.clone(),
+            rate_limiter.clone(),
+            &service_config,
+        );
         let app = register_routes(app);
         app.service(get_swagger_ui())
+            .wrap(middleware::from_fn(rate_limit))
+            // Outside the rate limit, so preflight requests are not limited and rejections
+            // are readable by browser applications
+            .wrap(middleware::Condition::new(
+                cors_config.is_enabled(),
+                cors(&cors_config),
+            ))
+            .wrap(middleware::from_fn(security_headers))
+            .wrap(middleware::from_fn(request_metrics))
+            .wrap(middleware::from_fn(request_id))
     })
-    .bind((host.clone(), port))?
-    .shutdown_timeout(30) // 30 seconds graceful shutdown timeout
-    .workers(4) // Set number of workers
-    .keep_alive(Duration::from_secs(75)) // Keep-alive timeout
-    .run();
+    .on_connect(tls::capture_client_certificate);
+    let server = match &tls_reloader {
+        Some(tls_reloader) => {
+            server.bind_rustls_0_23((host.clone(), port), tls_reloader.server_config()?)?
+        }
+        None => server.bind((host.clone(), port))?,
+    };
+    let scheme = if tls_reloader.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    let tls_reload_handle = tls_reloader.map(TlsReloader::spawn);
+    let server = server
+        .shutdown_timeout(config.server.shutdown_timeout_seconds)
+        .disable_signals() // Shutdown signals are handled below, after readiness is flipped
+        .workers(config.server.workers)
+        .keep_alive(Duration::from_secs(config.server.keep_alive_seconds))
+        .run();
 
-    println!("Server started at http://{}:{}", host, port);
+    tracing::info!("Server started at {}://{}:{}", scheme, host, port);
 
     let server_handle = server.handle();
 
     // Wait for the server to finish or for a Ctrl+C signal
     tokio::select! {
         _ = server => {
-            println!("Server finished");
+            tracing::info!("Server finished");
         }
-        _ = signal::ctrl_c() => {
-            println!("Received ctrl+c signal, shutting down gracefully");
+        signal = shutdown_signal() => {
+            tracing::info!(signal, "Received shutdown signal, shutting down gracefully");
+
+            // Fail the readiness probe first, so the orchestrator stops routing new traffic
+            // while in-flight requests are still served
+            readiness.begin_shutdown();
+            tokio::time::sleep(Duration::from_secs(config.server.shutdown_drain_seconds)).await;
 
             // Stop accepting new connections
             server_handle.stop(true).await;
 
             // Give some time for cleanup
             tokio::time::sleep(Duration::from_secs(1)).await;
-            println!("Cleanup completed, server shutting down");
+            tracing::info!("Cleanup completed, server shutting down");
         }
     }
 
+    // Send the spans of the last requests before exiting
+    let _ = tokio::task::spawn_blocking(move || {
+        tracer_provider.shutdown_with_timeout(Duration::from_secs(5))
+    })
+    .await;
+
+    for handle in [
+        token_cleanup_handle,
+        service_account_lifecycle_handle,
+        key_rotation_handle,
+        tls_reload_handle,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        handle.abort();
+    }
+
     Ok(())
 }
+
+/// Waits for ctrl+c or, on Unix, the SIGTERM sent by orchestrators, returning its name
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler");
+        tokio::select! {
+            _ = signal::ctrl_c() => "ctrl+c",
+            _ = terminate.recv() => "SIGTERM",
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal::ctrl_c().await;
+        "ctrl+c"
+    }
+}
@@ -0,0 +1,31 @@
+use actix_cors::Cors;
+
+use crate::config::CorsConfig;
+
+/// Builds the CORS middleware of the configuration, validated by `AppConfig::validate`.
+///
+/// Requests from origins that are not allowed are still served, without CORS headers, so
+/// browsers refuse to expose the response while other HTTP clients are unaffected. Wrap it
+/// in `actix_web::middleware::Condition` with [`CorsConfig::is_enabled`], so preflight
+/// requests are not answered when no origin is allowed.
+pub fn cors(config: &CorsConfig) -> Cors {
+    let cors = Cors::default()
+        .allowed_methods(config.allowed_methods.iter().map(String::as_str))
+        .allowed_headers(config.allowed_headers.iter().map(String::as_str))
+        .expose_headers(config.exposed_headers.iter().map(String::as_str))
+        .max_age(config.max_age_seconds)
+        .block_on_origin_mismatch(false);
+    let cors = if config.allows_any_origin() {
+        cors.allow_any_origin().send_wildcard()
+    } else {
+        config
+            .allowed_origins
+            .iter()
+            .fold(cors, |cors, origin| cors.allowed_origin(origin))
+    };
+    if config.allow_credentials {
+        cors.supports_credentials()
+    } else {
+        cors
+    }
+}
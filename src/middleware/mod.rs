@@ -0,0 +1,5 @@
+pub mod cors;
+pub mod rate_limit;
+pub mod request_id;
+pub mod request_metrics;
+pub mod security_headers;
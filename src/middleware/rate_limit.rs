@@ -0,0 +1,166 @@
+use std::net::IpAddr;
+
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::http::{Method, StatusCode};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpResponse, web};
+use serde_json::json;
+
+use crate::utils::metrics::METRICS;
+use crate::utils::rate_limit::{RateLimitCategory, RateLimitDecision, RateLimiter};
+use crate::utils::tls::ClientCertificate;
+
+pub const RATE_LIMIT_LIMIT_HEADER: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATE_LIMIT_REMAINING_HEADER: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATE_LIMIT_RESET_HEADER: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+/// Paths probed by infrastructure, never limited
+const EXEMPT_PATHS: [&str; 5] = ["/healthz", "/readyz", "/metrics", "/docs", "/api-docs"];
+
+/// Rule a request is limited by, `None` when it is not limited
+pub fn categorize(method: &Method, path: &str) -> Option<RateLimitCategory> {
+    if EXEMPT_PATHS
+        .iter()
+        .any(|exempt| path == *exempt || path.starts_with(&format!("{}/", exempt)))
+    {
+        return None;
+    }
+    if path == "/token" {
+        return Some(RateLimitCategory::Token);
+    }
+    // Creating, importing and rotating environment keys generate or parse key material
+    if method == Method::POST && path.starts_with("/environment-keys") {
+        return Some(RateLimitCategory::KeyGeneration);
+    }
+    Some(RateLimitCategory::Admin)
+}
+
+/// Verified identity of a client, the thumbprint of its TLS client certificate. Credentials
+/// in the request are only checked later, so the token service charges the bucket of a
+/// client once it authenticated it.
+fn client_identity(request: &ServiceRequest) -> Option<String> {
+    request
+        .conn_data::<ClientCertificate>()
+        .map(|certificate| format!("x5t#S256:{}", certificate.thumbprint_sha256()))
+}
+
+fn client_ip(request: &ServiceRequest, limiter: &RateLimiter) -> Option<IpAddr> {
+    let forwarded_for = request
+        .headers()
+        .get_all("X-Forwarded-For")
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(",");
+    limiter.client_ip(
+        request.peer_addr().map(|address| address.ip()),
+        Some(forwarded_for.as_str()).filter(|value| !value.is_empty()),
+    )
+}
+
+fn insert_headers(headers: &mut header::HeaderMap, decision: &RateLimitDecision) {
+    for (name, value) in [
+        (RATE_LIMIT_LIMIT_HEADER, decision.limit as u64),
+        (RATE_LIMIT_REMAINING_HEADER, decision.remaining as u64),
+        (RATE_LIMIT_RESET_HEADER, decision.reset),
+    ] {
+        headers.insert(name, HeaderValue::from(value));
+    }
+}
+
+/// Limits requests to the admin and token APIs per client IP and TLS client certificate.
+///
+/// Passes every request through unless a [`RateLimiter`] is registered as app data. Limited
+/// requests get a `429 Too Many Requests` with `Retry-After`, and every checked response
+/// carries the `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset` headers. When
+/// the store fails the request is let through, so an outage of the database only disables
+/// the limits.
+///
+/// Register with `actix_web::middleware::from_fn(rate_limit)`.
+pub async fn rate_limit<B: MessageBody + 'static>(
+    request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let Some(limiter) = request.app_data::<web::Data<RateLimiter>>().cloned() else {
+        return Ok(next.call(request).await?.map_into_left_body());
+    };
+    let Some(category) = categorize(request.method(), request.path()) else {
+        return Ok(next.call(request).await?.map_into_left_body());
+    };
+
+    let ip = client_ip(&request, &limiter);
+    let client = client_identity(&request);
+    let decision = match limiter.check(category, ip, client.as_deref()).await {
+        Ok(decision) => decision,
+        Err(error) => {
+            tracing::warn!(error = %error, "Rate limit store failed, request not limited");
+            return Ok(next.call(request).await?.map_into_left_body());
+        }
+    };
+
+    if !decision.allowed {
+        METRICS
+            .rate_limited_requests_total
+            .inc(&[category.as_str()]);
+        tracing::info!(
+            category = category.as_str(),
+            ip = ip.map(|ip| ip.to_string()).unwrap_or_default(),
+            client = client.unwrap_or_default(),
+            retry_after = decision.retry_after,
+            "Request rate limited"
+        );
+        let mut response = HttpResponse::build(StatusCode::TOO_MANY_REQUESTS)
+            .insert_header((header::RETRY_AFTER, decision.retry_after.to_string()))
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .json(json!({
+                "error": "rate_limited",
+                "error_description": format!(
+                    "Rate limit exceeded, retry in {} seconds",
+                    decision.retry_after
+                ),
+            }));
+        insert_headers(response.headers_mut(), &decision);
+        return Ok(request.into_response(response).map_into_right_body());
+    }
+
+    let mut response = next.call(request).await?;
+    insert_headers(response.headers_mut(), &decision);
+    Ok(response.map_into_left_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_categorize() {
+        assert_eq!(
+            categorize(&Method::POST, "/token"),
+            Some(RateLimitCategory::Token)
+        );
+        assert_eq!(
+            categorize(&Method::POST, "/environment-keys/123/rotate"),
+            Some(RateLimitCategory::KeyGeneration)
+        );
+        assert_eq!(
+            categorize(&Method::POST, "/environment-keys"),
+            Some(RateLimitCategory::KeyGeneration)
+        );
+        assert_eq!(
+            categorize(&Method::GET, "/environment-keys"),
+            Some(RateLimitCategory::Admin)
+        );
+        assert_eq!(
+            categorize(&Method::DELETE, "/projects/123"),
+            Some(RateLimitCategory::Admin)
+        );
+        assert_eq!(categorize(&Method::GET, "/healthz"), None);
+        assert_eq!(categorize(&Method::GET, "/metrics"), None);
+        assert_eq!(categorize(&Method::GET, "/docs/index.html"), None);
+        assert_eq!(
+            categorize(&Method::GET, "/metricsx"),
+            Some(RateLimitCategory::Admin)
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::time::Instant;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::HeaderMap;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use tracing::{Instrument, field};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest request ID accepted from a client or proxy
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// ID correlating the log lines of a request, available in the request extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Whether a request ID received in `X-Request-Id` is kept rather than replaced
+pub fn is_valid_request_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LENGTH
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Reads the `traceparent` and `tracestate` headers of a request for the propagator
+struct RequestHeaders<'a>(&'a HeaderMap);
+
+impl Extractor for RequestHeaders<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
+
+/// Runs the request in a `request` span carrying its ID and logs its outcome.
+///
+/// The ID of an upstream proxy in `X-Request-Id` is kept when valid, otherwise one is
+/// generated. It is echoed in the `X-Request-Id` response header either way. The span
+/// continues the distributed trace of a `traceparent` header, when the caller sent one.
+///
+/// Register with `actix_web::middleware::from_fn(request_id)`.
+pub async fn request_id(
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid_request_id(value))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    request
+        .extensions_mut()
+        .insert(RequestId(request_id.clone()));
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.path(),
+        route = field::Empty,
+        status = field::Empty,
+        otel.kind = "server",
+        otel.status_code = field::Empty,
+    );
+    let caller = TraceContextPropagator::new().extract(&RequestHeaders(request.headers()));
+    let _ = span.set_parent(caller);
+    let started = Instant::now();
+    let response = next.call(request).instrument(span.clone()).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+
+    let _entered = span.enter();
+    match response {
+        Ok(mut response) => {
+            let status = response.status().as_u16();
+            if response.status().is_server_error() {
+                // The body of a 5xx response is generic, the error is only logged here
+                let error = response.response().error().map(field::display);
+                tracing::error!(status, elapsed_ms, error, "Request failed");
+                span.record("otel.status_code", "ERROR");
+            } else {
+                tracing::info!(status, elapsed_ms, "Request completed");
+            }
+            // Recorded once logged, so the completion line does not repeat the status
+            // The exported span is already started, so it is renamed rather than recorded
+            let method = response.request().method().to_string();
+            match response.request().match_pattern() {
+                Some(route) => {
+                    span.context()
+                        .span()
+                        .update_name(format!("{} {}", method, route));
+                    span.record("route", route);
+                }
+                None => span.context().span().update_name(method),
+            }
+            span.record("status", status);
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                response.headers_mut().insert(REQUEST_ID_HEADER, value);
+            }
+            Ok(response)
+        }
+        Err(error) => {
+            tracing::error!(error = %error, elapsed_ms, "Request failed");
+            span.record("otel.status_code", "ERROR");
+            Err(error)
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::time::Instant;
+
+use actix_web::Error;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+
+use crate::utils::metrics::METRICS;
+
+/// Route label of requests no route matched, so unknown paths do not create new series
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Counts requests and records their duration by method, route pattern and status.
+///
+/// Register with `actix_web::middleware::from_fn(request_metrics)`.
+pub async fn request_metrics(
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let response = next.call(request).await;
+    let elapsed = started.elapsed().as_secs_f64();
+
+    let (route, status) = match &response {
+        Ok(response) => (
+            response.request().match_pattern(),
+            response.status().as_u16(),
+        ),
+        Err(error) => (None, error.as_response_error().status_code().as_u16()),
+    };
+    let route = route.unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let status = status.to_string();
+
+    METRICS.http_requests_total.inc(&[&method, &route, &status]);
+    METRICS
+        .http_request_duration_seconds
+        .observe(&[&method, &route], elapsed);
+
+    response
+}
@@ -0,0 +1,92 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, web};
+use ipnet::IpNet;
+
+use crate::config::SecurityHeadersConfig;
+
+/// Security headers configuration with the proxies trusted to report the scheme of requests
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    config: SecurityHeadersConfig,
+    trusted_proxies: Vec<IpNet>,
+}
+
+impl SecurityHeaders {
+    pub fn new(config: SecurityHeadersConfig) -> Self {
+        Self {
+            config,
+            trusted_proxies: Vec::new(),
+        }
+    }
+
+    /// Replaces the proxies whose `X-Forwarded-Proto` header is trusted
+    pub fn with_trusted_proxies(mut self, trusted_proxies: Vec<IpNet>) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+
+    /// Whether a request reached the server over HTTPS, directly or through a trusted proxy
+    fn is_https(&self, request: &ServiceRequest) -> bool {
+        if request.app_config().secure() {
+            return true;
+        }
+        let from_trusted_proxy = request.peer_addr().is_some_and(|peer| {
+            let peer = peer.ip().to_canonical();
+            self.trusted_proxies
+                .iter()
+                .any(|proxy| proxy.contains(&peer))
+        });
+        // The proxy closest to the client reports the scheme first
+        from_trusted_proxy
+            && request
+                .headers()
+                .get("X-Forwarded-Proto")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"))
+    }
+}
+
+/// Adds the security headers of the [`SecurityHeaders`] registered as app data.
+///
+/// Every response gets `X-Content-Type-Options: nosniff`, `X-Frame-Options: DENY` and
+/// `Referrer-Policy: no-referrer`. Responses of the API carry keys, tokens and secrets, so
+/// those not choosing their own `Cache-Control` get `no-store`. `Strict-Transport-Security`
+/// is only sent over HTTPS, including HTTPS terminated by a trusted proxy setting
+/// `X-Forwarded-Proto`. Requests pass through unchanged when no configuration is registered.
+///
+/// Register with `actix_web::middleware::from_fn(security_headers)`.
+pub async fn security_headers(
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let Some(security_headers) = request.app_data::<web::Data<SecurityHeaders>>().cloned() else {
+        return next.call(request).await;
+    };
+    let https = security_headers.is_https(&request);
+
+    let mut response = next.call(request).await?;
+    let headers = response.headers_mut();
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    headers.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+    headers.insert(
+        header::REFERRER_POLICY,
+        HeaderValue::from_static("no-referrer"),
+    );
+    if !headers.contains_key(header::CACHE_CONTROL) {
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    }
+    if let Some(hsts) = security_headers.config.hsts().filter(|_| https) {
+        headers.insert(
+            header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::try_from(hsts)?,
+        );
+    }
+    Ok(response)
+}
@@ -9,9 +9,11 @@ use crate::models::sort::SortOrder;
 pub struct AccessToken {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<Uuid>,
+    pub jti: String,
     pub project_access_id: Uuid,
     pub algorithm: String,
     pub token: String,
+    pub token_use: String,
     pub expires_at: DateTime<Utc>,
     pub active: bool,
     pub created_at: DateTime<Utc>,
@@ -22,12 +24,16 @@ pub struct AccessToken {
 pub struct AccessTokenResponse {
     #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
     pub id: String,
+    #[schema(example = "5b0c1f0e-4f7a-4a43-9d55-1b2f7f0e2c11")]
+    pub jti: String,
     #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
     pub project_access_id: String,
     #[schema(example = "HS256")]
     pub algorithm: String,
     #[schema(example = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...")]
     pub token: String,
+    #[schema(example = "access")]
+    pub token_use: String,
     #[schema(example = "2025-07-01T00:00:00.000Z")]
     pub expires_at: String,
     #[schema(example = "true")]
@@ -42,9 +48,11 @@ impl From<AccessToken> for AccessTokenResponse {
     fn from(value: AccessToken) -> Self {
         Self {
             id: value.id.unwrap().to_string(),
+            jti: value.jti,
             project_access_id: value.project_access_id.to_string(),
             algorithm: value.algorithm,
             token: value.token,
+            token_use: value.token_use,
             expires_at: value.expires_at.to_string(),
             active: value.active,
             created_at: value.created_at.to_string(),
@@ -66,6 +74,10 @@ pub struct AccessTokenCreatePayloadWithAccessToken {
     pub algorithm: String,
     pub expires_at: String,
     pub access_token: String,
+    /// JWT ID embedded in the token; generated by the database when omitted
+    pub jti: Option<String>,
+    /// Either `access` or `refresh`
+    pub token_use: String,
 }
 
 impl From<AccessTokenCreatePayloadWithAccessToken> for AccessTokenCreatePayload {
@@ -136,6 +148,7 @@ mod tests {
     fn test_access_token_default() {
         let access_token = AccessToken::default();
         assert!(access_token.id.is_none());
+        assert_eq!(access_token.jti, "");
         assert_eq!(access_token.project_access_id, Uuid::nil());
         assert_eq!(access_token.algorithm, "");
         assert_eq!(access_token.token, "");
@@ -190,9 +203,11 @@ mod tests {
 
         let access_token = AccessToken {
             id: Some(id),
+            jti: "jti-1".to_string(),
             project_access_id,
             algorithm: "HS256".to_string(),
             token: "sometoken".to_string(),
+            token_use: "access".to_string(),
             expires_at: expires,
             active: true,
             created_at: now,
@@ -201,9 +216,11 @@ mod tests {
 
         let response = AccessTokenResponse::from(access_token);
         assert_eq!(response.id, id.to_string());
+        assert_eq!(response.jti, "jti-1");
         assert_eq!(response.project_access_id, project_access_id.to_string());
         assert_eq!(response.algorithm, "HS256");
         assert_eq!(response.token, "sometoken");
+        assert_eq!(response.token_use, "access");
         assert_eq!(response.expires_at, expires.to_string());
         assert!(response.active);
         assert_eq!(response.created_at, now.to_string());
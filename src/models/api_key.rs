@@ -0,0 +1,334 @@
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use rand::{RngCore, rngs::OsRng};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    models::{service_account_credential::parse_expires_at, sort::SortOrder},
+    utils::security::generate_opaque_token,
+};
+
+/// Marker every API key starts with, so leaked keys are easy to recognise and scan for
+pub const API_KEY_PREFIX: &str = "sg_live_";
+
+/// Number of random bytes in the lookup prefix of a key, hex encoded. The prefix is unique,
+/// so it is long enough for collisions between generated keys to be negligible.
+const LOOKUP_PREFIX_BYTES: usize = 8;
+
+/// Length of the lookup prefix of keys generated before it was widened, still accepted
+const LEGACY_LOOKUP_PREFIX_BYTES: usize = 4;
+
+/// Opaque credential bound to a project access, for integrations that cannot handle JWTs.
+///
+/// A key reads `sg_live_<prefix>_<secret>`. The prefix is stored in clear to find the key,
+/// while only the SHA-256 hash of the whole key is kept.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct ApiKey {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    pub project_access_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub enabled: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct ApiKeyResponse {
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub id: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub project_access_id: String,
+    #[schema(example = "billing-webhook")]
+    pub name: String,
+    #[schema(example = "3f9a0c1e5b7d2a48")]
+    pub prefix: String,
+    /// Full API key, only returned when the key is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[schema(example = json!(["payments:read"]))]
+    pub scopes: Vec<String>,
+    #[schema(example = "true")]
+    pub enabled: bool,
+    #[schema(example = "2025-12-31T23:59:59.000Z")]
+    pub expires_at: Option<String>,
+    #[schema(example = "2025-06-20T09:12:01.000Z")]
+    pub last_used_at: Option<String>,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub created_at: String,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub updated_at: String,
+}
+
+impl ApiKeyResponse {
+    /// Builds the response returned once, right after the key was generated
+    pub fn with_key(api_key: ApiKey, key: String) -> Self {
+        Self {
+            key: Some(key),
+            ..Self::from(api_key)
+        }
+    }
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(value: ApiKey) -> Self {
+        Self {
+            id: value.id.unwrap().to_string(),
+            project_access_id: value.project_access_id.to_string(),
+            name: value.name,
+            prefix: value.prefix,
+            key: None,
+            scopes: value.scopes,
+            enabled: value.enabled,
+            expires_at: value.expires_at.map(|expires_at| expires_at.to_string()),
+            last_used_at: value
+                .last_used_at
+                .map(|last_used_at| last_used_at.to_string()),
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct ApiKeyFilter {
+    pub project_access_id: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ApiKeyCreatePayload {
+    pub project_access_id: String,
+    #[schema(example = "billing-webhook")]
+    pub name: String,
+    /// Scopes of the key, drawn from the scopes granted to the project access.
+    /// Defaults to every granted scope.
+    pub scopes: Option<Vec<String>>,
+    /// RFC 3339 date after which the key stops working
+    #[schema(example = "2025-12-31T23:59:59Z")]
+    pub expires_at: Option<String>,
+}
+
+impl ApiKeyCreatePayload {
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_name(&self.name)?;
+        parse_expires_at(self.expires_at.as_deref())?;
+        Ok(())
+    }
+
+    /// Generates the key
+    pub fn with_key(self) -> ApiKeyCreatePayloadWithKey {
+        let (prefix, key) = generate_api_key();
+        ApiKeyCreatePayloadWithKey {
+            project_access_id: self.project_access_id,
+            name: self.name,
+            scopes: self.scopes,
+            expires_at: self.expires_at,
+            prefix,
+            key,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ApiKeyCreatePayloadWithKey {
+    pub project_access_id: String,
+    pub name: String,
+    pub scopes: Option<Vec<String>>,
+    pub expires_at: Option<String>,
+    pub prefix: String,
+    pub key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ApiKeyUpdatePayload {
+    pub name: Option<String>,
+    pub enabled: Option<bool>,
+    /// RFC 3339 date after which the key stops working
+    pub expires_at: Option<String>,
+}
+
+impl ApiKeyUpdatePayload {
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(name) = &self.name {
+            validate_name(name)?;
+        }
+        parse_expires_at(self.expires_at.as_deref())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ApiKeyVerifyPayload {
+    #[schema(example = "sg_live_3f9a0c1e_...")]
+    pub api_key: String,
+}
+
+/// What a valid API key resolves to
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ApiKeyVerification {
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub api_key_id: String,
+    #[schema(example = "billing-webhook")]
+    pub name: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub project_access_id: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub service_account_id: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub project_id: String,
+    #[schema(example = "payments")]
+    pub project_name: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub environment_id: String,
+    #[schema(example = "prod")]
+    pub environment_name: String,
+    /// Scopes of the key that are still granted to the project access
+    #[schema(example = json!(["payments:read"]))]
+    pub scopes: Vec<String>,
+    #[schema(example = "2025-12-31T23:59:59.000Z")]
+    pub expires_at: Option<String>,
+}
+
+fn validate_name(name: &str) -> Result<(), Error> {
+    if name.trim().is_empty() {
+        return Err(Error::msg("API key name must not be empty"));
+    }
+    Ok(())
+}
+
+/// Generates a new API key, returning its lookup prefix and the full key
+pub fn generate_api_key() -> (String, String) {
+    let mut bytes = [0u8; LOOKUP_PREFIX_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    let prefix = hex::encode(bytes);
+    let key = format!("{}{}_{}", API_KEY_PREFIX, prefix, generate_opaque_token());
+    (prefix, key)
+}
+
+/// Extracts the lookup prefix of an API key, if it is well formed
+pub fn lookup_prefix(key: &str) -> Option<&str> {
+    let (prefix, secret) = key.strip_prefix(API_KEY_PREFIX)?.split_once('_')?;
+    let well_formed = [LOOKUP_PREFIX_BYTES * 2, LEGACY_LOOKUP_PREFIX_BYTES * 2]
+        .contains(&prefix.len())
+        && prefix.bytes().all(|byte| byte.is_ascii_hexdigit())
+        && !secret.is_empty();
+    well_formed.then_some(prefix)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ApiKeySortableFields {
+    Id,
+    ProjectAccessId,
+    Name,
+    ExpiresAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl From<ApiKeySortableFields> for String {
+    fn from(value: ApiKeySortableFields) -> Self {
+        match value {
+            ApiKeySortableFields::Id => "id".to_string(),
+            ApiKeySortableFields::ProjectAccessId => "project_access_id".to_string(),
+            ApiKeySortableFields::Name => "name".to_string(),
+            ApiKeySortableFields::ExpiresAt => "expires_at".to_string(),
+            ApiKeySortableFields::CreatedAt => "created_at".to_string(),
+            ApiKeySortableFields::UpdatedAt => "updated_at".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKeySortOrder {
+    pub field: ApiKeySortableFields,
+    pub order: SortOrder,
+}
+
+impl ApiKeySortOrder {
+    pub fn new(field: ApiKeySortableFields, order: SortOrder) -> Self {
+        Self { field, order }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_api_key() {
+        let (prefix, key) = generate_api_key();
+        assert_eq!(prefix.len(), 16);
+        assert!(key.starts_with(&format!("sg_live_{}_", prefix)));
+        assert_eq!(lookup_prefix(&key), Some(prefix.as_str()));
+
+        let (_, other) = generate_api_key();
+        assert_ne!(key, other);
+    }
+
+    #[test]
+    fn test_lookup_prefix_rejects_malformed_keys() {
+        assert_eq!(
+            lookup_prefix("sg_live_0a1b2c3d4e5f6071_secret"),
+            Some("0a1b2c3d4e5f6071")
+        );
+        // Keys generated with the shorter prefix keep working
+        assert_eq!(lookup_prefix("sg_live_0a1b2c3d_secret"), Some("0a1b2c3d"));
+        assert!(lookup_prefix("sg_live_0a1b2c3d4e_secret").is_none());
+        assert!(lookup_prefix("sg_test_0a1b2c3d_secret").is_none());
+        assert!(lookup_prefix("sg_live_0a1b2c3d_").is_none());
+        assert!(lookup_prefix("sg_live_0a1b_secret").is_none());
+        assert!(lookup_prefix("sg_live_zzzzzzzz_secret").is_none());
+        assert!(lookup_prefix("").is_none());
+    }
+
+    #[test]
+    fn test_create_payload_validate() {
+        let payload = ApiKeyCreatePayload {
+            name: "webhook".to_string(),
+            ..Default::default()
+        };
+        assert!(payload.validate().is_ok());
+
+        let payload = ApiKeyCreatePayload {
+            name: "".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            payload.validate().unwrap_err().to_string(),
+            "API key name must not be empty"
+        );
+    }
+
+    #[test]
+    fn test_response_only_exposes_key_when_requested() {
+        let api_key = ApiKey {
+            id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+
+        let response = serde_json::to_value(ApiKeyResponse::from(api_key.clone())).unwrap();
+        assert!(response.get("key").is_none());
+        assert!(response.get("key_hash").is_none());
+
+        let response = ApiKeyResponse::with_key(api_key, "sg_live_key".to_string());
+        assert_eq!(response.key.as_deref(), Some("sg_live_key"));
+    }
+
+    #[test]
+    fn test_api_key_sortable_fields_to_string() {
+        assert_eq!(String::from(ApiKeySortableFields::Id), "id");
+        assert_eq!(
+            String::from(ApiKeySortableFields::ProjectAccessId),
+            "project_access_id"
+        );
+        assert_eq!(String::from(ApiKeySortableFields::Name), "name");
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A service account or source IP was locked out after too many failed authentications
+pub const AUDIT_EVENT_AUTHENTICATION_LOCKOUT: &str = "authentication_lockout";
+/// An administrator lifted the lockout of a service account or source IP
+pub const AUDIT_EVENT_AUTHENTICATION_UNLOCK: &str = "authentication_unlock";
+/// A service account was disabled because it expired
+pub const AUDIT_EVENT_SERVICE_ACCOUNT_EXPIRED: &str = "service_account_expired";
+/// A service account will be disabled soon unless a token is issued to it
+pub const AUDIT_EVENT_SERVICE_ACCOUNT_INACTIVITY_WARNING: &str =
+    "service_account_inactivity_warning";
+/// A service account was disabled because no token was issued to it for too long
+pub const AUDIT_EVENT_SERVICE_ACCOUNT_INACTIVITY_DISABLED: &str =
+    "service_account_inactivity_disabled";
+/// An environment key was rotated by the rotation scheduler
+pub const AUDIT_EVENT_ENVIRONMENT_KEY_ROTATED: &str = "environment_key_rotated";
+
+/// Security relevant event kept for later review
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub event_type: String,
+    pub subject_type: String,
+    pub subject: String,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AuditEventResponse {
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub id: String,
+    #[schema(example = "authentication_lockout")]
+    pub event_type: String,
+    #[schema(example = "service_account")]
+    pub subject_type: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub subject: String,
+    #[schema(example = "Locked for 900 seconds after 10 failed authentications")]
+    pub details: Option<String>,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub created_at: String,
+}
+
+impl From<AuditEvent> for AuditEventResponse {
+    fn from(value: AuditEvent) -> Self {
+        Self {
+            id: value.id.to_string(),
+            event_type: value.event_type,
+            subject_type: value.subject_type,
+            subject: value.subject,
+            details: value.details,
+            created_at: value.created_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct AuditEventFilter {
+    pub event_type: Option<String>,
+    pub subject_type: Option<String>,
+    pub subject: Option<String>,
+}
@@ -0,0 +1,141 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// What failed client authentications are counted against
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthenticationSubjectType {
+    /// A service account, identified by its ID
+    ServiceAccount,
+    /// A source IP address
+    Ip,
+}
+
+impl AuthenticationSubjectType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthenticationSubjectType::ServiceAccount => "service_account",
+            AuthenticationSubjectType::Ip => "ip",
+        }
+    }
+}
+
+impl fmt::Display for AuthenticationSubjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuthenticationSubjectType {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "service_account" => Ok(AuthenticationSubjectType::ServiceAccount),
+            "ip" => Ok(AuthenticationSubjectType::Ip),
+            _ => Err(Error::msg(format!("Invalid subject type: {}", value))),
+        }
+    }
+}
+
+/// Recent failed authentications of a service account or source IP
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthenticationFailure {
+    pub subject_type: String,
+    pub subject: String,
+    pub failure_count: i32,
+    pub last_failure_at: DateTime<Utc>,
+    /// Attempts are rejected without being checked until then
+    pub blocked_until: Option<DateTime<Utc>>,
+    /// When the subject was last locked out, as opposed to backing off
+    pub locked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuthenticationFailure {
+    pub fn is_locked(&self) -> bool {
+        self.locked_at.is_some() && self.blocked_until.is_some_and(|until| until > Utc::now())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AuthenticationFailureResponse {
+    #[schema(example = "service_account")]
+    pub subject_type: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub subject: String,
+    #[schema(example = 3)]
+    pub failure_count: i32,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub last_failure_at: String,
+    #[schema(example = "2025-06-16T03:48:26.000Z")]
+    pub blocked_until: Option<String>,
+    #[schema(example = "false")]
+    pub locked: bool,
+}
+
+impl From<AuthenticationFailure> for AuthenticationFailureResponse {
+    fn from(value: AuthenticationFailure) -> Self {
+        Self {
+            locked: value.is_locked(),
+            subject_type: value.subject_type,
+            subject: value.subject,
+            failure_count: value.failure_count,
+            last_failure_at: value.last_failure_at.to_string(),
+            blocked_until: value.blocked_until.map(|until| until.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct AuthenticationFailureFilter {
+    pub subject_type: Option<String>,
+    /// Only list subjects that are currently locked out
+    pub locked: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn failure(locked: bool, blocked_for: Duration) -> AuthenticationFailure {
+        AuthenticationFailure {
+            subject_type: "ip".to_string(),
+            subject: "203.0.113.7".to_string(),
+            failure_count: 1,
+            last_failure_at: Utc::now(),
+            blocked_until: Some(Utc::now() + blocked_for),
+            locked_at: locked.then(Utc::now),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_authentication_subject_type_from_str() {
+        assert_eq!(
+            "service_account"
+                .parse::<AuthenticationSubjectType>()
+                .unwrap(),
+            AuthenticationSubjectType::ServiceAccount
+        );
+        assert_eq!(
+            "ip".parse::<AuthenticationSubjectType>().unwrap(),
+            AuthenticationSubjectType::Ip
+        );
+        assert!("user".parse::<AuthenticationSubjectType>().is_err());
+        assert_eq!(AuthenticationSubjectType::Ip.to_string(), "ip");
+    }
+
+    #[test]
+    fn test_authentication_failure_is_locked() {
+        assert!(failure(true, Duration::minutes(5)).is_locked());
+        assert!(!failure(false, Duration::minutes(5)).is_locked());
+        assert!(!failure(true, Duration::minutes(-5)).is_locked());
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::sort::SortOrder;
+
+/// Claims set by the token endpoint itself, which templates may not override
+pub const RESERVED_CLAIMS: [&str; 12] = [
+    "iss",
+    "sub",
+    "aud",
+    "exp",
+    "nbf",
+    "iat",
+    "jti",
+    "scope",
+    "scopes",
+    "meta",
+    "act",
+    "client_id",
+];
+
+/// Placeholders that can be used in a template as `{{name}}`
+pub const PLACEHOLDERS: [&str; 8] = [
+    "service_account.id",
+    "service_account.name",
+    "service_account.email",
+    "project.id",
+    "project.name",
+    "environment.id",
+    "environment.name",
+    "project_access.id",
+];
+
+/// A custom claim rendered into the tokens of a project.
+///
+/// A template either applies to the whole project, to one of its environments or to a
+/// single project access. More specific templates replace less specific ones with the
+/// same claim name.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct ClaimTemplate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    pub project_id: Uuid,
+    pub environment_id: Option<Uuid>,
+    pub project_access_id: Option<Uuid>,
+    pub claim: String,
+    pub template: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ClaimTemplate {
+    /// Precedence of the template, higher values win
+    pub fn precedence(&self) -> u8 {
+        match (self.environment_id, self.project_access_id) {
+            (_, Some(_)) => 2,
+            (Some(_), None) => 1,
+            (None, None) => 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct ClaimTemplateResponse {
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub id: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub project_id: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub environment_id: Option<String>,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub project_access_id: Option<String>,
+    #[schema(example = "tenant")]
+    pub claim: String,
+    #[schema(example = "{{environment.name}}-{{service_account.email}}")]
+    pub template: String,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub created_at: String,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub updated_at: String,
+}
+
+impl From<ClaimTemplate> for ClaimTemplateResponse {
+    fn from(value: ClaimTemplate) -> Self {
+        Self {
+            id: value.id.unwrap().to_string(),
+            project_id: value.project_id.to_string(),
+            environment_id: value.environment_id.map(|id| id.to_string()),
+            project_access_id: value.project_access_id.map(|id| id.to_string()),
+            claim: value.claim,
+            template: value.template,
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct ClaimTemplateFilter {
+    pub project_id: Option<String>,
+    pub environment_id: Option<String>,
+    pub project_access_id: Option<String>,
+    pub claim: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ClaimTemplateCreatePayload {
+    pub project_id: String,
+    pub environment_id: Option<String>,
+    pub project_access_id: Option<String>,
+    pub claim: String,
+    pub template: String,
+}
+
+impl ClaimTemplateCreatePayload {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.environment_id.is_some() && self.project_access_id.is_some() {
+            return Err(Error::msg(
+                "A claim template applies to either an environment or a project access",
+            ));
+        }
+        validate_claim(&self.claim)?;
+        validate_template(&self.template)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ClaimTemplateUpdatePayload {
+    pub claim: Option<String>,
+    pub template: Option<String>,
+}
+
+impl ClaimTemplateUpdatePayload {
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(claim) = &self.claim {
+            validate_claim(claim)?;
+        }
+        if let Some(template) = &self.template {
+            validate_template(template)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rejects empty claim names and claims reserved for the token endpoint
+pub fn validate_claim(claim: &str) -> Result<(), Error> {
+    if claim.trim().is_empty() {
+        return Err(Error::msg("Claim name must not be empty"));
+    }
+    if RESERVED_CLAIMS.contains(&claim) {
+        return Err(Error::msg(format!(
+            "Claim {} is reserved and cannot be templated",
+            claim
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that every placeholder of a template is closed and known
+pub fn validate_template(template: &str) -> Result<(), Error> {
+    for placeholder in placeholders(template)? {
+        if !PLACEHOLDERS.contains(&placeholder) {
+            return Err(Error::msg(format!("Unknown placeholder: {}", placeholder)));
+        }
+    }
+    Ok(())
+}
+
+/// Lists the trimmed placeholder names of a template
+fn placeholders(template: &str) -> Result<Vec<&str>, Error> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| Error::msg("Unterminated placeholder in template"))?;
+        names.push(after[..end].trim());
+        rest = &after[end + 2..];
+    }
+    Ok(names)
+}
+
+/// Values available to placeholders when a token is issued
+#[derive(Debug, Clone, Default)]
+pub struct ClaimTemplateContext {
+    pub service_account_id: Uuid,
+    pub service_account_name: String,
+    pub service_account_email: String,
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub environment_id: Uuid,
+    pub environment_name: String,
+    pub project_access_id: Uuid,
+}
+
+impl ClaimTemplateContext {
+    fn value(&self, placeholder: &str) -> Option<String> {
+        match placeholder {
+            "service_account.id" => Some(self.service_account_id.to_string()),
+            "service_account.name" => Some(self.service_account_name.clone()),
+            "service_account.email" => Some(self.service_account_email.clone()),
+            "project.id" => Some(self.project_id.to_string()),
+            "project.name" => Some(self.project_name.clone()),
+            "environment.id" => Some(self.environment_id.to_string()),
+            "environment.name" => Some(self.environment_name.clone()),
+            "project_access.id" => Some(self.project_access_id.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Replaces the placeholders of a template with their values
+    pub fn render(&self, template: &str) -> Result<String, Error> {
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            rendered.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after
+                .find("}}")
+                .ok_or_else(|| Error::msg("Unterminated placeholder in template"))?;
+            let placeholder = after[..end].trim();
+            let value = self
+                .value(placeholder)
+                .ok_or_else(|| Error::msg(format!("Unknown placeholder: {}", placeholder)))?;
+            rendered.push_str(&value);
+            rest = &after[end + 2..];
+        }
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+
+    /// Renders the templates that apply to a token, more specific templates winning
+    pub fn render_claims(
+        &self,
+        templates: &[ClaimTemplate],
+    ) -> Result<HashMap<String, String>, Error> {
+        let mut ordered: Vec<&ClaimTemplate> = templates.iter().collect();
+        ordered.sort_by_key(|template| template.precedence());
+
+        let mut claims = HashMap::new();
+        for template in ordered {
+            claims.insert(template.claim.clone(), self.render(&template.template)?);
+        }
+        Ok(claims)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ClaimTemplateSortableFields {
+    Id,
+    ProjectId,
+    Claim,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl From<ClaimTemplateSortableFields> for String {
+    fn from(value: ClaimTemplateSortableFields) -> Self {
+        match value {
+            ClaimTemplateSortableFields::Id => "id".to_string(),
+            ClaimTemplateSortableFields::ProjectId => "project_id".to_string(),
+            ClaimTemplateSortableFields::Claim => "claim".to_string(),
+            ClaimTemplateSortableFields::CreatedAt => "created_at".to_string(),
+            ClaimTemplateSortableFields::UpdatedAt => "updated_at".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClaimTemplateSortOrder {
+    pub field: ClaimTemplateSortableFields,
+    pub order: SortOrder,
+}
+
+impl ClaimTemplateSortOrder {
+    pub fn new(field: ClaimTemplateSortableFields, order: SortOrder) -> Self {
+        Self { field, order }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> ClaimTemplateContext {
+        ClaimTemplateContext {
+            service_account_email: "worker@example.com".to_string(),
+            environment_name: "prod".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn template(claim: &str, template: &str, precedence: u8) -> ClaimTemplate {
+        ClaimTemplate {
+            claim: claim.to_string(),
+            template: template.to_string(),
+            environment_id: (precedence == 1).then(Uuid::new_v4),
+            project_access_id: (precedence == 2).then(Uuid::new_v4),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_claim_template_default() {
+        let claim_template = ClaimTemplate::default();
+        assert!(claim_template.id.is_none());
+        assert!(claim_template.environment_id.is_none());
+        assert!(claim_template.project_access_id.is_none());
+        assert_eq!(claim_template.precedence(), 0);
+    }
+
+    #[test]
+    fn test_validate_claim_rejects_reserved_claims() {
+        assert!(validate_claim("tenant").is_ok());
+        assert!(validate_claim("").is_err());
+        assert_eq!(
+            validate_claim("sub").unwrap_err().to_string(),
+            "Claim sub is reserved and cannot be templated"
+        );
+        assert!(validate_claim("exp").is_err());
+    }
+
+    #[test]
+    fn test_validate_template() {
+        assert!(validate_template("static").is_ok());
+        assert!(validate_template("{{ environment.name }}/{{service_account.email}}").is_ok());
+        assert_eq!(
+            validate_template("{{service_account.secret}}")
+                .unwrap_err()
+                .to_string(),
+            "Unknown placeholder: service_account.secret"
+        );
+        assert!(validate_template("{{environment.name").is_err());
+    }
+
+    #[test]
+    fn test_create_payload_rejects_two_levels() {
+        let payload = ClaimTemplateCreatePayload {
+            environment_id: Some(Uuid::new_v4().to_string()),
+            project_access_id: Some(Uuid::new_v4().to_string()),
+            claim: "tenant".to_string(),
+            template: "acme".to_string(),
+            ..Default::default()
+        };
+        assert!(payload.validate().is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let rendered = context()
+            .render("{{environment.name}}:{{ service_account.email }}!")
+            .unwrap();
+        assert_eq!(rendered, "prod:worker@example.com!");
+    }
+
+    #[test]
+    fn test_render_claims_prefers_specific_templates() {
+        let templates = vec![
+            template("tenant", "access", 2),
+            template("tenant", "project", 0),
+            template("region", "{{environment.name}}", 0),
+            template("tenant", "environment", 1),
+        ];
+
+        let claims = context().render_claims(&templates).unwrap();
+        assert_eq!(claims.get("tenant"), Some(&"access".to_string()));
+        assert_eq!(claims.get("region"), Some(&"prod".to_string()));
+    }
+
+    #[test]
+    fn test_claim_template_sortable_fields_to_string() {
+        assert_eq!(String::from(ClaimTemplateSortableFields::Id), "id");
+        assert_eq!(String::from(ClaimTemplateSortableFields::Claim), "claim");
+        assert_eq!(
+            String::from(ClaimTemplateSortableFields::ProjectId),
+            "project_id"
+        );
+    }
+}
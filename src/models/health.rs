@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub const HEALTH_STATUS_OK: &str = "ok";
+pub const HEALTH_STATUS_FAILING: &str = "failing";
+
+/// Outcome of a single readiness check
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct HealthCheck {
+    #[schema(example = "database")]
+    pub name: String,
+    #[schema(example = "ok")]
+    pub status: String,
+    /// What was checked, or why the check failed
+    #[schema(example = "Database is reachable")]
+    pub detail: String,
+}
+
+impl HealthCheck {
+    pub fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: HEALTH_STATUS_OK.to_string(),
+            detail: detail.into(),
+        }
+    }
+
+    pub fn failing(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: HEALTH_STATUS_FAILING.to_string(),
+            detail: detail.into(),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status == HEALTH_STATUS_OK
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct HealthResponse {
+    #[schema(example = "ok")]
+    pub status: String,
+    /// Individual checks, empty for the liveness probe
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthResponse {
+    /// Aggregates checks, the response is only ok when every check is
+    pub fn from_checks(checks: Vec<HealthCheck>) -> Self {
+        let status = if checks.iter().all(HealthCheck::is_ok) {
+            HEALTH_STATUS_OK
+        } else {
+            HEALTH_STATUS_FAILING
+        };
+        Self {
+            status: status.to_string(),
+            checks,
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status == HEALTH_STATUS_OK
+    }
+}
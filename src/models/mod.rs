@@ -1,10 +1,20 @@
 pub mod access_token;
+pub mod api_key;
+pub mod audit_event;
+pub mod authentication_failure;
+pub mod claim_template;
 pub mod environment;
 pub mod environment_key;
+pub mod health;
 pub mod pagination;
 pub mod project;
 pub mod project_access;
 pub mod project_access_scopes;
 pub mod project_scope;
+pub mod revocation_list;
 pub mod service_account;
+pub mod service_account_credential;
+pub mod service_account_key;
 pub mod sort;
+pub mod token;
+pub mod token_policy;
@@ -13,6 +13,8 @@ pub struct ProjectAccess {
     pub service_account_id: Uuid,
     pub environment_id: Uuid,
     pub enabled: bool,
+    /// CIDR ranges narrowing those of the service account, no narrowing when empty
+    pub allowed_cidrs: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -29,6 +31,8 @@ pub struct ProjectAccessResponse {
     pub environment_id: String,
     #[schema(example = "true")]
     pub enabled: bool,
+    #[schema(example = json!(["10.1.0.0/16"]))]
+    pub allowed_cidrs: Vec<String>,
     #[schema(example = "2025-06-16T03:48:22.000Z")]
     pub created_at: String,
     #[schema(example = "2025-06-16T03:48:22.000Z")]
@@ -43,6 +47,7 @@ impl From<ProjectAccess> for ProjectAccessResponse {
             service_account_id: value.service_account_id.to_string(),
             environment_id: value.environment_id.to_string(),
             enabled: value.enabled,
+            allowed_cidrs: value.allowed_cidrs,
             created_at: value.created_at.to_string(),
             updated_at: value.updated_at.to_string(),
         }
@@ -63,11 +68,17 @@ pub struct ProjectAccessCreatePayload {
     pub service_account_id: String,
     pub environment_id: String,
     pub enabled: bool,
+    /// CIDR ranges further restricting where tokens for this grant may be requested from
+    #[serde(default)]
+    pub allowed_cidrs: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct ProjectAccessUpdatePayload {
     pub enabled: Option<bool>,
+    /// Replaces the allowed CIDR ranges, an empty list removes the narrowing
+    #[serde(default)]
+    pub allowed_cidrs: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -117,6 +128,7 @@ mod tests {
         assert_eq!(project_access.service_account_id, Uuid::nil());
         assert_eq!(project_access.environment_id, Uuid::nil());
         assert!(!project_access.enabled);
+        assert!(project_access.allowed_cidrs.is_empty());
     }
 
     #[test]
@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Number of seconds a signed revocation list stays valid for verifiers
+pub const REVOCATION_LIST_TTL_SECONDS: i64 = 300;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct RevokedToken {
+    #[schema(example = "5b0c1f0e-4f7a-4a43-9d55-1b2f7f0e2c11")]
+    pub jti: String,
+    #[schema(example = 1751328000)]
+    pub exp: i64,
+}
+
+impl RevokedToken {
+    pub fn new(jti: impl Into<String>, expires_at: DateTime<Utc>) -> Self {
+        Self {
+            jti: jti.into(),
+            exp: expires_at.timestamp(),
+        }
+    }
+}
+
+/// Claims of the signed revocation list served to offline JWT verifiers
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RevocationListClaims {
+    /// Environment the revocation list belongs to
+    pub sub: String,
+    /// Issued at (as UTC timestamp)
+    pub iat: i64,
+    /// Expiration time of the list itself (as UTC timestamp)
+    pub exp: i64,
+    /// Revoked tokens that have not expired yet
+    pub revoked: Vec<RevokedToken>,
+}
+
+impl RevocationListClaims {
+    pub fn new(environment_id: Uuid, revoked: Vec<RevokedToken>) -> Self {
+        let now = Utc::now().timestamp();
+        Self {
+            sub: environment_id.to_string(),
+            iat: now,
+            exp: now + REVOCATION_LIST_TTL_SECONDS,
+            revoked,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct RevocationListQuery {
+    #[schema(example = "HS256")]
+    pub algorithm: Option<String>,
+}
+
+/// Computes a strong ETag for a revocation list issued at `issued_at`.
+///
+/// The tag depends on the revoked entries, on the signing key version and on the
+/// `REVOCATION_LIST_TTL_SECONDS` window the list is issued in, so it stays stable between
+/// polls even though every signed list carries a new `iat`. A list cached during a window
+/// is valid until the window ends, and the tag changes with the next one so verifiers get
+/// a fresh list before theirs expires.
+pub fn revocation_list_etag(
+    environment_id: Uuid,
+    key_version: &str,
+    revoked: &[RevokedToken],
+    issued_at: i64,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(environment_id.as_bytes());
+    hasher.update(key_version.as_bytes());
+    hasher.update(
+        issued_at
+            .div_euclid(REVOCATION_LIST_TTL_SECONDS)
+            .to_be_bytes(),
+    );
+    for token in revoked {
+        hasher.update(token.jti.as_bytes());
+        hasher.update(token.exp.to_be_bytes());
+    }
+    format!("\"{}\"", hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_revoked_token_new() {
+        let expires_at = Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+        let token = RevokedToken::new("jti-1", expires_at);
+        assert_eq!(token.jti, "jti-1");
+        assert_eq!(token.exp, expires_at.timestamp());
+    }
+
+    #[test]
+    fn test_revocation_list_claims_new() {
+        let environment_id = Uuid::new_v4();
+        let claims = RevocationListClaims::new(environment_id, Vec::new());
+        assert_eq!(claims.sub, environment_id.to_string());
+        assert_eq!(claims.exp - claims.iat, REVOCATION_LIST_TTL_SECONDS);
+        assert!(claims.revoked.is_empty());
+    }
+
+    #[test]
+    fn test_revocation_list_etag_is_stable() {
+        let environment_id = Uuid::new_v4();
+        let revoked = vec![RevokedToken {
+            jti: "jti-1".to_string(),
+            exp: 1893456000,
+        }];
+        let first = revocation_list_etag(environment_id, "v1", &revoked, 1751328000);
+        let second = revocation_list_etag(environment_id, "v1", &revoked, 1751328010);
+        assert_eq!(first, second);
+        assert!(first.starts_with('"') && first.ends_with('"'));
+    }
+
+    #[test]
+    fn test_revocation_list_etag_changes_with_content() {
+        let environment_id = Uuid::new_v4();
+        let revoked = vec![RevokedToken {
+            jti: "jti-1".to_string(),
+            exp: 1893456000,
+        }];
+        let etag = revocation_list_etag(environment_id, "v1", &revoked, 1751328000);
+        assert_ne!(
+            etag,
+            revocation_list_etag(environment_id, "v1", &[], 1751328000)
+        );
+        assert_ne!(
+            etag,
+            revocation_list_etag(environment_id, "v2", &revoked, 1751328000)
+        );
+    }
+
+    #[test]
+    fn test_revocation_list_etag_changes_before_cached_list_expires() {
+        let environment_id = Uuid::new_v4();
+        let issued_at = 1751328000 + 10;
+        let etag = revocation_list_etag(environment_id, "v1", &[], issued_at);
+        let expires_at = issued_at + REVOCATION_LIST_TTL_SECONDS;
+        assert_ne!(
+            etag,
+            revocation_list_etag(environment_id, "v1", &[], expires_at)
+        );
+    }
+}
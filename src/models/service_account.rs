@@ -15,10 +15,25 @@ pub struct ServiceAccount {
     pub secret: String,
     pub description: String,
     pub enabled: bool,
+    /// CIDR ranges tokens may be requested from, any address when empty
+    pub allowed_cidrs: Vec<String>,
+    /// The service account can no longer authenticate after this instant
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_token_issued_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl ServiceAccount {
+    /// Whether the service account is enabled and not expired
+    pub fn is_active(&self) -> bool {
+        self.enabled
+            && self
+                .expires_at
+                .is_none_or(|expires_at| expires_at > Utc::now())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
 pub struct ServiceAccountResponse {
     #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
@@ -33,6 +48,12 @@ pub struct ServiceAccountResponse {
     pub description: String,
     #[schema(example = "true")]
     pub enabled: bool,
+    #[schema(example = json!(["10.0.0.0/8"]))]
+    pub allowed_cidrs: Vec<String>,
+    #[schema(example = "2026-06-16T03:48:22.000Z")]
+    pub expires_at: Option<String>,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub last_token_issued_at: Option<String>,
     #[schema(example = "2025-06-16T03:48:22.000Z")]
     pub created_at: String,
     #[schema(example = "2025-06-16T03:48:22.000Z")]
@@ -48,6 +69,11 @@ impl From<ServiceAccount> for ServiceAccountResponse {
             secret: value.secret,
             description: value.description,
             enabled: value.enabled,
+            allowed_cidrs: value.allowed_cidrs,
+            expires_at: value.expires_at.map(|expires_at| expires_at.to_string()),
+            last_token_issued_at: value
+                .last_token_issued_at
+                .map(|issued_at| issued_at.to_string()),
             created_at: value.created_at.to_string(),
             updated_at: value.updated_at.to_string(),
         }
@@ -69,6 +95,13 @@ pub struct ServiceAccountCreatePayload {
     pub secret: String,
     pub description: String,
     pub enabled: bool,
+    /// CIDR ranges or single addresses tokens may be requested from, any address if omitted
+    #[serde(default)]
+    pub allowed_cidrs: Option<Vec<String>>,
+    /// RFC 3339 instant after which the service account can no longer authenticate
+    #[serde(default)]
+    #[schema(example = "2026-06-16T03:48:22Z")]
+    pub expires_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
@@ -78,6 +111,13 @@ pub struct ServiceAccountUpdatePayload {
     pub secret: Option<String>,
     pub description: Option<String>,
     pub enabled: Option<bool>,
+    /// Replaces the allowed CIDR ranges, an empty list allows any address
+    #[serde(default)]
+    pub allowed_cidrs: Option<Vec<String>>,
+    /// RFC 3339 instant after which the service account can no longer authenticate
+    #[serde(default)]
+    #[schema(example = "2026-06-16T03:48:22Z")]
+    pub expires_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -126,6 +166,27 @@ mod tests {
         assert_eq!(sa.secret, "");
         assert_eq!(sa.description, "");
         assert!(!sa.enabled);
+        assert!(sa.allowed_cidrs.is_empty());
+        assert!(sa.expires_at.is_none());
+    }
+
+    #[test]
+    fn test_service_account_is_active() {
+        let mut sa = ServiceAccount {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(sa.is_active());
+
+        sa.expires_at = Some(Utc::now() + chrono::Duration::days(1));
+        assert!(sa.is_active());
+
+        sa.expires_at = Some(Utc::now() - chrono::Duration::seconds(1));
+        assert!(!sa.is_active());
+
+        sa.expires_at = None;
+        sa.enabled = false;
+        assert!(!sa.is_active());
     }
 
     #[test]
@@ -0,0 +1,354 @@
+use anyhow::Error;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{models::sort::SortOrder, utils::security::generate_opaque_token};
+
+/// How long a rotated credential keeps working when the request does not say otherwise
+pub const DEFAULT_ROTATION_GRACE_PERIOD_SECONDS: i64 = 86_400;
+
+/// Longest grace period accepted when rotating a credential
+pub const MAX_ROTATION_GRACE_PERIOD_SECONDS: i64 = 30 * 86_400;
+
+/// Additional secret a service account can authenticate with.
+///
+/// A service account may hold several credentials at once, so a secret can be rotated
+/// while workloads still using the previous one keep working until it expires. Only the
+/// SHA-256 hash of the secret is stored.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct ServiceAccountCredential {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    pub service_account_id: Uuid,
+    pub label: String,
+    pub secret_hash: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ServiceAccountCredential {
+    /// Whether the credential can still be used to authenticate
+    pub fn is_active(&self) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| expires_at > Utc::now())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct ServiceAccountCredentialResponse {
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub id: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub service_account_id: String,
+    #[schema(example = "ci-2025-06")]
+    pub label: String,
+    /// Plaintext secret, only returned when the credential is created or rotated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    #[schema(example = "true")]
+    pub active: bool,
+    #[schema(example = "2025-07-16T03:48:22.000Z")]
+    pub expires_at: Option<String>,
+    #[schema(example = "2025-06-20T09:12:01.000Z")]
+    pub last_used_at: Option<String>,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub created_at: String,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub updated_at: String,
+}
+
+impl ServiceAccountCredentialResponse {
+    /// Builds the response returned once, right after the secret was generated
+    pub fn with_secret(credential: ServiceAccountCredential, secret: String) -> Self {
+        Self {
+            secret: Some(secret),
+            ..Self::from(credential)
+        }
+    }
+}
+
+impl From<ServiceAccountCredential> for ServiceAccountCredentialResponse {
+    fn from(value: ServiceAccountCredential) -> Self {
+        Self {
+            active: value.is_active(),
+            id: value.id.unwrap().to_string(),
+            service_account_id: value.service_account_id.to_string(),
+            label: value.label,
+            secret: None,
+            expires_at: value.expires_at.map(|expires_at| expires_at.to_string()),
+            last_used_at: value
+                .last_used_at
+                .map(|last_used_at| last_used_at.to_string()),
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct ServiceAccountCredentialFilter {
+    pub service_account_id: Option<String>,
+    /// Only list credentials that have not expired (`true`) or that have (`false`)
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ServiceAccountCredentialCreatePayload {
+    #[schema(example = "ci-2025-06")]
+    pub label: String,
+    /// RFC 3339 date after which the credential stops working
+    #[schema(example = "2025-12-31T23:59:59Z")]
+    pub expires_at: Option<String>,
+}
+
+impl ServiceAccountCredentialCreatePayload {
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_label(&self.label)?;
+        parse_expires_at(self.expires_at.as_deref())?;
+        Ok(())
+    }
+
+    /// Generates the secret of the credential for a service account
+    pub fn with_secret(
+        self,
+        service_account_id: Uuid,
+    ) -> ServiceAccountCredentialCreatePayloadWithSecret {
+        ServiceAccountCredentialCreatePayloadWithSecret {
+            service_account_id: service_account_id.to_string(),
+            label: self.label,
+            expires_at: self.expires_at,
+            secret: generate_opaque_token(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ServiceAccountCredentialCreatePayloadWithSecret {
+    pub service_account_id: String,
+    pub label: String,
+    pub expires_at: Option<String>,
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ServiceAccountCredentialUpdatePayload {
+    pub label: Option<String>,
+    /// RFC 3339 date after which the credential stops working
+    pub expires_at: Option<String>,
+}
+
+impl ServiceAccountCredentialUpdatePayload {
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(label) = &self.label {
+            validate_label(label)?;
+        }
+        parse_expires_at(self.expires_at.as_deref())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ServiceAccountCredentialRotatePayload {
+    /// Label of the new credential, defaults to the label of the rotated one
+    pub label: Option<String>,
+    /// How long the rotated credential keeps working, defaults to one day
+    #[schema(example = 86400)]
+    pub grace_period_seconds: Option<i64>,
+    /// RFC 3339 date after which the new credential stops working
+    pub expires_at: Option<String>,
+}
+
+impl ServiceAccountCredentialRotatePayload {
+    /// Checks the payload and returns the grace period of the rotated credential
+    pub fn validate(&self) -> Result<Duration, Error> {
+        if let Some(label) = &self.label {
+            validate_label(label)?;
+        }
+        parse_expires_at(self.expires_at.as_deref())?;
+
+        let grace_period_seconds = self
+            .grace_period_seconds
+            .unwrap_or(DEFAULT_ROTATION_GRACE_PERIOD_SECONDS);
+        if !(0..=MAX_ROTATION_GRACE_PERIOD_SECONDS).contains(&grace_period_seconds) {
+            return Err(Error::msg(format!(
+                "Grace period must be between 0 and {} seconds",
+                MAX_ROTATION_GRACE_PERIOD_SECONDS
+            )));
+        }
+        Ok(Duration::seconds(grace_period_seconds))
+    }
+}
+
+fn validate_label(label: &str) -> Result<(), Error> {
+    if label.trim().is_empty() {
+        return Err(Error::msg("Credential label must not be empty"));
+    }
+    Ok(())
+}
+
+/// Parses an optional RFC 3339 expiry, which must lie in the future
+pub fn parse_expires_at(expires_at: Option<&str>) -> Result<Option<DateTime<Utc>>, Error> {
+    let Some(expires_at) = expires_at else {
+        return Ok(None);
+    };
+    let expires_at = DateTime::parse_from_rfc3339(expires_at)
+        .map_err(|_| Error::msg("Invalid expiration date"))?
+        .with_timezone(&Utc);
+    if expires_at <= Utc::now() {
+        return Err(Error::msg("Expiration date must be in the future"));
+    }
+    Ok(Some(expires_at))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ServiceAccountCredentialSortableFields {
+    Id,
+    ServiceAccountId,
+    Label,
+    ExpiresAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl From<ServiceAccountCredentialSortableFields> for String {
+    fn from(value: ServiceAccountCredentialSortableFields) -> Self {
+        match value {
+            ServiceAccountCredentialSortableFields::Id => "id".to_string(),
+            ServiceAccountCredentialSortableFields::ServiceAccountId => {
+                "service_account_id".to_string()
+            }
+            ServiceAccountCredentialSortableFields::Label => "label".to_string(),
+            ServiceAccountCredentialSortableFields::ExpiresAt => "expires_at".to_string(),
+            ServiceAccountCredentialSortableFields::CreatedAt => "created_at".to_string(),
+            ServiceAccountCredentialSortableFields::UpdatedAt => "updated_at".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceAccountCredentialSortOrder {
+    pub field: ServiceAccountCredentialSortableFields,
+    pub order: SortOrder,
+}
+
+impl ServiceAccountCredentialSortOrder {
+    pub fn new(field: ServiceAccountCredentialSortableFields, order: SortOrder) -> Self {
+        Self { field, order }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_account_credential_is_active() {
+        let mut credential = ServiceAccountCredential::default();
+        assert!(credential.is_active());
+
+        credential.expires_at = Some(Utc::now() + Duration::minutes(5));
+        assert!(credential.is_active());
+
+        credential.expires_at = Some(Utc::now() - Duration::seconds(1));
+        assert!(!credential.is_active());
+    }
+
+    #[test]
+    fn test_create_payload_validate() {
+        let payload = ServiceAccountCredentialCreatePayload {
+            label: "ci".to_string(),
+            expires_at: None,
+        };
+        assert!(payload.validate().is_ok());
+
+        let payload = ServiceAccountCredentialCreatePayload {
+            label: " ".to_string(),
+            expires_at: None,
+        };
+        assert_eq!(
+            payload.validate().unwrap_err().to_string(),
+            "Credential label must not be empty"
+        );
+
+        let payload = ServiceAccountCredentialCreatePayload {
+            label: "ci".to_string(),
+            expires_at: Some("2020-01-01T00:00:00Z".to_string()),
+        };
+        assert_eq!(
+            payload.validate().unwrap_err().to_string(),
+            "Expiration date must be in the future"
+        );
+    }
+
+    #[test]
+    fn test_create_payload_with_secret_generates_distinct_secrets() {
+        let payload = ServiceAccountCredentialCreatePayload {
+            label: "ci".to_string(),
+            expires_at: None,
+        };
+        let service_account_id = Uuid::new_v4();
+
+        let first = payload.clone().with_secret(service_account_id);
+        let second = payload.with_secret(service_account_id);
+        assert_eq!(first.service_account_id, service_account_id.to_string());
+        assert_ne!(first.secret, second.secret);
+    }
+
+    #[test]
+    fn test_rotate_payload_validate() {
+        let payload = ServiceAccountCredentialRotatePayload::default();
+        assert_eq!(
+            payload.validate().unwrap(),
+            Duration::seconds(DEFAULT_ROTATION_GRACE_PERIOD_SECONDS)
+        );
+
+        let payload = ServiceAccountCredentialRotatePayload {
+            grace_period_seconds: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(payload.validate().unwrap(), Duration::zero());
+
+        let payload = ServiceAccountCredentialRotatePayload {
+            grace_period_seconds: Some(-1),
+            ..Default::default()
+        };
+        assert!(payload.validate().is_err());
+    }
+
+    #[test]
+    fn test_response_only_exposes_secret_when_requested() {
+        let credential = ServiceAccountCredential {
+            id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+
+        let response =
+            serde_json::to_value(ServiceAccountCredentialResponse::from(credential.clone()))
+                .unwrap();
+        assert!(response.get("secret").is_none());
+        assert!(response.get("secret_hash").is_none());
+
+        let response = ServiceAccountCredentialResponse::with_secret(credential, "s3cret".into());
+        assert_eq!(response.secret.as_deref(), Some("s3cret"));
+    }
+
+    #[test]
+    fn test_service_account_credential_sortable_fields_to_string() {
+        assert_eq!(
+            String::from(ServiceAccountCredentialSortableFields::Id),
+            "id"
+        );
+        assert_eq!(
+            String::from(ServiceAccountCredentialSortableFields::Label),
+            "label"
+        );
+        assert_eq!(
+            String::from(ServiceAccountCredentialSortableFields::ExpiresAt),
+            "expires_at"
+        );
+    }
+}
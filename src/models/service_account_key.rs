@@ -0,0 +1,195 @@
+use std::str::FromStr;
+
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::Algorithm;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{models::sort::SortOrder, utils::tokens::key_builder::KeyBuilder};
+
+/// Public key a service account signs client assertions with (RFC 7523)
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct ServiceAccountKey {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    pub service_account_id: Uuid,
+    pub name: String,
+    pub algorithm: String,
+    pub public_key: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct ServiceAccountKeyResponse {
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub id: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub service_account_id: String,
+    #[schema(example = "ci-deployer-2025")]
+    pub name: String,
+    #[schema(example = "RS256")]
+    pub algorithm: String,
+    #[schema(example = "-----BEGIN PUBLIC KEY-----\n...\n-----END PUBLIC KEY-----\n")]
+    pub public_key: String,
+    #[schema(example = "true")]
+    pub enabled: bool,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub created_at: String,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub updated_at: String,
+}
+
+impl From<ServiceAccountKey> for ServiceAccountKeyResponse {
+    fn from(value: ServiceAccountKey) -> Self {
+        Self {
+            id: value.id.unwrap().to_string(),
+            service_account_id: value.service_account_id.to_string(),
+            name: value.name,
+            algorithm: value.algorithm,
+            public_key: value.public_key,
+            enabled: value.enabled,
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct ServiceAccountKeyFilter {
+    pub service_account_id: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ServiceAccountKeyCreatePayload {
+    pub service_account_id: String,
+    pub name: String,
+    /// Algorithm of the client assertions signed with the key
+    #[schema(example = "RS256")]
+    pub algorithm: String,
+    /// RSA or EC public key in PEM format
+    pub public_key: String,
+    pub enabled: Option<bool>,
+}
+
+impl ServiceAccountKeyCreatePayload {
+    /// Checks the key and returns it re-encoded as a PEM public key
+    pub fn validate(&self) -> Result<String, Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::msg("Key name must not be empty"));
+        }
+
+        let algorithm = Algorithm::from_str(&self.algorithm)
+            .map_err(|_| Error::msg(format!("Invalid algorithm: {}", self.algorithm)))?;
+        let (key_type, public_key) = KeyBuilder::public_key_from_pem(&self.public_key)
+            .map_err(|_| Error::msg("Invalid public key"))?;
+        if !key_type.supports(algorithm) {
+            return Err(Error::msg(format!(
+                "Public key cannot verify {:?} signatures",
+                algorithm
+            )));
+        }
+
+        Ok(public_key)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ServiceAccountKeyUpdatePayload {
+    pub name: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ServiceAccountKeySortableFields {
+    Id,
+    ServiceAccountId,
+    Name,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl From<ServiceAccountKeySortableFields> for String {
+    fn from(value: ServiceAccountKeySortableFields) -> Self {
+        match value {
+            ServiceAccountKeySortableFields::Id => "id".to_string(),
+            ServiceAccountKeySortableFields::ServiceAccountId => "service_account_id".to_string(),
+            ServiceAccountKeySortableFields::Name => "name".to_string(),
+            ServiceAccountKeySortableFields::CreatedAt => "created_at".to_string(),
+            ServiceAccountKeySortableFields::UpdatedAt => "updated_at".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceAccountKeySortOrder {
+    pub field: ServiceAccountKeySortableFields,
+    pub order: SortOrder,
+}
+
+impl ServiceAccountKeySortOrder {
+    pub fn new(field: ServiceAccountKeySortableFields, order: SortOrder) -> Self {
+        Self { field, order }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(algorithm: &str, public_key: String) -> ServiceAccountKeyCreatePayload {
+        ServiceAccountKeyCreatePayload {
+            service_account_id: Uuid::new_v4().to_string(),
+            name: "deployer".to_string(),
+            algorithm: algorithm.to_string(),
+            public_key,
+            enabled: None,
+        }
+    }
+
+    #[test]
+    fn test_create_payload_validate() {
+        let key_pair = KeyBuilder::new().generate_key(Algorithm::RS256).unwrap();
+        let public_key = key_pair.public_key_str.unwrap();
+
+        assert_eq!(
+            payload("RS256", public_key.clone()).validate().unwrap(),
+            public_key
+        );
+        assert_eq!(
+            payload("XX256", public_key.clone())
+                .validate()
+                .unwrap_err()
+                .to_string(),
+            "Invalid algorithm: XX256"
+        );
+        assert_eq!(
+            payload("ES256", public_key.clone())
+                .validate()
+                .unwrap_err()
+                .to_string(),
+            "Public key cannot verify ES256 signatures"
+        );
+        assert_eq!(
+            payload("RS256", key_pair.private_key_str)
+                .validate()
+                .unwrap_err()
+                .to_string(),
+            "Invalid public key"
+        );
+    }
+
+    #[test]
+    fn test_service_account_key_sortable_fields_to_string() {
+        assert_eq!(String::from(ServiceAccountKeySortableFields::Id), "id");
+        assert_eq!(
+            String::from(ServiceAccountKeySortableFields::ServiceAccountId),
+            "service_account_id"
+        );
+        assert_eq!(String::from(ServiceAccountKeySortableFields::Name), "name");
+    }
+}
@@ -0,0 +1,256 @@
+use actix_web::{HttpResponse, ResponseError, http::StatusCode};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub const GRANT_TYPE_CLIENT_CREDENTIALS: &str = "client_credentials";
+pub const GRANT_TYPE_REFRESH_TOKEN: &str = "refresh_token";
+pub const GRANT_TYPE_TOKEN_EXCHANGE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+
+pub const TOKEN_TYPE_ACCESS_TOKEN: &str = "urn:ietf:params:oauth:token-type:access_token";
+pub const TOKEN_TYPE_JWT: &str = "urn:ietf:params:oauth:token-type:jwt";
+
+pub const CLIENT_ASSERTION_TYPE_JWT_BEARER: &str =
+    "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+pub const TOKEN_USE_ACCESS: &str = "access";
+pub const TOKEN_USE_REFRESH: &str = "refresh";
+
+/// Form body of a token request (RFC 6749 section 4.4 and 6, RFC 8693 section 2.1)
+///
+/// Clients authenticate with `client_id`/`client_secret`, with HTTP Basic authentication or
+/// with a `client_assertion` JWT signed by one of their registered keys (RFC 7523). The client
+/// ID is the service account ID.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct TokenRequest {
+    #[schema(example = "client_credentials")]
+    pub grant_type: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub client_id: Option<String>,
+    #[schema(example = "supersecretvalue")]
+    pub client_secret: Option<String>,
+    #[schema(example = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer")]
+    pub client_assertion_type: Option<String>,
+    /// JWT signed by the client, with the service account ID as `iss` and `sub` and the token
+    /// endpoint URL as `aud`
+    pub client_assertion: Option<String>,
+    /// Environment the token is issued for; required by the `client_credentials` grant
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub environment_id: Option<String>,
+    /// Space separated project scopes, defaults to every scope granted to the service account
+    #[schema(example = "read write")]
+    pub scope: Option<String>,
+    /// Signing algorithm, defaults to the first algorithm allowed by the token policy
+    #[schema(example = "RS256")]
+    pub algorithm: Option<String>,
+    /// Requested lifetime in seconds, capped by the token policy
+    #[schema(example = 900)]
+    pub expires_in: Option<i64>,
+    pub refresh_token: Option<String>,
+    /// Token of the caller the client acts on behalf of; required by the token exchange grant
+    pub subject_token: Option<String>,
+    #[schema(example = "urn:ietf:params:oauth:token-type:access_token")]
+    pub subject_token_type: Option<String>,
+    #[schema(example = "urn:ietf:params:oauth:token-type:access_token")]
+    pub requested_token_type: Option<String>,
+}
+
+impl TokenRequest {
+    /// Splits the requested scopes, dropping duplicates
+    pub fn scopes(&self) -> Option<Vec<String>> {
+        self.scope.as_ref().map(|scope| {
+            let mut scopes: Vec<String> = Vec::new();
+            for scope in scope.split_whitespace() {
+                if !scopes.iter().any(|existing| existing == scope) {
+                    scopes.push(scope.to_string());
+                }
+            }
+            scopes
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TokenResponse {
+    #[schema(example = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...")]
+    pub access_token: String,
+    #[schema(example = "Bearer")]
+    pub token_type: String,
+    #[schema(example = 900)]
+    pub expires_in: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "read write")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "Jz5b6bQ0u7m1oPZ3cC2vT0aR9nKx8yWq4eF1hL6dS2U")]
+    pub refresh_token: Option<String>,
+    /// Type of the issued token, only returned by the token exchange grant
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "urn:ietf:params:oauth:token-type:access_token")]
+    pub issued_token_type: Option<String>,
+}
+
+/// Claims of a client assertion read by the token endpoint
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClientAssertionClaims {
+    pub iss: String,
+    pub sub: String,
+    pub exp: i64,
+    pub jti: Option<String>,
+}
+
+/// Error returned by the token endpoint (RFC 6749 section 5.2)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenError {
+    InvalidRequest(String),
+    InvalidClient,
+    InvalidGrant(String),
+    UnauthorizedClient(String),
+    UnsupportedGrantType(String),
+    InvalidScope(String),
+    ServerError(String),
+    /// Client authentication is blocked after repeated failures, for the given seconds
+    TooManyAttempts(i64),
+    /// The authenticated client exhausted its rate limit, for the given seconds
+    RateLimited(u64),
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TokenErrorResponse {
+    #[schema(example = "invalid_scope")]
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "Scope admin is not granted")]
+    pub error_description: Option<String>,
+}
+
+impl TokenError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            TokenError::InvalidRequest(_) => "invalid_request",
+            TokenError::InvalidClient | TokenError::TooManyAttempts(_) => "invalid_client",
+            TokenError::InvalidGrant(_) => "invalid_grant",
+            TokenError::UnauthorizedClient(_) => "unauthorized_client",
+            TokenError::UnsupportedGrantType(_) => "unsupported_grant_type",
+            TokenError::InvalidScope(_) => "invalid_scope",
+            TokenError::ServerError(_) => "server_error",
+            TokenError::RateLimited(_) => "rate_limited",
+        }
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            TokenError::InvalidClient => Some("Client authentication failed"),
+            TokenError::TooManyAttempts(_) => {
+                Some("Too many failed authentication attempts, retry later")
+            }
+            TokenError::RateLimited(_) => Some("Rate limit exceeded, retry later"),
+            TokenError::InvalidRequest(description)
+            | TokenError::InvalidGrant(description)
+            | TokenError::UnauthorizedClient(description)
+            | TokenError::UnsupportedGrantType(description)
+            | TokenError::InvalidScope(description) => Some(description),
+            // Internal details are not leaked to clients
+            TokenError::ServerError(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenError::ServerError(description) => write!(f, "{}: {}", self.code(), description),
+            _ => match self.description() {
+                Some(description) => write!(f, "{}: {}", self.code(), description),
+                None => write!(f, "{}", self.code()),
+            },
+        }
+    }
+}
+
+impl ResponseError for TokenError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            TokenError::InvalidClient => StatusCode::UNAUTHORIZED,
+            TokenError::TooManyAttempts(_) | TokenError::RateLimited(_) => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            TokenError::ServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        response.insert_header(("Cache-Control", "no-store"));
+        if let TokenError::InvalidClient = self {
+            response.insert_header(("WWW-Authenticate", "Basic realm=\"sentinel-guard\""));
+        }
+        match self {
+            TokenError::TooManyAttempts(retry_after) => {
+                response.insert_header(("Retry-After", retry_after.to_string()));
+            }
+            TokenError::RateLimited(retry_after) => {
+                response.insert_header(("Retry-After", retry_after.to_string()));
+            }
+            _ => {}
+        }
+        response.json(TokenErrorResponse {
+            error: self.code().to_string(),
+            error_description: self.description().map(str::to_string),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_request_scopes() {
+        let request = TokenRequest {
+            scope: Some(" read  write read ".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            request.scopes(),
+            Some(vec!["read".to_string(), "write".to_string()])
+        );
+        assert_eq!(TokenRequest::default().scopes(), None);
+    }
+
+    #[test]
+    fn test_token_error_status_codes() {
+        assert_eq!(
+            TokenError::InvalidClient.status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            TokenError::InvalidScope("admin".to_string()).status_code(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            TokenError::ServerError("database".to_string()).status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            TokenError::TooManyAttempts(30).status_code(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[test]
+    fn test_token_error_too_many_attempts_sets_retry_after() {
+        let response = TokenError::TooManyAttempts(30).error_response();
+        assert_eq!(response.headers().get("Retry-After").unwrap(), "30");
+        let response = TokenError::RateLimited(12).error_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get("Retry-After").unwrap(), "12");
+    }
+
+    #[test]
+    fn test_token_error_hides_server_error_details() {
+        let error = TokenError::ServerError("connection refused".to_string());
+        assert_eq!(error.code(), "server_error");
+        assert!(error.description().is_none());
+    }
+}
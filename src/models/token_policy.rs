@@ -0,0 +1,460 @@
+use std::str::FromStr;
+
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::Algorithm;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::sort::SortOrder;
+
+pub const DEFAULT_MAX_TTL_SECONDS: i64 = 3600;
+pub const DEFAULT_TTL_SECONDS: i64 = 900;
+pub const DEFAULT_REFRESH_TOKEN_TTL_SECONDS: i64 = 86400;
+
+/// Token policy of a project, or an override of it for a single environment.
+///
+/// Every setting is optional: unset settings fall back to the project policy and then
+/// to the built-in defaults of `EffectiveTokenPolicy`.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct TokenPolicy {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    pub project_id: Uuid,
+    pub environment_id: Option<Uuid>,
+    pub max_ttl_seconds: Option<i64>,
+    pub default_ttl_seconds: Option<i64>,
+    pub audience: Option<Vec<String>>,
+    pub issuer: Option<String>,
+    pub allowed_algorithms: Option<Vec<String>>,
+    pub allow_refresh_tokens: Option<bool>,
+    pub refresh_token_ttl_seconds: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct TokenPolicyResponse {
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub id: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub project_id: String,
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub environment_id: Option<String>,
+    #[schema(example = 3600)]
+    pub max_ttl_seconds: Option<i64>,
+    #[schema(example = 900)]
+    pub default_ttl_seconds: Option<i64>,
+    #[schema(example = json!(["https://api.example.com"]))]
+    pub audience: Option<Vec<String>>,
+    #[schema(example = "https://sentinel-guard.example.com")]
+    pub issuer: Option<String>,
+    #[schema(example = json!(["RS256", "HS256"]))]
+    pub allowed_algorithms: Option<Vec<String>>,
+    #[schema(example = "false")]
+    pub allow_refresh_tokens: Option<bool>,
+    #[schema(example = 86400)]
+    pub refresh_token_ttl_seconds: Option<i64>,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub created_at: String,
+    #[schema(example = "2025-06-16T03:48:22.000Z")]
+    pub updated_at: String,
+}
+
+impl From<TokenPolicy> for TokenPolicyResponse {
+    fn from(value: TokenPolicy) -> Self {
+        Self {
+            id: value.id.unwrap().to_string(),
+            project_id: value.project_id.to_string(),
+            environment_id: value.environment_id.map(|id| id.to_string()),
+            max_ttl_seconds: value.max_ttl_seconds,
+            default_ttl_seconds: value.default_ttl_seconds,
+            audience: value.audience,
+            issuer: value.issuer,
+            allowed_algorithms: value.allowed_algorithms,
+            allow_refresh_tokens: value.allow_refresh_tokens,
+            refresh_token_ttl_seconds: value.refresh_token_ttl_seconds,
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct TokenPolicyFilter {
+    pub project_id: Option<String>,
+    pub environment_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct TokenPolicyCreatePayload {
+    pub project_id: String,
+    pub environment_id: Option<String>,
+    pub max_ttl_seconds: Option<i64>,
+    pub default_ttl_seconds: Option<i64>,
+    pub audience: Option<Vec<String>>,
+    pub issuer: Option<String>,
+    pub allowed_algorithms: Option<Vec<String>>,
+    pub allow_refresh_tokens: Option<bool>,
+    pub refresh_token_ttl_seconds: Option<i64>,
+}
+
+impl TokenPolicyCreatePayload {
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_settings(
+            self.max_ttl_seconds,
+            self.default_ttl_seconds,
+            self.refresh_token_ttl_seconds,
+            self.allowed_algorithms.as_deref(),
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct TokenPolicyUpdatePayload {
+    pub max_ttl_seconds: Option<i64>,
+    pub default_ttl_seconds: Option<i64>,
+    pub audience: Option<Vec<String>>,
+    pub issuer: Option<String>,
+    pub allowed_algorithms: Option<Vec<String>>,
+    pub allow_refresh_tokens: Option<bool>,
+    pub refresh_token_ttl_seconds: Option<i64>,
+}
+
+impl TokenPolicyUpdatePayload {
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_settings(
+            self.max_ttl_seconds,
+            self.default_ttl_seconds,
+            self.refresh_token_ttl_seconds,
+            self.allowed_algorithms.as_deref(),
+        )
+    }
+}
+
+fn validate_settings(
+    max_ttl_seconds: Option<i64>,
+    default_ttl_seconds: Option<i64>,
+    refresh_token_ttl_seconds: Option<i64>,
+    allowed_algorithms: Option<&[String]>,
+) -> Result<(), Error> {
+    for (name, value) in [
+        ("max_ttl_seconds", max_ttl_seconds),
+        ("default_ttl_seconds", default_ttl_seconds),
+        ("refresh_token_ttl_seconds", refresh_token_ttl_seconds),
+    ] {
+        if value.is_some_and(|value| value <= 0) {
+            return Err(Error::msg(format!("{} must be positive", name)));
+        }
+    }
+
+    if let (Some(max_ttl_seconds), Some(default_ttl_seconds)) =
+        (max_ttl_seconds, default_ttl_seconds)
+        && default_ttl_seconds > max_ttl_seconds
+    {
+        return Err(Error::msg(
+            "default_ttl_seconds must not exceed max_ttl_seconds",
+        ));
+    }
+
+    if let Some(allowed_algorithms) = allowed_algorithms {
+        if allowed_algorithms.is_empty() {
+            return Err(Error::msg("allowed_algorithms must not be empty"));
+        }
+        for algorithm in allowed_algorithms {
+            Algorithm::from_str(algorithm)
+                .map_err(|_| Error::msg(format!("Invalid algorithm: {}", algorithm)))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Token policy applying to an environment once the environment override, the project
+/// policy and the built-in defaults have been merged
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct EffectiveTokenPolicy {
+    #[schema(example = 3600)]
+    pub max_ttl_seconds: i64,
+    #[schema(example = 900)]
+    pub default_ttl_seconds: i64,
+    #[schema(example = json!(["https://api.example.com"]))]
+    pub audience: Vec<String>,
+    #[schema(example = "https://sentinel-guard.example.com")]
+    pub issuer: Option<String>,
+    #[schema(value_type = Vec<String>, example = json!(["RS256", "HS256"]))]
+    pub allowed_algorithms: Vec<Algorithm>,
+    #[schema(example = "false")]
+    pub allow_refresh_tokens: bool,
+    #[schema(example = 86400)]
+    pub refresh_token_ttl_seconds: i64,
+}
+
+impl Default for EffectiveTokenPolicy {
+    fn default() -> Self {
+        Self {
+            max_ttl_seconds: DEFAULT_MAX_TTL_SECONDS,
+            default_ttl_seconds: DEFAULT_TTL_SECONDS,
+            audience: Vec::new(),
+            issuer: None,
+            allowed_algorithms: vec![
+                Algorithm::HS256,
+                Algorithm::HS384,
+                Algorithm::HS512,
+                Algorithm::RS256,
+                Algorithm::RS384,
+                Algorithm::RS512,
+                Algorithm::PS256,
+                Algorithm::PS384,
+                Algorithm::PS512,
+            ],
+            allow_refresh_tokens: false,
+            refresh_token_ttl_seconds: DEFAULT_REFRESH_TOKEN_TTL_SECONDS,
+        }
+    }
+}
+
+impl EffectiveTokenPolicy {
+    /// Merges the project policy and the environment override on top of the defaults
+    pub fn resolve(
+        project: Option<&TokenPolicy>,
+        environment: Option<&TokenPolicy>,
+    ) -> Result<Self, Error> {
+        Self::default().merge(project, environment)
+    }
+
+    /// Merges the project policy and the environment override on top of this policy
+    pub fn merge(
+        self,
+        project: Option<&TokenPolicy>,
+        environment: Option<&TokenPolicy>,
+    ) -> Result<Self, Error> {
+        let mut policy = self;
+        for layer in [project, environment].into_iter().flatten() {
+            if let Some(max_ttl_seconds) = layer.max_ttl_seconds {
+                policy.max_ttl_seconds = max_ttl_seconds;
+            }
+            if let Some(default_ttl_seconds) = layer.default_ttl_seconds {
+                policy.default_ttl_seconds = default_ttl_seconds;
+            }
+            if let Some(audience) = &layer.audience {
+                policy.audience = audience.clone();
+            }
+            if let Some(issuer) = &layer.issuer {
+                policy.issuer = Some(issuer.clone());
+            }
+            if let Some(allowed_algorithms) = &layer.allowed_algorithms {
+                policy.allowed_algorithms = allowed_algorithms
+                    .iter()
+                    .map(|algorithm| Algorithm::from_str(algorithm))
+                    .collect::<Result<_, _>>()?;
+            }
+            if let Some(allow_refresh_tokens) = layer.allow_refresh_tokens {
+                policy.allow_refresh_tokens = allow_refresh_tokens;
+            }
+            if let Some(refresh_token_ttl_seconds) = layer.refresh_token_ttl_seconds {
+                policy.refresh_token_ttl_seconds = refresh_token_ttl_seconds;
+            }
+        }
+
+        // An environment may lower the maximum below a default inherited from the project
+        policy.default_ttl_seconds = policy.default_ttl_seconds.min(policy.max_ttl_seconds);
+        Ok(policy)
+    }
+
+    /// Returns the lifetime of a new token, rejecting requests above the maximum TTL
+    pub fn ttl_seconds(&self, requested: Option<i64>) -> Result<i64, Error> {
+        match requested {
+            None => Ok(self.default_ttl_seconds),
+            Some(requested) if requested <= 0 => {
+                Err(Error::msg("Requested token lifetime must be positive"))
+            }
+            Some(requested) if requested > self.max_ttl_seconds => Err(Error::msg(format!(
+                "Requested token lifetime exceeds the maximum of {} seconds",
+                self.max_ttl_seconds
+            ))),
+            Some(requested) => Ok(requested),
+        }
+    }
+
+    pub fn is_algorithm_allowed(&self, algorithm: Algorithm) -> bool {
+        self.allowed_algorithms.contains(&algorithm)
+    }
+
+    pub fn ensure_algorithm_allowed(&self, algorithm: Algorithm) -> Result<(), Error> {
+        if !self.is_algorithm_allowed(algorithm) {
+            return Err(Error::msg(format!(
+                "Algorithm {:?} is not allowed by the token policy",
+                algorithm
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum TokenPolicySortableFields {
+    Id,
+    ProjectId,
+    EnvironmentId,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl From<TokenPolicySortableFields> for String {
+    fn from(value: TokenPolicySortableFields) -> Self {
+        match value {
+            TokenPolicySortableFields::Id => "id".to_string(),
+            TokenPolicySortableFields::ProjectId => "project_id".to_string(),
+            TokenPolicySortableFields::EnvironmentId => "environment_id".to_string(),
+            TokenPolicySortableFields::CreatedAt => "created_at".to_string(),
+            TokenPolicySortableFields::UpdatedAt => "updated_at".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TokenPolicySortOrder {
+    pub field: TokenPolicySortableFields,
+    pub order: SortOrder,
+}
+
+impl TokenPolicySortOrder {
+    pub fn new(field: TokenPolicySortableFields, order: SortOrder) -> Self {
+        Self { field, order }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(environment_id: Option<Uuid>) -> TokenPolicy {
+        TokenPolicy {
+            id: Some(Uuid::new_v4()),
+            project_id: Uuid::new_v4(),
+            environment_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_token_policy_default() {
+        let policy = TokenPolicy::default();
+        assert!(policy.id.is_none());
+        assert!(policy.environment_id.is_none());
+        assert!(policy.max_ttl_seconds.is_none());
+        assert!(policy.allowed_algorithms.is_none());
+    }
+
+    #[test]
+    fn test_effective_token_policy_defaults() {
+        let policy = EffectiveTokenPolicy::resolve(None, None).unwrap();
+        assert_eq!(policy, EffectiveTokenPolicy::default());
+        assert!(!policy.allow_refresh_tokens);
+        assert!(policy.is_algorithm_allowed(Algorithm::RS256));
+        assert!(!policy.is_algorithm_allowed(Algorithm::ES256));
+    }
+
+    #[test]
+    fn test_effective_token_policy_environment_overrides_project() {
+        let project = TokenPolicy {
+            max_ttl_seconds: Some(7200),
+            default_ttl_seconds: Some(1800),
+            issuer: Some("project-issuer".to_string()),
+            audience: Some(vec!["project-api".to_string()]),
+            ..policy(None)
+        };
+        let environment = TokenPolicy {
+            max_ttl_seconds: Some(600),
+            allowed_algorithms: Some(vec!["RS256".to_string()]),
+            allow_refresh_tokens: Some(true),
+            ..policy(Some(Uuid::new_v4()))
+        };
+
+        let effective = EffectiveTokenPolicy::resolve(Some(&project), Some(&environment)).unwrap();
+        assert_eq!(effective.max_ttl_seconds, 600);
+        assert_eq!(effective.default_ttl_seconds, 600);
+        assert_eq!(effective.issuer, Some("project-issuer".to_string()));
+        assert_eq!(effective.audience, vec!["project-api".to_string()]);
+        assert_eq!(effective.allowed_algorithms, vec![Algorithm::RS256]);
+        assert!(effective.allow_refresh_tokens);
+    }
+
+    #[test]
+    fn test_effective_token_policy_ttl_seconds() {
+        let policy = EffectiveTokenPolicy::default();
+        assert_eq!(policy.ttl_seconds(None).unwrap(), DEFAULT_TTL_SECONDS);
+        assert_eq!(policy.ttl_seconds(Some(60)).unwrap(), 60);
+        assert!(policy.ttl_seconds(Some(0)).is_err());
+        assert!(
+            policy
+                .ttl_seconds(Some(DEFAULT_MAX_TTL_SECONDS + 1))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_effective_token_policy_ensure_algorithm_allowed() {
+        let policy = EffectiveTokenPolicy {
+            allowed_algorithms: vec![Algorithm::HS256],
+            ..Default::default()
+        };
+        assert!(policy.ensure_algorithm_allowed(Algorithm::HS256).is_ok());
+        let error = policy
+            .ensure_algorithm_allowed(Algorithm::RS256)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Algorithm RS256 is not allowed by the token policy"
+        );
+    }
+
+    #[test]
+    fn test_token_policy_create_payload_validate() {
+        let payload = TokenPolicyCreatePayload {
+            project_id: Uuid::new_v4().to_string(),
+            max_ttl_seconds: Some(600),
+            default_ttl_seconds: Some(300),
+            allowed_algorithms: Some(vec!["HS256".to_string()]),
+            ..Default::default()
+        };
+        assert!(payload.validate().is_ok());
+
+        let payload = TokenPolicyCreatePayload {
+            max_ttl_seconds: Some(300),
+            default_ttl_seconds: Some(600),
+            ..Default::default()
+        };
+        assert!(payload.validate().is_err());
+
+        let payload = TokenPolicyCreatePayload {
+            allowed_algorithms: Some(vec!["XX256".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            payload.validate().unwrap_err().to_string(),
+            "Invalid algorithm: XX256"
+        );
+
+        let payload = TokenPolicyUpdatePayload {
+            max_ttl_seconds: Some(-1),
+            ..Default::default()
+        };
+        assert!(payload.validate().is_err());
+    }
+
+    #[test]
+    fn test_token_policy_sortable_fields_to_string() {
+        assert_eq!(String::from(TokenPolicySortableFields::Id), "id");
+        assert_eq!(
+            String::from(TokenPolicySortableFields::ProjectId),
+            "project_id"
+        );
+        assert_eq!(
+            String::from(TokenPolicySortableFields::EnvironmentId),
+            "environment_id"
+        );
+    }
+}
@@ -3,15 +3,18 @@ use std::sync::Arc;
 use anyhow::Error;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::{QueryBuilder, Row};
+use sqlx::{PgExecutor, QueryBuilder, Row};
 use uuid::Uuid;
 
 use crate::{
+    config::TokenCleanupMode,
     models::{
         access_token::{
-            AccessToken, AccessTokenCreatePayloadWithAccessToken, AccessTokenFilter, AccessTokenSortOrder, AccessTokenUpdatePayload
+            AccessToken, AccessTokenCreatePayloadWithAccessToken, AccessTokenFilter,
+            AccessTokenSortOrder, AccessTokenUpdatePayload,
         },
         pagination::Pagination,
+        revocation_list::RevokedToken,
     },
     repositories::base::Repository,
 };
@@ -26,21 +29,97 @@ impl AccessTokenRepository {
         Self { pool }
     }
 
-}
+    /// Lists the revoked access tokens of an environment that have not expired yet
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find_revoked_by_environment(
+        &self,
+        environment_id: Uuid,
+    ) -> Result<Vec<RevokedToken>, Error> {
+        let rows = sqlx::query!(
+            "SELECT access_tokens.jti, access_tokens.expires_at FROM access_tokens INNER JOIN project_access ON project_access.id = access_tokens.project_access_id WHERE project_access.environment_id = $1 AND access_tokens.token_use = 'access' AND access_tokens.active = false AND access_tokens.expires_at > now() ORDER BY access_tokens.expires_at, access_tokens.jti",
+            environment_id,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
 
-#[async_trait]
-impl Repository<AccessToken> for AccessTokenRepository {
-    type CreatePayload = AccessTokenCreatePayloadWithAccessToken;
-    type UpdatePayload = AccessTokenUpdatePayload;
-    type Filter = AccessTokenFilter;
-    type Sort = AccessTokenSortOrder;
+        Ok(rows
+            .into_iter()
+            .map(|row| RevokedToken::new(row.jti, row.expires_at))
+            .collect())
+    }
 
-    async fn create(&self, item: Self::CreatePayload) -> Result<AccessToken, Error> {
+    /// Returns the active, unexpired access token with the given jti issued in an environment
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find_active_by_jti(
+        &self,
+        jti: &str,
+        environment_id: Uuid,
+    ) -> Result<Option<AccessToken>, Error> {
+        sqlx::query_as!(
+            AccessToken,
+            "SELECT access_tokens.id, access_tokens.jti, access_tokens.project_access_id, access_tokens.algorithm, access_tokens.token, access_tokens.token_use, access_tokens.expires_at, access_tokens.active, access_tokens.created_at, access_tokens.updated_at FROM access_tokens INNER JOIN project_access ON project_access.id = access_tokens.project_access_id WHERE access_tokens.jti = $1 AND project_access.environment_id = $2 AND access_tokens.token_use = 'access' AND access_tokens.active = true AND access_tokens.expires_at > now() LIMIT 1",
+            jti,
+            environment_id,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)
+    }
+
+    /// Returns an active, unexpired refresh token without using it up.
+    ///
+    /// `token_hash` is the SHA-256 hash stored in place of the refresh token itself.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<AccessToken>, Error> {
+        sqlx::query_as!(
+            AccessToken,
+            "SELECT id, jti, project_access_id, algorithm, token, token_use, expires_at, active, created_at, updated_at FROM access_tokens WHERE token = $1 AND token_use = 'refresh' AND active = true AND expires_at > now()",
+            token_hash,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)
+    }
+
+    /// Deactivates an unexpired refresh token and returns it, so each refresh token is used once.
+    ///
+    /// `token_hash` is the SHA-256 hash stored in place of the refresh token itself.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn consume_refresh_token<'e, E>(
+        executor: E,
+        token_hash: &str,
+    ) -> Result<Option<AccessToken>, Error>
+    where
+        E: PgExecutor<'e>,
+    {
+        sqlx::query_as!(
+            AccessToken,
+            "UPDATE access_tokens SET active = false, updated_at = now() WHERE token = $1 AND token_use = 'refresh' AND active = true AND expires_at > now() RETURNING id, jti, project_access_id, algorithm, token, token_use, expires_at, active, created_at, updated_at",
+            token_hash,
+        )
+        .fetch_optional(executor)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)
+    }
+
+    /// Records an issued token with `executor`, so it can be stored in the transaction that
+    /// consumes the refresh token it replaces
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn insert<'e, E>(
+        executor: E,
+        item: AccessTokenCreatePayloadWithAccessToken,
+    ) -> Result<AccessToken, Error>
+    where
+        E: PgExecutor<'e>,
+    {
         let access_token = AccessToken {
             id: None,
+            jti: item.jti.unwrap_or_default(),
             project_access_id: item.project_access_id.parse().unwrap(),
             algorithm: item.algorithm,
             token: item.access_token,
+            token_use: item.token_use,
             active: true,
             expires_at: DateTime::parse_from_rfc3339(&item.expires_at)
                 .unwrap()
@@ -51,14 +130,16 @@ impl Repository<AccessToken> for AccessTokenRepository {
 
         let created_access_token = sqlx::query_as!(
             AccessToken,
-            "INSERT INTO access_tokens (project_access_id, algorithm, token, expires_at, active) VALUES ($1, $2, $3, $4, $5) RETURNING id, project_access_id, algorithm, token, expires_at, active, created_at, updated_at",
+            "INSERT INTO access_tokens (jti, project_access_id, algorithm, token, token_use, expires_at, active) VALUES (COALESCE(NULLIF($1, ''), gen_random_uuid()::text), $2, $3, $4, $5, $6, $7) RETURNING id, jti, project_access_id, algorithm, token, token_use, expires_at, active, created_at, updated_at",
+            access_token.jti,
             access_token.project_access_id,
             access_token.algorithm,
             access_token.token,
+            access_token.token_use,
             access_token.expires_at,
             access_token.active,
         )
-        .fetch_one(&*self.pool)
+        .fetch_one(executor)
         .await;
 
         match created_access_token {
@@ -67,12 +148,58 @@ impl Repository<AccessToken> for AccessTokenRepository {
         }
     }
 
+    /// Removes up to `batch_size` tokens that expired before `cutoff` and returns how many were removed.
+    ///
+    /// Revoked tokens that have not expired yet are kept, since offline verifiers still need
+    /// them in the revocation list.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn purge_expired(
+        &self,
+        cutoff: DateTime<Utc>,
+        batch_size: i64,
+        mode: TokenCleanupMode,
+    ) -> Result<u64, Error> {
+        let removed = match mode {
+            TokenCleanupMode::Delete => sqlx::query!(
+                "DELETE FROM access_tokens WHERE id IN (SELECT id FROM access_tokens WHERE expires_at < $1 ORDER BY expires_at LIMIT $2 FOR UPDATE SKIP LOCKED)",
+                cutoff,
+                batch_size,
+            )
+            .execute(&*self.pool)
+            .await
+            .map(|result| result.rows_affected()),
+            // Counts the deleted rows, archived copies already present are skipped by the insert
+            TokenCleanupMode::Archive => sqlx::query_scalar!(
+                r#"WITH purged AS (DELETE FROM access_tokens WHERE id IN (SELECT id FROM access_tokens WHERE expires_at < $1 ORDER BY expires_at LIMIT $2 FOR UPDATE SKIP LOCKED) RETURNING id, jti, project_access_id, algorithm, token, token_use, expires_at, active, created_at, updated_at), archived AS (INSERT INTO access_tokens_archive (id, jti, project_access_id, algorithm, token, token_use, expires_at, active, created_at, updated_at) SELECT id, jti, project_access_id, algorithm, token, token_use, expires_at, active, created_at, updated_at FROM purged ON CONFLICT (id) DO NOTHING) SELECT count(*) AS "removed!" FROM purged"#,
+                cutoff,
+                batch_size,
+            )
+            .fetch_one(&*self.pool)
+            .await
+            .map(|removed| removed as u64),
+        };
+
+        removed.map_err(<sqlx::Error as Into<Error>>::into)
+    }
+}
+
+#[async_trait]
+impl Repository<AccessToken> for AccessTokenRepository {
+    type CreatePayload = AccessTokenCreatePayloadWithAccessToken;
+    type UpdatePayload = AccessTokenUpdatePayload;
+    type Filter = AccessTokenFilter;
+    type Sort = AccessTokenSortOrder;
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn create(&self, item: Self::CreatePayload) -> Result<AccessToken, Error> {
+        Self::insert(&*self.pool, item).await
+    }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn read(&self, id: Uuid) -> Result<Option<AccessToken>, Error> {
         let access_token = sqlx::query_as!(
             AccessToken,
-            "SELECT id, project_access_id, algorithm, token, expires_at, active, created_at, updated_at FROM access_tokens WHERE id = $1 LIMIT 1",
+            "SELECT id, jti, project_access_id, algorithm, token, token_use, expires_at, active, created_at, updated_at FROM access_tokens WHERE id = $1 LIMIT 1",
             id,
         )
         .fetch_optional(&*self.pool)
@@ -86,6 +213,7 @@ impl Repository<AccessToken> for AccessTokenRepository {
         Ok(access_token)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn update(&self, id: Uuid, update: Self::UpdatePayload) -> Result<AccessToken, Error> {
         let mut changes = Vec::new();
 
@@ -113,7 +241,7 @@ impl Repository<AccessToken> for AccessTokenRepository {
         }
         query.push(", updated_at = ").push_bind(Utc::now());
         query.push(" WHERE id = ").push_bind(id);
-        query.push(" RETURNING id, project_access_id, algorithm, token, expires_at, active, created_at, updated_at");
+        query.push(" RETURNING id, jti, project_access_id, algorithm, token, token_use, expires_at, active, created_at, updated_at");
 
         let result = query
             .build()
@@ -121,9 +249,11 @@ impl Repository<AccessToken> for AccessTokenRepository {
             .await
             .map(|row| AccessToken {
                 id: row.get("id"),
+                jti: row.get("jti"),
                 project_access_id: row.get("project_access_id"),
                 algorithm: row.get("algorithm"),
                 token: row.get("token"),
+                token_use: row.get("token_use"),
                 expires_at: row.get("expires_at"),
                 active: row.get("active"),
                 created_at: row.get("created_at"),
@@ -136,6 +266,7 @@ impl Repository<AccessToken> for AccessTokenRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn delete(&self, id: Uuid) -> Result<bool, Error> {
         let deleted = sqlx::query!("DELETE FROM access_tokens WHERE id = $1 RETURNING id", id)
             .fetch_optional(&*self.pool)
@@ -149,6 +280,7 @@ impl Repository<AccessToken> for AccessTokenRepository {
         Ok(true)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn find(
         &self,
         filter: Self::Filter,
@@ -156,7 +288,7 @@ impl Repository<AccessToken> for AccessTokenRepository {
         pagination: Option<Pagination>,
     ) -> Result<Vec<AccessToken>, Error> {
         let mut query = QueryBuilder::new(
-            "SELECT id, project_access_id, algorithm, token, active, expires_at, created_at, updated_at FROM access_tokens ",
+            "SELECT id, jti, project_access_id, algorithm, token, token_use, active, expires_at, created_at, updated_at FROM access_tokens ",
         );
 
         let mut conditions_list: Vec<(&str, String)> = Vec::new();
@@ -212,9 +344,11 @@ impl Repository<AccessToken> for AccessTokenRepository {
             .into_iter()
             .map(|row| AccessToken {
                 id: row.get("id"),
+                jti: row.get("jti"),
                 project_access_id: row.get("project_access_id"),
                 algorithm: row.get("algorithm"),
                 token: row.get("token"),
+                token_use: row.get("token_use"),
                 expires_at: row.get("expires_at"),
                 active: row.get("active"),
                 created_at: row.get("created_at"),
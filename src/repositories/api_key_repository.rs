@@ -0,0 +1,297 @@
+use std::sync::Arc;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        api_key::{
+            ApiKey, ApiKeyCreatePayload, ApiKeyCreatePayloadWithKey, ApiKeyFilter, ApiKeySortOrder,
+            ApiKeyUpdatePayload, ApiKeyVerification, lookup_prefix,
+        },
+        pagination::Pagination,
+        service_account_credential::parse_expires_at,
+    },
+    repositories::{
+        base::Repository, project_access_scopes_repository::ProjectAccessScopesRepository,
+    },
+    utils::security::{hash_token, secrets_equal},
+};
+
+#[derive(Clone)]
+pub struct ApiKeyRepository {
+    pub pool: Arc<sqlx::postgres::PgPool>,
+    pub project_access_scopes_repository: ProjectAccessScopesRepository,
+}
+
+impl ApiKeyRepository {
+    pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
+        Self {
+            project_access_scopes_repository: ProjectAccessScopesRepository::new(pool.clone()),
+            pool,
+        }
+    }
+
+    /// Resolves an API key and records its use.
+    ///
+    /// Only enabled, unexpired keys whose project access, project, environment and service
+    /// account are all enabled, and whose service account has not expired, resolve. Scopes no
+    /// longer granted to the project access are dropped from the result.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn verify(&self, key: &str) -> Result<Option<ApiKeyVerification>, Error> {
+        let Some(prefix) = lookup_prefix(key) else {
+            return Ok(None);
+        };
+
+        let row = sqlx::query!(
+            "SELECT api_keys.id, api_keys.name, api_keys.key_hash, api_keys.scopes, api_keys.expires_at, project_access.id AS project_access_id, project_access.service_account_id, projects.id AS project_id, projects.name AS project_name, environment.id AS environment_id, environment.name AS environment_name FROM api_keys INNER JOIN project_access ON project_access.id = api_keys.project_access_id INNER JOIN projects ON projects.id = project_access.project_id INNER JOIN environment ON environment.id = project_access.environment_id INNER JOIN service_account ON service_account.id = project_access.service_account_id WHERE api_keys.prefix = $1 AND api_keys.enabled = true AND (api_keys.expires_at IS NULL OR api_keys.expires_at > now()) AND project_access.enabled = true AND projects.enabled = true AND environment.enabled = true AND service_account.enabled = true AND (service_account.expires_at IS NULL OR service_account.expires_at > now()) LIMIT 1",
+            prefix,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        if !secrets_equal(&hash_token(key), &row.key_hash) {
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            "UPDATE api_keys SET last_used_at = now() WHERE id = $1",
+            row.id
+        )
+        .execute(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let granted = self
+            .project_access_scopes_repository
+            .find_scope_names(row.project_access_id)
+            .await?;
+
+        Ok(Some(ApiKeyVerification {
+            api_key_id: row.id.to_string(),
+            name: row.name,
+            project_access_id: row.project_access_id.to_string(),
+            service_account_id: row.service_account_id.to_string(),
+            project_id: row.project_id.to_string(),
+            project_name: row.project_name,
+            environment_id: row.environment_id.to_string(),
+            environment_name: row.environment_name,
+            scopes: row
+                .scopes
+                .into_iter()
+                .filter(|scope| granted.contains(scope))
+                .collect(),
+            expires_at: row.expires_at.map(|expires_at| expires_at.to_string()),
+        }))
+    }
+
+    fn map_error(error: sqlx::Error) -> Error {
+        match error {
+            sqlx::Error::RowNotFound => Error::msg("API key not found"),
+            sqlx::Error::Database(e) => {
+                let error_message = e.message();
+                tracing::warn!(error = error_message, "Database rejected the query");
+
+                match error_message {
+                    s if s.contains("unique constraint") || s.contains("duplicate key") => {
+                        Error::msg("API key already exists")
+                    }
+                    s if s.contains("foreign key")
+                        && s.contains("api_keys_project_access_id_fkey") =>
+                    {
+                        Error::msg("Project access not found")
+                    }
+                    _ => Error::msg("No changes were made"),
+                }
+            }
+            _ => error.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<ApiKey> for ApiKeyRepository {
+    type CreatePayload = ApiKeyCreatePayloadWithKey;
+    type UpdatePayload = ApiKeyUpdatePayload;
+    type Filter = ApiKeyFilter;
+    type Sort = ApiKeySortOrder;
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn create(&self, item: Self::CreatePayload) -> Result<ApiKey, Error> {
+        let payload = ApiKeyCreatePayload {
+            project_access_id: item.project_access_id,
+            name: item.name,
+            scopes: item.scopes,
+            expires_at: item.expires_at,
+        };
+        payload.validate()?;
+        let expires_at = parse_expires_at(payload.expires_at.as_deref())?;
+        let project_access_id = Uuid::parse_str(&payload.project_access_id)
+            .map_err(|_| Error::msg("Invalid project access ID"))?;
+
+        let granted = self
+            .project_access_scopes_repository
+            .find_scope_names(project_access_id)
+            .await?;
+        let scopes = match payload.scopes {
+            None => granted,
+            Some(requested) => {
+                if let Some(scope) = requested.iter().find(|scope| !granted.contains(scope)) {
+                    return Err(Error::msg(format!(
+                        "Scope {} is not granted to the project access",
+                        scope
+                    )));
+                }
+                requested
+            }
+        };
+
+        sqlx::query_as!(
+            ApiKey,
+            "INSERT INTO api_keys (project_access_id, name, prefix, key_hash, scopes, expires_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id, project_access_id, name, prefix, key_hash, scopes, enabled, expires_at, last_used_at, created_at, updated_at",
+            project_access_id,
+            payload.name,
+            item.prefix,
+            hash_token(&item.key),
+            &scopes,
+            expires_at,
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(Self::map_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn read(&self, id: Uuid) -> Result<Option<ApiKey>, Error> {
+        let api_key = sqlx::query_as!(
+            ApiKey,
+            "SELECT id, project_access_id, name, prefix, key_hash, scopes, enabled, expires_at, last_used_at, created_at, updated_at FROM api_keys WHERE id = $1 LIMIT 1",
+            id,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if api_key.is_none() {
+            return Err(Error::msg("API key not found"));
+        }
+
+        Ok(api_key)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn update(&self, id: Uuid, update: Self::UpdatePayload) -> Result<ApiKey, Error> {
+        if update.name.is_none() && update.enabled.is_none() && update.expires_at.is_none() {
+            return Err(Error::msg("No changes to update"));
+        }
+        update.validate()?;
+        let expires_at = parse_expires_at(update.expires_at.as_deref())?;
+
+        sqlx::query_as!(
+            ApiKey,
+            "UPDATE api_keys SET name = COALESCE($1, name), enabled = COALESCE($2, enabled), expires_at = COALESCE($3, expires_at), updated_at = $4 WHERE id = $5 RETURNING id, project_access_id, name, prefix, key_hash, scopes, enabled, expires_at, last_used_at, created_at, updated_at",
+            update.name,
+            update.enabled,
+            expires_at,
+            Utc::now(),
+            id,
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(Self::map_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn delete(&self, id: Uuid) -> Result<bool, Error> {
+        let deleted = sqlx::query!("DELETE FROM api_keys WHERE id = $1 RETURNING id", id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if deleted.is_none() {
+            return Err(Error::msg("API key not found"));
+        }
+
+        Ok(true)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn find(
+        &self,
+        filter: Self::Filter,
+        sort: Option<Vec<Self::Sort>>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<ApiKey>, Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, project_access_id, name, prefix, key_hash, scopes, enabled, expires_at, last_used_at, created_at, updated_at FROM api_keys ",
+        );
+
+        let project_access_id = filter
+            .project_access_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| Error::msg("Invalid project access ID"))?;
+
+        if project_access_id.is_some() || filter.enabled.is_some() {
+            query.push("WHERE ");
+            let mut conditions = query.separated(" AND ");
+            if let Some(project_access_id) = project_access_id {
+                conditions
+                    .push("project_access_id = ")
+                    .push_bind_unseparated(project_access_id);
+            }
+            if let Some(enabled) = filter.enabled {
+                conditions.push("enabled = ").push_bind_unseparated(enabled);
+            }
+        }
+
+        if let Some(sort) = sort {
+            query.push(" ORDER BY ");
+            let mut order_by = query.separated(", ");
+            for sort in sort {
+                let field = String::from(sort.field);
+                order_by.push(format!("{} {}", field, sort.order));
+            }
+        }
+
+        if let Some(pagination) = pagination {
+            query
+                .push(" LIMIT ")
+                .push_bind(pagination.limit.unwrap_or(10));
+            query
+                .push(" OFFSET ")
+                .push_bind(pagination.offset.unwrap_or(0));
+        }
+
+        let api_keys = query
+            .build()
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?
+            .into_iter()
+            .map(|row| ApiKey {
+                id: row.get("id"),
+                project_access_id: row.get("project_access_id"),
+                name: row.get("name"),
+                prefix: row.get("prefix"),
+                key_hash: row.get("key_hash"),
+                scopes: row.get("scopes"),
+                enabled: row.get("enabled"),
+                expires_at: row.get("expires_at"),
+                last_used_at: row.get("last_used_at"),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+            })
+            .collect();
+
+        Ok(api_keys)
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use anyhow::Error;
+use sqlx::{PgExecutor, QueryBuilder, Row};
+
+use crate::models::{
+    audit_event::{AuditEvent, AuditEventFilter},
+    pagination::Pagination,
+};
+
+#[derive(Clone)]
+pub struct AuditEventRepository {
+    pub pool: Arc<sqlx::postgres::PgPool>,
+}
+
+impl AuditEventRepository {
+    pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// Records an event with any executor, so it can be part of the transaction causing it
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn insert<'e, E>(
+        executor: E,
+        event_type: &str,
+        subject_type: &str,
+        subject: &str,
+        details: Option<String>,
+    ) -> Result<AuditEvent, Error>
+    where
+        E: PgExecutor<'e>,
+    {
+        sqlx::query_as!(
+            AuditEvent,
+            "INSERT INTO audit_events (event_type, subject_type, subject, details) VALUES ($1, $2, $3, $4) RETURNING id, event_type, subject_type, subject, details, created_at",
+            event_type,
+            subject_type,
+            subject,
+            details,
+        )
+        .fetch_one(executor)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)
+    }
+
+    /// Lists events, most recent first
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find(
+        &self,
+        filter: AuditEventFilter,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<AuditEvent>, Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, event_type, subject_type, subject, details, created_at FROM audit_events ",
+        );
+
+        let conditions_list: Vec<(&str, String)> = [
+            ("event_type = ", filter.event_type),
+            ("subject_type = ", filter.subject_type),
+            ("subject = ", filter.subject),
+        ]
+        .into_iter()
+        .filter_map(|(condition, value)| value.map(|value| (condition, value)))
+        .collect();
+
+        if !conditions_list.is_empty() {
+            query.push("WHERE ");
+            let mut conditions = query.separated(" AND ");
+            for (condition, value) in conditions_list {
+                conditions.push(condition).push_bind_unseparated(value);
+            }
+        }
+
+        query.push(" ORDER BY created_at DESC, id");
+
+        if let Some(pagination) = pagination {
+            query
+                .push(" LIMIT ")
+                .push_bind(pagination.limit.unwrap_or(10));
+            query
+                .push(" OFFSET ")
+                .push_bind(pagination.offset.unwrap_or(0));
+        }
+
+        let events = query
+            .build()
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?
+            .into_iter()
+            .map(|row| AuditEvent {
+                id: row.get("id"),
+                event_type: row.get("event_type"),
+                subject_type: row.get("subject_type"),
+                subject: row.get("subject"),
+                details: row.get("details"),
+                created_at: row.get("created_at"),
+            })
+            .collect();
+
+        Ok(events)
+    }
+}
@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use sqlx::{QueryBuilder, Row};
+
+use crate::{
+    config::LockoutConfig,
+    models::{
+        audit_event::{AUDIT_EVENT_AUTHENTICATION_LOCKOUT, AUDIT_EVENT_AUTHENTICATION_UNLOCK},
+        authentication_failure::{
+            AuthenticationFailure, AuthenticationFailureFilter, AuthenticationSubjectType,
+        },
+        pagination::Pagination,
+    },
+    repositories::audit_event_repository::AuditEventRepository,
+};
+
+#[derive(Clone)]
+pub struct AuthenticationFailureRepository {
+    pub pool: Arc<sqlx::postgres::PgPool>,
+}
+
+impl AuthenticationFailureRepository {
+    pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// Returns until when the first blocked subject among `subjects` is blocked, if any is
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find_blocked_until(
+        &self,
+        subjects: &[(AuthenticationSubjectType, String)],
+    ) -> Result<Option<DateTime<Utc>>, Error> {
+        let subject_types: Vec<String> = subjects
+            .iter()
+            .map(|(subject_type, _)| subject_type.to_string())
+            .collect();
+        let subject_values: Vec<String> = subjects
+            .iter()
+            .map(|(_, subject)| subject.clone())
+            .collect();
+
+        let row = sqlx::query!(
+            "SELECT MAX(blocked_until) AS blocked_until FROM authentication_failures WHERE (subject_type, subject) IN (SELECT * FROM UNNEST($1::text[], $2::text[])) AND blocked_until > now()",
+            &subject_types,
+            &subject_values,
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(row.blocked_until)
+    }
+
+    /// Counts a failed authentication of a subject and blocks it according to `config`.
+    ///
+    /// The count restarts when the last failure is older than the window of `config`.
+    /// Reaching the lockout threshold records an audit event in the same transaction.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn record_failure(
+        &self,
+        subject_type: AuthenticationSubjectType,
+        subject: &str,
+        config: &LockoutConfig,
+    ) -> Result<AuthenticationFailure, Error> {
+        let window_start = Utc::now() - chrono::Duration::seconds(config.failure_window_seconds);
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let failure_count = sqlx::query_scalar!(
+            "INSERT INTO authentication_failures (subject_type, subject, failure_count, last_failure_at) VALUES ($1, $2, 1, now()) ON CONFLICT (subject_type, subject) DO UPDATE SET failure_count = CASE WHEN authentication_failures.last_failure_at < $3 THEN 1 ELSE authentication_failures.failure_count + 1 END, last_failure_at = now() RETURNING failure_count",
+            subject_type.as_str(),
+            subject,
+            window_start,
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let (block, locked) = config.block_after(failure_count);
+        let failure = sqlx::query_as!(
+            AuthenticationFailure,
+            "UPDATE authentication_failures SET blocked_until = $1, locked_at = CASE WHEN $2 THEN now() ELSE NULL END WHERE subject_type = $3 AND subject = $4 RETURNING subject_type, subject, failure_count, last_failure_at, blocked_until, locked_at, created_at",
+            Utc::now() + block,
+            locked,
+            subject_type.as_str(),
+            subject,
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if locked {
+            AuditEventRepository::insert(
+                &mut *transaction,
+                AUDIT_EVENT_AUTHENTICATION_LOCKOUT,
+                subject_type.as_str(),
+                subject,
+                Some(format!(
+                    "Locked for {} seconds after {} failed authentications",
+                    block.num_seconds(),
+                    failure_count
+                )),
+            )
+            .await?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(failure)
+    }
+
+    /// Removes up to `batch_size` subjects that are no longer blocked and whose last failure
+    /// is older than `window_start`, returning how many were removed
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn purge_stale(
+        &self,
+        window_start: DateTime<Utc>,
+        batch_size: i64,
+    ) -> Result<u64, Error> {
+        sqlx::query!(
+            "DELETE FROM authentication_failures WHERE (subject_type, subject) IN (SELECT subject_type, subject FROM authentication_failures WHERE last_failure_at < $1 AND (blocked_until IS NULL OR blocked_until < now()) ORDER BY last_failure_at LIMIT $2 FOR UPDATE SKIP LOCKED)",
+            window_start,
+            batch_size,
+        )
+        .execute(&*self.pool)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(<sqlx::Error as Into<Error>>::into)
+    }
+
+    /// Forgets the failures of a subject after it authenticated successfully
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn clear(
+        &self,
+        subject_type: AuthenticationSubjectType,
+        subject: &str,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "DELETE FROM authentication_failures WHERE subject_type = $1 AND subject = $2",
+            subject_type.as_str(),
+            subject,
+        )
+        .execute(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(())
+    }
+
+    /// Lifts the block of a subject and records who was unlocked
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn unlock(
+        &self,
+        subject_type: AuthenticationSubjectType,
+        subject: &str,
+    ) -> Result<(), Error> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let deleted = sqlx::query!(
+            "DELETE FROM authentication_failures WHERE subject_type = $1 AND subject = $2 RETURNING failure_count",
+            subject_type.as_str(),
+            subject,
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?
+        .ok_or_else(|| Error::msg("Authentication failures not found"))?;
+
+        AuditEventRepository::insert(
+            &mut *transaction,
+            AUDIT_EVENT_AUTHENTICATION_UNLOCK,
+            subject_type.as_str(),
+            subject,
+            Some(format!(
+                "Unlocked after {} failed authentications",
+                deleted.failure_count
+            )),
+        )
+        .await?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find(
+        &self,
+        filter: AuthenticationFailureFilter,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<AuthenticationFailure>, Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT subject_type, subject, failure_count, last_failure_at, blocked_until, locked_at, created_at FROM authentication_failures ",
+        );
+
+        let subject_type = filter
+            .subject_type
+            .as_deref()
+            .map(str::parse::<AuthenticationSubjectType>)
+            .transpose()?;
+
+        if subject_type.is_some() || filter.locked.is_some() {
+            query.push("WHERE ");
+            let mut conditions = query.separated(" AND ");
+            if let Some(subject_type) = subject_type {
+                conditions
+                    .push("subject_type = ")
+                    .push_bind_unseparated(subject_type.as_str());
+            }
+            match filter.locked {
+                Some(true) => {
+                    conditions.push("locked_at IS NOT NULL AND blocked_until > now()");
+                }
+                Some(false) => {
+                    conditions.push("(locked_at IS NULL OR blocked_until <= now())");
+                }
+                None => {}
+            }
+        }
+
+        query.push(" ORDER BY last_failure_at DESC, subject_type, subject");
+
+        if let Some(pagination) = pagination {
+            query
+                .push(" LIMIT ")
+                .push_bind(pagination.limit.unwrap_or(10));
+            query
+                .push(" OFFSET ")
+                .push_bind(pagination.offset.unwrap_or(0));
+        }
+
+        let failures = query
+            .build()
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?
+            .into_iter()
+            .map(|row| AuthenticationFailure {
+                subject_type: row.get("subject_type"),
+                subject: row.get("subject"),
+                failure_count: row.get("failure_count"),
+                last_failure_at: row.get("last_failure_at"),
+                blocked_until: row.get("blocked_until"),
+                locked_at: row.get("locked_at"),
+                created_at: row.get("created_at"),
+            })
+            .collect();
+
+        Ok(failures)
+    }
+}
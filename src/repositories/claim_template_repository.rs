@@ -0,0 +1,307 @@
+use std::sync::Arc;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        claim_template::{
+            ClaimTemplate, ClaimTemplateContext, ClaimTemplateCreatePayload, ClaimTemplateFilter,
+            ClaimTemplateSortOrder, ClaimTemplateUpdatePayload,
+        },
+        pagination::Pagination,
+    },
+    repositories::base::Repository,
+};
+
+#[derive(Clone)]
+pub struct ClaimTemplateRepository {
+    pub pool: Arc<sqlx::postgres::PgPool>,
+}
+
+impl ClaimTemplateRepository {
+    pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the project, environment and access level templates that apply to a project access
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find_applicable(
+        &self,
+        project_access_id: Uuid,
+    ) -> Result<Vec<ClaimTemplate>, Error> {
+        sqlx::query_as!(
+            ClaimTemplate,
+            "SELECT claim_templates.id, claim_templates.project_id, claim_templates.environment_id, claim_templates.project_access_id, claim_templates.claim, claim_templates.template, claim_templates.created_at, claim_templates.updated_at FROM claim_templates INNER JOIN project_access ON project_access.project_id = claim_templates.project_id WHERE project_access.id = $1 AND ((claim_templates.environment_id IS NULL AND claim_templates.project_access_id IS NULL) OR claim_templates.environment_id = project_access.environment_id OR claim_templates.project_access_id = project_access.id)",
+            project_access_id,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)
+    }
+
+    /// Loads the values placeholders are rendered with for a project access
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn context(&self, project_access_id: Uuid) -> Result<ClaimTemplateContext, Error> {
+        let row = sqlx::query!(
+            "SELECT project_access.id, service_account.id AS service_account_id, service_account.name AS service_account_name, service_account.email AS service_account_email, projects.id AS project_id, projects.name AS project_name, environment.id AS environment_id, environment.name AS environment_name FROM project_access INNER JOIN service_account ON service_account.id = project_access.service_account_id INNER JOIN projects ON projects.id = project_access.project_id INNER JOIN environment ON environment.id = project_access.environment_id WHERE project_access.id = $1 LIMIT 1",
+            project_access_id,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?
+        .ok_or_else(|| Error::msg("Project access not found"))?;
+
+        Ok(ClaimTemplateContext {
+            service_account_id: row.service_account_id,
+            service_account_name: row.service_account_name,
+            service_account_email: row.service_account_email,
+            project_id: row.project_id,
+            project_name: row.project_name,
+            environment_id: row.environment_id,
+            environment_name: row.environment_name,
+            project_access_id: row.id,
+        })
+    }
+
+    fn map_error(error: sqlx::Error) -> Error {
+        match error {
+            sqlx::Error::RowNotFound => Error::msg("Claim template not found"),
+            sqlx::Error::Database(e) => {
+                let error_message = e.message();
+                tracing::warn!(error = error_message, "Database rejected the query");
+
+                match error_message {
+                    s if s.contains("unique constraint") || s.contains("duplicate key") => {
+                        Error::msg("Claim template already exists")
+                    }
+                    s if s.contains("foreign key")
+                        && s.contains("claim_templates_project_id_fkey") =>
+                    {
+                        Error::msg("Project not found")
+                    }
+                    s if s.contains("foreign key")
+                        && s.contains("claim_templates_environment_id_fkey") =>
+                    {
+                        Error::msg("Environment not found")
+                    }
+                    s if s.contains("foreign key")
+                        && s.contains("claim_templates_project_access_id_fkey") =>
+                    {
+                        Error::msg("Project access not found")
+                    }
+                    _ => Error::msg("No changes were made"),
+                }
+            }
+            _ => error.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<ClaimTemplate> for ClaimTemplateRepository {
+    type CreatePayload = ClaimTemplateCreatePayload;
+    type UpdatePayload = ClaimTemplateUpdatePayload;
+    type Filter = ClaimTemplateFilter;
+    type Sort = ClaimTemplateSortOrder;
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn create(&self, item: Self::CreatePayload) -> Result<ClaimTemplate, Error> {
+        item.validate()?;
+
+        let project_id =
+            Uuid::parse_str(&item.project_id).map_err(|_| Error::msg("Invalid project ID"))?;
+        let environment_id = item
+            .environment_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| Error::msg("Invalid environment ID"))?;
+        let project_access_id = item
+            .project_access_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| Error::msg("Invalid project access ID"))?;
+
+        if let Some(environment_id) = environment_id {
+            let environment = sqlx::query!(
+                "SELECT project_id FROM environment WHERE id = $1 LIMIT 1",
+                environment_id,
+            )
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?
+            .ok_or_else(|| Error::msg("Environment not found"))?;
+
+            if environment.project_id != project_id {
+                return Err(Error::msg("Environment does not belong to the project"));
+            }
+        }
+
+        if let Some(project_access_id) = project_access_id {
+            let project_access = sqlx::query!(
+                "SELECT project_id FROM project_access WHERE id = $1 LIMIT 1",
+                project_access_id,
+            )
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?
+            .ok_or_else(|| Error::msg("Project access not found"))?;
+
+            if project_access.project_id != project_id {
+                return Err(Error::msg("Project access does not belong to the project"));
+            }
+        }
+
+        sqlx::query_as!(
+            ClaimTemplate,
+            "INSERT INTO claim_templates (project_id, environment_id, project_access_id, claim, template) VALUES ($1, $2, $3, $4, $5) RETURNING id, project_id, environment_id, project_access_id, claim, template, created_at, updated_at",
+            project_id,
+            environment_id,
+            project_access_id,
+            item.claim,
+            item.template,
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(Self::map_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn read(&self, id: Uuid) -> Result<Option<ClaimTemplate>, Error> {
+        let claim_template = sqlx::query_as!(
+            ClaimTemplate,
+            "SELECT id, project_id, environment_id, project_access_id, claim, template, created_at, updated_at FROM claim_templates WHERE id = $1 LIMIT 1",
+            id,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if claim_template.is_none() {
+            return Err(Error::msg("Claim template not found"));
+        }
+
+        Ok(claim_template)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn update(&self, id: Uuid, update: Self::UpdatePayload) -> Result<ClaimTemplate, Error> {
+        update.validate()?;
+
+        if update.claim.is_none() && update.template.is_none() {
+            return Err(Error::msg("No changes to update"));
+        }
+
+        sqlx::query_as!(
+            ClaimTemplate,
+            "UPDATE claim_templates SET claim = COALESCE($1, claim), template = COALESCE($2, template), updated_at = $3 WHERE id = $4 RETURNING id, project_id, environment_id, project_access_id, claim, template, created_at, updated_at",
+            update.claim,
+            update.template,
+            Utc::now(),
+            id,
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(Self::map_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn delete(&self, id: Uuid) -> Result<bool, Error> {
+        let deleted = sqlx::query!("DELETE FROM claim_templates WHERE id = $1 RETURNING id", id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if deleted.is_none() {
+            return Err(Error::msg("Claim template not found"));
+        }
+
+        Ok(true)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn find(
+        &self,
+        filter: Self::Filter,
+        sort: Option<Vec<Self::Sort>>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<ClaimTemplate>, Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, project_id, environment_id, project_access_id, claim, template, created_at, updated_at FROM claim_templates ",
+        );
+
+        let mut conditions_list = Vec::new();
+
+        if let Some(project_id) = &filter.project_id {
+            let project_id =
+                Uuid::parse_str(project_id).map_err(|_| Error::msg("Invalid project ID"))?;
+            conditions_list.push(("project_id = ", project_id));
+        }
+
+        if let Some(environment_id) = &filter.environment_id {
+            let environment_id = Uuid::parse_str(environment_id)
+                .map_err(|_| Error::msg("Invalid environment ID"))?;
+            conditions_list.push(("environment_id = ", environment_id));
+        }
+
+        if let Some(project_access_id) = &filter.project_access_id {
+            let project_access_id = Uuid::parse_str(project_access_id)
+                .map_err(|_| Error::msg("Invalid project access ID"))?;
+            conditions_list.push(("project_access_id = ", project_access_id));
+        }
+
+        if !conditions_list.is_empty() || filter.claim.is_some() {
+            query.push("WHERE ");
+            let mut conditions = query.separated(" AND ");
+            for (condition, value) in conditions_list {
+                conditions.push(condition).push_bind_unseparated(value);
+            }
+            if let Some(claim) = filter.claim {
+                conditions.push("claim = ").push_bind_unseparated(claim);
+            }
+        }
+
+        if let Some(sort) = sort {
+            query.push(" ORDER BY ");
+            let mut order_by = query.separated(", ");
+            for sort in sort {
+                let field = String::from(sort.field);
+                order_by.push(format!("{} {}", field, sort.order));
+            }
+        }
+
+        if let Some(pagination) = pagination {
+            query
+                .push(" LIMIT ")
+                .push_bind(pagination.limit.unwrap_or(10));
+            query
+                .push(" OFFSET ")
+                .push_bind(pagination.offset.unwrap_or(0));
+        }
+
+        let claim_templates = query
+            .build()
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?
+            .into_iter()
+            .map(|row| ClaimTemplate {
+                id: row.get("id"),
+                project_id: row.get("project_id"),
+                environment_id: row.get("environment_id"),
+                project_access_id: row.get("project_access_id"),
+                claim: row.get("claim"),
+                template: row.get("template"),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+            })
+            .collect();
+
+        Ok(claim_templates)
+    }
+}
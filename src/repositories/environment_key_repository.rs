@@ -2,7 +2,10 @@ use std::sync::Arc;
 
 use anyhow::Error;
 use async_trait::async_trait;
-use jsonwebtoken::Algorithm;
+use jsonwebtoken::{Algorithm, Validation};
+use rand::Rng;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use sqlx::{QueryBuilder, Row};
 use uuid::Uuid;
 
@@ -10,33 +13,96 @@ use std::str::FromStr;
 
 use crate::{
     models::{
+        audit_event::AUDIT_EVENT_ENVIRONMENT_KEY_ROTATED,
         environment_key::{
             EnvironmentKey, EnvironmentKeyCreatePayload, EnvironmentKeyFilter,
-            EnvironmentKeySortOrder, EnvironmentKeyUpdatePayload,
+            EnvironmentKeyImportPayload, EnvironmentKeySortOrder, EnvironmentKeyUpdatePayload,
         },
         pagination::Pagination,
+        token_policy::EffectiveTokenPolicy,
+    },
+    repositories::{
+        audit_event_repository::AuditEventRepository, base::Repository,
+        token_policy_repository::TokenPolicyRepository,
+    },
+    utils::{
+        key_store::{self, KeyStore, database::DatabaseKeyStore},
+        metrics::METRICS,
+        security::SecretsManager,
     },
-    repositories::base::Repository,
-    utils::security::SecretsManager,
 };
 
 use crate::utils::tokens::key_builder::KeyBuilder;
 
+/// Replacement key generated for a due environment key during a scheduled rotation
+struct GeneratedKey {
+    id: Uuid,
+    environment_id: Uuid,
+    algorithm: String,
+    reference: String,
+    /// Seconds the replaced key keeps verifying tokens
+    retention_seconds: i64,
+}
+
 #[derive(Clone)]
 pub struct EnvironmentKeyRepository {
     pub pool: Arc<sqlx::postgres::PgPool>,
-    pub secrets_manager: SecretsManager,
+    /// Where the private key material is kept, the `key` column only holds its reference
+    pub key_store: Arc<dyn KeyStore>,
+    pub token_policy_repository: TokenPolicyRepository,
+    /// Upper bound of the random delay added to every rotation schedule
+    pub rotation_jitter_seconds: i64,
 }
 
 impl EnvironmentKeyRepository {
-    pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
-        let secrets_manager = SecretsManager::new(true).unwrap();
+    /// Keeps key material in the database, encrypted by `secrets_manager`, unless another
+    /// store is set with `with_key_store`
+    pub fn new(pool: Arc<sqlx::postgres::PgPool>, secrets_manager: SecretsManager) -> Self {
+        let key_store = Arc::new(DatabaseKeyStore::new(secrets_manager));
+        let token_policy_repository = TokenPolicyRepository::new(pool.clone());
         Self {
             pool,
-            secrets_manager,
+            key_store,
+            token_policy_repository,
+            rotation_jitter_seconds: 0,
         }
     }
 
+    /// Replaces the store keeping the private key material
+    pub fn with_key_store(mut self, key_store: Arc<dyn KeyStore>) -> Self {
+        self.key_store = key_store;
+        self
+    }
+
+    /// Token policy applied to environments without a policy of their own, it bounds how long
+    /// a rotated key keeps verifying the tokens it signed
+    pub fn with_token_defaults(mut self, defaults: EffectiveTokenPolicy) -> Self {
+        self.token_policy_repository = self.token_policy_repository.with_defaults(defaults);
+        self
+    }
+
+    /// Pushes every rotation schedule back by a random delay of up to `jitter_seconds`, so
+    /// keys created or rotated together do not all come due at once
+    pub fn with_rotation_jitter(mut self, jitter_seconds: i64) -> Self {
+        self.rotation_jitter_seconds = jitter_seconds;
+        self
+    }
+
+    fn rotation_jitter(&self) -> f64 {
+        rand::thread_rng().gen_range(0..=self.rotation_jitter_seconds.max(0)) as f64
+    }
+
+    /// Seconds a key replaced in an environment keeps verifying tokens, the longest lifetime
+    /// of the tokens it may have signed
+    async fn previous_key_retention(&self, environment_id: Uuid) -> Result<i64, Error> {
+        Ok(self
+            .token_policy_repository
+            .resolve(environment_id)
+            .await?
+            .max_ttl_seconds)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     pub async fn get_environment_key(
         self,
         environment_id: Uuid,
@@ -49,83 +115,475 @@ impl EnvironmentKeyRepository {
         )
         .fetch_optional(&*self.pool)
         .await
-        .map_err(|_| Error::msg("Database error"))?;
+        .map_err(|error| {
+            tracing::warn!(error = %error, "Failed to load the active environment key");
+            Error::msg("Database error")
+        })?;
 
         let row = row.ok_or_else(|| Error::msg("Environment key not found"))?;
-        let key = self.decrypt_key(row.key, environment_id)?;
-        Ok(key)
+        self.key_store.fetch(environment_id, &row.key).await
+    }
+
+    /// Returns the active key of an environment together with its key material.
+    ///
+    /// When no algorithm is given, the oldest active key of the environment is used. Fails
+    /// when the key store does not export key material, use `find_active_key` and `sign_jwt`
+    /// to sign with any key store.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn get_active_key(
+        &self,
+        environment_id: Uuid,
+        algorithm: Option<Algorithm>,
+    ) -> Result<(EnvironmentKey, String), Error> {
+        let (environment_key, reference) =
+            self.find_active_key(environment_id, algorithm)
+                .await?
+                .ok_or_else(|| Error::msg("Environment key not found"))?;
+        let key = self.key_store.fetch(environment_id, &reference).await?;
+        Ok((environment_key, key))
+    }
+
+    /// Returns the active key of an environment together with the reference of its key
+    /// material in the key store, or `None` when the environment has no such key.
+    ///
+    /// When no algorithm is given, the oldest active key of the environment is used.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find_active_key(
+        &self,
+        environment_id: Uuid,
+        algorithm: Option<Algorithm>,
+    ) -> Result<Option<(EnvironmentKey, String)>, Error> {
+        let algorithm = algorithm.map(|algorithm| format!("{:?}", algorithm));
+        let row = sqlx::query!(
+            "SELECT id, environment_id, algorithm, key, active, rotation_interval_days, next_rotation_at, created_at, updated_at FROM environment_key WHERE environment_id = $1 AND ($2::TEXT IS NULL OR algorithm = $2) AND active = true ORDER BY created_at, id LIMIT 1",
+            environment_id,
+            algorithm,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(Some((
+            EnvironmentKey {
+                id: Some(row.id),
+                environment_id: row.environment_id,
+                algorithm: Algorithm::from_str(&row.algorithm)?,
+                active: row.active,
+                rotation_interval_days: row.rotation_interval_days,
+                next_rotation_at: row.next_rotation_at,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            },
+            row.key,
+        )))
+    }
+
+    /// Signs claims as a JWT with an environment key, given the reference of its key material.
+    /// The `kid` header of the token is the ID of the environment key.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn sign_jwt<T: Serialize>(
+        &self,
+        environment_key: &EnvironmentKey,
+        reference: &str,
+        claims: &T,
+    ) -> Result<String, Error> {
+        key_store::encode_jwt(
+            self.key_store.as_ref(),
+            environment_key.environment_id,
+            environment_key.id,
+            reference,
+            environment_key.algorithm,
+            claims,
+        )
+        .await
+    }
+
+    /// Decodes a JWT signed by any active key of an environment for an algorithm, or by the
+    /// key an active key replaced while it is retained.
+    ///
+    /// Returns `None` when no such key verifies the token or its claims are not valid.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn verify_jwt<T: DeserializeOwned>(
+        &self,
+        environment_id: Uuid,
+        algorithm: Algorithm,
+        token: &str,
+        validation: &Validation,
+    ) -> Result<Option<T>, Error> {
+        let rows = sqlx::query!(
+            "SELECT key, CASE WHEN previous_key_expires_at > now() THEN previous_key END AS previous_key FROM environment_key WHERE environment_id = $1 AND algorithm = $2 AND active = true ORDER BY created_at, id",
+            environment_id,
+            &format!("{:?}", algorithm),
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let references = rows
+            .into_iter()
+            .flat_map(|row| std::iter::once(row.key).chain(row.previous_key));
+        for reference in references {
+            if let Ok(claims) = key_store::decode_jwt(
+                self.key_store.as_ref(),
+                environment_id,
+                &reference,
+                token,
+                validation,
+            )
+            .await
+            {
+                return Ok(Some(claims));
+            }
+        }
+        Ok(None)
     }
 
+    /// Replaces the key material of an environment key. The replaced material keeps verifying
+    /// tokens for the maximum token lifetime of the environment, the material it retained
+    /// from an earlier rotation is discarded.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     pub async fn rotate_key(&self, id: Uuid) -> Result<EnvironmentKey, Error> {
         let environment_key = self.read(id).await?;
         let environment_key =
             environment_key.ok_or_else(|| Error::msg("Environment key not found"))?;
         let algorithm = environment_key.algorithm;
         let environment_id = environment_key.environment_id;
-        let key_encrypted = self.generate_encrypted_key(algorithm, environment_id)?;
+        let retention_seconds = self.previous_key_retention(environment_id).await?;
+        let reference = self.key_store.generate(environment_id, algorithm).await?;
         let row = sqlx::query!(
-            "UPDATE environment_key SET key = $1, updated_at = $2 WHERE id = $3 RETURNING id, environment_id, algorithm, active, created_at, updated_at",
-            key_encrypted,
+            "UPDATE environment_key SET key = $1, updated_at = $2, next_rotation_at = now() + make_interval(days => environment_key.rotation_interval_days) + make_interval(secs => $4), previous_key = previous.key, previous_key_expires_at = now() + make_interval(secs => $5) FROM (SELECT id, key, previous_key FROM environment_key WHERE id = $3 FOR UPDATE) AS previous WHERE environment_key.id = previous.id RETURNING environment_key.id, environment_key.environment_id, environment_key.algorithm, environment_key.active, environment_key.rotation_interval_days, environment_key.next_rotation_at, environment_key.created_at, environment_key.updated_at, previous.previous_key AS retired_key",
+            reference,
             chrono::Utc::now(),
             id,
+            self.rotation_jitter(),
+            retention_seconds as f64,
         )
         .fetch_one(&*self.pool)
-        .await
-        .map_err(|_| Error::msg("Database error"))?;
+        .await;
+        let row = match row {
+            Ok(row) => row,
+            Err(error) => {
+                tracing::warn!(error = %error, "Failed to store the rotated environment key");
+                self.discard_key(environment_id, &reference).await;
+                return Err(Error::msg("Database error"));
+            }
+        };
+        if let Some(retired_key) = &row.retired_key {
+            self.discard_key(environment_id, retired_key).await;
+        }
+        METRICS.environment_key_rotations_total.inc(&["manual"]);
 
         Ok(EnvironmentKey {
             id: Some(row.id),
             environment_id: row.environment_id,
             algorithm: Algorithm::from_str(&row.algorithm).unwrap(),
             active: row.active,
+            rotation_interval_days: row.rotation_interval_days,
+            next_rotation_at: row.next_rotation_at,
             created_at: row.created_at,
             updated_at: row.updated_at,
         })
     }
 
-    pub fn generate_key(&self, algorithm: Algorithm) -> Result<String, Error> {
-        let key = KeyBuilder::new().generate_key(algorithm)?;
-        Ok(key.private_key_str)
+    /// Fails when an environment key holds a reference the key store does not recognize,
+    /// which happens when the key store backend is changed without migrating the keys
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn ensure_key_store_holds_keys(&self) -> Result<(), Error> {
+        let keys = sqlx::query!("SELECT id, key, previous_key FROM environment_key ORDER BY id")
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let foreign: Vec<String> = keys
+            .into_iter()
+            .filter(|key| {
+                !self.key_store.recognizes(&key.key)
+                    || key
+                        .previous_key
+                        .as_ref()
+                        .is_some_and(|previous_key| !self.key_store.recognizes(previous_key))
+            })
+            .map(|key| key.id.to_string())
+            .collect();
+        if !foreign.is_empty() {
+            return Err(Error::msg(format!(
+                "Environment keys {} are not kept in the configured key store, migrate them before changing security.key_store.backend",
+                foreign.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Rotates the active keys whose scheduled rotation is due, oldest due first.
+    ///
+    /// The replacement keys are generated before any row is locked, so slow key stores
+    /// never hold the locks. Due keys are then locked with `SKIP LOCKED`, so several
+    /// instances running the scheduler never rotate the same key twice, and replacements
+    /// for keys rotated elsewhere meanwhile are discarded. The next rotation of each key is
+    /// pushed back by a random delay of up to `rotation_jitter_seconds`, spreading keys
+    /// scheduled together over time. Replaced keys keep verifying tokens like with
+    /// `rotate_key`. Every rotation is recorded as an audit event in the same transaction.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn rotate_due_keys(&self, batch_size: i64) -> Result<Vec<EnvironmentKey>, Error> {
+        let due = sqlx::query!(
+            "SELECT id, environment_id, algorithm FROM environment_key WHERE active = true AND next_rotation_at <= now() ORDER BY next_rotation_at, id LIMIT $1",
+            batch_size,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let mut generated = Vec::with_capacity(due.len());
+        for key in due {
+            let retention_seconds = match self.previous_key_retention(key.environment_id).await {
+                Ok(retention_seconds) => retention_seconds,
+                Err(error) => {
+                    self.discard_keys(Self::references(generated)).await;
+                    return Err(error);
+                }
+            };
+            let reference = match Algorithm::from_str(&key.algorithm) {
+                Ok(algorithm) => self.key_store.generate(key.environment_id, algorithm).await,
+                Err(error) => Err(error.into()),
+            };
+            match reference {
+                Ok(reference) => generated.push(GeneratedKey {
+                    id: key.id,
+                    environment_id: key.environment_id,
+                    algorithm: key.algorithm,
+                    reference,
+                    retention_seconds,
+                }),
+                Err(error) => {
+                    self.discard_keys(Self::references(generated)).await;
+                    return Err(error);
+                }
+            }
+        }
+
+        match self.replace_due_keys(&generated).await {
+            Ok((rotated, replaced)) => {
+                let unused = generated
+                    .into_iter()
+                    .filter(|generated| !rotated.iter().any(|key| key.id == Some(generated.id)))
+                    .collect();
+                self.discard_keys(Self::references(unused)).await;
+                self.discard_keys(replaced).await;
+                METRICS
+                    .environment_key_rotations_total
+                    .inc_by(&["scheduled"], rotated.len() as u64);
+                Ok(rotated)
+            }
+            Err(error) => {
+                self.discard_keys(Self::references(generated)).await;
+                Err(error)
+            }
+        }
     }
 
-    pub fn generate_encrypted_key(
+    /// Swaps the keys of `generated` still due and unlocked for their replacement in one
+    /// transaction, returning the rotated keys and the retained references they retired
+    async fn replace_due_keys(
         &self,
-        algorithm: Algorithm,
-        environment_id: Uuid,
-    ) -> Result<String, Error> {
-        let key = self.generate_key(algorithm)?;
-        let resource_id = environment_id;
-        let key_encrypted = self.secrets_manager.encrypt(&key, &resource_id)?;
-        Ok(key_encrypted)
+        generated: &[GeneratedKey],
+    ) -> Result<(Vec<EnvironmentKey>, Vec<(Uuid, String)>), Error> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let ids: Vec<Uuid> = generated.iter().map(|generated| generated.id).collect();
+        let due = sqlx::query!(
+            "SELECT id, algorithm, key, previous_key FROM environment_key WHERE id = ANY($1) AND active = true AND next_rotation_at <= now() ORDER BY next_rotation_at, id FOR UPDATE SKIP LOCKED",
+            &ids,
+        )
+        .fetch_all(&mut *transaction)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let mut rotated = Vec::with_capacity(due.len());
+        let mut replaced = Vec::with_capacity(due.len());
+        for key in due {
+            let Some(generated) = generated
+                .iter()
+                .find(|generated| generated.id == key.id && generated.algorithm == key.algorithm)
+            else {
+                continue;
+            };
+            let row = sqlx::query!(
+                "UPDATE environment_key SET key = $1, updated_at = now(), next_rotation_at = now() + make_interval(days => rotation_interval_days) + make_interval(secs => $2), previous_key = $3, previous_key_expires_at = now() + make_interval(secs => $4) WHERE id = $5 RETURNING id, environment_id, algorithm, active, rotation_interval_days, next_rotation_at, created_at, updated_at",
+                generated.reference,
+                self.rotation_jitter(),
+                key.key,
+                generated.retention_seconds as f64,
+                key.id,
+            )
+            .fetch_one(&mut *transaction)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+            if let Some(previous_key) = key.previous_key {
+                replaced.push((generated.environment_id, previous_key));
+            }
+
+            let environment_key = EnvironmentKey {
+                id: Some(row.id),
+                environment_id: row.environment_id,
+                algorithm: Algorithm::from_str(&row.algorithm)?,
+                active: row.active,
+                rotation_interval_days: row.rotation_interval_days,
+                next_rotation_at: row.next_rotation_at,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            };
+
+            AuditEventRepository::insert(
+                &mut *transaction,
+                AUDIT_EVENT_ENVIRONMENT_KEY_ROTATED,
+                "environment_key",
+                &row.id.to_string(),
+                environment_key.next_rotation_at.map(|next_rotation_at| {
+                    format!("Rotated on schedule, next rotation at {}", next_rotation_at)
+                }),
+            )
+            .await?;
+
+            rotated.push(environment_key);
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok((rotated, replaced))
     }
 
-    pub fn decrypt_key(&self, key: String, environment_id: Uuid) -> Result<String, Error> {
-        let key_decrypted = self.secrets_manager.decrypt(&key, &environment_id)?;
-        Ok(key_decrypted)
+    /// Stops retaining the keys replaced by rotations once the tokens they signed expired,
+    /// returning how many were discarded
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn discard_expired_previous_keys(&self) -> Result<usize, Error> {
+        let expired = sqlx::query!(
+            "UPDATE environment_key SET previous_key = NULL, previous_key_expires_at = NULL FROM (SELECT id, previous_key FROM environment_key WHERE previous_key_expires_at <= now() FOR UPDATE SKIP LOCKED) AS expired WHERE environment_key.id = expired.id RETURNING environment_key.environment_id, expired.previous_key",
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let references: Vec<(Uuid, String)> = expired
+            .into_iter()
+            .filter_map(|row| Some((row.environment_id, row.previous_key?)))
+            .collect();
+        let discarded = references.len();
+        self.discard_keys(references).await;
+        Ok(discarded)
     }
-}
 
-#[async_trait]
-impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
-    type CreatePayload = EnvironmentKeyCreatePayload;
-    type UpdatePayload = EnvironmentKeyUpdatePayload;
-    type Filter = EnvironmentKeyFilter;
-    type Sort = EnvironmentKeySortOrder;
+    fn references(generated: Vec<GeneratedKey>) -> Vec<(Uuid, String)> {
+        generated
+            .into_iter()
+            .map(|generated| (generated.environment_id, generated.reference))
+            .collect()
+    }
 
-    async fn create(&self, item: Self::CreatePayload) -> Result<EnvironmentKey, Error> {
-        let algorithm = Algorithm::from_str(&item.algorithm).unwrap();
-        let resource_id = Uuid::parse_str(&item.environment_id).unwrap();
-        let key_encrypted = self.generate_encrypted_key(algorithm, resource_id)?;
+    /// Removes key material that is no longer referenced from the key store.
+    ///
+    /// Failures are only logged, the material is unreachable once its reference is gone.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn discard_key(&self, environment_id: Uuid, reference: &str) {
+        if let Err(error) = self.key_store.delete(environment_id, reference).await {
+            tracing::warn!(
+                environment_id = %environment_id,
+                error = %error,
+                "Failed to remove key material"
+            );
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn discard_keys(&self, references: Vec<(Uuid, String)>) {
+        for (environment_id, reference) in references {
+            self.discard_key(environment_id, &reference).await;
+        }
+    }
+
+    /// Stores existing key material in the key store like generated keys, so tokens keep
+    /// being signed with the keys in use before migrating to SentinelGuard
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn import_key(
+        &self,
+        item: EnvironmentKeyImportPayload,
+    ) -> Result<EnvironmentKey, Error> {
+        let (algorithm, resource_id) = self
+            .validate_new_key(&item.environment_id, &item.algorithm)
+            .await?;
+        let key = KeyBuilder::import_key(algorithm, &item.key, item.encoding)?;
+        let rotation_interval_days = item
+            .rotation_interval_days
+            .map(rotation_interval_days)
+            .transpose()?
+            .flatten();
+        let reference = self
+            .key_store
+            .store(resource_id, algorithm, &key.private_key_str)
+            .await?;
+        self.insert_key(
+            resource_id,
+            algorithm,
+            reference,
+            item.active,
+            rotation_interval_days,
+        )
+        .await
+    }
 
+    /// Parses the environment and algorithm of a new key and checks the token policy allows it
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn validate_new_key(
+        &self,
+        environment_id: &str,
+        algorithm: &str,
+    ) -> Result<(Algorithm, Uuid), Error> {
+        let algorithm = Algorithm::from_str(algorithm)
+            .map_err(|_| Error::msg(format!("Invalid algorithm: {}", algorithm)))?;
+        let resource_id =
+            Uuid::parse_str(environment_id).map_err(|_| Error::msg("Invalid environment ID"))?;
+
+        self.token_policy_repository
+            .resolve(resource_id)
+            .await?
+            .ensure_algorithm_allowed(algorithm)?;
+
+        Ok((algorithm, resource_id))
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn insert_key(
+        &self,
+        resource_id: Uuid,
+        algorithm: Algorithm,
+        reference: String,
+        active: bool,
+        rotation_interval_days: Option<i32>,
+    ) -> Result<EnvironmentKey, Error> {
         let row = sqlx::query!(
-            "INSERT INTO environment_key (environment_id, algorithm, key, active) VALUES ($1, $2, $3, $4) RETURNING id, environment_id, algorithm, key, active, created_at, updated_at",
-            Uuid::parse_str(&item.environment_id).unwrap(),
+            "INSERT INTO environment_key (environment_id, algorithm, key, active, rotation_interval_days, next_rotation_at) VALUES ($1, $2, $3, $4, $5, now() + make_interval(days => $5) + make_interval(secs => $6)) RETURNING id, environment_id, algorithm, key, active, rotation_interval_days, next_rotation_at, created_at, updated_at",
+            resource_id,
             &format!("{:?}", algorithm),
-            key_encrypted,
-            &item.active
+            reference,
+            &active,
+            rotation_interval_days,
+            self.rotation_jitter(),
         )
         .fetch_one(&*self.pool)
         .await;
+        if row.is_err() {
+            self.discard_key(resource_id, &reference).await;
+        }
 
         match row {
             Ok(row) => Ok(EnvironmentKey {
@@ -133,6 +591,8 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
                 environment_id: row.environment_id,
                 algorithm: Algorithm::from_str(&row.algorithm).unwrap(),
                 active: row.active,
+                rotation_interval_days: row.rotation_interval_days,
+                next_rotation_at: row.next_rotation_at,
                 created_at: row.created_at,
                 updated_at: row.updated_at,
             }),
@@ -140,7 +600,7 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Environment key not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
-                    dbg!(&error_message);
+                    tracing::warn!(error = error_message, "Database rejected the query");
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
                             if s.contains("idx_environment_key_environment_id_algorithm") {
@@ -148,7 +608,6 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
                                     "Environment Id and Algorithm combination already exists",
                                 ))
                             } else {
-                                dbg!(&error_message);
                                 Err(Error::msg("No changes were made"))
                             }
                         }
@@ -163,9 +622,44 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
         }
     }
 
+    pub fn generate_key(&self, algorithm: Algorithm) -> Result<String, Error> {
+        let key = KeyBuilder::new().generate_key(algorithm)?;
+        Ok(key.private_key_str)
+    }
+}
+
+#[async_trait]
+impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
+    type CreatePayload = EnvironmentKeyCreatePayload;
+    type UpdatePayload = EnvironmentKeyUpdatePayload;
+    type Filter = EnvironmentKeyFilter;
+    type Sort = EnvironmentKeySortOrder;
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn create(&self, item: Self::CreatePayload) -> Result<EnvironmentKey, Error> {
+        let (algorithm, resource_id) = self
+            .validate_new_key(&item.environment_id, &item.algorithm)
+            .await?;
+        let rotation_interval_days = item
+            .rotation_interval_days
+            .map(rotation_interval_days)
+            .transpose()?
+            .flatten();
+        let reference = self.key_store.generate(resource_id, algorithm).await?;
+        self.insert_key(
+            resource_id,
+            algorithm,
+            reference,
+            item.active,
+            rotation_interval_days,
+        )
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn read(&self, id: Uuid) -> Result<Option<EnvironmentKey>, Error> {
         let row = sqlx::query!(
-            "SELECT id, environment_id, algorithm as algorithm, active, created_at, updated_at FROM environment_key WHERE id = $1 LIMIT 1",
+            "SELECT id, environment_id, algorithm as algorithm, active, rotation_interval_days, next_rotation_at, created_at, updated_at FROM environment_key WHERE id = $1 LIMIT 1",
             id,
         )
         .fetch_optional(&*self.pool)
@@ -178,6 +672,8 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
                 environment_id: row.environment_id,
                 algorithm: Algorithm::from_str(&row.algorithm).unwrap(),
                 active: row.active,
+                rotation_interval_days: row.rotation_interval_days,
+                next_rotation_at: row.next_rotation_at,
                 created_at: row.created_at,
                 updated_at: row.updated_at,
             })),
@@ -185,6 +681,7 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn update(&self, id: Uuid, update: Self::UpdatePayload) -> Result<EnvironmentKey, Error> {
         let mut changes = Vec::new();
 
@@ -195,7 +692,12 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
             }
         }
 
-        if changes.is_empty() {
+        let rotation_interval = update
+            .rotation_interval_days
+            .map(rotation_interval_days)
+            .transpose()?;
+
+        if changes.is_empty() && rotation_interval.is_none() {
             return Err(Error::msg("No changes to update"));
         }
 
@@ -211,10 +713,29 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
                     .push_bind_unseparated(value);
             }
         }
+        match rotation_interval {
+            Some(Some(days)) => {
+                separated
+                    .push("rotation_interval_days = ")
+                    .push_bind_unseparated(days);
+                separated
+                    .push("next_rotation_at = now() + make_interval(days => ")
+                    .push_bind_unseparated(days)
+                    .push_unseparated(") + make_interval(secs => ")
+                    .push_bind_unseparated(self.rotation_jitter())
+                    .push_unseparated(")");
+            }
+            Some(None) => {
+                separated.push("rotation_interval_days = NULL, next_rotation_at = NULL");
+            }
+            None => {}
+        }
 
         query.push(", updated_at = ").push_bind(chrono::Utc::now());
         query.push(" WHERE id = ").push_bind(id);
-        query.push(" RETURNING id, environment_id, algorithm, active, created_at, updated_at");
+        query.push(
+            " RETURNING id, environment_id, algorithm, active, rotation_interval_days, next_rotation_at, created_at, updated_at",
+        );
 
         let result = query
             .build()
@@ -225,6 +746,8 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
                 environment_id: row.get("environment_id"),
                 algorithm: Algorithm::from_str(&row.get::<String, _>("algorithm")).unwrap(),
                 active: row.get("active"),
+                rotation_interval_days: row.get("rotation_interval_days"),
+                next_rotation_at: row.get("next_rotation_at"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             });
@@ -235,6 +758,7 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Environment key not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
+                    tracing::warn!(error = error_message, "Database rejected the query");
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
                             if s.contains("idx_environment_key_environment_id_algorithm") {
@@ -260,17 +784,24 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn delete(&self, id: Uuid) -> Result<bool, Error> {
-        let deleted = sqlx::query!("DELETE FROM environment_key WHERE id = $1 RETURNING id", id)
-            .fetch_optional(&*self.pool)
-            .await
-            .map_err(<sqlx::Error as Into<Error>>::into)?;
-        if deleted.is_none() {
-            return Err(Error::msg("Environment key not found"));
+        let deleted = sqlx::query!(
+            "DELETE FROM environment_key WHERE id = $1 RETURNING environment_id, key, previous_key",
+            id
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+        let deleted = deleted.ok_or_else(|| Error::msg("Environment key not found"))?;
+        self.discard_key(deleted.environment_id, &deleted.key).await;
+        if let Some(previous_key) = &deleted.previous_key {
+            self.discard_key(deleted.environment_id, previous_key).await;
         }
         Ok(true)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn find(
         &self,
         filter: Self::Filter,
@@ -278,7 +809,7 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
         pagination: Option<Pagination>,
     ) -> Result<Vec<EnvironmentKey>, Error> {
         let mut query = QueryBuilder::new(
-            "SELECT id, environment_id, algorithm, active, created_at, updated_at FROM environment_key ",
+            "SELECT id, environment_id, algorithm, active, rotation_interval_days, next_rotation_at, created_at, updated_at FROM environment_key ",
         );
         let mut conditions_list: Vec<(&str, String)> = Vec::new();
         if let Some(algorithm) = &filter.algorithm {
@@ -315,7 +846,7 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
                 .push_bind(Uuid::parse_str(environment_id).unwrap());
         }
 
-        dbg!(&query.sql());
+        tracing::debug!(sql = %query.sql(), "Listing environment keys");
 
         if let Some(sort) = sort {
             query.push(" ORDER BY ");
@@ -344,6 +875,8 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
                 environment_id: row.get("environment_id"),
                 algorithm: Algorithm::from_str(&row.get::<String, _>("algorithm")).unwrap(),
                 active: row.get("active"),
+                rotation_interval_days: row.get("rotation_interval_days"),
+                next_rotation_at: row.get("next_rotation_at"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             })
@@ -351,3 +884,12 @@ impl Repository<EnvironmentKey> for EnvironmentKeyRepository {
         Ok(keys)
     }
 }
+
+/// Validates a rotation interval, where 0 means the key is only rotated on demand
+fn rotation_interval_days(days: i32) -> Result<Option<i32>, Error> {
+    match days {
+        days if days < 0 => Err(Error::msg("Rotation interval days must not be negative")),
+        0 => Ok(None),
+        days => Ok(Some(days)),
+    }
+}
@@ -35,6 +35,7 @@ impl Repository<Environment> for EnvironmentRepository {
     type Filter = EnvironmentFilter;
     type Sort = EnvironmentSortOrder;
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn create(&self, item: Self::CreatePayload) -> Result<Environment, Error> {
         let environment = Environment {
             id: None,
@@ -63,6 +64,7 @@ impl Repository<Environment> for EnvironmentRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Environment not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
+                    tracing::warn!(error = error_message, "Database rejected the query");
 
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
@@ -83,6 +85,7 @@ impl Repository<Environment> for EnvironmentRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn read(&self, id: Uuid) -> Result<Option<Environment>, Error> {
         let environment = sqlx::query_as!(
             Environment,
@@ -100,6 +103,7 @@ impl Repository<Environment> for EnvironmentRepository {
         Ok(environment)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn update(&self, id: Uuid, update: Self::UpdatePayload) -> Result<Environment, Error> {
         let mut changes = Vec::new();
 
@@ -159,6 +163,7 @@ impl Repository<Environment> for EnvironmentRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Environment not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
+                    tracing::warn!(error = error_message, "Database rejected the query");
 
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
@@ -179,6 +184,7 @@ impl Repository<Environment> for EnvironmentRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn delete(&self, id: Uuid) -> Result<bool, Error> {
         let deleted = sqlx::query!("DELETE FROM environment WHERE id = $1 RETURNING id", id,)
             .fetch_optional(&*self.pool)
@@ -192,6 +198,7 @@ impl Repository<Environment> for EnvironmentRepository {
         Ok(true)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn find(
         &self,
         filter: Self::Filter,
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use anyhow::Error;
 use async_trait::async_trait;
 use chrono::Utc;
-use sqlx::{QueryBuilder, Row};
+use sqlx::{PgExecutor, QueryBuilder, Row};
 use uuid::Uuid;
 
 use crate::{
@@ -15,6 +15,7 @@ use crate::{
         },
     },
     repositories::base::Repository,
+    utils::network::normalize_cidrs,
 };
 
 #[derive(Clone)]
@@ -26,35 +27,37 @@ impl ProjectAccessRepository {
     pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
         Self { pool }
     }
-}
-
-#[async_trait]
-impl Repository<ProjectAccess> for ProjectAccessRepository {
-    type CreatePayload = ProjectAccessCreatePayload;
-    type UpdatePayload = ProjectAccessUpdatePayload;
-    type Filter = ProjectAccessFilter;
-    type Sort = ProjectAccessSortOrder;
 
-    async fn create(&self, item: Self::CreatePayload) -> Result<ProjectAccess, Error> {
+    /// Creates a project access with any executor, so it can be part of a larger transaction
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn insert<'e, E>(
+        executor: E,
+        item: ProjectAccessCreatePayload,
+    ) -> Result<ProjectAccess, Error>
+    where
+        E: PgExecutor<'e>,
+    {
         let project_access = ProjectAccess {
             id: None,
             project_id: item.project_id.parse().unwrap(),
             service_account_id: item.service_account_id.parse().unwrap(),
             environment_id: item.environment_id.parse().unwrap(),
             enabled: item.enabled,
+            allowed_cidrs: normalize_cidrs(&item.allowed_cidrs.unwrap_or_default())?,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
 
         let created_project_access = sqlx::query_as!(
             ProjectAccess,
-            "INSERT INTO project_access (project_id, service_account_id, environment_id, enabled) VALUES ($1, $2, $3, $4) RETURNING id, project_id, service_account_id, environment_id, enabled, created_at, updated_at",
+            "INSERT INTO project_access (project_id, service_account_id, environment_id, enabled, allowed_cidrs) VALUES ($1, $2, $3, $4, $5) RETURNING id, project_id, service_account_id, environment_id, enabled, allowed_cidrs, created_at, updated_at",
             project_access.project_id,
             project_access.service_account_id,
             project_access.environment_id,
             project_access.enabled,
+            &project_access.allowed_cidrs,
         )
-        .fetch_one(&*self.pool)
+        .fetch_one(executor)
         .await;
 
         match created_project_access {
@@ -64,6 +67,7 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
                     sqlx::Error::RowNotFound => Err(Error::msg("Project access not found")),
                     sqlx::Error::Database(e) => {
                         let error_message = e.message();
+                        tracing::warn!(error = error_message, "Database rejected the query");
 
                         match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
@@ -97,6 +101,40 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
         }
     }
 
+    /// Finds the access grant of a service account in an environment.
+    ///
+    /// Only grants that are enabled, together with their project and environment, are returned.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find_enabled_by_service_account_and_environment(
+        &self,
+        service_account_id: Uuid,
+        environment_id: Uuid,
+    ) -> Result<Option<ProjectAccess>, Error> {
+        sqlx::query_as!(
+            ProjectAccess,
+            "SELECT project_access.id, project_access.project_id, project_access.service_account_id, project_access.environment_id, project_access.enabled, project_access.allowed_cidrs, project_access.created_at, project_access.updated_at FROM project_access INNER JOIN projects ON projects.id = project_access.project_id INNER JOIN environment ON environment.id = project_access.environment_id WHERE project_access.service_account_id = $1 AND project_access.environment_id = $2 AND project_access.enabled = true AND projects.enabled = true AND environment.enabled = true LIMIT 1",
+            service_account_id,
+            environment_id,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)
+    }
+}
+
+#[async_trait]
+impl Repository<ProjectAccess> for ProjectAccessRepository {
+    type CreatePayload = ProjectAccessCreatePayload;
+    type UpdatePayload = ProjectAccessUpdatePayload;
+    type Filter = ProjectAccessFilter;
+    type Sort = ProjectAccessSortOrder;
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn create(&self, item: Self::CreatePayload) -> Result<ProjectAccess, Error> {
+        Self::insert(&*self.pool, item).await
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn read(&self, id: Uuid) -> Result<Option<ProjectAccess>, Error> {
         let project_access = sqlx::query_as!(
             ProjectAccess,
@@ -106,6 +144,7 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
                 service_account_id, 
                 environment_id, 
                 enabled, 
+                allowed_cidrs, 
                 created_at, 
                 updated_at
             FROM project_access
@@ -124,6 +163,7 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
         Ok(project_access)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn update(&self, id: Uuid, update: Self::UpdatePayload) -> Result<ProjectAccess, Error> {
         let mut changes = Vec::new();
 
@@ -134,7 +174,13 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
             }
         }
 
-        if changes.is_empty() {
+        let allowed_cidrs = update
+            .allowed_cidrs
+            .as_deref()
+            .map(normalize_cidrs)
+            .transpose()?;
+
+        if changes.is_empty() && allowed_cidrs.is_none() {
             return Err(Error::msg("No changes to update"));
         }
 
@@ -150,11 +196,16 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
                     .push_bind_unseparated(value);
             }
         }
+        if let Some(allowed_cidrs) = allowed_cidrs {
+            separated
+                .push("allowed_cidrs = ")
+                .push_bind_unseparated(allowed_cidrs);
+        }
 
         query.push(", updated_at = ").push_bind(Utc::now());
         query.push(" WHERE id = ").push_bind(id);
         query
-            .push(" RETURNING id, project_id, service_account_id, environment_id, enabled, created_at, updated_at");
+            .push(" RETURNING id, project_id, service_account_id, environment_id, enabled, allowed_cidrs, created_at, updated_at");
 
         let result = query
             .build()
@@ -166,6 +217,7 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
                 service_account_id: row.get("service_account_id"),
                 environment_id: row.get("environment_id"),
                 enabled: row.get("enabled"),
+                allowed_cidrs: row.get("allowed_cidrs"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             });
@@ -177,6 +229,7 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
                     sqlx::Error::RowNotFound => Err(Error::msg("Project access not found")),
                     sqlx::Error::Database(e) => {
                         let error_message = e.message();
+                        tracing::warn!(error = error_message, "Database rejected the query");
 
                         match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
@@ -210,6 +263,7 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn delete(&self, id: Uuid) -> Result<bool, Error> {
         let deleted = sqlx::query!("DELETE FROM project_access WHERE id = $1 RETURNING id", id,)
             .fetch_optional(&*self.pool)
@@ -223,6 +277,7 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
         Ok(true)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn find(
         &self,
         filter: Self::Filter,
@@ -230,7 +285,7 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
         pagination: Option<Pagination>,
     ) -> Result<Vec<ProjectAccess>, Error> {
         let mut query = QueryBuilder::new(
-            "SELECT id, project_id, service_account_id, environment_id, enabled, created_at, updated_at FROM project_access ",
+            "SELECT id, project_id, service_account_id, environment_id, enabled, allowed_cidrs, created_at, updated_at FROM project_access ",
         );
 
         let mut conditions_list: Vec<(&str, String)> = Vec::new();
@@ -322,6 +377,7 @@ impl Repository<ProjectAccess> for ProjectAccessRepository {
                 service_account_id: row.get("service_account_id"),
                 environment_id: row.get("environment_id"),
                 enabled: row.get("enabled"),
+                allowed_cidrs: row.get("allowed_cidrs"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             })
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use anyhow::Error;
 use async_trait::async_trait;
 use chrono::Utc;
-use sqlx::{QueryBuilder, Row};
+use sqlx::{PgExecutor, QueryBuilder, Row};
 use uuid::Uuid;
 
 use crate::{
@@ -26,16 +26,16 @@ impl ProjectAccessScopesRepository {
     pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
         Self { pool }
     }
-}
 
-#[async_trait]
-impl Repository<ProjectAccessScope> for ProjectAccessScopesRepository {
-    type CreatePayload = ProjectAccessScopeCreatePayload;
-    type UpdatePayload = ProjectAccessScopeUpdatePayload;
-    type Filter = ProjectAccessScopeFilter;
-    type Sort = ProjectAccessScopeSortOrder;
-
-    async fn create(&self, item: Self::CreatePayload) -> Result<ProjectAccessScope, Error> {
+    /// Creates a project access scope with any executor, so it can be part of a larger transaction
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn insert<'e, E>(
+        executor: E,
+        item: ProjectAccessScopeCreatePayload,
+    ) -> Result<ProjectAccessScope, Error>
+    where
+        E: PgExecutor<'e>,
+    {
         let project_access_scope = ProjectAccessScope {
             id: None,
             project_access_id: item.project_access_id.parse().unwrap(),
@@ -52,7 +52,7 @@ impl Repository<ProjectAccessScope> for ProjectAccessScopesRepository {
             project_access_scope.scope_id,
             project_access_scope.enabled,
         )
-        .fetch_one(&*self.pool)
+        .fetch_one(executor)
         .await;
 
         match created {
@@ -61,6 +61,7 @@ impl Repository<ProjectAccessScope> for ProjectAccessScopesRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Project access scope not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
+                    tracing::warn!(error = error_message, "Database rejected the query");
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
                             if s.contains("idx_project_access_scopes_project_access_id_scope_id") {
@@ -82,6 +83,34 @@ impl Repository<ProjectAccessScope> for ProjectAccessScopesRepository {
         }
     }
 
+    /// Lists the names of the enabled project scopes granted to a project access
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find_scope_names(&self, project_access_id: Uuid) -> Result<Vec<String>, Error> {
+        let rows = sqlx::query!(
+            "SELECT project_scopes.scope FROM project_access_scopes INNER JOIN project_scopes ON project_scopes.id = project_access_scopes.scope_id WHERE project_access_scopes.project_access_id = $1 AND project_access_scopes.enabled = true AND project_scopes.enabled = true ORDER BY project_scopes.scope",
+            project_access_id,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(rows.into_iter().map(|row| row.scope).collect())
+    }
+}
+
+#[async_trait]
+impl Repository<ProjectAccessScope> for ProjectAccessScopesRepository {
+    type CreatePayload = ProjectAccessScopeCreatePayload;
+    type UpdatePayload = ProjectAccessScopeUpdatePayload;
+    type Filter = ProjectAccessScopeFilter;
+    type Sort = ProjectAccessScopeSortOrder;
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn create(&self, item: Self::CreatePayload) -> Result<ProjectAccessScope, Error> {
+        Self::insert(&*self.pool, item).await
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn read(&self, id: Uuid) -> Result<Option<ProjectAccessScope>, Error> {
         let scope = sqlx::query_as!(
             ProjectAccessScope,
@@ -99,6 +128,7 @@ impl Repository<ProjectAccessScope> for ProjectAccessScopesRepository {
         Ok(scope)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn update(
         &self,
         id: Uuid,
@@ -151,6 +181,7 @@ impl Repository<ProjectAccessScope> for ProjectAccessScopesRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Project access scope not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
+                    tracing::warn!(error = error_message, "Database rejected the query");
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
                             if s.contains("idx_project_access_scopes_project_access_id_scope_id") {
@@ -172,6 +203,7 @@ impl Repository<ProjectAccessScope> for ProjectAccessScopesRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn delete(&self, id: Uuid) -> Result<bool, Error> {
         let deleted = sqlx::query!(
             "DELETE FROM project_access_scopes WHERE id = $1 RETURNING id",
@@ -188,6 +220,7 @@ impl Repository<ProjectAccessScope> for ProjectAccessScopesRepository {
         Ok(true)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn find(
         &self,
         filter: Self::Filter,
@@ -33,6 +33,7 @@ impl Repository<Project> for ProjectRepository {
     type Filter = ProjectFilter;
     type Sort = ProjectSortOrder;
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn create(&self, item: Self::CreatePayload) -> Result<Project, Error> {
         let project = Project {
             id: None,
@@ -54,15 +55,16 @@ impl Repository<Project> for ProjectRepository {
         .await
         .map_err(<sqlx::Error as Into<Error>>::into);
 
-        if created_project.is_err() {
+        if let Err(error) = &created_project {
             return Err(Error::msg(
-                "Failed to create project: ".to_owned() + &created_project.unwrap_err().to_string(),
+                "Failed to create project: ".to_owned() + &error.to_string(),
             ));
         }
 
         Ok(created_project.unwrap())
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn read(&self, id: Uuid) -> Result<Option<Project>, Error> {
         let project = sqlx::query_as!(
             Project,
@@ -80,6 +82,7 @@ impl Repository<Project> for ProjectRepository {
         Ok(project)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn update(&self, id: Uuid, update: Self::UpdatePayload) -> Result<Project, Error> {
         let mut changes = Vec::new();
 
@@ -140,6 +143,7 @@ impl Repository<Project> for ProjectRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Project not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
+                    tracing::warn!(error = error_message, "Database rejected the query");
 
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
@@ -153,6 +157,7 @@ impl Repository<Project> for ProjectRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn delete(&self, id: Uuid) -> Result<bool, Error> {
         let result = sqlx::query!("DELETE FROM projects WHERE id = $1", id,)
             .execute(&*self.pool)
@@ -164,6 +169,7 @@ impl Repository<Project> for ProjectRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn find(
         &self,
         filter: Self::Filter,
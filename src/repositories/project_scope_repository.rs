@@ -35,6 +35,7 @@ impl Repository<ProjectScope> for ProjectScopeRepository {
     type Filter = ProjectScopeFilter;
     type Sort = ProjectScopeSortOrder;
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn create(&self, item: Self::CreatePayload) -> Result<ProjectScope, Error> {
         let project_scope = ProjectScope {
             id: None,
@@ -63,6 +64,7 @@ impl Repository<ProjectScope> for ProjectScopeRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Project scope not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
+                    tracing::warn!(error = error_message, "Database rejected the query");
 
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
@@ -85,6 +87,7 @@ impl Repository<ProjectScope> for ProjectScopeRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn read(&self, id: Uuid) -> Result<Option<ProjectScope>, Error> {
         let project_scope = sqlx::query_as!(
             ProjectScope,
@@ -102,6 +105,7 @@ impl Repository<ProjectScope> for ProjectScopeRepository {
         Ok(project_scope)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn update(&self, id: Uuid, update: Self::UpdatePayload) -> Result<ProjectScope, Error> {
         let mut changes = Vec::new();
 
@@ -162,6 +166,7 @@ impl Repository<ProjectScope> for ProjectScopeRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Project scope not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
+                    tracing::warn!(error = error_message, "Database rejected the query");
 
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
@@ -184,6 +189,7 @@ impl Repository<ProjectScope> for ProjectScopeRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn delete(&self, id: Uuid) -> Result<bool, Error> {
         let deleted = sqlx::query!("DELETE FROM project_scopes WHERE id = $1 RETURNING id", id,)
             .fetch_optional(&*self.pool)
@@ -197,6 +203,7 @@ impl Repository<ProjectScope> for ProjectScopeRepository {
         Ok(true)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn find(
         &self,
         filter: Self::Filter,
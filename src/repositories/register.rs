@@ -3,16 +3,33 @@ use actix_web::{App, web};
 use sqlx::postgres::PgPool;
 use std::sync::Arc;
 
+use crate::models::token_policy::EffectiveTokenPolicy;
 use crate::repositories::{
+    access_token_repository::AccessTokenRepository, api_key_repository::ApiKeyRepository,
+    audit_event_repository::AuditEventRepository,
+    authentication_failure_repository::AuthenticationFailureRepository,
+    claim_template_repository::ClaimTemplateRepository,
     environment_key_repository::EnvironmentKeyRepository,
     environment_repository::EnvironmentRepository,
     project_access_repository::ProjectAccessRepository,
     project_access_scopes_repository::ProjectAccessScopesRepository,
     project_repository::ProjectRepository, project_scope_repository::ProjectScopeRepository,
+    service_account_credential_repository::ServiceAccountCredentialRepository,
+    service_account_key_repository::ServiceAccountKeyRepository,
     service_account_repository::ServiceAccountRepository,
+    token_policy_repository::TokenPolicyRepository,
 };
+use crate::utils::key_store::KeyStore;
+use crate::utils::security::SecretsManager;
 
-pub fn register_repositories<T>(app: App<T>, pool: Arc<PgPool>) -> App<T>
+pub fn register_repositories<T>(
+    app: App<T>,
+    pool: Arc<PgPool>,
+    secrets_manager: SecretsManager,
+    key_store: Arc<dyn KeyStore>,
+    token_defaults: EffectiveTokenPolicy,
+    key_rotation_jitter_seconds: i64,
+) -> App<T>
 where
     T: ServiceFactory<
             ServiceRequest,
@@ -22,12 +39,37 @@ where
             InitError = (),
         >,
 {
-    app
-        .app_data(web::Data::new(ProjectRepository::new(pool.clone())))
-        .app_data(web::Data::new(ServiceAccountRepository::new(pool.clone())))
+    app.app_data(web::Data::new(ProjectRepository::new(pool.clone())))
+        .app_data(web::Data::new(ServiceAccountRepository::new(
+            pool.clone(),
+            secrets_manager.clone(),
+        )))
         .app_data(web::Data::new(ProjectScopeRepository::new(pool.clone())))
         .app_data(web::Data::new(EnvironmentRepository::new(pool.clone())))
         .app_data(web::Data::new(ProjectAccessRepository::new(pool.clone())))
-        .app_data(web::Data::new(ProjectAccessScopesRepository::new(pool.clone())))
-        .app_data(web::Data::new(EnvironmentKeyRepository::new(pool.clone())))
+        .app_data(web::Data::new(ProjectAccessScopesRepository::new(
+            pool.clone(),
+        )))
+        .app_data(web::Data::new(
+            EnvironmentKeyRepository::new(pool.clone(), secrets_manager)
+                .with_key_store(key_store)
+                .with_token_defaults(token_defaults.clone())
+                .with_rotation_jitter(key_rotation_jitter_seconds),
+        ))
+        .app_data(web::Data::new(AccessTokenRepository::new(pool.clone())))
+        .app_data(web::Data::new(
+            TokenPolicyRepository::new(pool.clone()).with_defaults(token_defaults),
+        ))
+        .app_data(web::Data::new(ClaimTemplateRepository::new(pool.clone())))
+        .app_data(web::Data::new(ServiceAccountKeyRepository::new(
+            pool.clone(),
+        )))
+        .app_data(web::Data::new(ServiceAccountCredentialRepository::new(
+            pool.clone(),
+        )))
+        .app_data(web::Data::new(ApiKeyRepository::new(pool.clone())))
+        .app_data(web::Data::new(AuthenticationFailureRepository::new(
+            pool.clone(),
+        )))
+        .app_data(web::Data::new(AuditEventRepository::new(pool.clone())))
 }
@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        pagination::Pagination,
+        service_account_credential::{
+            ServiceAccountCredential, ServiceAccountCredentialCreatePayload,
+            ServiceAccountCredentialCreatePayloadWithSecret, ServiceAccountCredentialFilter,
+            ServiceAccountCredentialRotatePayload, ServiceAccountCredentialSortOrder,
+            ServiceAccountCredentialUpdatePayload, parse_expires_at,
+        },
+    },
+    repositories::base::Repository,
+    utils::security::hash_token,
+};
+
+#[derive(Clone)]
+pub struct ServiceAccountCredentialRepository {
+    pub pool: Arc<sqlx::postgres::PgPool>,
+}
+
+impl ServiceAccountCredentialRepository {
+    pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// Returns the unexpired credential of a service account matching a secret and records its use
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn verify(
+        &self,
+        service_account_id: Uuid,
+        secret: &str,
+    ) -> Result<Option<ServiceAccountCredential>, Error> {
+        sqlx::query_as!(
+            ServiceAccountCredential,
+            "UPDATE service_account_credentials SET last_used_at = now() WHERE service_account_id = $1 AND secret_hash = $2 AND (expires_at IS NULL OR expires_at > now()) RETURNING id, service_account_id, label, secret_hash, expires_at, last_used_at, created_at, updated_at",
+            service_account_id,
+            hash_token(secret),
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)
+    }
+
+    /// Replaces a credential with a new one holding `secret`.
+    ///
+    /// The rotated credential keeps working for the grace period of the payload, or until it
+    /// expires if that comes first, so workloads can pick up the new secret without downtime.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    pub async fn rotate(
+        &self,
+        id: Uuid,
+        secret: &str,
+        payload: ServiceAccountCredentialRotatePayload,
+    ) -> Result<ServiceAccountCredential, Error> {
+        let grace_period = payload.validate()?;
+        let expires_at = parse_expires_at(payload.expires_at.as_deref())?;
+        let rotated_until = Utc::now() + grace_period;
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let rotated = sqlx::query!(
+            "UPDATE service_account_credentials SET expires_at = LEAST(COALESCE(expires_at, $1), $1), updated_at = now() WHERE id = $2 AND (expires_at IS NULL OR expires_at > now()) RETURNING service_account_id, label",
+            rotated_until,
+            id,
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?
+        .ok_or_else(|| Error::msg("Service account credential not found"))?;
+
+        let credential = sqlx::query_as!(
+            ServiceAccountCredential,
+            "INSERT INTO service_account_credentials (service_account_id, label, secret_hash, expires_at) VALUES ($1, $2, $3, $4) RETURNING id, service_account_id, label, secret_hash, expires_at, last_used_at, created_at, updated_at",
+            rotated.service_account_id,
+            payload.label.unwrap_or(rotated.label),
+            hash_token(secret),
+            expires_at,
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .map_err(Self::map_error)?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(credential)
+    }
+
+    fn map_error(error: sqlx::Error) -> Error {
+        match error {
+            sqlx::Error::RowNotFound => Error::msg("Service account credential not found"),
+            sqlx::Error::Database(e) => {
+                let error_message = e.message();
+                tracing::warn!(error = error_message, "Database rejected the query");
+
+                match error_message {
+                    s if s.contains("unique constraint") || s.contains("duplicate key") => {
+                        Error::msg("Service account credential already exists")
+                    }
+                    s if s.contains("foreign key")
+                        && s.contains("service_account_credentials_service_account_id_fkey") =>
+                    {
+                        Error::msg("Service account not found")
+                    }
+                    _ => Error::msg("No changes were made"),
+                }
+            }
+            _ => error.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<ServiceAccountCredential> for ServiceAccountCredentialRepository {
+    type CreatePayload = ServiceAccountCredentialCreatePayloadWithSecret;
+    type UpdatePayload = ServiceAccountCredentialUpdatePayload;
+    type Filter = ServiceAccountCredentialFilter;
+    type Sort = ServiceAccountCredentialSortOrder;
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn create(&self, item: Self::CreatePayload) -> Result<ServiceAccountCredential, Error> {
+        let payload = ServiceAccountCredentialCreatePayload {
+            label: item.label,
+            expires_at: item.expires_at,
+        };
+        payload.validate()?;
+        let expires_at = parse_expires_at(payload.expires_at.as_deref())?;
+        let service_account_id = Uuid::parse_str(&item.service_account_id)
+            .map_err(|_| Error::msg("Invalid service account ID"))?;
+
+        sqlx::query_as!(
+            ServiceAccountCredential,
+            "INSERT INTO service_account_credentials (service_account_id, label, secret_hash, expires_at) VALUES ($1, $2, $3, $4) RETURNING id, service_account_id, label, secret_hash, expires_at, last_used_at, created_at, updated_at",
+            service_account_id,
+            payload.label,
+            hash_token(&item.secret),
+            expires_at,
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(Self::map_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn read(&self, id: Uuid) -> Result<Option<ServiceAccountCredential>, Error> {
+        let credential = sqlx::query_as!(
+            ServiceAccountCredential,
+            "SELECT id, service_account_id, label, secret_hash, expires_at, last_used_at, created_at, updated_at FROM service_account_credentials WHERE id = $1 LIMIT 1",
+            id,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if credential.is_none() {
+            return Err(Error::msg("Service account credential not found"));
+        }
+
+        Ok(credential)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn update(
+        &self,
+        id: Uuid,
+        update: Self::UpdatePayload,
+    ) -> Result<ServiceAccountCredential, Error> {
+        if update.label.is_none() && update.expires_at.is_none() {
+            return Err(Error::msg("No changes to update"));
+        }
+        update.validate()?;
+        let expires_at = parse_expires_at(update.expires_at.as_deref())?;
+
+        sqlx::query_as!(
+            ServiceAccountCredential,
+            "UPDATE service_account_credentials SET label = COALESCE($1, label), expires_at = COALESCE($2, expires_at), updated_at = $3 WHERE id = $4 RETURNING id, service_account_id, label, secret_hash, expires_at, last_used_at, created_at, updated_at",
+            update.label,
+            expires_at,
+            Utc::now(),
+            id,
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(Self::map_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn delete(&self, id: Uuid) -> Result<bool, Error> {
+        let deleted = sqlx::query!(
+            "DELETE FROM service_account_credentials WHERE id = $1 RETURNING id",
+            id
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if deleted.is_none() {
+            return Err(Error::msg("Service account credential not found"));
+        }
+
+        Ok(true)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn find(
+        &self,
+        filter: Self::Filter,
+        sort: Option<Vec<Self::Sort>>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<ServiceAccountCredential>, Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, service_account_id, label, secret_hash, expires_at, last_used_at, created_at, updated_at FROM service_account_credentials ",
+        );
+
+        let service_account_id = filter
+            .service_account_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| Error::msg("Invalid service account ID"))?;
+
+        if service_account_id.is_some() || filter.active.is_some() {
+            query.push("WHERE ");
+            let mut conditions = query.separated(" AND ");
+            if let Some(service_account_id) = service_account_id {
+                conditions
+                    .push("service_account_id = ")
+                    .push_bind_unseparated(service_account_id);
+            }
+            match filter.active {
+                Some(true) => {
+                    conditions.push("(expires_at IS NULL OR expires_at > now())");
+                }
+                Some(false) => {
+                    conditions.push("expires_at <= now()");
+                }
+                None => {}
+            }
+        }
+
+        if let Some(sort) = sort {
+            query.push(" ORDER BY ");
+            let mut order_by = query.separated(", ");
+            for sort in sort {
+                let field = String::from(sort.field);
+                order_by.push(format!("{} {}", field, sort.order));
+            }
+        }
+
+        if let Some(pagination) = pagination {
+            query
+                .push(" LIMIT ")
+                .push_bind(pagination.limit.unwrap_or(10));
+            query
+                .push(" OFFSET ")
+                .push_bind(pagination.offset.unwrap_or(0));
+        }
+
+        let credentials = query
+            .build()
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?
+            .into_iter()
+            .map(|row| ServiceAccountCredential {
+                id: row.get("id"),
+                service_account_id: row.get("service_account_id"),
+                label: row.get("label"),
+                secret_hash: row.get("secret_hash"),
+                expires_at: row.get("expires_at"),
+                last_used_at: row.get("last_used_at"),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+            })
+            .collect();
+
+        Ok(credentials)
+    }
+}
@@ -0,0 +1,261 @@
+use std::sync::Arc;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        pagination::Pagination,
+        service_account_key::{
+            ServiceAccountKey, ServiceAccountKeyCreatePayload, ServiceAccountKeyFilter,
+            ServiceAccountKeySortOrder, ServiceAccountKeyUpdatePayload,
+        },
+    },
+    repositories::base::Repository,
+};
+
+#[derive(Clone)]
+pub struct ServiceAccountKeyRepository {
+    pub pool: Arc<sqlx::postgres::PgPool>,
+}
+
+impl ServiceAccountKeyRepository {
+    pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the enabled keys of a service account for an algorithm
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn find_enabled_by_service_account(
+        &self,
+        service_account_id: Uuid,
+        algorithm: &str,
+    ) -> Result<Vec<ServiceAccountKey>, Error> {
+        sqlx::query_as!(
+            ServiceAccountKey,
+            "SELECT id, service_account_id, name, algorithm, public_key, enabled, created_at, updated_at FROM service_account_keys WHERE service_account_id = $1 AND algorithm = $2 AND enabled = true ORDER BY created_at, id",
+            service_account_id,
+            algorithm,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)
+    }
+
+    /// Records the ID of a client assertion, returning `false` when it was already used.
+    ///
+    /// IDs of expired assertions are dropped first, since those assertions are rejected anyway.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn record_assertion_jti(
+        &self,
+        service_account_id: Uuid,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        sqlx::query!("DELETE FROM client_assertion_jtis WHERE expires_at < now()")
+            .execute(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let result = sqlx::query!(
+            "INSERT INTO client_assertion_jtis (service_account_id, jti, expires_at) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+            service_account_id,
+            jti,
+            expires_at,
+        )
+        .execute(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    fn map_error(error: sqlx::Error) -> Error {
+        match error {
+            sqlx::Error::RowNotFound => Error::msg("Service account key not found"),
+            sqlx::Error::Database(e) => {
+                let error_message = e.message();
+                tracing::warn!(error = error_message, "Database rejected the query");
+
+                match error_message {
+                    s if s.contains("unique constraint") || s.contains("duplicate key") => {
+                        Error::msg("Service account key already exists")
+                    }
+                    s if s.contains("foreign key")
+                        && s.contains("service_account_keys_service_account_id_fkey") =>
+                    {
+                        Error::msg("Service account not found")
+                    }
+                    _ => Error::msg("No changes were made"),
+                }
+            }
+            _ => error.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<ServiceAccountKey> for ServiceAccountKeyRepository {
+    type CreatePayload = ServiceAccountKeyCreatePayload;
+    type UpdatePayload = ServiceAccountKeyUpdatePayload;
+    type Filter = ServiceAccountKeyFilter;
+    type Sort = ServiceAccountKeySortOrder;
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn create(&self, item: Self::CreatePayload) -> Result<ServiceAccountKey, Error> {
+        let public_key = item.validate()?;
+        let service_account_id = Uuid::parse_str(&item.service_account_id)
+            .map_err(|_| Error::msg("Invalid service account ID"))?;
+
+        sqlx::query_as!(
+            ServiceAccountKey,
+            "INSERT INTO service_account_keys (service_account_id, name, algorithm, public_key, enabled) VALUES ($1, $2, $3, $4, $5) RETURNING id, service_account_id, name, algorithm, public_key, enabled, created_at, updated_at",
+            service_account_id,
+            item.name,
+            item.algorithm,
+            public_key,
+            item.enabled.unwrap_or(true),
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(Self::map_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn read(&self, id: Uuid) -> Result<Option<ServiceAccountKey>, Error> {
+        let service_account_key = sqlx::query_as!(
+            ServiceAccountKey,
+            "SELECT id, service_account_id, name, algorithm, public_key, enabled, created_at, updated_at FROM service_account_keys WHERE id = $1 LIMIT 1",
+            id,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if service_account_key.is_none() {
+            return Err(Error::msg("Service account key not found"));
+        }
+
+        Ok(service_account_key)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn update(
+        &self,
+        id: Uuid,
+        update: Self::UpdatePayload,
+    ) -> Result<ServiceAccountKey, Error> {
+        if update.name.is_none() && update.enabled.is_none() {
+            return Err(Error::msg("No changes to update"));
+        }
+        if update
+            .name
+            .as_deref()
+            .is_some_and(|name| name.trim().is_empty())
+        {
+            return Err(Error::msg("Key name must not be empty"));
+        }
+
+        sqlx::query_as!(
+            ServiceAccountKey,
+            "UPDATE service_account_keys SET name = COALESCE($1, name), enabled = COALESCE($2, enabled), updated_at = $3 WHERE id = $4 RETURNING id, service_account_id, name, algorithm, public_key, enabled, created_at, updated_at",
+            update.name,
+            update.enabled,
+            Utc::now(),
+            id,
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(Self::map_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn delete(&self, id: Uuid) -> Result<bool, Error> {
+        let deleted = sqlx::query!(
+            "DELETE FROM service_account_keys WHERE id = $1 RETURNING id",
+            id
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if deleted.is_none() {
+            return Err(Error::msg("Service account key not found"));
+        }
+
+        Ok(true)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn find(
+        &self,
+        filter: Self::Filter,
+        sort: Option<Vec<Self::Sort>>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<ServiceAccountKey>, Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, service_account_id, name, algorithm, public_key, enabled, created_at, updated_at FROM service_account_keys ",
+        );
+
+        let service_account_id = filter
+            .service_account_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| Error::msg("Invalid service account ID"))?;
+
+        if service_account_id.is_some() || filter.enabled.is_some() {
+            query.push("WHERE ");
+            let mut conditions = query.separated(" AND ");
+            if let Some(service_account_id) = service_account_id {
+                conditions
+                    .push("service_account_id = ")
+                    .push_bind_unseparated(service_account_id);
+            }
+            if let Some(enabled) = filter.enabled {
+                conditions.push("enabled = ").push_bind_unseparated(enabled);
+            }
+        }
+
+        if let Some(sort) = sort {
+            query.push(" ORDER BY ");
+            let mut order_by = query.separated(", ");
+            for sort in sort {
+                let field = String::from(sort.field);
+                order_by.push(format!("{} {}", field, sort.order));
+            }
+        }
+
+        if let Some(pagination) = pagination {
+            query
+                .push(" LIMIT ")
+                .push_bind(pagination.limit.unwrap_or(10));
+            query
+                .push(" OFFSET ")
+                .push_bind(pagination.offset.unwrap_or(0));
+        }
+
+        let service_account_keys = query
+            .build()
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?
+            .into_iter()
+            .map(|row| ServiceAccountKey {
+                id: row.get("id"),
+                service_account_id: row.get("service_account_id"),
+                name: row.get("name"),
+                algorithm: row.get("algorithm"),
+                public_key: row.get("public_key"),
+                enabled: row.get("enabled"),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+            })
+            .collect();
+
+        Ok(service_account_keys)
+    }
+}
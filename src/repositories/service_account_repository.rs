@@ -1,15 +1,21 @@
 use std::sync::Arc;
 
+use crate::models::audit_event::{
+    AUDIT_EVENT_SERVICE_ACCOUNT_EXPIRED, AUDIT_EVENT_SERVICE_ACCOUNT_INACTIVITY_DISABLED,
+    AUDIT_EVENT_SERVICE_ACCOUNT_INACTIVITY_WARNING,
+};
 use crate::models::pagination::Pagination;
 use crate::models::service_account::{
     ServiceAccount, ServiceAccountCreatePayload, ServiceAccountFilter, ServiceAccountSortOrder,
     ServiceAccountUpdatePayload,
 };
+use crate::models::service_account_credential::parse_expires_at;
 use crate::repositories::base::Repository;
+use crate::utils::network::normalize_cidrs;
 use crate::utils::security::SecretsManager;
 use anyhow::Error;
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::QueryBuilder;
 use sqlx::Row;
 use uuid::Uuid;
@@ -21,12 +27,143 @@ pub struct ServiceAccountRepository {
 }
 
 impl ServiceAccountRepository {
-    pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
+    pub fn new(pool: Arc<sqlx::postgres::PgPool>, secrets_manager: SecretsManager) -> Self {
         Self {
             pool,
-            secrets_manager: SecretsManager::new(true).unwrap(),
+            secrets_manager,
         }
     }
+
+    /// Returns the service account with an ID, or `None` when there is none
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<ServiceAccount>, Error> {
+        sqlx::query_as!(
+            ServiceAccount,
+            r#"
+            SELECT id, name, email, secret, description, enabled, allowed_cidrs, expires_at, last_token_issued_at, created_at, updated_at 
+            FROM service_account 
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)
+    }
+
+    /// Records that a token was issued to the service account, which keeps it active
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    pub async fn record_token_issued(&self, id: Uuid) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE service_account SET last_token_issued_at = now(), inactivity_warned_at = NULL WHERE id = $1",
+            id,
+        )
+        .execute(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(())
+    }
+
+    /// Disables every enabled service account past its expiration, returning their IDs.
+    ///
+    /// The audit events are written by the same statement, so each disablement is recorded
+    /// exactly once even when several instances run the job.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn disable_expired(&self) -> Result<Vec<Uuid>, Error> {
+        let disabled = sqlx::query_scalar!(
+            r#"
+            WITH disabled AS (
+                UPDATE service_account SET enabled = false, updated_at = now()
+                WHERE enabled = true AND expires_at <= now()
+                RETURNING id, expires_at
+            ), events AS (
+                INSERT INTO audit_events (event_type, subject_type, subject, details)
+                SELECT $1, 'service_account', id::text, 'Expired at ' || expires_at::text FROM disabled
+            )
+            SELECT id AS "id!" FROM disabled
+            "#,
+            AUDIT_EVENT_SERVICE_ACCOUNT_EXPIRED,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(disabled)
+    }
+
+    /// Disables every enabled service account without token issuance, creation or
+    /// re-enablement since `cutoff` that was warned of it no later than `warned_before`,
+    /// returning their IDs
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn disable_inactive(
+        &self,
+        cutoff: DateTime<Utc>,
+        warned_before: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, Error> {
+        let disabled = sqlx::query_scalar!(
+            r#"
+            WITH disabled AS (
+                UPDATE service_account SET enabled = false, updated_at = now()
+                WHERE enabled = true
+                    AND GREATEST(created_at, last_token_issued_at, enabled_at) <= $2
+                    AND inactivity_warned_at <= $3
+                RETURNING id, GREATEST(created_at, last_token_issued_at, enabled_at) AS last_active_at
+            ), events AS (
+                INSERT INTO audit_events (event_type, subject_type, subject, details)
+                SELECT $1, 'service_account', id::text, 'Inactive since ' || last_active_at::text FROM disabled
+            )
+            SELECT id AS "id!" FROM disabled
+            "#,
+            AUDIT_EVENT_SERVICE_ACCOUNT_INACTIVITY_DISABLED,
+            cutoff,
+            warned_before,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(disabled)
+    }
+
+    /// Warns, once, every enabled service account inactive since `warning_cutoff` that it
+    /// will be disabled when `inactivity_period` has passed since its last activity, and
+    /// `warning_period` since this warning, returning their IDs
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn warn_inactive(
+        &self,
+        warning_cutoff: DateTime<Utc>,
+        inactivity_period: chrono::Duration,
+        warning_period: chrono::Duration,
+    ) -> Result<Vec<Uuid>, Error> {
+        let warned = sqlx::query_scalar!(
+            r#"
+            WITH warned AS (
+                UPDATE service_account SET inactivity_warned_at = now()
+                WHERE enabled = true
+                    AND inactivity_warned_at IS NULL
+                    AND GREATEST(created_at, last_token_issued_at, enabled_at) <= $2
+                RETURNING id, GREATEST(
+                    GREATEST(created_at, last_token_issued_at, enabled_at) + make_interval(secs => $3),
+                    now() + make_interval(secs => $4)
+                ) AS disable_at
+            ), events AS (
+                INSERT INTO audit_events (event_type, subject_type, subject, details)
+                SELECT $1, 'service_account', id::text, 'Will be disabled for inactivity at ' || disable_at::text || ' unless a token is issued' FROM warned
+            )
+            SELECT id AS "id!" FROM warned
+            "#,
+            AUDIT_EVENT_SERVICE_ACCOUNT_INACTIVITY_WARNING,
+            warning_cutoff,
+            inactivity_period.num_seconds() as f64,
+            warning_period.num_seconds() as f64,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        Ok(warned)
+    }
 }
 
 #[async_trait]
@@ -36,6 +173,7 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
     type Filter = ServiceAccountFilter;
     type Sort = ServiceAccountSortOrder;
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn create(&self, item: Self::CreatePayload) -> Result<ServiceAccount, Error> {
         let id = Uuid::new_v4();
         let service_account = ServiceAccount {
@@ -45,6 +183,9 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
             secret: self.secrets_manager.encrypt(&item.secret, &id)?,
             description: item.description,
             enabled: item.enabled,
+            allowed_cidrs: normalize_cidrs(&item.allowed_cidrs.unwrap_or_default())?,
+            expires_at: parse_expires_at(item.expires_at.as_deref())?,
+            last_token_issued_at: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -52,9 +193,9 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
         let result = sqlx::query_as!(
             ServiceAccount,
             r#"
-            INSERT INTO service_account (id, name, email, secret, description, enabled) 
-            VALUES ($1, $2, $3, $4, $5, $6) 
-            RETURNING id, name, email, secret, description, enabled, created_at, updated_at
+            INSERT INTO service_account (id, name, email, secret, description, enabled, allowed_cidrs, expires_at) 
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8) 
+            RETURNING id, name, email, secret, description, enabled, allowed_cidrs, expires_at, last_token_issued_at, created_at, updated_at
             "#,
             service_account.id,
             service_account.name,
@@ -62,6 +203,8 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
             service_account.secret,
             service_account.description,
             service_account.enabled,
+            &service_account.allowed_cidrs,
+            service_account.expires_at,
         )
         .fetch_one(&*self.pool)
         .await;
@@ -72,6 +215,7 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Service account not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
+                    tracing::warn!(error = error_message, "Database rejected the query");
 
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
@@ -91,19 +235,9 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn read(&self, id: Uuid) -> Result<Option<ServiceAccount>, Error> {
-        let service_account = sqlx::query_as!(
-            ServiceAccount,
-            r#"
-            SELECT id, name, email, secret, description, enabled, created_at, updated_at 
-            FROM service_account 
-            WHERE id = $1
-            "#,
-            id
-        )
-        .fetch_optional(&*self.pool)
-        .await
-        .map_err(<sqlx::Error as Into<Error>>::into)?;
+        let service_account = self.find_by_id(id).await?;
 
         if service_account.is_none() {
             return Err(Error::msg("Service account not found"));
@@ -112,6 +246,7 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
         Ok(service_account)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn update(&self, id: Uuid, update: Self::UpdatePayload) -> Result<ServiceAccount, Error> {
         let mut changes = Vec::new();
 
@@ -134,12 +269,23 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
 
         if let Some(enabled) = update.enabled {
             match enabled {
-                true => changes.push(("enabled = true", "".to_string())),
+                // Enabling a disabled service account restarts its inactivity period
+                true => changes.push((
+                    "enabled_at = CASE WHEN enabled THEN enabled_at ELSE now() END, inactivity_warned_at = CASE WHEN enabled THEN inactivity_warned_at ELSE NULL END, enabled = true",
+                    "".to_string(),
+                )),
                 false => changes.push(("enabled = false", "".to_string())),
             }
         }
 
-        if changes.is_empty() {
+        let allowed_cidrs = update
+            .allowed_cidrs
+            .as_deref()
+            .map(normalize_cidrs)
+            .transpose()?;
+        let expires_at = parse_expires_at(update.expires_at.as_deref())?;
+
+        if changes.is_empty() && allowed_cidrs.is_none() && expires_at.is_none() {
             return Err(Error::msg("No changes to update"));
         }
 
@@ -155,11 +301,21 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
                     .push_bind_unseparated(value);
             }
         }
+        if let Some(allowed_cidrs) = allowed_cidrs {
+            separated
+                .push("allowed_cidrs = ")
+                .push_bind_unseparated(allowed_cidrs);
+        }
+        if let Some(expires_at) = expires_at {
+            separated
+                .push("expires_at = ")
+                .push_bind_unseparated(expires_at);
+        }
 
         query.push(", updated_at = ").push_bind(Utc::now());
         query.push(" WHERE id = ").push_bind(id);
         query.push(
-            " RETURNING id, name, email, secret, description, enabled, created_at, updated_at",
+            " RETURNING id, name, email, secret, description, enabled, allowed_cidrs, expires_at, last_token_issued_at, created_at, updated_at",
         );
 
         let result = query
@@ -173,6 +329,9 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
                 secret: row.get("secret"),
                 description: row.get("description"),
                 enabled: row.get("enabled"),
+                allowed_cidrs: row.get("allowed_cidrs"),
+                expires_at: row.get("expires_at"),
+                last_token_issued_at: row.get("last_token_issued_at"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             });
@@ -183,6 +342,7 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
                 sqlx::Error::RowNotFound => Err(Error::msg("Service account not found")),
                 sqlx::Error::Database(e) => {
                     let error_message = e.message();
+                    tracing::warn!(error = error_message, "Database rejected the query");
 
                     match error_message {
                         s if s.contains("unique constraint") || s.contains("duplicate key") => {
@@ -202,6 +362,7 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
     async fn delete(&self, id: Uuid) -> Result<bool, Error> {
         let result = sqlx::query!("DELETE FROM service_account WHERE id = $1", id)
             .execute(&*self.pool)
@@ -213,6 +374,7 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
     async fn find(
         &self,
         filter: Self::Filter,
@@ -220,7 +382,7 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
         pagination: Option<Pagination>,
     ) -> Result<Vec<ServiceAccount>, Error> {
         let mut query = QueryBuilder::new(
-            "SELECT id, name, email, secret, description, enabled, created_at, updated_at FROM service_account ",
+            "SELECT id, name, email, secret, description, enabled, allowed_cidrs, expires_at, last_token_issued_at, created_at, updated_at FROM service_account ",
         );
 
         let mut conditions_list = Vec::new();
@@ -287,6 +449,9 @@ impl Repository<ServiceAccount> for ServiceAccountRepository {
                 secret: row.get("secret"),
                 description: row.get("description"),
                 enabled: row.get("enabled"),
+                allowed_cidrs: row.get("allowed_cidrs"),
+                expires_at: row.get("expires_at"),
+                last_token_issued_at: row.get("last_token_issued_at"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             })
@@ -0,0 +1,310 @@
+use std::sync::Arc;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        pagination::Pagination,
+        token_policy::{
+            EffectiveTokenPolicy, TokenPolicy, TokenPolicyCreatePayload, TokenPolicyFilter,
+            TokenPolicySortOrder, TokenPolicyUpdatePayload,
+        },
+    },
+    repositories::base::Repository,
+};
+
+#[derive(Clone)]
+pub struct TokenPolicyRepository {
+    pub pool: Arc<sqlx::postgres::PgPool>,
+    /// Policy the project policy and the environment override are merged on top of
+    pub defaults: EffectiveTokenPolicy,
+}
+
+impl TokenPolicyRepository {
+    pub fn new(pool: Arc<sqlx::postgres::PgPool>) -> Self {
+        Self {
+            pool,
+            defaults: EffectiveTokenPolicy::default(),
+        }
+    }
+
+    /// Replaces the policy of environments whose project sets no policy
+    pub fn with_defaults(mut self, defaults: EffectiveTokenPolicy) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Merges the project policy and the environment override that apply to an environment.
+    ///
+    /// Environments without any policy get the configured defaults.
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn resolve(&self, environment_id: Uuid) -> Result<EffectiveTokenPolicy, Error> {
+        let policies = sqlx::query_as!(
+            TokenPolicy,
+            "SELECT token_policies.id, token_policies.project_id, token_policies.environment_id, token_policies.max_ttl_seconds, token_policies.default_ttl_seconds, token_policies.audience, token_policies.issuer, token_policies.allowed_algorithms, token_policies.allow_refresh_tokens, token_policies.refresh_token_ttl_seconds, token_policies.created_at, token_policies.updated_at FROM token_policies INNER JOIN environment ON environment.project_id = token_policies.project_id WHERE environment.id = $1 AND (token_policies.environment_id IS NULL OR token_policies.environment_id = $1)",
+            environment_id,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let project = policies
+            .iter()
+            .find(|policy| policy.environment_id.is_none());
+        let environment = policies
+            .iter()
+            .find(|policy| policy.environment_id.is_some());
+
+        self.defaults.clone().merge(project, environment)
+    }
+
+    fn map_error(error: sqlx::Error) -> Error {
+        match error {
+            sqlx::Error::RowNotFound => Error::msg("Token policy not found"),
+            sqlx::Error::Database(e) => {
+                let error_message = e.message();
+                tracing::warn!(error = error_message, "Database rejected the query");
+
+                match error_message {
+                    s if s.contains("unique constraint") || s.contains("duplicate key") => {
+                        Error::msg("Token policy already exists")
+                    }
+                    s if s.contains("foreign key")
+                        && s.contains("token_policies_project_id_fkey") =>
+                    {
+                        Error::msg("Project not found")
+                    }
+                    s if s.contains("foreign key")
+                        && s.contains("token_policies_environment_id_fkey") =>
+                    {
+                        Error::msg("Environment not found")
+                    }
+                    _ => Error::msg("No changes were made"),
+                }
+            }
+            _ => error.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<TokenPolicy> for TokenPolicyRepository {
+    type CreatePayload = TokenPolicyCreatePayload;
+    type UpdatePayload = TokenPolicyUpdatePayload;
+    type Filter = TokenPolicyFilter;
+    type Sort = TokenPolicySortOrder;
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn create(&self, item: Self::CreatePayload) -> Result<TokenPolicy, Error> {
+        item.validate()?;
+
+        let project_id =
+            Uuid::parse_str(&item.project_id).map_err(|_| Error::msg("Invalid project ID"))?;
+        let environment_id = item
+            .environment_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| Error::msg("Invalid environment ID"))?;
+
+        if let Some(environment_id) = environment_id {
+            let environment = sqlx::query!(
+                "SELECT project_id FROM environment WHERE id = $1 LIMIT 1",
+                environment_id,
+            )
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?
+            .ok_or_else(|| Error::msg("Environment not found"))?;
+
+            if environment.project_id != project_id {
+                return Err(Error::msg("Environment does not belong to the project"));
+            }
+        }
+
+        sqlx::query_as!(
+            TokenPolicy,
+            "INSERT INTO token_policies (project_id, environment_id, max_ttl_seconds, default_ttl_seconds, audience, issuer, allowed_algorithms, allow_refresh_tokens, refresh_token_ttl_seconds) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING id, project_id, environment_id, max_ttl_seconds, default_ttl_seconds, audience, issuer, allowed_algorithms, allow_refresh_tokens, refresh_token_ttl_seconds, created_at, updated_at",
+            project_id,
+            environment_id,
+            item.max_ttl_seconds,
+            item.default_ttl_seconds,
+            item.audience.as_deref(),
+            item.issuer,
+            item.allowed_algorithms.as_deref(),
+            item.allow_refresh_tokens,
+            item.refresh_token_ttl_seconds,
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(Self::map_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn read(&self, id: Uuid) -> Result<Option<TokenPolicy>, Error> {
+        let token_policy = sqlx::query_as!(
+            TokenPolicy,
+            "SELECT id, project_id, environment_id, max_ttl_seconds, default_ttl_seconds, audience, issuer, allowed_algorithms, allow_refresh_tokens, refresh_token_ttl_seconds, created_at, updated_at FROM token_policies WHERE id = $1 LIMIT 1",
+            id,
+        )
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if token_policy.is_none() {
+            return Err(Error::msg("Token policy not found"));
+        }
+
+        Ok(token_policy)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn update(&self, id: Uuid, update: Self::UpdatePayload) -> Result<TokenPolicy, Error> {
+        update.validate()?;
+
+        let current = self
+            .read(id)
+            .await?
+            .ok_or_else(|| Error::msg("Token policy not found"))?;
+
+        if update.max_ttl_seconds.is_none()
+            && update.default_ttl_seconds.is_none()
+            && update.audience.is_none()
+            && update.issuer.is_none()
+            && update.allowed_algorithms.is_none()
+            && update.allow_refresh_tokens.is_none()
+            && update.refresh_token_ttl_seconds.is_none()
+        {
+            return Err(Error::msg("No changes to update"));
+        }
+
+        // The TTL pair is validated against the stored values so a partial update cannot
+        // leave the default above the maximum
+        let max_ttl_seconds = update.max_ttl_seconds.or(current.max_ttl_seconds);
+        let default_ttl_seconds = update.default_ttl_seconds.or(current.default_ttl_seconds);
+        if let (Some(max_ttl_seconds), Some(default_ttl_seconds)) =
+            (max_ttl_seconds, default_ttl_seconds)
+            && default_ttl_seconds > max_ttl_seconds
+        {
+            return Err(Error::msg(
+                "default_ttl_seconds must not exceed max_ttl_seconds",
+            ));
+        }
+
+        let audience = update.audience.or(current.audience);
+        let allowed_algorithms = update.allowed_algorithms.or(current.allowed_algorithms);
+
+        sqlx::query_as!(
+            TokenPolicy,
+            "UPDATE token_policies SET max_ttl_seconds = $1, default_ttl_seconds = $2, audience = $3, issuer = $4, allowed_algorithms = $5, allow_refresh_tokens = $6, refresh_token_ttl_seconds = $7, updated_at = $8 WHERE id = $9 RETURNING id, project_id, environment_id, max_ttl_seconds, default_ttl_seconds, audience, issuer, allowed_algorithms, allow_refresh_tokens, refresh_token_ttl_seconds, created_at, updated_at",
+            max_ttl_seconds,
+            default_ttl_seconds,
+            audience.as_deref(),
+            update.issuer.or(current.issuer),
+            allowed_algorithms.as_deref(),
+            update.allow_refresh_tokens.or(current.allow_refresh_tokens),
+            update
+                .refresh_token_ttl_seconds
+                .or(current.refresh_token_ttl_seconds),
+            Utc::now(),
+            id,
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(Self::map_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id), err(level = "debug"))]
+    async fn delete(&self, id: Uuid) -> Result<bool, Error> {
+        let deleted = sqlx::query!("DELETE FROM token_policies WHERE id = $1 RETURNING id", id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        if deleted.is_none() {
+            return Err(Error::msg("Token policy not found"));
+        }
+
+        Ok(true)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    async fn find(
+        &self,
+        filter: Self::Filter,
+        sort: Option<Vec<Self::Sort>>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<TokenPolicy>, Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, project_id, environment_id, max_ttl_seconds, default_ttl_seconds, audience, issuer, allowed_algorithms, allow_refresh_tokens, refresh_token_ttl_seconds, created_at, updated_at FROM token_policies ",
+        );
+
+        let mut conditions_list = Vec::new();
+
+        if let Some(project_id) = &filter.project_id {
+            let project_id =
+                Uuid::parse_str(project_id).map_err(|_| Error::msg("Invalid project ID"))?;
+            conditions_list.push(("project_id = ", project_id));
+        }
+
+        if let Some(environment_id) = &filter.environment_id {
+            let environment_id = Uuid::parse_str(environment_id)
+                .map_err(|_| Error::msg("Invalid environment ID"))?;
+            conditions_list.push(("environment_id = ", environment_id));
+        }
+
+        if !conditions_list.is_empty() {
+            query.push("WHERE ");
+            let mut conditions = query.separated(" AND ");
+            for (condition, value) in conditions_list {
+                conditions.push(condition).push_bind_unseparated(value);
+            }
+        }
+
+        if let Some(sort) = sort {
+            query.push(" ORDER BY ");
+            let mut order_by = query.separated(", ");
+            for sort in sort {
+                let field = String::from(sort.field);
+                order_by.push(format!("{} {}", field, sort.order));
+            }
+        }
+
+        if let Some(pagination) = pagination {
+            query
+                .push(" LIMIT ")
+                .push_bind(pagination.limit.unwrap_or(10));
+            query
+                .push(" OFFSET ")
+                .push_bind(pagination.offset.unwrap_or(0));
+        }
+
+        let token_policies = query
+            .build()
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?
+            .into_iter()
+            .map(|row| TokenPolicy {
+                id: row.get("id"),
+                project_id: row.get("project_id"),
+                environment_id: row.get("environment_id"),
+                max_ttl_seconds: row.get("max_ttl_seconds"),
+                default_ttl_seconds: row.get("default_ttl_seconds"),
+                audience: row.get("audience"),
+                issuer: row.get("issuer"),
+                allowed_algorithms: row.get("allowed_algorithms"),
+                allow_refresh_tokens: row.get("allow_refresh_tokens"),
+                refresh_token_ttl_seconds: row.get("refresh_token_ttl_seconds"),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+            })
+            .collect();
+
+        Ok(token_policies)
+    }
+}
@@ -0,0 +1,214 @@
+use crate::models::api_key::{
+    ApiKeyCreatePayload, ApiKeyFilter, ApiKeyResponse, ApiKeySortOrder, ApiKeySortableFields,
+    ApiKeyUpdatePayload, ApiKeyVerification, ApiKeyVerifyPayload,
+};
+use crate::models::pagination::Pagination;
+use crate::models::sort::SortOrder;
+use crate::repositories::api_key_repository::ApiKeyRepository;
+use crate::repositories::base::Repository;
+use actix_web::{Error, HttpResponse, web};
+
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    tag = "API Keys",
+    request_body = ApiKeyCreatePayload,
+    responses(
+        (status = 201, description = "API key created, the key is only returned once", body = ApiKeyResponse),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "Project access not found", body = String),
+    ),
+)]
+pub async fn post(
+    repository: web::Data<ApiKeyRepository>,
+    payload: web::Json<ApiKeyCreatePayload>,
+) -> Result<HttpResponse, Error> {
+    let payload = payload.into_inner().with_key();
+    let key = payload.key.clone();
+    let api_key = repository.create(payload).await;
+
+    if let Err(error) = &api_key {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Project access not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::Created().json(ApiKeyResponse::with_key(api_key.unwrap(), key)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api-keys/{id}",
+    tag = "API Keys",
+    responses(
+        (status = 200, description = "API key found", body = ApiKeyResponse),
+        (status = 404, description = "API key not found", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "API Key ID"),
+    ),
+)]
+pub async fn get(
+    repository: web::Data<ApiKeyRepository>,
+    id: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, Error> {
+    let api_key = repository
+        .read(id.into_inner())
+        .await
+        .map_err(actix_web::error::ErrorNotFound)?;
+
+    Ok(HttpResponse::Ok().json(api_key.map(ApiKeyResponse::from)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api-keys/{id}",
+    tag = "API Keys",
+    request_body = ApiKeyUpdatePayload,
+    responses(
+        (status = 200, description = "API key updated", body = ApiKeyResponse),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "API key not found", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "API Key ID"),
+    ),
+)]
+pub async fn patch(
+    repository: web::Data<ApiKeyRepository>,
+    id: web::Path<uuid::Uuid>,
+    payload: web::Json<ApiKeyUpdatePayload>,
+) -> Result<HttpResponse, Error> {
+    let api_key = repository
+        .update(id.into_inner(), payload.into_inner())
+        .await;
+
+    if let Err(error) = &api_key {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "API key not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(ApiKeyResponse::from(api_key.unwrap())))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{id}",
+    tag = "API Keys",
+    responses(
+        (status = 204, description = "API key deleted", body = ()),
+        (status = 404, description = "API key not found", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "API Key ID"),
+    )
+)]
+pub async fn delete(
+    repository: web::Data<ApiKeyRepository>,
+    id: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, Error> {
+    let result = repository.delete(id.into_inner()).await;
+
+    if let Err(error) = &result {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "API key not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorInternalServerError(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    tag = "API Keys",
+    responses(
+        (status = 200, description = "API keys found", body = Vec<ApiKeyResponse>),
+        (status = 400, description = "Invalid request", body = String),
+    ),
+    params(
+        ("project_access_id" = Option<String>, Query, description = "Filter API keys by project access ID"),
+        ("enabled" = Option<bool>, Query, description = "Filter API keys by enabled status"),
+        ("offset" = Option<u32>, Query, description = "Offset for pagination"),
+        ("limit" = Option<u32>, Query, description = "Number of items per page"),
+    )
+)]
+pub async fn list(
+    repository: web::Data<ApiKeyRepository>,
+    filter: web::Query<ApiKeyFilter>,
+    pagination: web::Query<Pagination>,
+) -> Result<HttpResponse, Error> {
+    let sort = vec![ApiKeySortOrder::new(
+        ApiKeySortableFields::CreatedAt,
+        SortOrder::Asc,
+    )];
+    let api_keys = repository
+        .find(
+            filter.into_inner(),
+            Some(sort),
+            Some(pagination.into_inner()),
+        )
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let responses: Vec<ApiKeyResponse> = api_keys.into_iter().map(ApiKeyResponse::from).collect();
+
+    Ok(HttpResponse::Ok().json(responses))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api-keys/verify",
+    tag = "API Keys",
+    request_body = ApiKeyVerifyPayload,
+    responses(
+        (status = 200, description = "API key is valid", body = ApiKeyVerification),
+        (status = 401, description = "API key is unknown, disabled or expired", body = String),
+    ),
+)]
+pub async fn verify(
+    repository: web::Data<ApiKeyRepository>,
+    payload: web::Json<ApiKeyVerifyPayload>,
+) -> Result<HttpResponse, Error> {
+    let verification = repository
+        .verify(&payload.api_key)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Invalid API key"))?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(verification))
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config.service(
+        web::scope("/api-keys")
+            .service(
+                actix_web::web::resource("")
+                    .route(actix_web::web::post().to(post))
+                    .route(actix_web::web::get().to(list)),
+            )
+            // Registered before /{id} so "verify" is not taken for a key ID
+            .service(actix_web::web::resource("/verify").route(actix_web::web::post().to(verify)))
+            .service(
+                actix_web::web::resource("/{id}")
+                    .route(actix_web::web::get().to(get))
+                    .route(actix_web::web::patch().to(patch))
+                    .route(actix_web::web::delete().to(delete)),
+            ),
+    );
+}
@@ -0,0 +1,43 @@
+use crate::models::audit_event::{AuditEventFilter, AuditEventResponse};
+use crate::models::pagination::Pagination;
+use crate::repositories::audit_event_repository::AuditEventRepository;
+use actix_web::{Error, HttpResponse, web};
+
+#[utoipa::path(
+    get,
+    path = "/audit-events",
+    tag = "Audit Events",
+    responses(
+        (status = 200, description = "Audit events found, most recent first", body = Vec<AuditEventResponse>),
+        (status = 400, description = "Invalid request", body = String),
+    ),
+    params(
+        ("event_type" = Option<String>, Query, description = "Filter audit events by type"),
+        ("subject_type" = Option<String>, Query, description = "Filter audit events by subject type"),
+        ("subject" = Option<String>, Query, description = "Filter audit events by subject"),
+        ("offset" = Option<u32>, Query, description = "Offset for pagination"),
+        ("limit" = Option<u32>, Query, description = "Number of items per page"),
+    )
+)]
+pub async fn list(
+    repository: web::Data<AuditEventRepository>,
+    filter: web::Query<AuditEventFilter>,
+    pagination: web::Query<Pagination>,
+) -> Result<HttpResponse, Error> {
+    let events = repository
+        .find(filter.into_inner(), Some(pagination.into_inner()))
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let responses: Vec<AuditEventResponse> =
+        events.into_iter().map(AuditEventResponse::from).collect();
+
+    Ok(HttpResponse::Ok().json(responses))
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config.service(
+        web::scope("/audit-events")
+            .service(actix_web::web::resource("").route(actix_web::web::get().to(list))),
+    );
+}
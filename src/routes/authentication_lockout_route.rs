@@ -0,0 +1,88 @@
+use crate::models::authentication_failure::{
+    AuthenticationFailureFilter, AuthenticationFailureResponse, AuthenticationSubjectType,
+};
+use crate::models::pagination::Pagination;
+use crate::repositories::authentication_failure_repository::AuthenticationFailureRepository;
+use actix_web::{Error, HttpResponse, web};
+
+#[utoipa::path(
+    get,
+    path = "/authentication-lockouts",
+    tag = "Authentication Lockouts",
+    responses(
+        (status = 200, description = "Subjects with recent failed authentications", body = Vec<AuthenticationFailureResponse>),
+        (status = 400, description = "Invalid request", body = String),
+    ),
+    params(
+        ("subject_type" = Option<String>, Query, description = "Filter by subject type, service_account or ip"),
+        ("locked" = Option<bool>, Query, description = "Only list subjects that are currently locked out"),
+        ("offset" = Option<u32>, Query, description = "Offset for pagination"),
+        ("limit" = Option<u32>, Query, description = "Number of items per page"),
+    )
+)]
+pub async fn list(
+    repository: web::Data<AuthenticationFailureRepository>,
+    filter: web::Query<AuthenticationFailureFilter>,
+    pagination: web::Query<Pagination>,
+) -> Result<HttpResponse, Error> {
+    let failures = repository
+        .find(filter.into_inner(), Some(pagination.into_inner()))
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let responses: Vec<AuthenticationFailureResponse> = failures
+        .into_iter()
+        .map(AuthenticationFailureResponse::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(responses))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/authentication-lockouts/{subject_type}/{subject}",
+    tag = "Authentication Lockouts",
+    responses(
+        (status = 204, description = "Lockout lifted and failures forgotten", body = ()),
+        (status = 400, description = "Invalid subject type", body = String),
+        (status = 404, description = "Authentication failures not found", body = String),
+    ),
+    params(
+        ("subject_type" = String, Path, description = "Subject type, service_account or ip"),
+        ("subject" = String, Path, description = "Service account ID or IP address"),
+    )
+)]
+pub async fn unlock(
+    repository: web::Data<AuthenticationFailureRepository>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, Error> {
+    let (subject_type, subject) = path.into_inner();
+    let subject_type = subject_type
+        .parse::<AuthenticationSubjectType>()
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let result = repository.unlock(subject_type, &subject).await;
+
+    if let Err(error) = &result {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Authentication failures not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorInternalServerError(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config.service(
+        web::scope("/authentication-lockouts")
+            .service(actix_web::web::resource("").route(actix_web::web::get().to(list)))
+            .service(
+                actix_web::web::resource("/{subject_type}/{subject}")
+                    .route(actix_web::web::delete().to(unlock)),
+            ),
+    );
+}
@@ -0,0 +1,198 @@
+use crate::models::claim_template::{
+    ClaimTemplateCreatePayload, ClaimTemplateFilter, ClaimTemplateResponse, ClaimTemplateSortOrder,
+    ClaimTemplateSortableFields, ClaimTemplateUpdatePayload,
+};
+use crate::models::pagination::Pagination;
+use crate::models::sort::SortOrder;
+use crate::repositories::base::Repository;
+use crate::repositories::claim_template_repository::ClaimTemplateRepository;
+use actix_web::{Error, HttpResponse, web};
+
+#[utoipa::path(
+    post,
+    path = "/claim-templates",
+    tag = "Claim Templates",
+    request_body = ClaimTemplateCreatePayload,
+    responses(
+        (status = 201, description = "Claim template created", body = ClaimTemplateResponse),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "Project, environment or project access not found", body = String),
+        (status = 409, description = "Claim template already exists", body = String),
+    ),
+)]
+pub async fn post(
+    repository: web::Data<ClaimTemplateRepository>,
+    payload: web::Json<ClaimTemplateCreatePayload>,
+) -> Result<HttpResponse, Error> {
+    let claim_template = repository.create(payload.into_inner()).await;
+
+    if let Err(error) = &claim_template {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Project not found" | "Environment not found" | "Project access not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            "Claim template already exists" => {
+                return Err(actix_web::error::ErrorConflict(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::Created().json(ClaimTemplateResponse::from(claim_template.unwrap())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/claim-templates/{id}",
+    tag = "Claim Templates",
+    responses(
+        (status = 200, description = "Claim template found", body = ClaimTemplateResponse),
+        (status = 404, description = "Claim template not found", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "Claim Template ID"),
+    ),
+)]
+pub async fn get(
+    repository: web::Data<ClaimTemplateRepository>,
+    id: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, Error> {
+    let claim_template = repository
+        .read(id.into_inner())
+        .await
+        .map_err(actix_web::error::ErrorNotFound)?;
+
+    Ok(HttpResponse::Ok().json(claim_template.map(ClaimTemplateResponse::from)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/claim-templates/{id}",
+    tag = "Claim Templates",
+    request_body = ClaimTemplateUpdatePayload,
+    responses(
+        (status = 200, description = "Claim template updated", body = ClaimTemplateResponse),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "Claim template not found", body = String),
+        (status = 409, description = "Claim template already exists", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "Claim Template ID"),
+    ),
+)]
+pub async fn patch(
+    repository: web::Data<ClaimTemplateRepository>,
+    id: web::Path<uuid::Uuid>,
+    payload: web::Json<ClaimTemplateUpdatePayload>,
+) -> Result<HttpResponse, Error> {
+    let claim_template = repository
+        .update(id.into_inner(), payload.into_inner())
+        .await;
+
+    if let Err(error) = &claim_template {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Claim template not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            "Claim template already exists" => {
+                return Err(actix_web::error::ErrorConflict(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(ClaimTemplateResponse::from(claim_template.unwrap())))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/claim-templates/{id}",
+    tag = "Claim Templates",
+    responses(
+        (status = 204, description = "Claim template deleted", body = ()),
+        (status = 404, description = "Claim template not found", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "Claim Template ID"),
+    )
+)]
+pub async fn delete(
+    repository: web::Data<ClaimTemplateRepository>,
+    id: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, Error> {
+    let result = repository.delete(id.into_inner()).await;
+
+    if let Err(error) = &result {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Claim template not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorInternalServerError(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    get,
+    path = "/claim-templates",
+    tag = "Claim Templates",
+    responses(
+        (status = 200, description = "Claim templates found", body = Vec<ClaimTemplateResponse>),
+        (status = 400, description = "Invalid request", body = String),
+    ),
+    params(
+        ("project_id" = Option<String>, Query, description = "Filter claim templates by project ID"),
+        ("environment_id" = Option<String>, Query, description = "Filter claim templates by environment ID"),
+        ("project_access_id" = Option<String>, Query, description = "Filter claim templates by project access ID"),
+        ("claim" = Option<String>, Query, description = "Filter claim templates by claim name"),
+        ("offset" = Option<u32>, Query, description = "Offset for pagination"),
+        ("limit" = Option<u32>, Query, description = "Number of items per page"),
+    )
+)]
+pub async fn list(
+    repository: web::Data<ClaimTemplateRepository>,
+    filter: web::Query<ClaimTemplateFilter>,
+    pagination: web::Query<Pagination>,
+) -> Result<HttpResponse, Error> {
+    let sort = vec![ClaimTemplateSortOrder::new(
+        ClaimTemplateSortableFields::Id,
+        SortOrder::Asc,
+    )];
+    let claim_templates = repository
+        .find(
+            filter.into_inner(),
+            Some(sort),
+            Some(pagination.into_inner()),
+        )
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let responses: Vec<ClaimTemplateResponse> = claim_templates
+        .into_iter()
+        .map(ClaimTemplateResponse::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(responses))
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config.service(
+        web::scope("/claim-templates")
+            .service(
+                actix_web::web::resource("")
+                    .route(actix_web::web::post().to(post))
+                    .route(actix_web::web::get().to(list)),
+            )
+            .service(
+                actix_web::web::resource("/{id}")
+                    .route(actix_web::web::get().to(get))
+                    .route(actix_web::web::patch().to(patch))
+                    .route(actix_web::web::delete().to(delete)),
+            ),
+    );
+}
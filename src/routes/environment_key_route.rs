@@ -1,6 +1,7 @@
 use crate::models::environment_key::{
-    EnvironmentKeyCreatePayload, EnvironmentKeyFilter, EnvironmentKeyResponse,
-    EnvironmentKeySortOrder, EnvironmentKeySortableFields, EnvironmentKeyUpdatePayload,
+    EnvironmentKeyCreatePayload, EnvironmentKeyFilter, EnvironmentKeyImportPayload,
+    EnvironmentKeyResponse, EnvironmentKeySortOrder, EnvironmentKeySortableFields,
+    EnvironmentKeyUpdatePayload,
 };
 use crate::models::pagination::Pagination;
 use crate::models::sort::SortOrder;
@@ -31,6 +32,27 @@ pub async fn post(
     Ok(HttpResponse::Created().json(EnvironmentKeyResponse::from(environment_key)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/environment-keys/import",
+    tag = "EnvironmentKeys",
+    request_body = EnvironmentKeyImportPayload,
+    responses(
+        (status = 201, description = "Environment key imported", body = EnvironmentKeyResponse),
+        (status = 400, description = "Invalid request or key", body = String),
+    ),
+)]
+pub async fn import_key(
+    repository: web::Data<EnvironmentKeyRepository>,
+    payload: web::Json<EnvironmentKeyImportPayload>,
+) -> Result<HttpResponse, Error> {
+    let environment_key = repository
+        .import_key(payload.into_inner())
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+    Ok(HttpResponse::Created().json(EnvironmentKeyResponse::from(environment_key)))
+}
+
 #[utoipa::path(
     post,
     path = "/environment-keys/{id}/rotate",
@@ -161,6 +183,10 @@ pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
                     .route(actix_web::web::post().to(post))
                     .route(actix_web::web::get().to(list)),
             )
+            // Registered before /{id}, which would otherwise match it
+            .service(
+                actix_web::web::resource("/import").route(actix_web::web::post().to(import_key)),
+            )
             .service(
                 actix_web::web::resource("/{id}")
                     .route(actix_web::web::get().to(get))
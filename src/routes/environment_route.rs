@@ -74,8 +74,8 @@ pub async fn patch(
     payload: web::Json<EnvironmentUpdatePayload>,
 ) -> Result<HttpResponse, Error> {
     let environment = repository.update(id.into_inner(), payload.into_inner()).await;
-    if environment.is_err() {
-        let error_message = environment.unwrap_err().to_string();
+    if let Err(error) = &environment {
+        let error_message = error.to_string();
         match error_message.as_str() {
             "No changes to update" => return Err(actix_web::error::ErrorBadRequest(error_message)),
             "Environment not found" => {
@@ -107,8 +107,8 @@ pub async fn delete(
     id: web::Path<uuid::Uuid>,
 ) -> Result<HttpResponse, Error> {
     let result = repository.delete(id.into_inner()).await;
-    if result.is_err() {
-        let error_message = result.unwrap_err().to_string();
+    if let Err(error) = &result {
+        let error_message = error.to_string();
         match error_message.as_str() {
             "Environment not found" => {
                 return Err(actix_web::error::ErrorNotFound(error_message));
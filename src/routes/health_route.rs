@@ -0,0 +1,45 @@
+use crate::models::health::HealthResponse;
+use crate::services::health_service::HealthService;
+use actix_web::http::header;
+use actix_web::{HttpResponse, web};
+
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Process is up", body = HealthResponse),
+    )
+)]
+pub async fn healthz(service: web::Data<HealthService>) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .json(service.liveness())
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Process is ready to serve traffic", body = HealthResponse),
+        (status = 503, description = "A readiness check is failing or the server is shutting down", body = HealthResponse),
+    )
+)]
+pub async fn readyz(service: web::Data<HealthService>) -> HttpResponse {
+    let readiness = service.readiness().await;
+    let mut response = if readiness.is_ok() {
+        HttpResponse::Ok()
+    } else {
+        HttpResponse::ServiceUnavailable()
+    };
+    response
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .json(readiness)
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config
+        .service(actix_web::web::resource("/healthz").route(actix_web::web::get().to(healthz)))
+        .service(actix_web::web::resource("/readyz").route(actix_web::web::get().to(readyz)));
+}
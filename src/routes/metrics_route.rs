@@ -0,0 +1,20 @@
+use crate::services::metrics_service::MetricsService;
+use actix_web::{HttpResponse, web};
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "Metrics",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text exposition format", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn get(service: web::Data<MetricsService>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(service.render())
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config.service(actix_web::web::resource("/metrics").route(actix_web::web::get().to(get)));
+}
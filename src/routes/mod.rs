@@ -1,8 +1,19 @@
+pub mod api_key_route;
+pub mod audit_event_route;
+pub mod authentication_lockout_route;
+pub mod claim_template_route;
 pub mod environment_key_route;
 pub mod environment_route;
+pub mod health_route;
+pub mod metrics_route;
 pub mod project_access_route;
 pub mod project_access_scopes_route;
 pub mod project_route;
 pub mod project_scope_route;
 pub mod register;
+pub mod revocation_list_route;
+pub mod service_account_credential_route;
+pub mod service_account_key_route;
 pub mod service_account_route;
+pub mod token_policy_route;
+pub mod token_route;
@@ -73,8 +73,8 @@ pub async fn patch(
     payload: web::Json<ProjectUpdatePayload>,
 ) -> Result<HttpResponse, Error> {
     let project = repository.update(id.into_inner(), payload.into_inner()).await;
-    if project.is_err() {
-        let error_message = project.unwrap_err().to_string();
+    if let Err(error) = &project {
+        let error_message = error.to_string();
         match error_message.as_str() {
             "No changes to update" => return Err(actix_web::error::ErrorBadRequest(error_message)),
             "Project not found" => return Err(actix_web::error::ErrorNotFound(error_message)),
@@ -75,8 +75,8 @@ pub async fn patch(
 ) -> Result<HttpResponse, Error> {
     let project_scope = repository.update(id.into_inner(), payload.into_inner()).await;
 
-    if project_scope.is_err() {
-        let error_message = project_scope.unwrap_err().to_string();
+    if let Err(error) = &project_scope {
+        let error_message = error.to_string();
         match error_message.as_str() {
             "No changes to update" => return Err(actix_web::error::ErrorBadRequest(error_message)),
             "Project scope not found" => {
@@ -110,8 +110,8 @@ pub async fn delete(
 ) -> Result<HttpResponse, Error> {
     let result = repository.delete(id.into_inner()).await;
 
-    if result.is_err() {
-        let error_message = result.unwrap_err().to_string();
+    if let Err(error) = &result {
+        let error_message = error.to_string();
         match error_message.as_str() {
             "Project scope not found" => {
                 return Err(actix_web::error::ErrorNotFound(error_message));
@@ -2,8 +2,11 @@ use actix_web::App;
 use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
 
 use crate::routes::{
-    environment_route, project_access_route, project_access_scopes_route, project_route,
-    project_scope_route, service_account_route,
+    api_key_route, audit_event_route, authentication_lockout_route, claim_template_route,
+    environment_key_route, environment_route, health_route, metrics_route, project_access_route,
+    project_access_scopes_route, project_route, project_scope_route, revocation_list_route,
+    service_account_credential_route, service_account_key_route, service_account_route,
+    token_policy_route, token_route,
 };
 
 pub fn register_routes<T>(app: App<T>) -> App<T>
@@ -17,12 +20,25 @@ where
         >,
 {
     let routes = [
+        health_route::configure_routes,
+        metrics_route::configure_routes,
         project_route::configure_routes,
+        // Nested under /service-accounts, so it has to be matched before that scope
+        service_account_credential_route::configure_routes,
         service_account_route::configure_routes,
         project_scope_route::configure_routes,
         environment_route::configure_routes,
+        environment_key_route::configure_routes,
         project_access_route::configure_routes,
         project_access_scopes_route::configure_routes,
+        revocation_list_route::configure_routes,
+        token_policy_route::configure_routes,
+        claim_template_route::configure_routes,
+        service_account_key_route::configure_routes,
+        api_key_route::configure_routes,
+        authentication_lockout_route::configure_routes,
+        audit_event_route::configure_routes,
+        token_route::configure_routes,
     ];
 
     app.configure(|config| {
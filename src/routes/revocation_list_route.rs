@@ -0,0 +1,101 @@
+use std::str::FromStr;
+
+use crate::models::revocation_list::{
+    RevocationListClaims, RevocationListQuery, revocation_list_etag,
+};
+use crate::repositories::access_token_repository::AccessTokenRepository;
+use crate::repositories::environment_key_repository::EnvironmentKeyRepository;
+use actix_web::http::header;
+use actix_web::{Error, HttpRequest, HttpResponse, web};
+use jsonwebtoken::Algorithm;
+
+/// Returns true when the `If-None-Match` header of the request matches the given ETag
+fn etag_matches(request: &HttpRequest, etag: &str) -> bool {
+    request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+        })
+        .unwrap_or(false)
+}
+
+#[utoipa::path(
+    get,
+    path = "/revocation-lists/{environment_id}",
+    tag = "Revocation Lists",
+    responses(
+        (status = 200, description = "Signed revocation list (compact JWT)", body = String, content_type = "application/jwt"),
+        (status = 304, description = "Revocation list has not changed"),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "Environment key not found", body = String),
+    ),
+    params(
+        ("environment_id" = String<uuid::Uuid>, Path, description = "Environment ID"),
+        ("algorithm" = Option<String>, Query, description = "Algorithm of the environment key used to sign the list"),
+    ),
+)]
+pub async fn get(
+    request: HttpRequest,
+    access_token_repository: web::Data<AccessTokenRepository>,
+    environment_key_repository: web::Data<EnvironmentKeyRepository>,
+    environment_id: web::Path<uuid::Uuid>,
+    query: web::Query<RevocationListQuery>,
+) -> Result<HttpResponse, Error> {
+    let environment_id = environment_id.into_inner();
+    let algorithm = query
+        .into_inner()
+        .algorithm
+        .map(|algorithm| Algorithm::from_str(&algorithm))
+        .transpose()
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let (environment_key, reference) = environment_key_repository
+        .find_active_key(environment_id, algorithm)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Environment key not found"))?;
+
+    let revoked = access_token_repository
+        .find_revoked_by_environment(environment_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let key_version = format!(
+        "{}:{:?}:{}",
+        environment_key.id.unwrap(),
+        environment_key.algorithm,
+        environment_key.updated_at.timestamp_micros()
+    );
+    let claims = RevocationListClaims::new(environment_id, revoked);
+    let etag = revocation_list_etag(environment_id, &key_version, &claims.revoked, claims.iat);
+
+    if etag_matches(&request, &etag) {
+        // Repeats the caching of the full response, which would otherwise be `no-store`
+        return Ok(HttpResponse::NotModified()
+            .insert_header((header::ETAG, etag))
+            .insert_header((header::CACHE_CONTROL, "no-cache"))
+            .finish());
+    }
+
+    let token = environment_key_repository
+        .sign_jwt(&environment_key, &reference, &claims)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/jwt")
+        .insert_header((header::ETAG, etag))
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .body(token))
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config.service(web::scope("/revocation-lists").service(
+        actix_web::web::resource("/{environment_id}").route(actix_web::web::get().to(get)),
+    ));
+}
@@ -0,0 +1,278 @@
+use crate::models::pagination::Pagination;
+use crate::models::service_account_credential::{
+    ServiceAccountCredential, ServiceAccountCredentialCreatePayload,
+    ServiceAccountCredentialFilter, ServiceAccountCredentialResponse,
+    ServiceAccountCredentialRotatePayload, ServiceAccountCredentialSortOrder,
+    ServiceAccountCredentialSortableFields, ServiceAccountCredentialUpdatePayload,
+};
+use crate::models::sort::SortOrder;
+use crate::repositories::base::Repository;
+use crate::repositories::service_account_credential_repository::ServiceAccountCredentialRepository;
+use crate::utils::security::generate_opaque_token;
+use actix_web::{Error, HttpResponse, web};
+use uuid::Uuid;
+
+/// Reads a credential, treating credentials of other service accounts as missing
+async fn read_credential(
+    repository: &ServiceAccountCredentialRepository,
+    service_account_id: Uuid,
+    id: Uuid,
+) -> Result<ServiceAccountCredential, Error> {
+    repository
+        .read(id)
+        .await
+        .ok()
+        .flatten()
+        .filter(|credential| credential.service_account_id == service_account_id)
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Service account credential not found"))
+}
+
+#[utoipa::path(
+    post,
+    path = "/service-accounts/{service_account_id}/credentials",
+    tag = "Service Account Credentials",
+    request_body = ServiceAccountCredentialCreatePayload,
+    responses(
+        (status = 201, description = "Service account credential created, the secret is only returned once", body = ServiceAccountCredentialResponse),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "Service account not found", body = String),
+    ),
+    params(
+        ("service_account_id" = String<uuid::Uuid>, Path, description = "Service Account ID"),
+    ),
+)]
+pub async fn post(
+    repository: web::Data<ServiceAccountCredentialRepository>,
+    service_account_id: web::Path<Uuid>,
+    payload: web::Json<ServiceAccountCredentialCreatePayload>,
+) -> Result<HttpResponse, Error> {
+    let payload = payload
+        .into_inner()
+        .with_secret(service_account_id.into_inner());
+    let secret = payload.secret.clone();
+    let credential = repository.create(payload).await;
+
+    if let Err(error) = &credential {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Service account not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(
+        HttpResponse::Created().json(ServiceAccountCredentialResponse::with_secret(
+            credential.unwrap(),
+            secret,
+        )),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/service-accounts/{service_account_id}/credentials/{id}",
+    tag = "Service Account Credentials",
+    responses(
+        (status = 200, description = "Service account credential found", body = ServiceAccountCredentialResponse),
+        (status = 404, description = "Service account credential not found", body = String),
+    ),
+    params(
+        ("service_account_id" = String<uuid::Uuid>, Path, description = "Service Account ID"),
+        ("id" = String<uuid::Uuid>, Path, description = "Service Account Credential ID"),
+    ),
+)]
+pub async fn get(
+    repository: web::Data<ServiceAccountCredentialRepository>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, Error> {
+    let (service_account_id, id) = path.into_inner();
+    let credential = read_credential(&repository, service_account_id, id).await?;
+
+    Ok(HttpResponse::Ok().json(ServiceAccountCredentialResponse::from(credential)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/service-accounts/{service_account_id}/credentials/{id}",
+    tag = "Service Account Credentials",
+    request_body = ServiceAccountCredentialUpdatePayload,
+    responses(
+        (status = 200, description = "Service account credential updated", body = ServiceAccountCredentialResponse),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "Service account credential not found", body = String),
+    ),
+    params(
+        ("service_account_id" = String<uuid::Uuid>, Path, description = "Service Account ID"),
+        ("id" = String<uuid::Uuid>, Path, description = "Service Account Credential ID"),
+    ),
+)]
+pub async fn patch(
+    repository: web::Data<ServiceAccountCredentialRepository>,
+    path: web::Path<(Uuid, Uuid)>,
+    payload: web::Json<ServiceAccountCredentialUpdatePayload>,
+) -> Result<HttpResponse, Error> {
+    let (service_account_id, id) = path.into_inner();
+    read_credential(&repository, service_account_id, id).await?;
+
+    let credential = repository.update(id, payload.into_inner()).await;
+
+    if let Err(error) = &credential {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Service account credential not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(ServiceAccountCredentialResponse::from(credential.unwrap())))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/service-accounts/{service_account_id}/credentials/{id}",
+    tag = "Service Account Credentials",
+    responses(
+        (status = 204, description = "Service account credential deleted", body = ()),
+        (status = 404, description = "Service account credential not found", body = String),
+    ),
+    params(
+        ("service_account_id" = String<uuid::Uuid>, Path, description = "Service Account ID"),
+        ("id" = String<uuid::Uuid>, Path, description = "Service Account Credential ID"),
+    )
+)]
+pub async fn delete(
+    repository: web::Data<ServiceAccountCredentialRepository>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, Error> {
+    let (service_account_id, id) = path.into_inner();
+    read_credential(&repository, service_account_id, id).await?;
+
+    let result = repository.delete(id).await;
+
+    if let Err(error) = &result {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Service account credential not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorInternalServerError(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    post,
+    path = "/service-accounts/{service_account_id}/credentials/{id}/rotate",
+    tag = "Service Account Credentials",
+    request_body = ServiceAccountCredentialRotatePayload,
+    responses(
+        (status = 201, description = "New service account credential created, the secret is only returned once", body = ServiceAccountCredentialResponse),
+        (status = 400, description = "Invalid request or expired credential", body = String),
+        (status = 404, description = "Service account credential not found", body = String),
+    ),
+    params(
+        ("service_account_id" = String<uuid::Uuid>, Path, description = "Service Account ID"),
+        ("id" = String<uuid::Uuid>, Path, description = "ID of the credential to rotate"),
+    ),
+)]
+pub async fn rotate(
+    repository: web::Data<ServiceAccountCredentialRepository>,
+    path: web::Path<(Uuid, Uuid)>,
+    payload: web::Json<ServiceAccountCredentialRotatePayload>,
+) -> Result<HttpResponse, Error> {
+    let (service_account_id, id) = path.into_inner();
+    let rotated = read_credential(&repository, service_account_id, id).await?;
+    if !rotated.is_active() {
+        return Err(actix_web::error::ErrorBadRequest(
+            "Expired credentials cannot be rotated",
+        ));
+    }
+
+    let secret = generate_opaque_token();
+    let credential = repository.rotate(id, &secret, payload.into_inner()).await;
+
+    if let Err(error) = &credential {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Service account credential not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(
+        HttpResponse::Created().json(ServiceAccountCredentialResponse::with_secret(
+            credential.unwrap(),
+            secret,
+        )),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/service-accounts/{service_account_id}/credentials",
+    tag = "Service Account Credentials",
+    responses(
+        (status = 200, description = "Service account credentials found", body = Vec<ServiceAccountCredentialResponse>),
+        (status = 400, description = "Invalid request", body = String),
+    ),
+    params(
+        ("service_account_id" = String<uuid::Uuid>, Path, description = "Service Account ID"),
+        ("active" = Option<bool>, Query, description = "Filter service account credentials by whether they have expired"),
+        ("offset" = Option<u32>, Query, description = "Offset for pagination"),
+        ("limit" = Option<u32>, Query, description = "Number of items per page"),
+    )
+)]
+pub async fn list(
+    repository: web::Data<ServiceAccountCredentialRepository>,
+    service_account_id: web::Path<Uuid>,
+    filter: web::Query<ServiceAccountCredentialFilter>,
+    pagination: web::Query<Pagination>,
+) -> Result<HttpResponse, Error> {
+    let filter = ServiceAccountCredentialFilter {
+        service_account_id: Some(service_account_id.into_inner().to_string()),
+        ..filter.into_inner()
+    };
+    let sort = vec![ServiceAccountCredentialSortOrder::new(
+        ServiceAccountCredentialSortableFields::CreatedAt,
+        SortOrder::Asc,
+    )];
+    let credentials = repository
+        .find(filter, Some(sort), Some(pagination.into_inner()))
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let responses: Vec<ServiceAccountCredentialResponse> = credentials
+        .into_iter()
+        .map(ServiceAccountCredentialResponse::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(responses))
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config.service(
+        web::scope("/service-accounts/{service_account_id}/credentials")
+            .service(
+                actix_web::web::resource("")
+                    .route(actix_web::web::post().to(post))
+                    .route(actix_web::web::get().to(list)),
+            )
+            .service(
+                actix_web::web::resource("/{id}")
+                    .route(actix_web::web::get().to(get))
+                    .route(actix_web::web::patch().to(patch))
+                    .route(actix_web::web::delete().to(delete)),
+            )
+            .service(
+                actix_web::web::resource("/{id}/rotate").route(actix_web::web::post().to(rotate)),
+            ),
+    );
+}
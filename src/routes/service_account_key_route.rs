@@ -0,0 +1,202 @@
+use crate::models::pagination::Pagination;
+use crate::models::service_account_key::{
+    ServiceAccountKeyCreatePayload, ServiceAccountKeyFilter, ServiceAccountKeyResponse,
+    ServiceAccountKeySortOrder, ServiceAccountKeySortableFields, ServiceAccountKeyUpdatePayload,
+};
+use crate::models::sort::SortOrder;
+use crate::repositories::base::Repository;
+use crate::repositories::service_account_key_repository::ServiceAccountKeyRepository;
+use actix_web::{Error, HttpResponse, web};
+
+#[utoipa::path(
+    post,
+    path = "/service-account-keys",
+    tag = "Service Account Keys",
+    request_body = ServiceAccountKeyCreatePayload,
+    responses(
+        (status = 201, description = "Service account key created", body = ServiceAccountKeyResponse),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "Service account not found", body = String),
+        (status = 409, description = "Service account key already exists", body = String),
+    ),
+)]
+pub async fn post(
+    repository: web::Data<ServiceAccountKeyRepository>,
+    payload: web::Json<ServiceAccountKeyCreatePayload>,
+) -> Result<HttpResponse, Error> {
+    let service_account_key = repository.create(payload.into_inner()).await;
+
+    if let Err(error) = &service_account_key {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Service account not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            "Service account key already exists" => {
+                return Err(actix_web::error::ErrorConflict(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(
+        HttpResponse::Created().json(ServiceAccountKeyResponse::from(
+            service_account_key.unwrap(),
+        )),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/service-account-keys/{id}",
+    tag = "Service Account Keys",
+    responses(
+        (status = 200, description = "Service account key found", body = ServiceAccountKeyResponse),
+        (status = 404, description = "Service account key not found", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "Service Account Key ID"),
+    ),
+)]
+pub async fn get(
+    repository: web::Data<ServiceAccountKeyRepository>,
+    id: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, Error> {
+    let service_account_key = repository
+        .read(id.into_inner())
+        .await
+        .map_err(actix_web::error::ErrorNotFound)?;
+
+    Ok(HttpResponse::Ok().json(service_account_key.map(ServiceAccountKeyResponse::from)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/service-account-keys/{id}",
+    tag = "Service Account Keys",
+    request_body = ServiceAccountKeyUpdatePayload,
+    responses(
+        (status = 200, description = "Service account key updated", body = ServiceAccountKeyResponse),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "Service account key not found", body = String),
+        (status = 409, description = "Service account key already exists", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "Service Account Key ID"),
+    ),
+)]
+pub async fn patch(
+    repository: web::Data<ServiceAccountKeyRepository>,
+    id: web::Path<uuid::Uuid>,
+    payload: web::Json<ServiceAccountKeyUpdatePayload>,
+) -> Result<HttpResponse, Error> {
+    let service_account_key = repository
+        .update(id.into_inner(), payload.into_inner())
+        .await;
+
+    if let Err(error) = &service_account_key {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Service account key not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            "Service account key already exists" => {
+                return Err(actix_web::error::ErrorConflict(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(ServiceAccountKeyResponse::from(
+        service_account_key.unwrap(),
+    )))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/service-account-keys/{id}",
+    tag = "Service Account Keys",
+    responses(
+        (status = 204, description = "Service account key deleted", body = ()),
+        (status = 404, description = "Service account key not found", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "Service Account Key ID"),
+    )
+)]
+pub async fn delete(
+    repository: web::Data<ServiceAccountKeyRepository>,
+    id: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, Error> {
+    let result = repository.delete(id.into_inner()).await;
+
+    if let Err(error) = &result {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Service account key not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorInternalServerError(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    get,
+    path = "/service-account-keys",
+    tag = "Service Account Keys",
+    responses(
+        (status = 200, description = "Service account keys found", body = Vec<ServiceAccountKeyResponse>),
+        (status = 400, description = "Invalid request", body = String),
+    ),
+    params(
+        ("service_account_id" = Option<String>, Query, description = "Filter service account keys by service account ID"),
+        ("enabled" = Option<bool>, Query, description = "Filter service account keys by enabled status"),
+        ("offset" = Option<u32>, Query, description = "Offset for pagination"),
+        ("limit" = Option<u32>, Query, description = "Number of items per page"),
+    )
+)]
+pub async fn list(
+    repository: web::Data<ServiceAccountKeyRepository>,
+    filter: web::Query<ServiceAccountKeyFilter>,
+    pagination: web::Query<Pagination>,
+) -> Result<HttpResponse, Error> {
+    let sort = vec![ServiceAccountKeySortOrder::new(
+        ServiceAccountKeySortableFields::Id,
+        SortOrder::Asc,
+    )];
+    let service_account_keys = repository
+        .find(
+            filter.into_inner(),
+            Some(sort),
+            Some(pagination.into_inner()),
+        )
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let responses: Vec<ServiceAccountKeyResponse> = service_account_keys
+        .into_iter()
+        .map(ServiceAccountKeyResponse::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(responses))
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config.service(
+        web::scope("/service-account-keys")
+            .service(
+                actix_web::web::resource("")
+                    .route(actix_web::web::post().to(post))
+                    .route(actix_web::web::get().to(list)),
+            )
+            .service(
+                actix_web::web::resource("/{id}")
+                    .route(actix_web::web::get().to(get))
+                    .route(actix_web::web::patch().to(patch))
+                    .route(actix_web::web::delete().to(delete)),
+            ),
+    );
+}
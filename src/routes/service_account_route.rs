@@ -74,8 +74,8 @@ pub async fn patch(
     payload: web::Json<ServiceAccountUpdatePayload>,
 ) -> Result<HttpResponse, Error> {
     let service_account = repository.update(id.into_inner(), payload.into_inner()).await;
-    if service_account.is_err() {
-        let error_message = service_account.unwrap_err().to_string();
+    if let Err(error) = &service_account {
+        let error_message = error.to_string();
         match error_message.as_str() {
             "No changes to update" => return Err(actix_web::error::ErrorBadRequest(error_message)),
             "Service account not found" => {
@@ -87,6 +87,12 @@ pub async fn patch(
             "Service account email already exists" => {
                 return Err(actix_web::error::ErrorConflict(error_message));
             }
+            "Invalid expiration date" | "Expiration date must be in the future" => {
+                return Err(actix_web::error::ErrorBadRequest(error_message));
+            }
+            s if s.starts_with("Invalid CIDR range") => {
+                return Err(actix_web::error::ErrorBadRequest(error_message));
+            }
             _ => {
                 return Err(actix_web::error::ErrorInternalServerError(error_message));
             },
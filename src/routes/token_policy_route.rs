@@ -0,0 +1,224 @@
+use crate::models::pagination::Pagination;
+use crate::models::sort::SortOrder;
+use crate::models::token_policy::{
+    EffectiveTokenPolicy, TokenPolicyCreatePayload, TokenPolicyFilter, TokenPolicyResponse,
+    TokenPolicySortOrder, TokenPolicySortableFields, TokenPolicyUpdatePayload,
+};
+use crate::repositories::base::Repository;
+use crate::repositories::token_policy_repository::TokenPolicyRepository;
+use actix_web::{Error, HttpResponse, web};
+
+#[utoipa::path(
+    post,
+    path = "/token-policies",
+    tag = "Token Policies",
+    request_body = TokenPolicyCreatePayload,
+    responses(
+        (status = 201, description = "Token policy created", body = TokenPolicyResponse),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "Project or environment not found", body = String),
+        (status = 409, description = "Token policy already exists", body = String),
+    ),
+)]
+pub async fn post(
+    repository: web::Data<TokenPolicyRepository>,
+    payload: web::Json<TokenPolicyCreatePayload>,
+) -> Result<HttpResponse, Error> {
+    let token_policy = repository.create(payload.into_inner()).await;
+
+    if let Err(error) = &token_policy {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Project not found" | "Environment not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            "Token policy already exists" => {
+                return Err(actix_web::error::ErrorConflict(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::Created().json(TokenPolicyResponse::from(token_policy.unwrap())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/token-policies/{id}",
+    tag = "Token Policies",
+    responses(
+        (status = 200, description = "Token policy found", body = TokenPolicyResponse),
+        (status = 404, description = "Token policy not found", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "Token Policy ID"),
+    ),
+)]
+pub async fn get(
+    repository: web::Data<TokenPolicyRepository>,
+    id: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, Error> {
+    let token_policy = repository
+        .read(id.into_inner())
+        .await
+        .map_err(actix_web::error::ErrorNotFound)?;
+
+    Ok(HttpResponse::Ok().json(token_policy.map(TokenPolicyResponse::from)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/token-policies/effective/{environment_id}",
+    tag = "Token Policies",
+    responses(
+        (status = 200, description = "Token policy applying to the environment", body = EffectiveTokenPolicy),
+        (status = 500, description = "Internal server error", body = String),
+    ),
+    params(
+        ("environment_id" = String<uuid::Uuid>, Path, description = "Environment ID"),
+    ),
+)]
+pub async fn effective(
+    repository: web::Data<TokenPolicyRepository>,
+    environment_id: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, Error> {
+    let policy = repository
+        .resolve(environment_id.into_inner())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(policy))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/token-policies/{id}",
+    tag = "Token Policies",
+    request_body = TokenPolicyUpdatePayload,
+    responses(
+        (status = 200, description = "Token policy updated", body = TokenPolicyResponse),
+        (status = 400, description = "Invalid request", body = String),
+        (status = 404, description = "Token policy not found", body = String),
+        (status = 500, description = "Internal server error", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "Token Policy ID"),
+    ),
+)]
+pub async fn patch(
+    repository: web::Data<TokenPolicyRepository>,
+    id: web::Path<uuid::Uuid>,
+    payload: web::Json<TokenPolicyUpdatePayload>,
+) -> Result<HttpResponse, Error> {
+    let token_policy = repository
+        .update(id.into_inner(), payload.into_inner())
+        .await;
+
+    if let Err(error) = &token_policy {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Token policy not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            "No changes were made" => {
+                return Err(actix_web::error::ErrorInternalServerError(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorBadRequest(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(TokenPolicyResponse::from(token_policy.unwrap())))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/token-policies/{id}",
+    tag = "Token Policies",
+    responses(
+        (status = 204, description = "Token policy deleted", body = ()),
+        (status = 404, description = "Token policy not found", body = String),
+    ),
+    params(
+        ("id" = String<uuid::Uuid>, Path, description = "Token Policy ID"),
+    )
+)]
+pub async fn delete(
+    repository: web::Data<TokenPolicyRepository>,
+    id: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, Error> {
+    let result = repository.delete(id.into_inner()).await;
+
+    if let Err(error) = &result {
+        let error_message = error.to_string();
+        match error_message.as_str() {
+            "Token policy not found" => {
+                return Err(actix_web::error::ErrorNotFound(error_message));
+            }
+            _ => return Err(actix_web::error::ErrorInternalServerError(error_message)),
+        }
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    get,
+    path = "/token-policies",
+    tag = "Token Policies",
+    responses(
+        (status = 200, description = "Token policies found", body = Vec<TokenPolicyResponse>),
+        (status = 400, description = "Invalid request", body = String),
+    ),
+    params(
+        ("project_id" = Option<String>, Query, description = "Filter token policies by project ID"),
+        ("environment_id" = Option<String>, Query, description = "Filter token policies by environment ID"),
+        ("offset" = Option<u32>, Query, description = "Offset for pagination"),
+        ("limit" = Option<u32>, Query, description = "Number of items per page"),
+    )
+)]
+pub async fn list(
+    repository: web::Data<TokenPolicyRepository>,
+    filter: web::Query<TokenPolicyFilter>,
+    pagination: web::Query<Pagination>,
+) -> Result<HttpResponse, Error> {
+    let sort = vec![TokenPolicySortOrder::new(
+        TokenPolicySortableFields::Id,
+        SortOrder::Asc,
+    )];
+    let token_policies = repository
+        .find(
+            filter.into_inner(),
+            Some(sort),
+            Some(pagination.into_inner()),
+        )
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let responses: Vec<TokenPolicyResponse> = token_policies
+        .into_iter()
+        .map(TokenPolicyResponse::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(responses))
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config.service(
+        web::scope("/token-policies")
+            .service(
+                actix_web::web::resource("")
+                    .route(actix_web::web::post().to(post))
+                    .route(actix_web::web::get().to(list)),
+            )
+            .service(
+                actix_web::web::resource("/effective/{environment_id}")
+                    .route(actix_web::web::get().to(effective)),
+            )
+            .service(
+                actix_web::web::resource("/{id}")
+                    .route(actix_web::web::get().to(get))
+                    .route(actix_web::web::patch().to(patch))
+                    .route(actix_web::web::delete().to(delete)),
+            ),
+    );
+}
@@ -0,0 +1,71 @@
+use crate::models::token::{TokenError, TokenErrorResponse, TokenRequest, TokenResponse};
+use crate::services::token_service::TokenService;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, web};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+/// Extracts the client credentials of an `Authorization: Basic` header
+fn basic_credentials(request: &HttpRequest) -> Result<Option<(String, String)>, TokenError> {
+    let Some(value) = request.headers().get(header::AUTHORIZATION) else {
+        return Ok(None);
+    };
+
+    let encoded = value
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix("Basic "))
+        .ok_or(TokenError::InvalidClient)?;
+    let decoded = STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .ok_or(TokenError::InvalidClient)?;
+    let (client_id, client_secret) = decoded.split_once(':').ok_or(TokenError::InvalidClient)?;
+
+    Ok(Some((client_id.to_string(), client_secret.to_string())))
+}
+
+#[utoipa::path(
+    post,
+    path = "/token",
+    tag = "Tokens",
+    request_body(content = TokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Token issued", body = TokenResponse),
+        (status = 400, description = "Invalid token request", body = TokenErrorResponse),
+        (status = 401, description = "Client authentication failed", body = TokenErrorResponse),
+    ),
+)]
+pub async fn post(
+    request: HttpRequest,
+    service: web::Data<TokenService>,
+    payload: web::Form<TokenRequest>,
+) -> Result<HttpResponse, TokenError> {
+    let credentials = basic_credentials(&request)?;
+    // Forwarding headers are only honoured from trusted proxies, as clients control them
+    let forwarded_for = request
+        .headers()
+        .get_all("X-Forwarded-For")
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(",");
+    let client_ip = service.client_ip(
+        request.peer_addr().map(|address| address.ip()),
+        Some(forwarded_for.as_str()).filter(|value| !value.is_empty()),
+    );
+    let response = service
+        .issue(payload.into_inner(), credentials, client_ip)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .json(response))
+}
+
+pub fn configure_routes(config: &mut actix_web::web::ServiceConfig) {
+    config.service(
+        web::scope("/token")
+            .service(actix_web::web::resource("").route(actix_web::web::post().to(post))),
+    );
+}
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::MIGRATOR;
+use crate::models::health::{HealthCheck, HealthResponse};
+use crate::utils::security::SecretsManager;
+
+/// Time a single readiness check may take before it is reported as failing
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+const CANARY: &str = "sentinel-guard-readiness-canary";
+
+/// Answers the liveness and readiness probes of the orchestrator.
+///
+/// Clones share the shutdown flag, so flipping it on the instance kept by `main` fails the
+/// readiness probe of every worker.
+#[derive(Clone)]
+pub struct HealthService {
+    pub pool: Arc<PgPool>,
+    pub secrets_manager: SecretsManager,
+    shutting_down: Arc<AtomicBool>,
+}
+
+impl HealthService {
+    pub fn new(pool: Arc<PgPool>, secrets_manager: SecretsManager) -> Self {
+        Self {
+            pool,
+            secrets_manager,
+            shutting_down: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Fails the readiness probe from now on, so no new traffic is routed to the process
+    pub fn begin_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// The process is up and serving requests
+    pub fn liveness(&self) -> HealthResponse {
+        HealthResponse::from_checks(Vec::new())
+    }
+
+    /// Whether the process can serve traffic, with the outcome of every check
+    pub async fn readiness(&self) -> HealthResponse {
+        let mut checks = Vec::with_capacity(4);
+        checks.push(if self.is_shutting_down() {
+            HealthCheck::failing("shutdown", "Server is shutting down")
+        } else {
+            HealthCheck::ok("shutdown", "Server is accepting traffic")
+        });
+        checks.push(self.check_database().await);
+        checks.push(self.check_migrations().await);
+        checks.push(self.check_secrets_manager());
+        HealthResponse::from_checks(checks)
+    }
+
+    async fn check_database(&self) -> HealthCheck {
+        let query = sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(&*self.pool);
+        match tokio::time::timeout(CHECK_TIMEOUT, query).await {
+            Ok(Ok(_)) => HealthCheck::ok("database", "Database is reachable"),
+            Ok(Err(error)) => HealthCheck::failing("database", error.to_string()),
+            Err(_) => HealthCheck::failing("database", "Database did not answer in time"),
+        }
+    }
+
+    /// Checks every migration embedded in the binary was applied successfully
+    async fn check_migrations(&self) -> HealthCheck {
+        let query = sqlx::query_scalar::<_, i64>(
+            "SELECT version FROM _sqlx_migrations WHERE success = true",
+        )
+        .fetch_all(&*self.pool);
+        let applied = match tokio::time::timeout(CHECK_TIMEOUT, query).await {
+            Ok(Ok(applied)) => applied,
+            Ok(Err(error)) => return HealthCheck::failing("migrations", error.to_string()),
+            Err(_) => {
+                return HealthCheck::failing("migrations", "Database did not answer in time");
+            }
+        };
+
+        let expected: Vec<i64> = MIGRATOR
+            .iter()
+            .filter(|migration| migration.migration_type.is_up_migration())
+            .map(|migration| migration.version)
+            .collect();
+        let missing: Vec<String> = expected
+            .iter()
+            .filter(|version| !applied.contains(version))
+            .map(|version| version.to_string())
+            .collect();
+
+        match (missing.is_empty(), expected.iter().max()) {
+            (true, Some(version)) => HealthCheck::ok(
+                "migrations",
+                format!("Database is at migration {}", version),
+            ),
+            (true, None) => HealthCheck::ok("migrations", "No migrations are expected"),
+            (false, _) => HealthCheck::failing(
+                "migrations",
+                format!("Migrations are not applied: {}", missing.join(", ")),
+            ),
+        }
+    }
+
+    /// Checks the master key works by encrypting and decrypting a canary value
+    fn check_secrets_manager(&self) -> HealthCheck {
+        let secrets_manager = &self.secrets_manager;
+        let resource_id = Uuid::nil();
+        let decrypted = secrets_manager
+            .encrypt(CANARY, &resource_id)
+            .and_then(|encrypted| secrets_manager.decrypt(&encrypted, &resource_id));
+        match decrypted {
+            Ok(decrypted) if decrypted == CANARY => {
+                HealthCheck::ok("secrets_manager", "Canary value decrypted")
+            }
+            Ok(_) => HealthCheck::failing("secrets_manager", "Canary value does not match"),
+            Err(error) => HealthCheck::failing("secrets_manager", error.to_string()),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use sqlx::postgres::PgPool;
+
+use crate::utils::metrics::METRICS;
+
+/// Renders the metrics scraped by Prometheus, with the connection pool read at scrape time
+#[derive(Clone)]
+pub struct MetricsService {
+    pub pool: Arc<PgPool>,
+}
+
+impl MetricsService {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub fn render(&self) -> String {
+        let size = self.pool.size();
+        let idle = self.pool.num_idle() as u32;
+        METRICS.db_pool_connections.set(size.into());
+        METRICS.db_pool_idle_connections.set(idle.into());
+        METRICS
+            .db_pool_in_use_connections
+            .set(size.saturating_sub(idle).into());
+        METRICS
+            .db_pool_max_connections
+            .set(self.pool.options().get_max_connections().into());
+        METRICS.render()
+    }
+}
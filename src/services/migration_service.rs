@@ -0,0 +1,228 @@
+//! Schema migrations embedded in the binary, applied at startup when asked to.
+
+use std::sync::Arc;
+
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use sqlx::postgres::PgPool;
+
+use crate::MIGRATOR;
+
+/// Key of the advisory lock held while migrating, so replicas starting together apply the
+/// migrations once. Reads "SENTINEL" in ASCII.
+pub const MIGRATION_LOCK_KEY: i64 = 0x5345_4E54_494E_454C;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    /// Embedded in the binary and not applied yet
+    Pending,
+    /// Recorded as failed by an earlier run, the schema needs manual repair
+    Failed,
+    /// Applied by a newer binary, unknown to this one
+    Unknown,
+}
+
+impl MigrationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Failed => "failed",
+            MigrationState::Unknown => "unknown",
+        }
+    }
+}
+
+/// A migration of the binary or of the database, and whether it was applied
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+    pub installed_on: Option<DateTime<Utc>>,
+    /// Whether the applied migration differs from the one embedded in the binary
+    pub checksum_mismatch: bool,
+}
+
+/// Row of the `_sqlx_migrations` table
+#[derive(sqlx::FromRow)]
+struct AppliedMigration {
+    version: i64,
+    description: String,
+    installed_on: DateTime<Utc>,
+    success: bool,
+    checksum: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct MigrationService {
+    pub pool: Arc<PgPool>,
+}
+
+impl MigrationService {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// Every migration of the binary and of the database, ordered by version
+    pub async fn status(&self) -> Result<Vec<MigrationStatus>, Error> {
+        let mut connection = self.pool.acquire().await?;
+        Ok(statuses(&applied_migrations(&mut connection).await?))
+    }
+
+    /// Refuses a database whose schema this binary cannot serve: one migrated by a newer
+    /// binary, or left behind by a failed migration
+    pub async fn ensure_compatible(&self) -> Result<(), Error> {
+        let mut connection = self.pool.acquire().await?;
+        ensure_compatible(&statuses(&applied_migrations(&mut connection).await?))
+    }
+
+    /// Refuses a database this binary cannot serve as is: one `ensure_compatible` refuses,
+    /// or one with migrations still to apply
+    pub async fn ensure_migrated(&self) -> Result<(), Error> {
+        let statuses = self.status().await?;
+        ensure_compatible(&statuses)?;
+
+        let pending: Vec<String> = statuses
+            .iter()
+            .filter(|status| status.state == MigrationState::Pending)
+            .map(|status| status.version.to_string())
+            .collect();
+        if !pending.is_empty() {
+            return Err(Error::msg(format!(
+                "Migrations {} are pending, start with --migrate or set database.migrate_on_startup to apply them",
+                pending.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Applies the pending migrations under the migration lock, returning the ones applied.
+    ///
+    /// Replicas waiting on the lock find the migrations applied once they get it.
+    pub async fn migrate(&self) -> Result<Vec<MigrationStatus>, Error> {
+        let mut connection = self.pool.acquire().await?;
+        sqlx::query("SELECT pg_advisory_lock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *connection)
+            .await?;
+
+        let result = Self::migrate_locked(&mut connection).await;
+
+        let unlocked = sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *connection)
+            .await;
+        if let Err(error) = unlocked {
+            // Closing the connection releases the lock
+            tracing::warn!(error = %error, "Failed to release the migration lock");
+            connection.detach();
+        }
+        result
+    }
+
+    async fn migrate_locked(connection: &mut PgConnection) -> Result<Vec<MigrationStatus>, Error> {
+        let before = statuses(&applied_migrations(connection).await?);
+        ensure_compatible(&before)?;
+
+        MIGRATOR.run(&mut *connection).await?;
+
+        let pending: Vec<i64> = before
+            .iter()
+            .filter(|status| status.state == MigrationState::Pending)
+            .map(|status| status.version)
+            .collect();
+        let after = statuses(&applied_migrations(connection).await?);
+        Ok(after
+            .into_iter()
+            .filter(|status| pending.contains(&status.version))
+            .collect())
+    }
+}
+
+/// Reads the `_sqlx_migrations` table, empty before the first migration created it
+async fn applied_migrations(connection: &mut PgConnection) -> Result<Vec<AppliedMigration>, Error> {
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(&mut *connection)
+        .await?;
+    if !exists {
+        return Ok(Vec::new());
+    }
+
+    let applied = sqlx::query_as::<_, AppliedMigration>(
+        "SELECT version, description, installed_on, success, checksum FROM _sqlx_migrations ORDER BY version",
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+    Ok(applied)
+}
+
+/// Matches the migrations of the binary with those recorded in the database
+fn statuses(applied: &[AppliedMigration]) -> Vec<MigrationStatus> {
+    let mut statuses: Vec<MigrationStatus> = MIGRATOR
+        .iter()
+        .filter(|migration| migration.migration_type.is_up_migration())
+        .map(|migration| {
+            let record = applied
+                .iter()
+                .find(|record| record.version == migration.version);
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                state: match record {
+                    Some(record) if record.success => MigrationState::Applied,
+                    Some(_) => MigrationState::Failed,
+                    None => MigrationState::Pending,
+                },
+                installed_on: record.map(|record| record.installed_on),
+                checksum_mismatch: record
+                    .is_some_and(|record| record.checksum != migration.checksum.as_ref()),
+            }
+        })
+        .collect();
+
+    for record in applied {
+        if !statuses
+            .iter()
+            .any(|status| status.version == record.version)
+        {
+            statuses.push(MigrationStatus {
+                version: record.version,
+                description: record.description.clone(),
+                state: MigrationState::Unknown,
+                installed_on: Some(record.installed_on),
+                checksum_mismatch: false,
+            });
+        }
+    }
+    statuses.sort_by_key(|status| status.version);
+    statuses
+}
+
+fn ensure_compatible(statuses: &[MigrationStatus]) -> Result<(), Error> {
+    let versions = |state: MigrationState| -> Vec<String> {
+        statuses
+            .iter()
+            .filter(|status| status.state == state)
+            .map(|status| status.version.to_string())
+            .collect()
+    };
+
+    let unknown = versions(MigrationState::Unknown);
+    if !unknown.is_empty() {
+        return Err(Error::msg(format!(
+            "Database schema is ahead of this binary, migrations {} are unknown",
+            unknown.join(", ")
+        )));
+    }
+    let failed = versions(MigrationState::Failed);
+    if !failed.is_empty() {
+        return Err(Error::msg(format!(
+            "Migrations {} failed to apply, the schema needs to be repaired",
+            failed.join(", ")
+        )));
+    }
+    Ok(())
+}
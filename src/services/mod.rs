@@ -0,0 +1,5 @@
+pub mod health_service;
+pub mod metrics_service;
+pub mod migration_service;
+pub mod register;
+pub mod token_service;
@@ -0,0 +1,43 @@
+use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::{App, web};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+use crate::config::AppConfig;
+use crate::services::health_service::HealthService;
+use crate::services::metrics_service::MetricsService;
+use crate::services::token_service::TokenService;
+use crate::utils::key_store::KeyStore;
+use crate::utils::rate_limit::RateLimiter;
+use crate::utils::security::SecretsManager;
+
+pub fn register_services<T>(
+    app: App<T>,
+    pool: Arc<PgPool>,
+    secrets_manager: SecretsManager,
+    key_store: Arc<dyn KeyStore>,
+    health_service: HealthService,
+    rate_limiter: Option<RateLimiter>,
+    config: &AppConfig,
+) -> App<T>
+where
+    T: ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse,
+            Error = actix_web::Error,
+            InitError = (),
+        >,
+{
+    app.app_data(web::Data::new(health_service))
+        .app_data(web::Data::new(MetricsService::new(pool.clone())))
+        .app_data(web::Data::new(
+            TokenService::new(pool, secrets_manager)
+                .with_key_store(key_store)
+                .with_lockout(config.security.lockout.clone())
+                .with_trusted_proxies(config.server.trusted_proxies.clone())
+                .with_token_endpoint_url(config.tokens.token_endpoint_url.clone())
+                .with_rate_limiter(rate_limiter)
+                .with_token_defaults(config.tokens.policy()),
+        ))
+}
@@ -0,0 +1,1014 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use ipnet::IpNet;
+use jsonwebtoken::{Algorithm, DecodingKey, Header, Validation, decode, decode_header};
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::{
+    config::LockoutConfig,
+    models::{
+        access_token::AccessTokenCreatePayloadWithAccessToken,
+        authentication_failure::AuthenticationSubjectType,
+        environment_key::EnvironmentKey,
+        project_access::ProjectAccess,
+        service_account::ServiceAccount,
+        token::{
+            CLIENT_ASSERTION_TYPE_JWT_BEARER, ClientAssertionClaims, GRANT_TYPE_CLIENT_CREDENTIALS,
+            GRANT_TYPE_REFRESH_TOKEN, GRANT_TYPE_TOKEN_EXCHANGE, TOKEN_TYPE_ACCESS_TOKEN,
+            TOKEN_TYPE_JWT, TOKEN_USE_ACCESS, TOKEN_USE_REFRESH, TokenError, TokenRequest,
+            TokenResponse,
+        },
+        token_policy::EffectiveTokenPolicy,
+    },
+    repositories::{
+        access_token_repository::AccessTokenRepository,
+        authentication_failure_repository::AuthenticationFailureRepository, base::Repository,
+        claim_template_repository::ClaimTemplateRepository,
+        environment_key_repository::EnvironmentKeyRepository,
+        project_access_repository::ProjectAccessRepository,
+        project_access_scopes_repository::ProjectAccessScopesRepository,
+        service_account_credential_repository::ServiceAccountCredentialRepository,
+        service_account_key_repository::ServiceAccountKeyRepository,
+        service_account_repository::ServiceAccountRepository,
+        token_policy_repository::TokenPolicyRepository,
+    },
+    utils::{
+        key_store::KeyStore,
+        metrics::METRICS,
+        network,
+        rate_limit::{RateLimitCategory, RateLimiter},
+        security::{SecretsManager, generate_opaque_token, hash_token, secrets_equal},
+        tokens::key_builder::{ActorClaim, Claims, KeyBuilder},
+    },
+};
+
+/// Algorithm recorded for refresh tokens, which are opaque and never signed
+const REFRESH_TOKEN_ALGORITHM: &str = "opaque";
+
+/// Longest lifetime accepted for a client assertion, bounding how long its ID is remembered
+const MAX_CLIENT_ASSERTION_LIFETIME_SECONDS: i64 = 300;
+
+/// Issues access tokens to service accounts, enforcing the token policy of the environment
+#[derive(Clone)]
+pub struct TokenService {
+    pub service_account_repository: ServiceAccountRepository,
+    pub project_access_repository: ProjectAccessRepository,
+    pub project_access_scopes_repository: ProjectAccessScopesRepository,
+    pub token_policy_repository: TokenPolicyRepository,
+    pub environment_key_repository: EnvironmentKeyRepository,
+    pub access_token_repository: AccessTokenRepository,
+    pub claim_template_repository: ClaimTemplateRepository,
+    pub service_account_key_repository: ServiceAccountKeyRepository,
+    pub service_account_credential_repository: ServiceAccountCredentialRepository,
+    pub authentication_failure_repository: AuthenticationFailureRepository,
+    pub lockout: LockoutConfig,
+    pub trusted_proxies: Vec<IpNet>,
+    /// Audience client assertions must carry, `private_key_jwt` is refused without it
+    pub token_endpoint_url: Option<String>,
+    /// Limits the token requests of each authenticated client, unlimited without it
+    pub rate_limiter: Option<RateLimiter>,
+}
+
+impl TokenService {
+    pub fn new(pool: Arc<sqlx::postgres::PgPool>, secrets_manager: SecretsManager) -> Self {
+        Self {
+            service_account_repository: ServiceAccountRepository::new(
+                pool.clone(),
+                secrets_manager.clone(),
+            ),
+            project_access_repository: ProjectAccessRepository::new(pool.clone()),
+            project_access_scopes_repository: ProjectAccessScopesRepository::new(pool.clone()),
+            token_policy_repository: TokenPolicyRepository::new(pool.clone()),
+            environment_key_repository: EnvironmentKeyRepository::new(
+                pool.clone(),
+                secrets_manager,
+            ),
+            access_token_repository: AccessTokenRepository::new(pool.clone()),
+            claim_template_repository: ClaimTemplateRepository::new(pool.clone()),
+            service_account_key_repository: ServiceAccountKeyRepository::new(pool.clone()),
+            service_account_credential_repository: ServiceAccountCredentialRepository::new(
+                pool.clone(),
+            ),
+            authentication_failure_repository: AuthenticationFailureRepository::new(pool),
+            lockout: LockoutConfig::default(),
+            trusted_proxies: Vec::new(),
+            token_endpoint_url: None,
+            rate_limiter: None,
+        }
+    }
+
+    /// Replaces the brute-force protection settings
+    pub fn with_lockout(mut self, lockout: LockoutConfig) -> Self {
+        self.lockout = lockout;
+        self
+    }
+
+    /// Replaces the policy applying to environments without a token policy
+    pub fn with_token_defaults(mut self, defaults: EffectiveTokenPolicy) -> Self {
+        self.token_policy_repository = self.token_policy_repository.with_defaults(defaults);
+        self
+    }
+
+    /// Replaces the store keeping the private key material of environment keys
+    pub fn with_key_store(mut self, key_store: Arc<dyn KeyStore>) -> Self {
+        self.environment_key_repository = self.environment_key_repository.with_key_store(key_store);
+        self
+    }
+
+    /// Replaces the proxies whose `X-Forwarded-For` header is trusted
+    pub fn with_trusted_proxies(mut self, trusted_proxies: Vec<IpNet>) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+
+    /// Replaces the public URL of the token endpoint, which client assertions are addressed to
+    pub fn with_token_endpoint_url(mut self, token_endpoint_url: Option<String>) -> Self {
+        self.token_endpoint_url = token_endpoint_url;
+        self
+    }
+
+    /// Replaces the limiter charging authenticated clients for their token requests
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Address of the client a request comes from, behind any trusted proxies
+    pub fn client_ip(&self, peer: Option<IpAddr>, forwarded_for: Option<&str>) -> Option<IpAddr> {
+        peer.map(|peer| network::client_ip(peer, forwarded_for, &self.trusted_proxies))
+    }
+
+    /// Handles a token request.
+    ///
+    /// `basic_credentials` holds the client ID and secret sent with HTTP Basic authentication
+    /// and `client_ip` the address failed authentications are also counted against and
+    /// allowlists are checked against.
+    pub async fn issue(
+        &self,
+        request: TokenRequest,
+        basic_credentials: Option<(String, String)>,
+        client_ip: Option<IpAddr>,
+    ) -> Result<TokenResponse, TokenError> {
+        // Unknown grant types share one label, so requests cannot create new series
+        let grant_type = match request.grant_type.as_str() {
+            GRANT_TYPE_CLIENT_CREDENTIALS => GRANT_TYPE_CLIENT_CREDENTIALS,
+            GRANT_TYPE_REFRESH_TOKEN => GRANT_TYPE_REFRESH_TOKEN,
+            GRANT_TYPE_TOKEN_EXCHANGE => GRANT_TYPE_TOKEN_EXCHANGE,
+            _ => "unsupported",
+        };
+
+        let result = self
+            .issue_token(request, basic_credentials, client_ip)
+            .await;
+        match &result {
+            Ok(_) => METRICS.tokens_issued_total.inc(&[grant_type]),
+            Err(error) => METRICS
+                .token_requests_failed_total
+                .inc(&[grant_type, error.code()]),
+        }
+        result
+    }
+
+    async fn issue_token(
+        &self,
+        request: TokenRequest,
+        basic_credentials: Option<(String, String)>,
+        client_ip: Option<IpAddr>,
+    ) -> Result<TokenResponse, TokenError> {
+        let service_account = self
+            .authenticate_client_guarded(&request, basic_credentials, client_ip)
+            .await?;
+        self.check_rate_limit(&service_account).await?;
+        Self::check_client_ip(&service_account.allowed_cidrs, client_ip)?;
+
+        let response = match request.grant_type.as_str() {
+            GRANT_TYPE_CLIENT_CREDENTIALS => {
+                let environment_id = Self::environment_id(&request)?;
+
+                let project_access = self
+                    .find_project_access(&service_account, environment_id, client_ip)
+                    .await?
+                    .ok_or_else(|| {
+                        TokenError::UnauthorizedClient(
+                            "Service account has no access to the environment".into(),
+                        )
+                    })?;
+
+                self.issue_for_access(&service_account, &project_access, &request, None)
+                    .await
+            }
+            GRANT_TYPE_REFRESH_TOKEN => {
+                let refresh_token = request.refresh_token.as_deref().ok_or_else(|| {
+                    TokenError::InvalidRequest("refresh_token is required".into())
+                })?;
+
+                // The refresh token is only used up once the new tokens can be issued
+                let refresh_token_hash = hash_token(refresh_token);
+                let stored = self
+                    .access_token_repository
+                    .find_refresh_token(&refresh_token_hash)
+                    .await
+                    .map_err(|error| TokenError::ServerError(error.to_string()))?
+                    .ok_or_else(|| {
+                        TokenError::InvalidGrant("Refresh token is invalid or expired".into())
+                    })?;
+
+                let project_access = self
+                    .project_access_repository
+                    .read(stored.project_access_id)
+                    .await
+                    .ok()
+                    .flatten()
+                    .filter(|project_access| {
+                        project_access.service_account_id == service_account.id.unwrap()
+                    })
+                    .ok_or_else(|| {
+                        TokenError::InvalidGrant(
+                            "Refresh token was not issued to the client".into(),
+                        )
+                    })?;
+
+                // The grant may have been disabled since the refresh token was issued
+                let project_access = self
+                    .find_project_access(&service_account, project_access.environment_id, client_ip)
+                    .await?
+                    .filter(|enabled| enabled.id == project_access.id)
+                    .ok_or_else(|| {
+                        TokenError::InvalidGrant("Project access is no longer enabled".into())
+                    })?;
+
+                self.issue_for_access(
+                    &service_account,
+                    &project_access,
+                    &request,
+                    Some(&refresh_token_hash),
+                )
+                .await
+            }
+            GRANT_TYPE_TOKEN_EXCHANGE => {
+                self.exchange_token(&service_account, &request, client_ip)
+                    .await
+            }
+            grant_type => Err(TokenError::UnsupportedGrantType(format!(
+                "Grant type {} is not supported",
+                grant_type
+            ))),
+        }?;
+
+        // Issuing a token keeps the service account from being disabled for inactivity
+        self.service_account_repository
+            .record_token_issued(service_account.id.unwrap())
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+
+        Ok(response)
+    }
+
+    /// Parses the environment a token is requested for
+    fn environment_id(request: &TokenRequest) -> Result<Uuid, TokenError> {
+        request
+            .environment_id
+            .as_deref()
+            .ok_or_else(|| TokenError::InvalidRequest("environment_id is required".into()))
+            .and_then(|environment_id| {
+                Uuid::parse_str(environment_id)
+                    .map_err(|_| TokenError::InvalidRequest("environment_id is invalid".into()))
+            })
+    }
+
+    /// Authenticates the client unless it or its address is blocked after repeated failures.
+    ///
+    /// Failed authentications are counted against the claimed client ID and the client IP,
+    /// while a successful one clears the failures of the service account.
+    async fn authenticate_client_guarded(
+        &self,
+        request: &TokenRequest,
+        basic_credentials: Option<(String, String)>,
+        client_ip: Option<IpAddr>,
+    ) -> Result<ServiceAccount, TokenError> {
+        if !self.lockout.enabled {
+            return self.authenticate_client(request, basic_credentials).await;
+        }
+
+        let client_id = Self::claimed_client_id(request, basic_credentials.as_ref())
+            .and_then(|client_id| Uuid::parse_str(&client_id).ok());
+        let subjects: Vec<(AuthenticationSubjectType, String)> = client_id
+            .map(|client_id| {
+                (
+                    AuthenticationSubjectType::ServiceAccount,
+                    client_id.to_string(),
+                )
+            })
+            .into_iter()
+            .chain(client_ip.map(|ip| (AuthenticationSubjectType::Ip, ip.to_string())))
+            .collect();
+
+        let blocked_until = self
+            .authentication_failure_repository
+            .find_blocked_until(&subjects)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+        if let Some(blocked_until) = blocked_until {
+            let retry_after = (blocked_until - Utc::now()).num_seconds().max(0) + 1;
+            return Err(TokenError::TooManyAttempts(retry_after));
+        }
+
+        match self.authenticate_client(request, basic_credentials).await {
+            Ok(service_account) => {
+                self.authentication_failure_repository
+                    .clear(
+                        AuthenticationSubjectType::ServiceAccount,
+                        &service_account.id.unwrap().to_string(),
+                    )
+                    .await
+                    .map_err(|error| TokenError::ServerError(error.to_string()))?;
+                Ok(service_account)
+            }
+            Err(TokenError::InvalidClient) => {
+                for (subject_type, subject) in &subjects {
+                    self.authentication_failure_repository
+                        .record_failure(*subject_type, subject, &self.lockout)
+                        .await
+                        .map_err(|error| TokenError::ServerError(error.to_string()))?;
+                }
+                Err(TokenError::InvalidClient)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Takes the request from the bucket of an authenticated client.
+    ///
+    /// Clients are only charged once authenticated, so a client ID claimed by anyone else
+    /// cannot exhaust its bucket. Like the rate limit middleware, a failing store lets the
+    /// request through.
+    async fn check_rate_limit(&self, service_account: &ServiceAccount) -> Result<(), TokenError> {
+        let Some(rate_limiter) = &self.rate_limiter else {
+            return Ok(());
+        };
+        let client = format!("service_account:{}", service_account.id.unwrap());
+        match rate_limiter
+            .check(RateLimitCategory::Token, None, Some(&client))
+            .await
+        {
+            Ok(decision) if !decision.allowed => {
+                METRICS
+                    .rate_limited_requests_total
+                    .inc(&[RateLimitCategory::Token.as_str()]);
+                Err(TokenError::RateLimited(decision.retry_after))
+            }
+            Ok(_) => Ok(()),
+            Err(error) => {
+                tracing::warn!(error = %error, "Rate limit store failed, request not limited");
+                Ok(())
+            }
+        }
+    }
+
+    /// Client ID a request claims, read before the client is authenticated
+    fn claimed_client_id(
+        request: &TokenRequest,
+        basic_credentials: Option<&(String, String)>,
+    ) -> Option<String> {
+        if let Some((client_id, _)) = basic_credentials {
+            return Some(client_id.clone());
+        }
+        if let Some(client_id) = &request.client_id {
+            return Some(client_id.clone());
+        }
+        let assertion = request.client_assertion.as_deref()?;
+        Self::unverified_assertion_subject(assertion)
+            .ok()
+            .map(|(_, client_id)| client_id)
+    }
+
+    /// Reads the header and subject of a client assertion without checking its signature
+    fn unverified_assertion_subject(assertion: &str) -> Result<(Header, String), TokenError> {
+        let header = decode_header(assertion).map_err(|_| TokenError::InvalidClient)?;
+
+        let mut unverified = Validation::new(header.alg);
+        unverified.insecure_disable_signature_validation();
+        unverified.validate_exp = false;
+        unverified.validate_aud = false;
+        unverified.required_spec_claims.clear();
+        let client_id =
+            decode::<ClientAssertionClaims>(assertion, &DecodingKey::from_secret(&[]), &unverified)
+                .map_err(|_| TokenError::InvalidClient)?
+                .claims
+                .sub;
+
+        Ok((header, client_id))
+    }
+
+    async fn authenticate_client(
+        &self,
+        request: &TokenRequest,
+        basic_credentials: Option<(String, String)>,
+    ) -> Result<ServiceAccount, TokenError> {
+        if request.client_assertion.is_some() || request.client_assertion_type.is_some() {
+            if basic_credentials.is_some() || request.client_secret.is_some() {
+                return Err(TokenError::InvalidRequest(
+                    "Only one client authentication method may be used".into(),
+                ));
+            }
+            return self.authenticate_client_assertion(request).await;
+        }
+
+        let (client_id, client_secret) = match (
+            basic_credentials,
+            &request.client_id,
+            &request.client_secret,
+        ) {
+            (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+                return Err(TokenError::InvalidRequest(
+                    "Only one client authentication method may be used".into(),
+                ));
+            }
+            (Some(credentials), None, None) => credentials,
+            (None, Some(client_id), Some(client_secret)) => {
+                (client_id.clone(), client_secret.clone())
+            }
+            _ => return Err(TokenError::InvalidClient),
+        };
+
+        let service_account = self.load_client(&client_id).await?;
+        if !service_account.is_active() {
+            return Err(TokenError::InvalidClient);
+        }
+
+        // The secret of the service account itself is checked first, then its rotatable credentials
+        let legacy_secret_matches = self
+            .service_account_repository
+            .secrets_manager
+            .decrypt(&service_account.secret, &service_account.id.unwrap())
+            .is_ok_and(|secret| secrets_equal(&secret, &client_secret));
+        if legacy_secret_matches {
+            return Ok(service_account);
+        }
+
+        let credential = self
+            .service_account_credential_repository
+            .verify(service_account.id.unwrap(), &client_secret)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+        if credential.is_none() {
+            return Err(TokenError::InvalidClient);
+        }
+
+        Ok(service_account)
+    }
+
+    /// Authenticates a client with a JWT signed by one of its registered keys (RFC 7523)
+    async fn authenticate_client_assertion(
+        &self,
+        request: &TokenRequest,
+    ) -> Result<ServiceAccount, TokenError> {
+        // The audience is configured, as the host a request names is chosen by the client
+        let Some(token_endpoint) = self.token_endpoint_url.as_deref() else {
+            return Err(TokenError::InvalidRequest(
+                "Client assertions are not accepted, tokens.token_endpoint_url is not configured"
+                    .into(),
+            ));
+        };
+        if request.client_assertion_type.as_deref() != Some(CLIENT_ASSERTION_TYPE_JWT_BEARER) {
+            return Err(TokenError::InvalidRequest(
+                "Unsupported client assertion type".into(),
+            ));
+        }
+        let assertion = request
+            .client_assertion
+            .as_deref()
+            .ok_or_else(|| TokenError::InvalidRequest("client_assertion is required".into()))?;
+        // The subject is read before the signature is checked, only to find the keys to check
+        // the signature with
+        let (header, client_id) = Self::unverified_assertion_subject(assertion)?;
+        if request
+            .client_id
+            .as_deref()
+            .is_some_and(|request_client_id| request_client_id != client_id)
+        {
+            return Err(TokenError::InvalidClient);
+        }
+
+        let service_account = self.load_client(&client_id).await?;
+        if !service_account.is_active() {
+            return Err(TokenError::InvalidClient);
+        }
+
+        let keys = self
+            .service_account_key_repository
+            .find_enabled_by_service_account(
+                service_account.id.unwrap(),
+                &format!("{:?}", header.alg),
+            )
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[token_endpoint]);
+        validation.set_issuer(&[&client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub", "aud"]);
+        validation.sub = Some(client_id.clone());
+        let claims = keys
+            .iter()
+            .find_map(|key| {
+                let decoding_key =
+                    KeyBuilder::decoding_key_from_public_pem(header.alg, &key.public_key).ok()?;
+                decode::<ClientAssertionClaims>(assertion, &decoding_key, &validation).ok()
+            })
+            .ok_or(TokenError::InvalidClient)?
+            .claims;
+
+        if claims.exp - Utc::now().timestamp() > MAX_CLIENT_ASSERTION_LIFETIME_SECONDS {
+            return Err(TokenError::InvalidClient);
+        }
+        let jti = claims.jti.ok_or(TokenError::InvalidClient)?;
+        let expires_at =
+            DateTime::<Utc>::from_timestamp(claims.exp, 0).ok_or(TokenError::InvalidClient)?;
+        let first_use = self
+            .service_account_key_repository
+            .record_assertion_jti(service_account.id.unwrap(), &jti, expires_at)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+        if !first_use {
+            return Err(TokenError::InvalidClient);
+        }
+
+        Ok(service_account)
+    }
+
+    /// Loads the service account identified by a client ID
+    async fn load_client(&self, client_id: &str) -> Result<ServiceAccount, TokenError> {
+        let client_id = Uuid::parse_str(client_id).map_err(|_| TokenError::InvalidClient)?;
+        self.service_account_repository
+            .find_by_id(client_id)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?
+            .ok_or(TokenError::InvalidClient)
+    }
+
+    /// Finds the enabled grant of the service account in an environment, rejecting clients
+    /// outside the address ranges the grant is narrowed to
+    async fn find_project_access(
+        &self,
+        service_account: &ServiceAccount,
+        environment_id: Uuid,
+        client_ip: Option<IpAddr>,
+    ) -> Result<Option<ProjectAccess>, TokenError> {
+        let project_access = self
+            .project_access_repository
+            .find_enabled_by_service_account_and_environment(
+                service_account.id.unwrap(),
+                environment_id,
+            )
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+
+        if let Some(project_access) = &project_access {
+            Self::check_client_ip(&project_access.allowed_cidrs, client_ip)?;
+        }
+
+        Ok(project_access)
+    }
+
+    /// Rejects clients outside an allowlist; an unknown address only passes an empty one
+    fn check_client_ip(
+        allowed_cidrs: &[String],
+        client_ip: Option<IpAddr>,
+    ) -> Result<(), TokenError> {
+        let allowed = match client_ip {
+            Some(client_ip) => network::ip_allowed(client_ip, allowed_cidrs),
+            None => allowed_cidrs.is_empty(),
+        };
+
+        if !allowed {
+            return Err(TokenError::UnauthorizedClient(
+                "Client address is not allowed".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Issues the tokens of a grant. With `refresh_token_hash`, the refresh token it hashes is
+    /// used up in the transaction recording the new tokens, once every check passed.
+    async fn issue_for_access(
+        &self,
+        service_account: &ServiceAccount,
+        project_access: &ProjectAccess,
+        request: &TokenRequest,
+        refresh_token_hash: Option<&str>,
+    ) -> Result<TokenResponse, TokenError> {
+        let policy = self
+            .token_policy_repository
+            .resolve(project_access.environment_id)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+
+        if refresh_token_hash.is_some() && !policy.allow_refresh_tokens {
+            return Err(TokenError::InvalidGrant(
+                "Refresh tokens are not allowed by the token policy".into(),
+            ));
+        }
+
+        let ttl_seconds = policy
+            .ttl_seconds(request.expires_in)
+            .map_err(|error| TokenError::InvalidRequest(error.to_string()))?;
+        let scopes = self
+            .resolve_scopes(project_access.id.unwrap(), request.scopes())
+            .await?;
+        let (environment_key, reference) = self
+            .select_signing_key(
+                project_access.environment_id,
+                &policy,
+                request.algorithm.as_deref(),
+            )
+            .await?;
+        let meta = self
+            .render_claim_templates(project_access.id.unwrap())
+            .await?;
+
+        let mut claims = Claims::new(service_account.id.unwrap().to_string(), ttl_seconds)
+            .with_jti(Uuid::new_v4().to_string())
+            .with_scopes(scopes.clone())
+            .with_meta(meta);
+        if let Some(issuer) = &policy.issuer {
+            claims = claims.with_issuer(issuer.clone());
+        }
+        if !policy.audience.is_empty() {
+            claims = claims.with_audience(policy.audience.clone());
+        }
+
+        let access_token = self.sign(&environment_key, &reference, &claims).await?;
+
+        let mut transaction = self
+            .access_token_repository
+            .pool
+            .begin()
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+        if let Some(refresh_token_hash) = refresh_token_hash {
+            // Another request may have used the refresh token since it was checked
+            AccessTokenRepository::consume_refresh_token(&mut *transaction, refresh_token_hash)
+                .await
+                .map_err(|error| TokenError::ServerError(error.to_string()))?
+                .ok_or_else(|| {
+                    TokenError::InvalidGrant("Refresh token is invalid or expired".into())
+                })?;
+        }
+        Self::store_access_token(
+            &mut *transaction,
+            project_access,
+            &environment_key,
+            &access_token,
+            &claims,
+        )
+        .await?;
+        let refresh_token = if policy.allow_refresh_tokens {
+            Some(
+                Self::issue_refresh_token(
+                    &mut *transaction,
+                    project_access,
+                    policy.refresh_token_ttl_seconds,
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+        transaction
+            .commit()
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+
+        Ok(TokenResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: ttl_seconds,
+            scope: (!scopes.is_empty()).then(|| scopes.join(" ")),
+            refresh_token,
+            issued_token_type: None,
+        })
+    }
+
+    /// Exchanges a subject token for a token acting on its behalf (RFC 8693).
+    ///
+    /// The issued token keeps the subject, is limited to the scopes shared by the subject
+    /// token and the acting service account, never outlives the subject token and records
+    /// the acting service account in its `act` claim chain.
+    async fn exchange_token(
+        &self,
+        service_account: &ServiceAccount,
+        request: &TokenRequest,
+        client_ip: Option<IpAddr>,
+    ) -> Result<TokenResponse, TokenError> {
+        let environment_id = Self::environment_id(request)?;
+        let subject_token = request
+            .subject_token
+            .as_deref()
+            .ok_or_else(|| TokenError::InvalidRequest("subject_token is required".into()))?;
+        match request.subject_token_type.as_deref() {
+            Some(TOKEN_TYPE_ACCESS_TOKEN | TOKEN_TYPE_JWT) => {}
+            Some(token_type) => {
+                return Err(TokenError::InvalidRequest(format!(
+                    "Subject token type {} is not supported",
+                    token_type
+                )));
+            }
+            None => {
+                return Err(TokenError::InvalidRequest(
+                    "subject_token_type is required".into(),
+                ));
+            }
+        }
+        let issued_token_type = match request.requested_token_type.as_deref() {
+            None | Some(TOKEN_TYPE_ACCESS_TOKEN) => TOKEN_TYPE_ACCESS_TOKEN,
+            Some(TOKEN_TYPE_JWT) => TOKEN_TYPE_JWT,
+            Some(token_type) => {
+                return Err(TokenError::InvalidRequest(format!(
+                    "Requested token type {} is not supported",
+                    token_type
+                )));
+            }
+        };
+
+        let project_access = self
+            .find_project_access(service_account, environment_id, client_ip)
+            .await?
+            .ok_or_else(|| {
+                TokenError::UnauthorizedClient(
+                    "Service account has no access to the environment".into(),
+                )
+            })?;
+        let subject = self
+            .verify_subject_token(environment_id, subject_token)
+            .await?;
+
+        let policy = self
+            .token_policy_repository
+            .resolve(environment_id)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+        let remaining_seconds = subject.exp - Utc::now().timestamp();
+        let ttl_seconds = policy
+            .ttl_seconds(request.expires_in)
+            .map_err(|error| TokenError::InvalidRequest(error.to_string()))?
+            .min(remaining_seconds);
+        if ttl_seconds <= 0 {
+            return Err(TokenError::InvalidGrant("Subject token is expired".into()));
+        }
+
+        let granted = self.granted_scopes(project_access.id.unwrap()).await?;
+        let available = subject
+            .scopes
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|scope| granted.contains(scope))
+            .collect();
+        let scopes = Self::select_scopes(available, request.scopes())?;
+        let (environment_key, reference) = self
+            .select_signing_key(environment_id, &policy, request.algorithm.as_deref())
+            .await?;
+
+        let actor = ActorClaim::new(service_account.id.unwrap().to_string(), subject.act);
+        let mut claims = Claims::new(subject.sub, ttl_seconds)
+            .with_jti(Uuid::new_v4().to_string())
+            .with_scopes(scopes.clone())
+            .with_meta(subject.meta.unwrap_or_default())
+            .with_actor(actor);
+        if let Some(issuer) = &policy.issuer {
+            claims = claims.with_issuer(issuer.clone());
+        }
+        if !policy.audience.is_empty() {
+            claims = claims.with_audience(policy.audience.clone());
+        }
+
+        let access_token = self.sign(&environment_key, &reference, &claims).await?;
+        Self::store_access_token(
+            &*self.access_token_repository.pool,
+            &project_access,
+            &environment_key,
+            &access_token,
+            &claims,
+        )
+        .await?;
+
+        Ok(TokenResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: ttl_seconds,
+            scope: (!scopes.is_empty()).then(|| scopes.join(" ")),
+            refresh_token: None,
+            issued_token_type: Some(issued_token_type.to_string()),
+        })
+    }
+
+    /// Verifies a subject token against the environment keys and the issued token records,
+    /// so tokens that were revoked or not issued in the environment are rejected
+    async fn verify_subject_token(
+        &self,
+        environment_id: Uuid,
+        subject_token: &str,
+    ) -> Result<Claims, TokenError> {
+        let invalid = || TokenError::InvalidGrant("Subject token is invalid".into());
+        let header = decode_header(subject_token).map_err(|_| invalid())?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = 0;
+        validation.validate_aud = false;
+        let subject: Claims = self
+            .environment_key_repository
+            .verify_jwt(environment_id, header.alg, subject_token, &validation)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?
+            .ok_or_else(invalid)?;
+
+        let jti = subject.jti.as_deref().ok_or_else(invalid)?;
+        self.access_token_repository
+            .find_active_by_jti(jti, environment_id)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?
+            .ok_or_else(|| {
+                TokenError::InvalidGrant("Subject token is revoked or unknown".into())
+            })?;
+
+        Ok(subject)
+    }
+
+    /// Signs the claims with an environment key
+    async fn sign(
+        &self,
+        environment_key: &EnvironmentKey,
+        reference: &str,
+        claims: &Claims,
+    ) -> Result<String, TokenError> {
+        self.environment_key_repository
+            .sign_jwt(environment_key, reference, claims)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))
+    }
+
+    /// Records a signed access token
+    async fn store_access_token<'e, E>(
+        executor: E,
+        project_access: &ProjectAccess,
+        environment_key: &EnvironmentKey,
+        access_token: &str,
+        claims: &Claims,
+    ) -> Result<(), TokenError>
+    where
+        E: PgExecutor<'e>,
+    {
+        let expires_at = DateTime::<Utc>::from_timestamp(claims.exp, 0)
+            .ok_or_else(|| TokenError::ServerError("Invalid token expiry".into()))?;
+
+        AccessTokenRepository::insert(
+            executor,
+            AccessTokenCreatePayloadWithAccessToken {
+                project_access_id: project_access.id.unwrap().to_string(),
+                algorithm: format!("{:?}", environment_key.algorithm),
+                expires_at: expires_at.to_rfc3339(),
+                access_token: access_token.to_string(),
+                jti: claims.jti.clone(),
+                token_use: TOKEN_USE_ACCESS.to_string(),
+            },
+        )
+        .await
+        .map_err(|error| TokenError::ServerError(error.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Renders the claim templates that apply to a project access
+    async fn render_claim_templates(
+        &self,
+        project_access_id: Uuid,
+    ) -> Result<HashMap<String, String>, TokenError> {
+        let templates = self
+            .claim_template_repository
+            .find_applicable(project_access_id)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))?;
+        if templates.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        self.claim_template_repository
+            .context(project_access_id)
+            .await
+            .and_then(|context| context.render_claims(&templates))
+            .map_err(|error| TokenError::ServerError(error.to_string()))
+    }
+
+    /// Checks the requested scopes against the grant, defaulting to every granted scope
+    async fn resolve_scopes(
+        &self,
+        project_access_id: Uuid,
+        requested: Option<Vec<String>>,
+    ) -> Result<Vec<String>, TokenError> {
+        let granted = self.granted_scopes(project_access_id).await?;
+        Self::select_scopes(granted, requested)
+    }
+
+    async fn granted_scopes(&self, project_access_id: Uuid) -> Result<Vec<String>, TokenError> {
+        self.project_access_scopes_repository
+            .find_scope_names(project_access_id)
+            .await
+            .map_err(|error| TokenError::ServerError(error.to_string()))
+    }
+
+    /// Checks the requested scopes against the available ones, defaulting to all of them
+    fn select_scopes(
+        available: Vec<String>,
+        requested: Option<Vec<String>>,
+    ) -> Result<Vec<String>, TokenError> {
+        match requested {
+            None => Ok(available),
+            Some(requested) => {
+                if let Some(scope) = requested.iter().find(|scope| !available.contains(scope)) {
+                    return Err(TokenError::InvalidScope(format!(
+                        "Scope {} is not granted",
+                        scope
+                    )));
+                }
+                Ok(requested)
+            }
+        }
+    }
+
+    /// Picks the active environment key used to sign the token, with the reference of its key
+    /// material.
+    ///
+    /// Without an explicit algorithm, the first algorithm allowed by the policy that has an
+    /// active key is used.
+    async fn select_signing_key(
+        &self,
+        environment_id: Uuid,
+        policy: &EffectiveTokenPolicy,
+        algorithm: Option<&str>,
+    ) -> Result<(EnvironmentKey, String), TokenError> {
+        let candidates = match algorithm {
+            Some(algorithm) => {
+                let algorithm = Algorithm::from_str(algorithm).map_err(|_| {
+                    TokenError::InvalidRequest(format!("Invalid algorithm: {}", algorithm))
+                })?;
+                policy
+                    .ensure_algorithm_allowed(algorithm)
+                    .map_err(|error| TokenError::InvalidRequest(error.to_string()))?;
+                vec![algorithm]
+            }
+            None => policy.allowed_algorithms.clone(),
+        };
+
+        for candidate in candidates {
+            let key = self
+                .environment_key_repository
+                .find_active_key(environment_id, Some(candidate))
+                .await
+                .map_err(|error| TokenError::ServerError(error.to_string()))?;
+            if let Some(key) = key {
+                return Ok(key);
+            }
+        }
+
+        match algorithm {
+            Some(algorithm) => Err(TokenError::InvalidRequest(format!(
+                "No active environment key for algorithm {}",
+                algorithm
+            ))),
+            None => Err(TokenError::ServerError(
+                "No active environment key is allowed by the token policy".into(),
+            )),
+        }
+    }
+
+    async fn issue_refresh_token<'e, E>(
+        executor: E,
+        project_access: &ProjectAccess,
+        ttl_seconds: i64,
+    ) -> Result<String, TokenError>
+    where
+        E: PgExecutor<'e>,
+    {
+        let refresh_token = generate_opaque_token();
+        AccessTokenRepository::insert(
+            executor,
+            AccessTokenCreatePayloadWithAccessToken {
+                project_access_id: project_access.id.unwrap().to_string(),
+                algorithm: REFRESH_TOKEN_ALGORITHM.to_string(),
+                expires_at: (Utc::now() + Duration::seconds(ttl_seconds)).to_rfc3339(),
+                access_token: hash_token(&refresh_token),
+                jti: None,
+                token_use: TOKEN_USE_REFRESH.to_string(),
+            },
+        )
+        .await
+        .map_err(|error| TokenError::ServerError(error.to_string()))?;
+
+        Ok(refresh_token)
+    }
+}
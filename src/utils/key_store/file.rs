@@ -0,0 +1,169 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+use async_trait::async_trait;
+use jsonwebtoken::Algorithm;
+use uuid::Uuid;
+
+use crate::utils::key_store::KeyStore;
+
+/// Keeps key material in files of a local directory, one directory per environment.
+///
+/// Key files are only readable by the owner of the process. The reference kept in the
+/// database is the name of the key file, so a database dump holds no key material.
+#[derive(Debug, Clone)]
+pub struct FileKeyStore {
+    directory: PathBuf,
+}
+
+impl FileKeyStore {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Resolves the path of a key file, refusing references that are not key file names
+    fn key_path(&self, environment_id: Uuid, reference: &str) -> Result<PathBuf> {
+        let name = key_name(reference).ok_or_else(|| Error::msg("Invalid key reference"))?;
+        Ok(self
+            .environment_directory(environment_id)
+            .join(format!("{}.key", name)))
+    }
+
+    fn environment_directory(&self, environment_id: Uuid) -> PathBuf {
+        self.directory.join(environment_id.to_string())
+    }
+}
+
+#[async_trait]
+impl KeyStore for FileKeyStore {
+    async fn store(&self, environment_id: Uuid, _: Algorithm, material: &str) -> Result<String> {
+        let reference = format!("{}.key", Uuid::new_v4());
+        let directory = self.environment_directory(environment_id);
+        let path = self.key_path(environment_id, &reference)?;
+        let material = material.to_string();
+
+        tokio::task::spawn_blocking(move || write_key_file(&directory, &path, &material)).await??;
+        Ok(reference)
+    }
+
+    async fn fetch(&self, environment_id: Uuid, reference: &str) -> Result<String> {
+        let path = self.key_path(environment_id, reference)?;
+        match tokio::fs::read_to_string(&path).await {
+            Ok(material) => Ok(material),
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                Err(Error::msg("Key material not found"))
+            }
+            Err(error) => Err(error).context("Failed to read key file"),
+        }
+    }
+
+    async fn delete(&self, environment_id: Uuid, reference: &str) -> Result<()> {
+        let path = self.key_path(environment_id, reference)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error).context("Failed to remove key file"),
+        }
+    }
+
+    fn recognizes(&self, reference: &str) -> bool {
+        key_name(reference).is_some()
+    }
+}
+
+/// Name of the key file a reference points to, if it is a key file name
+fn key_name(reference: &str) -> Option<Uuid> {
+    reference
+        .strip_suffix(".key")
+        .and_then(|name| Uuid::parse_str(name).ok())
+}
+
+#[cfg(unix)]
+fn write_key_file(directory: &Path, path: &Path, material: &str) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(directory)
+        .context("Failed to create key directory")?;
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .context("Failed to create key file")?;
+    file.write_all(material.as_bytes())
+        .context("Failed to write key file")?;
+    file.sync_all().context("Failed to write key file")
+}
+
+#[cfg(not(unix))]
+fn write_key_file(directory: &Path, path: &Path, material: &str) -> Result<()> {
+    std::fs::create_dir_all(directory).context("Failed to create key directory")?;
+    std::fs::write(path, material).context("Failed to write key file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_directory() -> PathBuf {
+        std::env::temp_dir().join(format!("sentinel-guard-keys-{}", Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_file_key_store_round_trip() {
+        let directory = test_directory();
+        let store = FileKeyStore::new(&directory);
+        let environment_id = Uuid::new_v4();
+
+        let reference = store
+            .generate(environment_id, Algorithm::HS256)
+            .await
+            .unwrap();
+        let path = directory.join(environment_id.to_string()).join(&reference);
+        assert!(path.exists());
+
+        let material = store.fetch(environment_id, &reference).await.unwrap();
+        assert_eq!(material.len(), 64);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), material);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Keys of another environment are not found under the same reference
+        let result = store.fetch(Uuid::new_v4(), &reference).await;
+        assert_eq!(result.unwrap_err().to_string(), "Key material not found");
+
+        store.delete(environment_id, &reference).await.unwrap();
+        assert!(!path.exists());
+        // Deleting twice is not an error
+        store.delete(environment_id, &reference).await.unwrap();
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_key_store_rejects_invalid_references() {
+        let store = FileKeyStore::new(test_directory());
+        let environment_id = Uuid::new_v4();
+
+        for reference in ["../../etc/passwd", "key", "/tmp/x.key", ""] {
+            let result = store.fetch(environment_id, reference).await;
+            assert_eq!(result.unwrap_err().to_string(), "Invalid key reference");
+            let result = store.delete(environment_id, reference).await;
+            assert_eq!(result.unwrap_err().to_string(), "Invalid key reference");
+            assert!(!store.recognizes(reference));
+        }
+        assert!(store.recognizes(&format!("{}.key", Uuid::new_v4())));
+    }
+}
@@ -0,0 +1,334 @@
+//! Storage backends for the private key material of environment keys.
+//!
+//! The `key` column of `environment_key` only holds a reference understood by the
+//! configured [`KeyStore`]: the encrypted material itself for the database store or a file
+//! name for the file store.
+//!
+//! No hardware store ships with SentinelGuard: keeping keys on an HSM needs a binding to
+//! its PKCS#11 module, which would implement [`KeyStore`] overriding `generate`, `sign` and
+//! `verify`. Until one is configurable, keys always live in the database or on disk.
+
+pub mod database;
+pub mod file;
+
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::config::{KeyStoreBackend, KeyStoreConfig};
+use crate::utils::security::SecretsManager;
+use crate::utils::tokens::key_builder::KeyBuilder;
+
+use self::database::DatabaseKeyStore;
+use self::file::FileKeyStore;
+
+/// Keeps the private key material of environment keys and signs with it.
+///
+/// Stores holding exportable material only implement `store`, `fetch` and `delete`; signing
+/// and verification then happen in the application. Stores whose keys never leave them, like
+/// a hardware token, override `generate`, `sign` and `verify` and refuse to `fetch`.
+#[async_trait]
+pub trait KeyStore: Send + Sync {
+    /// Stores key material of an environment, returning the reference to keep in the database
+    async fn store(
+        &self,
+        environment_id: Uuid,
+        algorithm: Algorithm,
+        material: &str,
+    ) -> Result<String>;
+
+    /// Returns the key material a reference points to
+    async fn fetch(&self, environment_id: Uuid, reference: &str) -> Result<String>;
+
+    /// Removes the key material a reference points to
+    async fn delete(&self, environment_id: Uuid, reference: &str) -> Result<()>;
+
+    /// Whether a reference has the shape of those this store hands out, so keys kept in
+    /// another store are detected before being used
+    fn recognizes(&self, reference: &str) -> bool;
+
+    /// Creates a new key for the algorithm, returning its reference
+    async fn generate(&self, environment_id: Uuid, algorithm: Algorithm) -> Result<String> {
+        let key = KeyBuilder::new().generate_key(algorithm)?;
+        self.store(environment_id, algorithm, &key.private_key_str)
+            .await
+    }
+
+    /// Signs a message with the key a reference points to
+    async fn sign(
+        &self,
+        environment_id: Uuid,
+        reference: &str,
+        algorithm: Algorithm,
+        message: &[u8],
+    ) -> Result<Vec<u8>> {
+        let material = self.fetch(environment_id, reference).await?;
+        sign_with_material(algorithm, &material, message)
+    }
+
+    /// Whether a signature of a message was made with the key a reference points to
+    async fn verify(
+        &self,
+        environment_id: Uuid,
+        reference: &str,
+        algorithm: Algorithm,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool> {
+        let material = self.fetch(environment_id, reference).await?;
+        verify_with_material(algorithm, &material, message, signature)
+    }
+}
+
+/// Builds the key store selected by the configuration, the database store encrypting with
+/// `secrets_manager`
+pub fn from_config(
+    config: &KeyStoreConfig,
+    secrets_manager: SecretsManager,
+) -> Result<Arc<dyn KeyStore>> {
+    match config.backend {
+        KeyStoreBackend::Database => Ok(Arc::new(DatabaseKeyStore::new(secrets_manager))),
+        KeyStoreBackend::File => {
+            let directory = config.directory.clone().ok_or_else(|| {
+                Error::msg("SENTINEL_GUARD_KEY_STORE_DIRECTORY is required by the file key store")
+            })?;
+            Ok(Arc::new(FileKeyStore::new(directory)))
+        }
+    }
+}
+
+/// Signs a message with key material as stored for generated keys
+pub fn sign_with_material(algorithm: Algorithm, material: &str, message: &[u8]) -> Result<Vec<u8>> {
+    let key = KeyBuilder::signing_key_from_str(algorithm, material)?;
+    let encoding_key = match algorithm {
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => EncodingKey::from_secret(&key),
+        _ => EncodingKey::from_rsa_pem(&key)?,
+    };
+    let signature = jsonwebtoken::crypto::sign(message, &encoding_key, algorithm)?;
+    Ok(URL_SAFE_NO_PAD.decode(signature)?)
+}
+
+/// Verifies a signature with key material as stored for generated keys
+pub fn verify_with_material(
+    algorithm: Algorithm,
+    material: &str,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    let decoding_key = KeyBuilder::decoding_key_from_str(algorithm, material)?;
+    Ok(jsonwebtoken::crypto::verify(
+        &URL_SAFE_NO_PAD.encode(signature),
+        message,
+        &decoding_key,
+        algorithm,
+    )?)
+}
+
+/// Encodes claims as a JWT signed by the key a reference points to, naming the environment
+/// key in the `kid` header so verifiers can pick it among the keys of the environment
+pub async fn encode_jwt<T: Serialize>(
+    key_store: &dyn KeyStore,
+    environment_id: Uuid,
+    key_id: Option<Uuid>,
+    reference: &str,
+    algorithm: Algorithm,
+    claims: &T,
+) -> Result<String> {
+    let mut header = Header::new(algorithm);
+    header.kid = key_id.map(|id| id.to_string());
+    let header = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?);
+    let claims = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?);
+    let message = format!("{}.{}", header, claims);
+    let signature = key_store
+        .sign(environment_id, reference, algorithm, message.as_bytes())
+        .await?;
+    Ok(format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// Decodes a JWT after checking it was signed by the key a reference points to.
+///
+/// The signature is checked by the key store, so it works with keys that cannot be exported;
+/// the claims are then validated against `validation`.
+pub async fn decode_jwt<T: DeserializeOwned>(
+    key_store: &dyn KeyStore,
+    environment_id: Uuid,
+    reference: &str,
+    token: &str,
+    validation: &Validation,
+) -> Result<T> {
+    let header = decode_header(token)?;
+    if !validation.algorithms.contains(&header.alg) {
+        return Err(Error::msg("Token algorithm is not allowed"));
+    }
+    let (message, signature) = token
+        .rsplit_once('.')
+        .ok_or_else(|| Error::msg("Token is malformed"))?;
+    let signature = URL_SAFE_NO_PAD.decode(signature)?;
+    if !key_store
+        .verify(
+            environment_id,
+            reference,
+            header.alg,
+            message.as_bytes(),
+            &signature,
+        )
+        .await?
+    {
+        return Err(Error::msg("Token signature is invalid"));
+    }
+
+    let mut validation = validation.clone();
+    validation.insecure_disable_signature_validation();
+    Ok(decode::<T>(token, &DecodingKey::from_secret(&[]), &validation)?.claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tokens::key_builder::Claims;
+
+    /// Keeps the material itself as reference, like the database store without encryption
+    struct PlainKeyStore;
+
+    #[async_trait]
+    impl KeyStore for PlainKeyStore {
+        async fn store(&self, _: Uuid, _: Algorithm, material: &str) -> Result<String> {
+            Ok(material.to_string())
+        }
+
+        async fn fetch(&self, _: Uuid, reference: &str) -> Result<String> {
+            Ok(reference.to_string())
+        }
+
+        async fn delete(&self, _: Uuid, _: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn recognizes(&self, _: &str) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encode_decode_jwt() {
+        for algorithm in [Algorithm::HS384, Algorithm::RS256, Algorithm::PS256] {
+            let environment_id = Uuid::new_v4();
+            let reference = PlainKeyStore
+                .generate(environment_id, algorithm)
+                .await
+                .unwrap();
+            let claims = Claims::new("worker", 300).with_jti("jti-1");
+
+            let key_id = Uuid::new_v4();
+            let token = encode_jwt(
+                &PlainKeyStore,
+                environment_id,
+                Some(key_id),
+                &reference,
+                algorithm,
+                &claims,
+            )
+            .await
+            .unwrap();
+            assert_eq!(decode_header(&token).unwrap().kid, Some(key_id.to_string()));
+
+            // Tokens are the ones jsonwebtoken would produce
+            let decoding_key = KeyBuilder::decoding_key_from_str(algorithm, &reference).unwrap();
+            let validation = Validation::new(algorithm);
+            let decoded = decode::<Claims>(&token, &decoding_key, &validation).unwrap();
+            assert_eq!(decoded.claims.jti.as_deref(), Some("jti-1"));
+
+            let decoded: Claims = decode_jwt(
+                &PlainKeyStore,
+                environment_id,
+                &reference,
+                &token,
+                &validation,
+            )
+            .await
+            .unwrap();
+            assert_eq!(decoded.sub, "worker");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decode_jwt_rejects_other_keys_and_expired_tokens() {
+        let environment_id = Uuid::new_v4();
+        let reference = PlainKeyStore
+            .generate(environment_id, Algorithm::HS256)
+            .await
+            .unwrap();
+        let other = PlainKeyStore
+            .generate(environment_id, Algorithm::HS256)
+            .await
+            .unwrap();
+        let validation = Validation::new(Algorithm::HS256);
+
+        let claims = Claims::new("worker", 300);
+        let token = encode_jwt(
+            &PlainKeyStore,
+            environment_id,
+            None,
+            &other,
+            Algorithm::HS256,
+            &claims,
+        )
+        .await
+        .unwrap();
+        let result = decode_jwt::<Claims>(
+            &PlainKeyStore,
+            environment_id,
+            &reference,
+            &token,
+            &validation,
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Token signature is invalid"
+        );
+
+        let claims = Claims::new("worker", -300);
+        let token = encode_jwt(
+            &PlainKeyStore,
+            environment_id,
+            None,
+            &reference,
+            Algorithm::HS256,
+            &claims,
+        )
+        .await
+        .unwrap();
+        let result = decode_jwt::<Claims>(
+            &PlainKeyStore,
+            environment_id,
+            &reference,
+            &token,
+            &validation,
+        )
+        .await;
+        assert!(result.is_err());
+
+        let result = decode_jwt::<Claims>(
+            &PlainKeyStore,
+            environment_id,
+            &reference,
+            &token,
+            &Validation::new(Algorithm::RS256),
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Token algorithm is not allowed"
+        );
+    }
+}
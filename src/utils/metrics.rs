@@ -0,0 +1,343 @@
+//! Process wide metrics, rendered in the Prometheus text exposition format.
+//!
+//! Metrics are kept in the [`METRICS`] registry so repositories and services record them
+//! without having the registry passed around, like the default registry of Prometheus
+//! client libraries. They are `prometheus` metrics behind wrappers taking label values as
+//! slices, and are encoded by its `TextEncoder`.
+
+use std::sync::LazyLock;
+
+use prometheus::core::Collector;
+use prometheus::{HistogramOpts, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+
+/// Upper bounds, in seconds, of the request duration histogram buckets
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// A counter for each combination of label values
+pub struct CounterVec(IntCounterVec);
+
+impl CounterVec {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Self(IntCounterVec::new(Opts::new(name, help), label_names).expect("Invalid counter"))
+    }
+
+    pub fn inc(&self, labels: &[&str]) {
+        self.inc_by(labels, 1);
+    }
+
+    pub fn inc_by(&self, labels: &[&str], value: u64) {
+        self.0.with_label_values(labels).inc_by(value);
+    }
+
+    /// Value of the counter, which is exposed from then on at 0 when it was never incremented
+    pub fn get(&self, labels: &[&str]) -> u64 {
+        self.0
+            .get_metric_with_label_values(labels)
+            .map(|counter| counter.get())
+            .unwrap_or_default()
+    }
+}
+
+/// A histogram for each combination of label values
+pub struct HistogramVec(prometheus::HistogramVec);
+
+impl HistogramVec {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        buckets: &'static [f64],
+    ) -> Self {
+        let opts = HistogramOpts::new(name, help).buckets(buckets.to_vec());
+        Self(prometheus::HistogramVec::new(opts, label_names).expect("Invalid histogram"))
+    }
+
+    pub fn observe(&self, labels: &[&str], value: f64) {
+        self.0.with_label_values(labels).observe(value);
+    }
+
+    /// Number of observations made with the label values
+    pub fn count(&self, labels: &[&str]) -> u64 {
+        self.0
+            .get_metric_with_label_values(labels)
+            .map(|histogram| histogram.get_sample_count())
+            .unwrap_or_default()
+    }
+}
+
+/// A gauge without labels, for values read at scrape time
+pub struct Gauge(IntGauge);
+
+impl Gauge {
+    pub fn new(name: &'static str, help: &'static str) -> Self {
+        Self(IntGauge::new(name, help).expect("Invalid gauge"))
+    }
+
+    pub fn set(&self, value: i64) {
+        self.0.set(value);
+    }
+}
+
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests_total: CounterVec,
+    pub http_request_duration_seconds: HistogramVec,
+    pub tokens_issued_total: CounterVec,
+    pub token_requests_failed_total: CounterVec,
+    pub environment_key_rotations_total: CounterVec,
+    pub secrets_operations_total: CounterVec,
+    pub rate_limited_requests_total: CounterVec,
+    pub job_runs_total: CounterVec,
+    pub access_tokens_removed_total: CounterVec,
+    pub service_accounts_disabled_total: CounterVec,
+    pub service_account_inactivity_warnings_total: CounterVec,
+    pub db_pool_connections: Gauge,
+    pub db_pool_idle_connections: Gauge,
+    pub db_pool_in_use_connections: Gauge,
+    pub db_pool_max_connections: Gauge,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let metrics = Self {
+            registry: Registry::new(),
+            http_requests_total: CounterVec::new(
+                "sentinel_guard_http_requests_total",
+                "HTTP requests handled, by route and status",
+                &["method", "route", "status"],
+            ),
+            http_request_duration_seconds: HistogramVec::new(
+                "sentinel_guard_http_request_duration_seconds",
+                "Time taken to handle HTTP requests, by route",
+                &["method", "route"],
+                DURATION_BUCKETS,
+            ),
+            tokens_issued_total: CounterVec::new(
+                "sentinel_guard_tokens_issued_total",
+                "Access tokens issued, by grant type",
+                &["grant_type"],
+            ),
+            token_requests_failed_total: CounterVec::new(
+                "sentinel_guard_token_requests_failed_total",
+                "Token requests rejected, by grant type and OAuth error",
+                &["grant_type", "error"],
+            ),
+            environment_key_rotations_total: CounterVec::new(
+                "sentinel_guard_environment_key_rotations_total",
+                "Environment keys rotated, by trigger",
+                &["trigger"],
+            ),
+            secrets_operations_total: CounterVec::new(
+                "sentinel_guard_secrets_operations_total",
+                "Encryptions and decryptions of secrets, by outcome",
+                &["operation", "outcome"],
+            ),
+            rate_limited_requests_total: CounterVec::new(
+                "sentinel_guard_rate_limited_requests_total",
+                "Requests refused by the rate limiter, by rule",
+                &["rule"],
+            ),
+            job_runs_total: CounterVec::new(
+                "sentinel_guard_job_runs_total",
+                "Runs of the background jobs, by job and outcome",
+                &["job", "outcome"],
+            ),
+            access_tokens_removed_total: CounterVec::new(
+                "sentinel_guard_access_tokens_removed_total",
+                "Expired access tokens removed by the cleanup job, by mode",
+                &["mode"],
+            ),
+            service_accounts_disabled_total: CounterVec::new(
+                "sentinel_guard_service_accounts_disabled_total",
+                "Service accounts disabled by the lifecycle job, by reason",
+                &["reason"],
+            ),
+            service_account_inactivity_warnings_total: CounterVec::new(
+                "sentinel_guard_service_account_inactivity_warnings_total",
+                "Service accounts warned of their upcoming inactivity disablement",
+                &[],
+            ),
+            db_pool_connections: Gauge::new(
+                "sentinel_guard_db_pool_connections",
+                "Open database connections",
+            ),
+            db_pool_idle_connections: Gauge::new(
+                "sentinel_guard_db_pool_idle_connections",
+                "Open database connections waiting to be used",
+            ),
+            db_pool_in_use_connections: Gauge::new(
+                "sentinel_guard_db_pool_in_use_connections",
+                "Database connections used by queries",
+            ),
+            db_pool_max_connections: Gauge::new(
+                "sentinel_guard_db_pool_max_connections",
+                "Maximum number of database connections",
+            ),
+        };
+
+        let collectors: [Box<dyn Collector>; 15] = [
+            Box::new(metrics.http_requests_total.0.clone()),
+            Box::new(metrics.http_request_duration_seconds.0.clone()),
+            Box::new(metrics.tokens_issued_total.0.clone()),
+            Box::new(metrics.token_requests_failed_total.0.clone()),
+            Box::new(metrics.environment_key_rotations_total.0.clone()),
+            Box::new(metrics.secrets_operations_total.0.clone()),
+            Box::new(metrics.rate_limited_requests_total.0.clone()),
+            Box::new(metrics.job_runs_total.0.clone()),
+            Box::new(metrics.access_tokens_removed_total.0.clone()),
+            Box::new(metrics.service_accounts_disabled_total.0.clone()),
+            Box::new(metrics.service_account_inactivity_warnings_total.0.clone()),
+            Box::new(metrics.db_pool_connections.0.clone()),
+            Box::new(metrics.db_pool_idle_connections.0.clone()),
+            Box::new(metrics.db_pool_in_use_connections.0.clone()),
+            Box::new(metrics.db_pool_max_connections.0.clone()),
+        ];
+        for collector in collectors {
+            metrics
+                .registry
+                .register(collector)
+                .expect("Metric registered twice");
+        }
+        metrics
+    }
+
+    /// Records an encryption or decryption of the secrets manager
+    pub fn record_secrets_operation(&self, operation: &str, succeeded: bool) {
+        let outcome = if succeeded { "success" } else { "failure" };
+        self.secrets_operations_total.inc(&[operation, outcome]);
+    }
+
+    /// Records a run of a background job
+    pub fn record_job_run(&self, job: &str, succeeded: bool) {
+        let outcome = if succeeded { "success" } else { "failure" };
+        self.job_runs_total.inc(&[job, outcome]);
+    }
+
+    /// Renders every metric in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        render(&self.registry)
+    }
+}
+
+/// Renders the metrics of a registry that were recorded at least once
+fn render(registry: &Registry) -> String {
+    TextEncoder::new()
+        .encode_to_string(&registry.gather())
+        .expect("Metrics are valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(collector: impl Collector + 'static) -> Registry {
+        let registry = Registry::new();
+        registry.register(Box::new(collector)).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_counter_vec_render() {
+        let counter = CounterVec::new("test_total", "A test counter", &["route", "status"]);
+        counter.inc(&["/projects/{id}", "200"]);
+        counter.inc_by(&["/projects/{id}", "200"], 2);
+        counter.inc(&["/say \"hi\"", "404"]);
+        counter.inc(&["C:\\tokens\nnext", "500"]);
+
+        assert_eq!(counter.get(&["/projects/{id}", "200"]), 3);
+        assert_eq!(counter.get(&["/projects", "200"]), 0);
+
+        let output = render(&registry(counter.0.clone()));
+        assert_eq!(
+            output,
+            "# HELP test_total A test counter\n\
+             # TYPE test_total counter\n\
+             test_total{route=\"/projects\",status=\"200\"} 0\n\
+             test_total{route=\"/projects/{id}\",status=\"200\"} 3\n\
+             test_total{route=\"/say \\\"hi\\\"\",status=\"404\"} 1\n\
+             test_total{route=\"C:\\\\tokens\\nnext\",status=\"500\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn test_histogram_vec_render() {
+        let histogram =
+            HistogramVec::new("test_seconds", "A test histogram", &["route"], &[0.25, 1.0]);
+        histogram.observe(&["/token"], 0.25);
+        histogram.observe(&["/token"], 0.5);
+        histogram.observe(&["/token"], 2.0);
+
+        assert_eq!(histogram.count(&["/token"]), 3);
+
+        let output = render(&registry(histogram.0.clone()));
+        assert_eq!(
+            output,
+            "# HELP test_seconds A test histogram\n\
+             # TYPE test_seconds histogram\n\
+             test_seconds_bucket{route=\"/token\",le=\"0.25\"} 1\n\
+             test_seconds_bucket{route=\"/token\",le=\"1\"} 2\n\
+             test_seconds_bucket{route=\"/token\",le=\"+Inf\"} 3\n\
+             test_seconds_sum{route=\"/token\"} 2.75\n\
+             test_seconds_count{route=\"/token\"} 3\n"
+        );
+    }
+
+    #[test]
+    fn test_histogram_vec_render_keeps_buckets_cumulative() {
+        let histogram = HistogramVec::new(
+            "test_seconds",
+            "A test histogram",
+            &["route"],
+            DURATION_BUCKETS,
+        );
+        let observations = [0.001, 0.02, 0.02, 0.3, 4.0, 60.0];
+        for value in observations {
+            histogram.observe(&["/token"], value);
+        }
+
+        let output = render(&registry(histogram.0.clone()));
+        let sample = |line: &str| line.rsplit(' ').next().unwrap().parse::<f64>().unwrap();
+        let buckets: Vec<f64> = output
+            .lines()
+            .filter(|line| line.starts_with("test_seconds_bucket"))
+            .map(sample)
+            .collect();
+        assert_eq!(buckets.len(), DURATION_BUCKETS.len() + 1);
+        assert!(buckets.windows(2).all(|pair| pair[0] <= pair[1]));
+        let count = output
+            .lines()
+            .find(|line| line.starts_with("test_seconds_count"))
+            .map(sample)
+            .unwrap();
+        let sum = output
+            .lines()
+            .find(|line| line.starts_with("test_seconds_sum"))
+            .map(sample)
+            .unwrap();
+        assert_eq!(buckets.last().copied(), Some(count));
+        assert_eq!(count, observations.len() as f64);
+        assert!((sum - observations.iter().sum::<f64>()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gauge_render() {
+        let gauge = Gauge::new("test_connections", "A test gauge");
+        gauge.set(4);
+
+        let output = render(&registry(gauge.0.clone()));
+        assert_eq!(
+            output,
+            "# HELP test_connections A test gauge\n\
+             # TYPE test_connections gauge\n\
+             test_connections 4\n"
+        );
+    }
+}
@@ -1,3 +1,10 @@
+pub mod key_store;
+pub mod logging;
+pub mod metrics;
+pub mod network;
+pub mod otlp;
+pub mod rate_limit;
 pub mod security;
 pub mod swagger;
+pub mod tls;
 pub mod tokens;
@@ -0,0 +1,144 @@
+use anyhow::{Error, Result};
+use ipnet::IpNet;
+use std::net::IpAddr;
+
+/// Parses a CIDR range, accepting a bare address as a single host range.
+///
+/// Host bits are cleared, so `10.1.2.3/8` is read as `10.0.0.0/8`.
+pub fn parse_cidr(value: &str) -> Result<IpNet> {
+    let value = value.trim();
+    value
+        .parse::<IpNet>()
+        .map(|network| network.trunc())
+        .or_else(|_| value.parse::<IpAddr>().map(IpNet::from))
+        .map_err(|_| Error::msg(format!("Invalid CIDR range: {}", value)))
+}
+
+/// Validates CIDR ranges and returns them in their canonical form, without duplicates
+pub fn normalize_cidrs(values: &[String]) -> Result<Vec<String>> {
+    let mut cidrs: Vec<String> = Vec::with_capacity(values.len());
+    for value in values {
+        let cidr = parse_cidr(value)?.to_string();
+        if !cidrs.contains(&cidr) {
+            cidrs.push(cidr);
+        }
+    }
+    Ok(cidrs)
+}
+
+/// Whether an address is inside one of the CIDR ranges; an empty list allows any address.
+///
+/// Ranges that no longer parse never match, so a corrupted allowlist fails closed.
+pub fn ip_allowed(ip: IpAddr, cidrs: &[String]) -> bool {
+    if cidrs.is_empty() {
+        return true;
+    }
+    let ip = ip.to_canonical();
+    cidrs
+        .iter()
+        .filter_map(|cidr| parse_cidr(cidr).ok())
+        .any(|network| network.contains(&ip))
+}
+
+/// Resolves the address of the client behind any trusted proxies.
+///
+/// `X-Forwarded-For` is only read when the peer is a trusted proxy. Its entries are walked
+/// from the right, as each proxy appends the address it received the request from, and the
+/// first one that is not a trusted proxy is the client.
+pub fn client_ip(peer: IpAddr, forwarded_for: Option<&str>, trusted_proxies: &[IpNet]) -> IpAddr {
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+
+    let mut client = peer.to_canonical();
+    if !is_trusted(&client) {
+        return client;
+    }
+
+    for hop in forwarded_for.unwrap_or_default().rsplit(',') {
+        let Ok(hop) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = hop.to_canonical();
+        if !is_trusted(&client) {
+            break;
+        }
+    }
+
+    client
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_cidr() {
+        assert_eq!(parse_cidr("10.1.2.3/8").unwrap().to_string(), "10.0.0.0/8");
+        assert_eq!(parse_cidr("192.0.2.1").unwrap().to_string(), "192.0.2.1/32");
+        assert_eq!(
+            parse_cidr("2001:db8::1/32").unwrap().to_string(),
+            "2001:db8::/32"
+        );
+        assert_eq!(
+            parse_cidr("10.0.0.0/33").unwrap_err().to_string(),
+            "Invalid CIDR range: 10.0.0.0/33"
+        );
+        assert!(parse_cidr("office").is_err());
+    }
+
+    #[test]
+    fn test_normalize_cidrs() {
+        let cidrs = normalize_cidrs(&[
+            "10.1.0.0/16".to_string(),
+            "10.1.2.3/16".to_string(),
+            " 192.0.2.1 ".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(cidrs, vec!["10.1.0.0/16", "192.0.2.1/32"]);
+    }
+
+    #[test]
+    fn test_ip_allowed() {
+        let cidrs = vec!["10.0.0.0/8".to_string(), "2001:db8::/32".to_string()];
+        assert!(ip_allowed(ip("10.20.30.40"), &cidrs));
+        assert!(ip_allowed(ip("::ffff:10.20.30.40"), &cidrs));
+        assert!(ip_allowed(ip("2001:db8::7"), &cidrs));
+        assert!(!ip_allowed(ip("192.0.2.1"), &cidrs));
+        assert!(ip_allowed(ip("192.0.2.1"), &[]));
+        assert!(!ip_allowed(ip("192.0.2.1"), &["invalid".to_string()]));
+    }
+
+    #[test]
+    fn test_client_ip_ignores_forwarded_for_from_untrusted_peer() {
+        let trusted = vec![parse_cidr("10.0.0.0/8").unwrap()];
+        assert_eq!(
+            client_ip(ip("192.0.2.1"), Some("203.0.113.7"), &trusted),
+            ip("192.0.2.1")
+        );
+        assert_eq!(
+            client_ip(ip("10.0.0.2"), Some("203.0.113.7"), &[]),
+            ip("10.0.0.2")
+        );
+    }
+
+    #[test]
+    fn test_client_ip_skips_trusted_proxies() {
+        let trusted = vec![parse_cidr("10.0.0.0/8").unwrap()];
+        assert_eq!(
+            client_ip(
+                ip("10.0.0.2"),
+                Some("198.51.100.9, 203.0.113.7, 10.0.0.3"),
+                &trusted
+            ),
+            ip("203.0.113.7")
+        );
+        assert_eq!(client_ip(ip("10.0.0.2"), None, &trusted), ip("10.0.0.2"));
+        assert_eq!(
+            client_ip(ip("10.0.0.2"), Some("garbage, 10.0.0.3"), &trusted),
+            ip("10.0.0.3")
+        );
+    }
+}
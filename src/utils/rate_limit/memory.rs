@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::config::RateLimitRule;
+use crate::utils::rate_limit::{RateLimitDecision, RateLimitStore, take};
+
+/// Buckets above which full ones are dropped, as they behave like missing ones
+const PRUNE_THRESHOLD: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+    full_at: Instant,
+}
+
+/// Keeps buckets in the memory of this process, so each replica limits on its own
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn acquire(&self, key: &str, rule: &RateLimitRule) -> Result<RateLimitDecision> {
+        let now = Instant::now();
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| bucket.full_at > now);
+        }
+
+        let (tokens, elapsed) = match buckets.get(key) {
+            Some(bucket) => (
+                bucket.tokens,
+                now.duration_since(bucket.updated_at).as_secs_f64(),
+            ),
+            None => (f64::from(rule.capacity), 0.0),
+        };
+        let (tokens, decision) = take(tokens, elapsed, rule);
+        buckets.insert(
+            key.to_string(),
+            Bucket {
+                tokens,
+                updated_at: now,
+                full_at: now + Duration::from_secs(decision.reset),
+            },
+        );
+        Ok(decision)
+    }
+}
@@ -0,0 +1,310 @@
+//! Token bucket rate limiting of the admin and token APIs.
+//!
+//! Every request takes one token from the bucket of its client IP and, once the client is
+//! verified, from the bucket of that client. Buckets refill continuously and are
+//! kept by the configured [`RateLimitStore`]: in memory, per replica, or in PostgreSQL,
+//! shared by every replica.
+
+pub mod memory;
+pub mod postgres;
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ipnet::IpNet;
+use sqlx::postgres::PgPool;
+
+use crate::config::{RateLimitConfig, RateLimitRule, RateLimitStoreBackend};
+use crate::utils::network;
+
+use self::memory::MemoryRateLimitStore;
+use self::postgres::PostgresRateLimitStore;
+
+/// Outcome of taking a request from a bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// Capacity of the bucket
+    pub limit: u32,
+    /// Requests left in the bucket
+    pub remaining: u32,
+    /// Seconds until a request is allowed again, 0 when this one was
+    pub retry_after: u64,
+    /// Seconds until the bucket is full again
+    pub reset: u64,
+}
+
+/// Keeps the buckets of the rate limiter
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Takes a request from the bucket of a key, which starts full
+    async fn acquire(&self, key: &str, rule: &RateLimitRule) -> Result<RateLimitDecision>;
+}
+
+/// Builds the store selected by the configuration
+pub fn from_config(config: &RateLimitConfig, pool: Arc<PgPool>) -> Arc<dyn RateLimitStore> {
+    match config.store {
+        RateLimitStoreBackend::Memory => Arc::new(MemoryRateLimitStore::new()),
+        RateLimitStoreBackend::Postgres => Arc::new(PostgresRateLimitStore::new(pool)),
+    }
+}
+
+/// Refills a bucket holding `tokens` for the `elapsed` seconds since it was last used, then
+/// takes a request from it. Returns the tokens left and the decision.
+pub fn take(tokens: f64, elapsed: f64, rule: &RateLimitRule) -> (f64, RateLimitDecision) {
+    let capacity = f64::from(rule.capacity);
+    let mut tokens = (tokens + elapsed.max(0.0) * rule.refill_per_second).min(capacity);
+    let allowed = tokens >= 1.0;
+    if allowed {
+        tokens -= 1.0;
+    }
+
+    let seconds_until = |target: f64| {
+        if tokens >= target {
+            0
+        } else {
+            ((target - tokens) / rule.refill_per_second).ceil() as u64
+        }
+    };
+    let decision = RateLimitDecision {
+        allowed,
+        limit: rule.capacity,
+        remaining: tokens.floor() as u32,
+        retry_after: if allowed { 0 } else { seconds_until(1.0) },
+        reset: seconds_until(capacity),
+    };
+    (tokens, decision)
+}
+
+/// Kind of request, each limited by its own rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitCategory {
+    /// Requests to the token endpoint
+    Token,
+    /// Requests generating key material, like rotating an environment key
+    KeyGeneration,
+    /// Any other request to the admin API
+    Admin,
+}
+
+impl RateLimitCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitCategory::Token => "token",
+            RateLimitCategory::KeyGeneration => "key_generation",
+            RateLimitCategory::Admin => "admin",
+        }
+    }
+}
+
+/// Checks requests against the buckets of their client IP and client identity
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    store: Arc<dyn RateLimitStore>,
+    trusted_proxies: Vec<IpNet>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter keeping its buckets in memory
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            store: Arc::new(MemoryRateLimitStore::new()),
+            trusted_proxies: Vec::new(),
+        }
+    }
+
+    pub fn with_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    pub fn with_trusted_proxies(mut self, trusted_proxies: Vec<IpNet>) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+
+    /// Address of the client a request comes from, behind any trusted proxies
+    pub fn client_ip(&self, peer: Option<IpAddr>, forwarded_for: Option<&str>) -> Option<IpAddr> {
+        peer.map(|peer| network::client_ip(peer, forwarded_for, &self.trusted_proxies))
+    }
+
+    pub fn rule(&self, category: RateLimitCategory) -> &RateLimitRule {
+        match category {
+            RateLimitCategory::Token => &self.config.token,
+            RateLimitCategory::KeyGeneration => &self.config.key_generation,
+            RateLimitCategory::Admin => &self.config.admin,
+        }
+    }
+
+    /// Takes a request from the buckets of the IP and the client, allowing it when both
+    /// have one left. Once a bucket denies the request the later ones are left untouched.
+    /// The decision reported is the one of the bucket closest to its limit.
+    pub async fn check(
+        &self,
+        category: RateLimitCategory,
+        ip: Option<IpAddr>,
+        client: Option<&str>,
+    ) -> Result<RateLimitDecision> {
+        let rule = self.rule(category);
+        let mut keys = Vec::new();
+        if let Some(ip) = ip {
+            keys.push(format!("{}:ip:{}", category.as_str(), ip));
+        }
+        if let Some(client) = client {
+            keys.push(format!("{}:client:{}", category.as_str(), client));
+        }
+
+        let mut decision: Option<RateLimitDecision> = None;
+        for key in keys {
+            let current = self.store.acquire(&key, rule).await?;
+            let denied = !current.allowed;
+            decision = Some(match decision {
+                None => current,
+                Some(previous) => binding(previous, current),
+            });
+            // A denied request must not drain the buckets it was never going to pass
+            if denied {
+                break;
+            }
+        }
+        Ok(decision.unwrap_or(RateLimitDecision {
+            allowed: true,
+            limit: rule.capacity,
+            remaining: rule.capacity,
+            retry_after: 0,
+            reset: 0,
+        }))
+    }
+}
+
+/// The decision of two buckets a request has to pass
+fn binding(first: RateLimitDecision, second: RateLimitDecision) -> RateLimitDecision {
+    match (first.allowed, second.allowed) {
+        (true, false) => second,
+        (false, true) => first,
+        (false, false) if second.retry_after > first.retry_after => second,
+        (true, true) if second.remaining < first.remaining => second,
+        _ => first,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(capacity: u32, refill_per_second: f64) -> RateLimitRule {
+        RateLimitRule {
+            capacity,
+            refill_per_second,
+        }
+    }
+
+    #[test]
+    fn test_take_consumes_and_refills() {
+        let rule = rule(3, 0.5);
+
+        let (tokens, decision) = take(3.0, 0.0, &rule);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 2);
+        assert_eq!(decision.reset, 2);
+
+        let (tokens, _) = take(tokens, 0.0, &rule);
+        let (tokens, decision) = take(tokens, 0.0, &rule);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+
+        let (tokens, decision) = take(tokens, 0.0, &rule);
+        assert!(!decision.allowed);
+        assert_eq!(decision.retry_after, 2);
+        assert_eq!(decision.reset, 6);
+
+        // Two seconds refill one token
+        let (_, decision) = take(tokens, 2.0, &rule);
+        assert!(decision.allowed);
+
+        // Never above the capacity
+        let (tokens, decision) = take(0.0, 3600.0, &rule);
+        assert_eq!(tokens, 2.0);
+        assert_eq!(decision.remaining, 2);
+    }
+
+    #[test]
+    fn test_binding_decision() {
+        let allowed = |remaining| RateLimitDecision {
+            allowed: true,
+            limit: 10,
+            remaining,
+            retry_after: 0,
+            reset: 1,
+        };
+        let denied = |retry_after| RateLimitDecision {
+            allowed: false,
+            limit: 10,
+            remaining: 0,
+            retry_after,
+            reset: 10,
+        };
+
+        assert_eq!(binding(allowed(5), allowed(2)), allowed(2));
+        assert_eq!(binding(allowed(5), denied(3)), denied(3));
+        assert_eq!(binding(denied(3), allowed(5)), denied(3));
+        assert_eq!(binding(denied(3), denied(7)), denied(7));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_limits_ip_and_client() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            admin: rule(2, 0.001),
+            ..Default::default()
+        });
+        let ip: IpAddr = "192.0.2.10".parse().unwrap();
+        let other_ip: IpAddr = "192.0.2.11".parse().unwrap();
+        let third_ip: IpAddr = "192.0.2.12".parse().unwrap();
+
+        for _ in 0..2 {
+            let decision = limiter
+                .check(RateLimitCategory::Admin, Some(ip), Some("client-a"))
+                .await
+                .unwrap();
+            assert!(decision.allowed);
+        }
+        let decision = limiter
+            .check(RateLimitCategory::Admin, Some(ip), None)
+            .await
+            .unwrap();
+        assert!(!decision.allowed);
+        assert!(decision.retry_after > 0);
+
+        // The client is limited from any address
+        let decision = limiter
+            .check(RateLimitCategory::Admin, Some(other_ip), Some("client-a"))
+            .await
+            .unwrap();
+        assert!(!decision.allowed);
+
+        // A request denied by its address leaves the bucket of its client untouched
+        let decision = limiter
+            .check(RateLimitCategory::Admin, Some(ip), Some("client-b"))
+            .await
+            .unwrap();
+        assert!(!decision.allowed);
+        for _ in 0..2 {
+            let decision = limiter
+                .check(RateLimitCategory::Admin, Some(third_ip), Some("client-b"))
+                .await
+                .unwrap();
+            assert!(decision.allowed);
+        }
+
+        // Other categories have their own buckets
+        let decision = limiter
+            .check(RateLimitCategory::Token, Some(ip), Some("client-a"))
+            .await
+            .unwrap();
+        assert!(decision.allowed);
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+
+use crate::config::RateLimitRule;
+use crate::utils::rate_limit::{RateLimitDecision, RateLimitStore, take};
+
+/// Acquisitions between two deletions of full buckets
+const PRUNE_INTERVAL: u64 = 1_000;
+
+/// Keeps buckets in the `rate_limit_buckets` table, so every replica shares them.
+///
+/// A bucket row is locked while a request is taken from it, which serializes the
+/// replicas limiting the same client.
+pub struct PostgresRateLimitStore {
+    pool: Arc<PgPool>,
+    acquisitions: AtomicU64,
+}
+
+impl PostgresRateLimitStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self {
+            pool,
+            acquisitions: AtomicU64::new(0),
+        }
+    }
+
+    /// Deletes the buckets that refilled completely, as they behave like missing ones
+    pub async fn prune(&self) -> Result<u64> {
+        let result = sqlx::query!("DELETE FROM rate_limit_buckets WHERE full_at < now()")
+            .execute(&*self.pool)
+            .await
+            .map_err(<sqlx::Error as Into<Error>>::into)?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for PostgresRateLimitStore {
+    async fn acquire(&self, key: &str, rule: &RateLimitRule) -> Result<RateLimitDecision> {
+        if self.acquisitions.fetch_add(1, Ordering::Relaxed) % PRUNE_INTERVAL == PRUNE_INTERVAL - 1
+        {
+            self.prune().await?;
+        }
+
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query!(
+            "INSERT INTO rate_limit_buckets (key, tokens, updated_at, full_at) VALUES ($1, $2, now(), now()) ON CONFLICT (key) DO NOTHING",
+            key,
+            f64::from(rule.capacity),
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        // `now()` is the start of the transaction, so the refill and the update agree on it
+        let bucket = sqlx::query!(
+            r#"SELECT tokens, EXTRACT(EPOCH FROM now() - updated_at)::float8 AS "elapsed!" FROM rate_limit_buckets WHERE key = $1 FOR UPDATE"#,
+            key,
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        let (tokens, decision) = take(bucket.tokens, bucket.elapsed, rule);
+        sqlx::query!(
+            "UPDATE rate_limit_buckets SET tokens = $2, updated_at = now(), full_at = now() + make_interval(secs => $3) WHERE key = $1",
+            key,
+            tokens,
+            decision.reset as f64,
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(<sqlx::Error as Into<Error>>::into)?;
+
+        transaction.commit().await?;
+        Ok(decision)
+    }
+}
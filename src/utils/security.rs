@@ -1,14 +1,15 @@
 use anyhow::{Context, Error, Result};
 use base64::Engine;
-use base64::engine::general_purpose::STANDARD;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
 use rand::{RngCore, rngs::OsRng};
-use std::env;
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use crate::utils::metrics::METRICS;
 use crate::utils::tokens::hmac::{HmacHashFunction, HmacKey};
 
 /// SecretsManager provides encryption and decryption functionality
-/// using a combination of a master key (from .env) and a resource-specific ID.
+/// using a combination of a master key (`security.master_key`) and a resource-specific ID.
 ///
 /// This ensures that each resource's data is encrypted with a unique key derived
 /// from both the master key and the resource's ID.
@@ -18,16 +19,15 @@ pub struct SecretsManager {
 }
 
 impl SecretsManager {
-    /// Creates a new SecretsManager instance using the master key from .env
-    pub fn new(load_dotenv: bool) -> Result<Self, Error> {
-        if load_dotenv {
-            dotenvy::dotenv().ok();
+    /// Creates a new SecretsManager instance using the given master key, see
+    /// [`SecurityConfig::secrets_manager`](crate::config::SecurityConfig::secrets_manager)
+    pub fn new(master_key: &str) -> Result<Self, Error> {
+        if master_key.is_empty() {
+            return Err(Error::msg("Master key must not be empty"));
         }
-        let master_key = env::var("SENTINEL_GUARD_MASTER_KEY")
-            .context("SENTINEL_GUARD_MASTER_KEY not found in environment variables")?;
 
         Ok(Self {
-            master_key: master_key.into_bytes(),
+            master_key: master_key.as_bytes().to_vec(),
         })
     }
 
@@ -40,6 +40,12 @@ impl SecretsManager {
     /// # Returns
     /// Base64-encoded encrypted data
     pub fn encrypt(&self, text: &str, resource_id: &Uuid) -> Result<String, Error> {
+        let encrypted = self.encrypt_text(text, resource_id);
+        METRICS.record_secrets_operation("encrypt", encrypted.is_ok());
+        encrypted
+    }
+
+    fn encrypt_text(&self, text: &str, resource_id: &Uuid) -> Result<String, Error> {
         // Generate a random initialization vector (IV)
         let mut iv = [0u8; 16];
         OsRng.fill_bytes(&mut iv);
@@ -68,6 +74,19 @@ impl SecretsManager {
     /// # Returns
     /// The original decrypted text
     pub fn decrypt(&self, encrypted_text: &str, resource_id: &Uuid) -> Result<String, Error> {
+        let decrypted = self.decrypt_text(encrypted_text, resource_id);
+        METRICS.record_secrets_operation("decrypt", decrypted.is_ok());
+        decrypted
+    }
+
+    /// Whether a text has the shape of the output of `encrypt`, without decrypting it
+    pub fn is_encrypted(text: &str) -> bool {
+        STANDARD
+            .decode(text)
+            .is_ok_and(|encrypted_data| encrypted_data.len() > 16)
+    }
+
+    fn decrypt_text(&self, encrypted_text: &str, resource_id: &Uuid) -> Result<String, Error> {
         // Decode the Base64 input
         let encrypted_data = STANDARD
             .decode(encrypted_text)
@@ -145,76 +164,105 @@ impl SecretsManager {
     }
 }
 
+/// Compares two secrets in constant time.
+///
+/// Both values are hashed first so the comparison does not leak their lengths either.
+pub fn secrets_equal(left: &str, right: &str) -> bool {
+    openssl::memcmp::eq(
+        &Sha256::digest(left.as_bytes()),
+        &Sha256::digest(right.as_bytes()),
+    )
+}
+
+/// Generates a random, URL-safe token carrying 256 bits of entropy
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hashes an opaque token for storage; tokens are looked up by this hash
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use temp_env;
 
     #[test]
     fn test_encrypt_decrypt() {
-        temp_env::with_var(
-            "SENTINEL_GUARD_MASTER_KEY",
-            Some("test-master-key-12345"),
-            || {
-                // Create a SecretsManager
-                let secrets_manager = SecretsManager::new(false).unwrap();
-
-                // Test data
-                let original_text = "This is a secret message";
-                let resource_id = Uuid::new_v4();
-
-                // Encrypt
-                let encrypted = secrets_manager
-                    .encrypt(original_text, &resource_id)
-                    .unwrap();
-
-                // Decrypt
-                let decrypted = secrets_manager.decrypt(&encrypted, &resource_id).unwrap();
-
-                // Verify
-                assert_eq!(original_text, decrypted);
-            },
-        );
+        // Create a SecretsManager
+        let secrets_manager = SecretsManager::new("test-master-key-12345").unwrap();
+
+        // Test data
+        let original_text = "This is a secret message";
+        let resource_id = Uuid::new_v4();
+
+        // Encrypt
+        let encrypted = secrets_manager
+            .encrypt(original_text, &resource_id)
+            .unwrap();
+
+        // Decrypt
+        let decrypted = secrets_manager.decrypt(&encrypted, &resource_id).unwrap();
+
+        // Verify
+        assert_eq!(original_text, decrypted);
+        assert!(SecretsManager::is_encrypted(&encrypted));
+        assert!(!SecretsManager::is_encrypted(original_text));
     }
 
     #[test]
     fn test_different_resource_ids() {
-        temp_env::with_var(
-            "SENTINEL_GUARD_MASTER_KEY",
-            Some("test-master-key-12345"),
-            || {
-                // Create a SecretsManager
-                let secrets_manager = SecretsManager::new(false).unwrap();
-
-                // Test data
-                let original_text = "This is a secret message";
-                let resource_id_1 = Uuid::new_v4();
-                let resource_id_2 = Uuid::new_v4();
-
-                // Encrypt with first resource ID
-                let encrypted = secrets_manager
-                    .encrypt(original_text, &resource_id_1)
-                    .unwrap();
-
-                // Try to decrypt with second resource ID (should fail or produce different output)
-                let result = secrets_manager.decrypt(&encrypted, &resource_id_2);
-
-                if let Ok(decrypted) = result {
-                    // If decryption succeeded, the result should be different
-                    assert_ne!(original_text, decrypted);
-                } else {
-                    // Or decryption might fail, which is also acceptable
-                    assert!(result.is_err());
-                }
-            },
-        );
+        // Create a SecretsManager
+        let secrets_manager = SecretsManager::new("test-master-key-12345").unwrap();
+
+        // Test data
+        let original_text = "This is a secret message";
+        let resource_id_1 = Uuid::new_v4();
+        let resource_id_2 = Uuid::new_v4();
+
+        // Encrypt with first resource ID
+        let encrypted = secrets_manager
+            .encrypt(original_text, &resource_id_1)
+            .unwrap();
+
+        // Try to decrypt with second resource ID (should fail or produce different output)
+        let result = secrets_manager.decrypt(&encrypted, &resource_id_2);
+
+        if let Ok(decrypted) = result {
+            // If decryption succeeded, the result should be different
+            assert_ne!(original_text, decrypted);
+        } else {
+            // Or decryption might fail, which is also acceptable
+            assert!(result.is_err());
+        }
     }
 
     #[test]
-    fn test_missing_env_var() {
-        temp_env::with_var_unset("SENTINEL_GUARD_MASTER_KEY", || {
-            let result = SecretsManager::new(false);
-            assert!(result.is_err());
-        });
+    fn test_secrets_equal() {
+        assert!(secrets_equal("secret", "secret"));
+        assert!(!secrets_equal("secret", "secret2"));
+        assert!(!secrets_equal("secret", ""));
+    }
+
+    #[test]
+    fn test_generate_opaque_token_is_unique_and_hashable() {
+        let first = generate_opaque_token();
+        let second = generate_opaque_token();
+        assert_ne!(first, second);
+        assert_eq!(first.len(), 43);
+        assert_eq!(hash_token(&first), hash_token(&first));
+        assert_ne!(hash_token(&first), hash_token(&second));
+    }
+
+    #[test]
+    fn test_empty_master_key() {
+        let result = SecretsManager::new("");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Master key must not be empty"
+        );
     }
 }
@@ -2,8 +2,11 @@ use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::routes::{
-    environment_route, project_access_route, project_access_scopes_route, project_route,
-    project_scope_route, service_account_route,
+    api_key_route, audit_event_route, authentication_lockout_route, claim_template_route,
+    environment_key_route, environment_route, health_route, metrics_route, project_access_route,
+    project_access_scopes_route, project_route, project_scope_route, revocation_list_route,
+    service_account_credential_route, service_account_key_route, service_account_route,
+    token_policy_route, token_route,
 };
 
 #[derive(OpenApi)]
@@ -29,6 +32,13 @@ use crate::routes::{
         environment_route::patch,
         environment_route::delete,
         environment_route::list,
+        environment_key_route::post,
+        environment_key_route::import_key,
+        environment_key_route::rotate_key,
+        environment_key_route::get,
+        environment_key_route::patch,
+        environment_key_route::delete,
+        environment_key_route::list,
         project_access_route::post,
         project_access_route::get,
         project_access_route::patch,
@@ -39,6 +49,42 @@ use crate::routes::{
         project_access_scopes_route::patch,
         project_access_scopes_route::delete,
         project_access_scopes_route::list,
+        revocation_list_route::get,
+        token_policy_route::post,
+        token_policy_route::get,
+        token_policy_route::effective,
+        token_policy_route::patch,
+        token_policy_route::delete,
+        token_policy_route::list,
+        claim_template_route::post,
+        claim_template_route::get,
+        claim_template_route::patch,
+        claim_template_route::delete,
+        claim_template_route::list,
+        service_account_key_route::post,
+        service_account_key_route::get,
+        service_account_key_route::patch,
+        service_account_key_route::delete,
+        service_account_key_route::list,
+        service_account_credential_route::post,
+        service_account_credential_route::get,
+        service_account_credential_route::patch,
+        service_account_credential_route::delete,
+        service_account_credential_route::rotate,
+        service_account_credential_route::list,
+        api_key_route::post,
+        api_key_route::get,
+        api_key_route::patch,
+        api_key_route::delete,
+        api_key_route::list,
+        api_key_route::verify,
+        authentication_lockout_route::list,
+        authentication_lockout_route::unlock,
+        audit_event_route::list,
+        token_route::post,
+        health_route::healthz,
+        health_route::readyz,
+        metrics_route::get,
     ),
     tags(
         (name = "SentinelGuard", description = "SentinelGuard API documentation.")
@@ -0,0 +1,639 @@
+//! HTTPS termination with rustls, with optional client certificate verification.
+//!
+//! The certificate, its key and the client CA bundle are held behind locks, so they can be
+//! reloaded without restarting the server: new connections use the new files while
+//! established ones keep the material they were negotiated with.
+
+use std::any::Any;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use actix_tls::accept::rustls_0_23::TlsStream;
+use actix_web::dev::Extensions;
+use actix_web::rt::net::TcpStream;
+use anyhow::Error;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use rustls::client::danger::HandshakeSignatureValid;
+use rustls::crypto::{CryptoProvider, ring};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::server::{ClientHello, NoClientAuth, ResolvesServerCert, WebPkiClientVerifier};
+use rustls::sign::CertifiedKey;
+use rustls::{DigitallySignedStruct, DistinguishedName, RootCertStore, SignatureScheme};
+use sha2::{Digest, Sha256};
+use tokio::task::JoinHandle;
+
+use crate::config::{ClientAuthMode, TlsConfig};
+
+/// Certificate chain a client presented and the CA bundle verified, available to handlers
+/// through `HttpRequest::conn_data::<ClientCertificate>()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertificate {
+    /// End-entity certificate first, followed by the intermediates the client sent
+    pub chain: Vec<CertificateDer<'static>>,
+}
+
+impl ClientCertificate {
+    /// DER encoding of the end-entity certificate
+    pub fn der(&self) -> &[u8] {
+        &self.chain[0]
+    }
+
+    /// Base64url SHA-256 thumbprint of the certificate, the `x5t#S256` confirmation of
+    /// certificate-bound access tokens (RFC 8705)
+    pub fn thumbprint_sha256(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.der()))
+    }
+}
+
+/// Stores the verified client certificate of a connection in its extensions.
+///
+/// Register with `HttpServer::on_connect(capture_client_certificate)`.
+pub fn capture_client_certificate(connection: &dyn Any, extensions: &mut Extensions) {
+    let Some(stream) = connection.downcast_ref::<TlsStream<TcpStream>>() else {
+        return;
+    };
+    let (_, session) = stream.get_ref();
+    if let Some(chain) = session.peer_certificates()
+        && !chain.is_empty()
+    {
+        extensions.insert(ClientCertificate {
+            chain: chain.to_vec(),
+        });
+    }
+}
+
+/// TLS material of the server, reloadable from the files of its configuration
+#[derive(Clone)]
+pub struct TlsReloader {
+    config: TlsConfig,
+    certificate: Arc<ReloadableCertificate>,
+    client_verifier: Arc<ReloadableClientVerifier>,
+}
+
+impl TlsReloader {
+    /// Loads the files of the configuration, failing when any is missing or invalid
+    pub fn load(config: &TlsConfig) -> Result<Self, Error> {
+        let certificate = load_certificate(config)?;
+        let client_verifier = load_client_verifier(config)?;
+        Ok(Self {
+            config: config.clone(),
+            certificate: Arc::new(ReloadableCertificate {
+                current: RwLock::new(certificate),
+            }),
+            client_verifier: Arc::new(ReloadableClientVerifier {
+                mode: config.client_auth,
+                current: RwLock::new(client_verifier),
+            }),
+        })
+    }
+
+    /// rustls configuration serving the current certificate, to pass to `bind_rustls_0_23`
+    pub fn server_config(&self) -> Result<rustls::ServerConfig, Error> {
+        let mut server_config = rustls::ServerConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()?
+            .with_client_cert_verifier(self.client_verifier.clone())
+            .with_cert_resolver(self.certificate.clone());
+        server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(server_config)
+    }
+
+    /// Reads the files again, keeping the current material when any fails to load
+    pub fn reload(&self) -> Result<(), Error> {
+        let certificate = load_certificate(&self.config)?;
+        let client_verifier = load_client_verifier(&self.config)?;
+        *self.certificate.current.write().unwrap() = certificate;
+        *self.client_verifier.current.write().unwrap() = client_verifier;
+        Ok(())
+    }
+
+    /// Certificate currently served to new connections
+    pub fn current_certificate(&self) -> Arc<CertifiedKey> {
+        self.certificate.current.read().unwrap().clone()
+    }
+
+    /// Starts a task reloading the files on SIGHUP and, unless the interval is 0, whenever
+    /// their modification time changes
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let interval_seconds = self.config.reload_interval_seconds;
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds.max(1)));
+            let mut modified = self.modification_times();
+            let mut hangup = hangup_signal();
+
+            loop {
+                let reason = tokio::select! {
+                    _ = interval.tick(), if interval_seconds > 0 => {
+                        let current = self.modification_times();
+                        if current == modified {
+                            continue;
+                        }
+                        modified = current;
+                        "file change"
+                    }
+                    _ = hangup.recv() => "SIGHUP",
+                };
+
+                match self.reload() {
+                    Ok(()) => tracing::info!(reason, "Reloaded TLS certificates"),
+                    Err(error) => tracing::error!(
+                        reason,
+                        error = %error,
+                        "Failed to reload TLS certificates, keeping the current ones"
+                    ),
+                }
+            }
+        })
+    }
+
+    fn modification_times(&self) -> Vec<Option<SystemTime>> {
+        [
+            &self.config.cert_path,
+            &self.config.key_path,
+            &self.config.client_ca_path,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+    }
+}
+
+/// Receives SIGHUP on Unix, never resolves elsewhere
+struct HangupSignal {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl HangupSignal {
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            signal.recv().await;
+            return;
+        }
+        std::future::pending::<()>().await
+    }
+}
+
+fn hangup_signal() -> HangupSignal {
+    #[cfg(unix)]
+    {
+        let signal = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok();
+        if signal.is_none() {
+            tracing::warn!(
+                "Failed to install SIGHUP handler, TLS certificates reload on change only"
+            );
+        }
+        HangupSignal { signal }
+    }
+    #[cfg(not(unix))]
+    {
+        HangupSignal {}
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn required_path<'a>(path: &'a Option<PathBuf>, key: &str) -> Result<&'a Path, Error> {
+    path.as_deref()
+        .ok_or_else(|| Error::msg(format!("{} is not set", key)))
+}
+
+fn load_certificate(config: &TlsConfig) -> Result<Arc<CertifiedKey>, Error> {
+    let cert_path = required_path(&config.cert_path, "server.tls.cert_path")?;
+    let key_path = required_path(&config.key_path, "server.tls.key_path")?;
+
+    let chain = load_certificates(cert_path)?;
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(|e| {
+        Error::msg(format!(
+            "Failed to read private key {}: {}",
+            key_path.display(),
+            e
+        ))
+    })?;
+    let certified_key = CertifiedKey::from_der(chain, key, &provider()).map_err(|e| {
+        Error::msg(format!(
+            "Private key {} does not match certificate {}: {}",
+            key_path.display(),
+            cert_path.display(),
+            e
+        ))
+    })?;
+    Ok(Arc::new(certified_key))
+}
+
+fn load_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, Error> {
+    let certificates = CertificateDer::pem_file_iter(path)
+        .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| {
+            Error::msg(format!(
+                "Failed to read certificates {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+    if certificates.is_empty() {
+        return Err(Error::msg(format!(
+            "No certificate found in {}",
+            path.display()
+        )));
+    }
+    Ok(certificates)
+}
+
+fn load_client_verifier(config: &TlsConfig) -> Result<Arc<dyn ClientCertVerifier>, Error> {
+    if config.client_auth == ClientAuthMode::None {
+        return Ok(Arc::new(NoClientAuth));
+    }
+
+    let ca_path = required_path(&config.client_ca_path, "server.tls.client_ca_path")?;
+    let mut roots = RootCertStore::empty();
+    for certificate in load_certificates(ca_path)? {
+        roots.add(certificate).map_err(|e| {
+            Error::msg(format!("Invalid client CA in {}: {}", ca_path.display(), e))
+        })?;
+    }
+
+    let builder = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider());
+    let builder = match config.client_auth {
+        ClientAuthMode::Optional => builder.allow_unauthenticated(),
+        _ => builder,
+    };
+    Ok(builder.build()?)
+}
+
+/// Serves whichever certificate was loaded last
+#[derive(Debug)]
+struct ReloadableCertificate {
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ResolvesServerCert for ReloadableCertificate {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().clone())
+    }
+}
+
+/// Verifies client certificates against whichever CA bundle was loaded last
+struct ReloadableClientVerifier {
+    mode: ClientAuthMode,
+    current: RwLock<Arc<dyn ClientCertVerifier>>,
+}
+
+impl ReloadableClientVerifier {
+    fn current(&self) -> Arc<dyn ClientCertVerifier> {
+        self.current.read().unwrap().clone()
+    }
+}
+
+impl fmt::Debug for ReloadableClientVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReloadableClientVerifier")
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+impl ClientCertVerifier for ReloadableClientVerifier {
+    fn offer_client_auth(&self) -> bool {
+        self.mode != ClientAuthMode::None
+    }
+
+    fn client_auth_mandatory(&self) -> bool {
+        self.mode == ClientAuthMode::Required
+    }
+
+    // The subjects cannot be borrowed from behind the lock. Without hints, clients send
+    // any certificate they have, which the CA bundle then accepts or rejects.
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        self.current()
+            .verify_client_cert(end_entity, intermediates, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.current().verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.current().verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.current().supported_verify_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::BigNum;
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::hash::MessageDigest;
+    use openssl::nid::Nid;
+    use openssl::pkey::{PKey, Private};
+    use openssl::x509::extension::{BasicConstraints, SubjectAlternativeName};
+    use openssl::x509::{X509, X509NameBuilder};
+    use rustls::{ClientConfig, ClientConnection, ServerConnection};
+    use uuid::Uuid;
+
+    struct Issued {
+        certificate: X509,
+        key: PKey<Private>,
+    }
+
+    impl Issued {
+        fn new(common_name: &str, issuer: Option<&Issued>, is_ca: bool) -> Self {
+            let key = PKey::from_ec_key(
+                EcKey::generate(&EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap()).unwrap(),
+            )
+            .unwrap();
+            let mut name = X509NameBuilder::new().unwrap();
+            name.append_entry_by_nid(Nid::COMMONNAME, common_name)
+                .unwrap();
+            let name = name.build();
+
+            let mut builder = X509::builder().unwrap();
+            builder.set_version(2).unwrap();
+            let serial = BigNum::from_u32(rand::random::<u32>()).unwrap();
+            builder
+                .set_serial_number(&serial.to_asn1_integer().unwrap())
+                .unwrap();
+            builder.set_subject_name(&name).unwrap();
+            builder
+                .set_issuer_name(issuer.map_or(&name, |issuer| issuer.certificate.subject_name()))
+                .unwrap();
+            builder.set_pubkey(&key).unwrap();
+            builder
+                .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+                .unwrap();
+            builder
+                .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+                .unwrap();
+            if is_ca {
+                builder
+                    .append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+                    .unwrap();
+            } else {
+                let san = SubjectAlternativeName::new()
+                    .dns(common_name)
+                    .build(
+                        &builder
+                            .x509v3_context(issuer.map(|issuer| issuer.certificate.as_ref()), None),
+                    )
+                    .unwrap();
+                builder.append_extension(san).unwrap();
+            }
+            builder
+                .sign(
+                    issuer.map_or(&key, |issuer| &issuer.key),
+                    MessageDigest::sha256(),
+                )
+                .unwrap();
+
+            Self {
+                certificate: builder.build(),
+                key,
+            }
+        }
+
+        fn der(&self) -> CertificateDer<'static> {
+            CertificateDer::from(self.certificate.to_der().unwrap())
+        }
+
+        fn key_der(&self) -> PrivateKeyDer<'static> {
+            PrivateKeyDer::try_from(self.key.private_key_to_pkcs8().unwrap()).unwrap()
+        }
+    }
+
+    /// CA, server certificate and client CA written to a temporary directory
+    struct Files {
+        directory: PathBuf,
+        server_ca: Issued,
+        client_ca: Issued,
+    }
+
+    impl Files {
+        fn new() -> Self {
+            let directory =
+                std::env::temp_dir().join(format!("sentinel-guard-tls-{}", Uuid::new_v4()));
+            fs::create_dir_all(&directory).unwrap();
+            let files = Self {
+                directory,
+                server_ca: Issued::new("Server CA", None, true),
+                client_ca: Issued::new("Client CA", None, true),
+            };
+            files.issue_server_certificate();
+            fs::write(
+                files.path("client-ca.crt"),
+                files.client_ca.certificate.to_pem().unwrap(),
+            )
+            .unwrap();
+            files
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.directory.join(name)
+        }
+
+        fn issue_server_certificate(&self) -> Issued {
+            let server = Issued::new("localhost", Some(&self.server_ca), false);
+            fs::write(self.path("tls.crt"), server.certificate.to_pem().unwrap()).unwrap();
+            fs::write(
+                self.path("tls.key"),
+                server.key.private_key_to_pem_pkcs8().unwrap(),
+            )
+            .unwrap();
+            server
+        }
+
+        fn config(&self, client_auth: ClientAuthMode) -> TlsConfig {
+            TlsConfig {
+                cert_path: Some(self.path("tls.crt")),
+                key_path: Some(self.path("tls.key")),
+                client_ca_path: (client_auth != ClientAuthMode::None)
+                    .then(|| self.path("client-ca.crt")),
+                client_auth,
+                reload_interval_seconds: 0,
+            }
+        }
+
+        fn client(&self, identity: Option<&Issued>) -> ClientConnection {
+            let mut roots = RootCertStore::empty();
+            roots.add(self.server_ca.der()).unwrap();
+            let builder = ClientConfig::builder_with_provider(provider())
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_root_certificates(roots);
+            let config = match identity {
+                Some(identity) => builder
+                    .with_client_auth_cert(vec![identity.der()], identity.key_der())
+                    .unwrap(),
+                None => builder.with_no_client_auth(),
+            };
+            ClientConnection::new(Arc::new(config), "localhost".try_into().unwrap()).unwrap()
+        }
+    }
+
+    impl Drop for Files {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.directory);
+        }
+    }
+
+    /// Exchanges handshake messages in memory until both sides are done or one fails
+    fn handshake(
+        client: &mut ClientConnection,
+        server: &mut ServerConnection,
+    ) -> Result<(), rustls::Error> {
+        for _ in 0..10 {
+            let mut buffer = Vec::new();
+            while client.wants_write() {
+                client.write_tls(&mut buffer).unwrap();
+            }
+            if !buffer.is_empty() {
+                server.read_tls(&mut buffer.as_slice()).unwrap();
+                server.process_new_packets()?;
+            }
+
+            let mut buffer = Vec::new();
+            while server.wants_write() {
+                server.write_tls(&mut buffer).unwrap();
+            }
+            if !buffer.is_empty() {
+                client.read_tls(&mut buffer.as_slice()).unwrap();
+                client.process_new_packets()?;
+            }
+
+            if !client.is_handshaking() && !server.is_handshaking() {
+                return Ok(());
+            }
+        }
+        panic!("Handshake did not complete");
+    }
+
+    fn accept(reloader: &TlsReloader) -> ServerConnection {
+        ServerConnection::new(Arc::new(reloader.server_config().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn test_handshake_without_client_auth() {
+        let files = Files::new();
+        let reloader = TlsReloader::load(&files.config(ClientAuthMode::None)).unwrap();
+
+        let mut server = accept(&reloader);
+        handshake(&mut files.client(None), &mut server).unwrap();
+        assert!(server.peer_certificates().is_none());
+    }
+
+    #[test]
+    fn test_handshake_with_required_client_certificate() {
+        let files = Files::new();
+        let reloader = TlsReloader::load(&files.config(ClientAuthMode::Required)).unwrap();
+
+        let client_certificate = Issued::new("client", Some(&files.client_ca), false);
+        let mut server = accept(&reloader);
+        handshake(&mut files.client(Some(&client_certificate)), &mut server).unwrap();
+        let certificate = ClientCertificate {
+            chain: server.peer_certificates().unwrap().to_vec(),
+        };
+        assert_eq!(certificate.der(), client_certificate.der().as_ref());
+        assert_eq!(
+            certificate.thumbprint_sha256(),
+            URL_SAFE_NO_PAD.encode(Sha256::digest(client_certificate.der()))
+        );
+
+        assert!(handshake(&mut files.client(None), &mut accept(&reloader)).is_err());
+
+        let other_ca = Issued::new("Other CA", None, true);
+        let stranger = Issued::new("client", Some(&other_ca), false);
+        assert!(handshake(&mut files.client(Some(&stranger)), &mut accept(&reloader)).is_err());
+    }
+
+    #[test]
+    fn test_handshake_with_optional_client_certificate() {
+        let files = Files::new();
+        let reloader = TlsReloader::load(&files.config(ClientAuthMode::Optional)).unwrap();
+
+        let mut server = accept(&reloader);
+        handshake(&mut files.client(None), &mut server).unwrap();
+        assert!(server.peer_certificates().is_none());
+
+        let client_certificate = Issued::new("client", Some(&files.client_ca), false);
+        let mut server = accept(&reloader);
+        handshake(&mut files.client(Some(&client_certificate)), &mut server).unwrap();
+        assert!(server.peer_certificates().is_some());
+    }
+
+    #[test]
+    fn test_reload_replaces_certificate() {
+        let files = Files::new();
+        let reloader = TlsReloader::load(&files.config(ClientAuthMode::None)).unwrap();
+        let before = reloader.current_certificate().cert[0].clone();
+
+        // Connections opened after the reload are served the new certificate
+        let renewed = files.issue_server_certificate();
+        reloader.reload().unwrap();
+        assert_eq!(reloader.current_certificate().cert[0], renewed.der());
+        assert_ne!(reloader.current_certificate().cert[0], before);
+        let mut server = accept(&reloader);
+        let mut client = files.client(None);
+        handshake(&mut client, &mut server).unwrap();
+        assert_eq!(client.peer_certificates().unwrap()[0], renewed.der());
+
+        // A broken file keeps the current certificate
+        fs::write(files.path("tls.key"), "not a key").unwrap();
+        assert!(reloader.reload().is_err());
+        assert_eq!(reloader.current_certificate().cert[0], renewed.der());
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_files() {
+        let files = Files::new();
+        let mut config = files.config(ClientAuthMode::None);
+        config.key_path = Some(files.path("missing.key"));
+        assert!(TlsReloader::load(&config).is_err());
+
+        // Key of another certificate
+        let other = Issued::new("localhost", Some(&files.server_ca), false);
+        fs::write(
+            files.path("other.key"),
+            other.key.private_key_to_pem_pkcs8().unwrap(),
+        )
+        .unwrap();
+        config.key_path = Some(files.path("other.key"));
+        let error = TlsReloader::load(&config).err().unwrap();
+        assert!(error.to_string().contains("does not match"), "{}", error);
+    }
+}
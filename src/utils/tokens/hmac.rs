@@ -1,12 +1,14 @@
 use hmac::{Hmac, Mac};
 use rand::{RngCore, thread_rng};
-use sha2::{Sha256, Sha512};
-use sha3::{Sha3_256, Sha3_512};
+use sha2::{Sha256, Sha384, Sha512};
+use sha3::{Sha3_256, Sha3_384, Sha3_512};
 
 // Type aliases for HMAC implementations
 type HmacSha256 = Hmac<Sha256>;
+type HmacSha384 = Hmac<Sha384>;
 type HmacSha512 = Hmac<Sha512>;
 type HmacSha3_256 = Hmac<Sha3_256>;
+type HmacSha3_384 = Hmac<Sha3_384>;
 type HmacSha3_512 = Hmac<Sha3_512>;
 
 // Helper macro to create HMAC instances with fully qualified syntax
@@ -25,8 +27,10 @@ pub struct HmacKey {
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum HmacHashFunction {
     Sha256,
+    Sha384,
     Sha512,
     Sha3_256,
+    Sha3_384,
     Sha3_512,
 }
 
@@ -59,8 +63,10 @@ impl HmacHashFunction {
     pub fn name(&self) -> &'static str {
         match self {
             HmacHashFunction::Sha256 => "SHA256",
+            HmacHashFunction::Sha384 => "SHA384",
             HmacHashFunction::Sha512 => "SHA512",
             HmacHashFunction::Sha3_256 => "SHA3_256",
+            HmacHashFunction::Sha3_384 => "SHA3_384",
             HmacHashFunction::Sha3_512 => "SHA3_512",
         }
     }
@@ -68,8 +74,10 @@ impl HmacHashFunction {
     pub fn recommended_by_length(&self) -> HmacKeyLength {
         match self {
             HmacHashFunction::Sha256 => HmacKeyLength::B256,
+            HmacHashFunction::Sha384 => HmacKeyLength::B384,
             HmacHashFunction::Sha512 => HmacKeyLength::B512,
             HmacHashFunction::Sha3_256 => HmacKeyLength::B256,
+            HmacHashFunction::Sha3_384 => HmacKeyLength::B384,
             HmacHashFunction::Sha3_512 => HmacKeyLength::B512,
         }
     }
@@ -77,8 +85,10 @@ impl HmacHashFunction {
     pub fn output_size_bytes(&self) -> usize {
         match self {
             HmacHashFunction::Sha256 => 32,
+            HmacHashFunction::Sha384 => 48,
             HmacHashFunction::Sha512 => 64,
             HmacHashFunction::Sha3_256 => 32,
+            HmacHashFunction::Sha3_384 => 48,
             HmacHashFunction::Sha3_512 => 64,
         }
     }
@@ -90,6 +100,11 @@ impl HmacHashFunction {
                 Mac::update(&mut mac, data);
                 Ok(mac.finalize().into_bytes().to_vec())
             }
+            HmacHashFunction::Sha384 => {
+                let mut mac = create_hmac!(HmacSha384, key);
+                Mac::update(&mut mac, data);
+                Ok(mac.finalize().into_bytes().to_vec())
+            }
             HmacHashFunction::Sha512 => {
                 let mut mac = create_hmac!(HmacSha512, key);
                 Mac::update(&mut mac, data);
@@ -100,6 +115,11 @@ impl HmacHashFunction {
                 Mac::update(&mut mac, data);
                 Ok(mac.finalize().into_bytes().to_vec())
             }
+            HmacHashFunction::Sha3_384 => {
+                let mut mac = create_hmac!(HmacSha3_384, key);
+                Mac::update(&mut mac, data);
+                Ok(mac.finalize().into_bytes().to_vec())
+            }
             HmacHashFunction::Sha3_512 => {
                 let mut mac = create_hmac!(HmacSha3_512, key);
                 Mac::update(&mut mac, data);
@@ -153,8 +173,10 @@ mod tests {
     #[test]
     fn test_hash_function_name() {
         assert_eq!(HmacHashFunction::Sha256.name(), "SHA256");
+        assert_eq!(HmacHashFunction::Sha384.name(), "SHA384");
         assert_eq!(HmacHashFunction::Sha512.name(), "SHA512");
         assert_eq!(HmacHashFunction::Sha3_256.name(), "SHA3_256");
+        assert_eq!(HmacHashFunction::Sha3_384.name(), "SHA3_384");
         assert_eq!(HmacHashFunction::Sha3_512.name(), "SHA3_512");
     }
 
@@ -164,6 +186,10 @@ mod tests {
             HmacHashFunction::Sha256.recommended_by_length(),
             HmacKeyLength::B256
         );
+        assert_eq!(
+            HmacHashFunction::Sha384.recommended_by_length(),
+            HmacKeyLength::B384
+        );
         assert_eq!(
             HmacHashFunction::Sha512.recommended_by_length(),
             HmacKeyLength::B512
@@ -172,6 +198,10 @@ mod tests {
             HmacHashFunction::Sha3_256.recommended_by_length(),
             HmacKeyLength::B256
         );
+        assert_eq!(
+            HmacHashFunction::Sha3_384.recommended_by_length(),
+            HmacKeyLength::B384
+        );
         assert_eq!(
             HmacHashFunction::Sha3_512.recommended_by_length(),
             HmacKeyLength::B512
@@ -181,8 +211,10 @@ mod tests {
     #[test]
     fn test_hash_function_output_size() {
         assert_eq!(HmacHashFunction::Sha256.output_size_bytes(), 32);
+        assert_eq!(HmacHashFunction::Sha384.output_size_bytes(), 48);
         assert_eq!(HmacHashFunction::Sha512.output_size_bytes(), 64);
         assert_eq!(HmacHashFunction::Sha3_256.output_size_bytes(), 32);
+        assert_eq!(HmacHashFunction::Sha3_384.output_size_bytes(), 48);
         assert_eq!(HmacHashFunction::Sha3_512.output_size_bytes(), 64);
     }
 
@@ -212,6 +244,11 @@ mod tests {
                 b"The quick brown fox jumps over the lazy dog".as_slice(),
                 HmacHashFunction::Sha256,
             ),
+            (
+                b"key".as_slice(),
+                b"The quick brown fox jumps over the lazy dog".as_slice(),
+                HmacHashFunction::Sha384,
+            ),
             (
                 b"key".as_slice(),
                 b"The quick brown fox jumps over the lazy dog".as_slice(),
@@ -222,6 +259,11 @@ mod tests {
                 b"The quick brown fox jumps over the lazy dog".as_slice(),
                 HmacHashFunction::Sha3_256,
             ),
+            (
+                b"key".as_slice(),
+                b"The quick brown fox jumps over the lazy dog".as_slice(),
+                HmacHashFunction::Sha3_384,
+            ),
             (
                 b"key".as_slice(),
                 b"The quick brown fox jumps over the lazy dog".as_slice(),
@@ -255,13 +297,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_hmac_sha384_known_answer() {
+        // RFC 4231 test case 2
+        let signature = HmacHashFunction::Sha384
+            .sign(b"Jefe", b"what do ya want for nothing?")
+            .expect("Signing failed");
+        assert_eq!(
+            hex::encode(signature),
+            "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649"
+        );
+    }
+
     #[test]
     fn test_hmac_with_empty_key() {
         let empty_key = vec![];
         let hash_funcs = [
             HmacHashFunction::Sha256,
+            HmacHashFunction::Sha384,
             HmacHashFunction::Sha512,
             HmacHashFunction::Sha3_256,
+            HmacHashFunction::Sha3_384,
             HmacHashFunction::Sha3_512,
         ];
 
@@ -282,8 +338,10 @@ mod tests {
         let empty_message = b"";
         let hash_funcs = [
             HmacHashFunction::Sha256,
+            HmacHashFunction::Sha384,
             HmacHashFunction::Sha512,
             HmacHashFunction::Sha3_256,
+            HmacHashFunction::Sha3_384,
             HmacHashFunction::Sha3_512,
         ];
 
@@ -303,8 +361,10 @@ mod tests {
     fn test_generate_hmac_key() {
         let hash_funcs = [
             HmacHashFunction::Sha256,
+            HmacHashFunction::Sha384,
             HmacHashFunction::Sha512,
             HmacHashFunction::Sha3_256,
+            HmacHashFunction::Sha3_384,
             HmacHashFunction::Sha3_512,
         ];
 
@@ -2,8 +2,11 @@
 //! for various JWT algorithms.
 
 use anyhow::{Context, Error, Result};
-use jsonwebtoken::{Algorithm, EncodingKey, Header};
-use openssl::pkey::PKey;
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header};
+use openssl::nid::Nid;
+use openssl::pkey::{Id, PKey};
 use std::collections::HashMap;
 use std::str;
 
@@ -13,6 +16,56 @@ use crate::utils::tokens::{
 };
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+/// Encoding of imported key material
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyEncoding {
+    /// PEM, for RSA private keys
+    Pem,
+    /// Hexadecimal, for HMAC secrets
+    Hex,
+    /// Standard base64 with padding, for HMAC secrets
+    Base64,
+    /// URL safe base64 without padding, for HMAC secrets
+    Base64Url,
+}
+
+/// Decodes an HMAC secret, trying every encoding when none is given and refusing secrets
+/// that decode to different bytes in several of them
+fn decode_secret(key: &str, encoding: Option<KeyEncoding>) -> Result<Vec<u8>> {
+    match encoding {
+        Some(KeyEncoding::Hex) => {
+            hex::decode(key).map_err(|_| Error::msg("HMAC secret is not valid hex"))
+        }
+        Some(KeyEncoding::Base64) => STANDARD
+            .decode(key)
+            .map_err(|_| Error::msg("HMAC secret is not valid base64")),
+        Some(KeyEncoding::Base64Url) => URL_SAFE_NO_PAD
+            .decode(key)
+            .map_err(|_| Error::msg("HMAC secret is not valid unpadded base64url")),
+        Some(KeyEncoding::Pem) => Err(Error::msg("HMAC secrets cannot be PEM encoded")),
+        None => {
+            let mut decoded: Vec<Vec<u8>> = [
+                hex::decode(key).ok(),
+                STANDARD.decode(key).ok(),
+                URL_SAFE_NO_PAD.decode(key).ok(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            decoded.dedup();
+            match decoded.len() {
+                0 => Err(Error::msg("HMAC secret must be encoded as hex or base64")),
+                1 => Ok(decoded.remove(0)),
+                _ => Err(Error::msg(
+                    "HMAC secret is ambiguous, set its encoding to hex, base64 or base64url",
+                )),
+            }
+        }
+    }
+}
 
 /// Standard JWT Claims as defined in RFC 7519
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,6 +94,29 @@ pub struct Claims {
     /// Additional metadata as key-value pairs
     #[serde(skip_serializing_if = "Option::is_none")]
     pub meta: Option<HashMap<String, String>>,
+    /// Actor acting on behalf of the subject (RFC 8693 section 4.1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+}
+
+/// Actor (act) claim, nesting the previous actors of a delegation chain
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ActorClaim {
+    /// Subject of the actor
+    pub sub: String,
+    /// Actor that acted before this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub act: Option<Box<ActorClaim>>,
+}
+
+impl ActorClaim {
+    /// Creates an actor claim on top of the previous actors of the chain
+    pub fn new(sub: impl Into<String>, previous: Option<ActorClaim>) -> Self {
+        Self {
+            sub: sub.into(),
+            act: previous.map(Box::new),
+        }
+    }
 }
 
 impl Claims {
@@ -57,6 +133,7 @@ impl Claims {
             jti: None,
             scopes: None,
             meta: None,
+            act: None,
         }
     }
 
@@ -105,6 +182,39 @@ impl Claims {
         }
         self
     }
+
+    /// Sets the actor (act) claim of a delegated token
+    pub fn with_actor(mut self, actor: ActorClaim) -> Self {
+        self.act = Some(actor);
+        self
+    }
+}
+
+/// Type of a public key registered to verify signatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublicKeyType {
+    Rsa,
+    EcP256,
+    EcP384,
+}
+
+impl PublicKeyType {
+    /// Whether signatures made with the algorithm can be verified with this type of key
+    pub fn supports(&self, algorithm: Algorithm) -> bool {
+        matches!(
+            (self, algorithm),
+            (
+                PublicKeyType::Rsa,
+                Algorithm::RS256
+                    | Algorithm::RS384
+                    | Algorithm::RS512
+                    | Algorithm::PS256
+                    | Algorithm::PS384
+                    | Algorithm::PS512
+            ) | (PublicKeyType::EcP256, Algorithm::ES256)
+                | (PublicKeyType::EcP384, Algorithm::ES384)
+        )
+    }
 }
 
 /// KeyBuilder is responsible for generating cryptographic keys based on the JWT algorithm.
@@ -195,12 +305,147 @@ impl KeyBuilder {
         })
     }
 
+    /// Loads existing key material to sign tokens with the given algorithm
+    ///
+    /// # Arguments
+    /// * `algorithm` - The algorithm the key will sign with
+    /// * `key` - An RSA private key in PEM format, or an HMAC secret encoded as hex or base64
+    /// * `encoding` - How the key is encoded, detected when unset
+    ///
+    /// # Returns
+    /// A `KeyPair` whose string form is the one stored for generated keys: hex for HMAC
+    /// secrets and PKCS#8 PEM for private keys
+    ///
+    /// # Errors
+    /// Returns an error if the key does not match the algorithm or is weaker than RFC 7518
+    /// allows: HMAC secrets shorter than the hash output or RSA keys below 2048 bits. Without
+    /// an `encoding`, HMAC secrets that decode to different bytes as hex and base64 are
+    /// rejected as ambiguous.
+    pub fn import_key(
+        algorithm: Algorithm,
+        key: &str,
+        encoding: Option<KeyEncoding>,
+    ) -> Result<KeyPair> {
+        let key = key.trim();
+        match algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                let secret = decode_secret(key, encoding)?;
+                let minimum_bytes = match algorithm {
+                    Algorithm::HS256 => HmacHashFunction::Sha256,
+                    Algorithm::HS384 => HmacHashFunction::Sha384,
+                    _ => HmacHashFunction::Sha512,
+                }
+                .output_size_bytes();
+                if secret.len() < minimum_bytes {
+                    return Err(Error::msg(format!(
+                        "HMAC secret for {:?} must be at least {} bits",
+                        algorithm,
+                        minimum_bytes * 8
+                    )));
+                }
+
+                Ok(KeyPair {
+                    private_key: secret.clone(),
+                    public_key: None,
+                    private_key_str: hex::encode(secret),
+                    public_key_str: None,
+                })
+            }
+            Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512 => {
+                if !matches!(encoding, None | Some(KeyEncoding::Pem)) {
+                    return Err(Error::msg("RSA private keys must be PEM encoded"));
+                }
+                let private_key = PKey::private_key_from_pem(key.as_bytes())
+                    .map_err(|_| Error::msg("Private key must be a PEM encoded RSA key"))?;
+                if private_key.id() != Id::RSA {
+                    return Err(Error::msg(format!(
+                        "Private key is not an RSA key, which {:?} requires",
+                        algorithm
+                    )));
+                }
+                if private_key.bits() < RsaKeyLength::B2048.as_bits() as u32 {
+                    return Err(Error::msg(format!(
+                        "RSA key for {:?} must be at least 2048 bits",
+                        algorithm
+                    )));
+                }
+
+                Self::from_private_key_pem(key)
+            }
+            _ => Err(Error::msg(format!(
+                "Importing keys for {:?} is not supported",
+                algorithm
+            ))),
+        }
+    }
+
+    /// Loads a public key from a PEM string
+    ///
+    /// # Arguments
+    /// * `pem_public_key` - A string containing an RSA or EC public key in PEM format
+    ///
+    /// # Returns
+    /// The type of the key and the key re-encoded as a SubjectPublicKeyInfo PEM
+    ///
+    /// # Errors
+    /// Returns an error if the key is not a PEM public key or uses an unsupported key type
+    pub fn public_key_from_pem(pem_public_key: &str) -> Result<(PublicKeyType, String)> {
+        let public_key = PKey::public_key_from_pem(pem_public_key.as_bytes())
+            .context("Failed to load public key from PEM")?;
+
+        let key_type = match public_key.id() {
+            Id::RSA => PublicKeyType::Rsa,
+            Id::EC => {
+                let curve = public_key
+                    .ec_key()
+                    .context("Failed to load EC public key")?
+                    .group()
+                    .curve_name();
+                match curve {
+                    Some(Nid::X9_62_PRIME256V1) => PublicKeyType::EcP256,
+                    Some(Nid::SECP384R1) => PublicKeyType::EcP384,
+                    _ => return Err(Error::msg("Unsupported EC curve")),
+                }
+            }
+            _ => return Err(Error::msg("Unsupported public key type")),
+        };
+
+        let public_pem = public_key
+            .public_key_to_pem()
+            .context("Failed to encode public key")?;
+
+        Ok((key_type, String::from_utf8(public_pem)?))
+    }
+
+    /// Builds the key verifying signatures made with the private key of a PEM public key
+    ///
+    /// # Errors
+    /// Returns an error if the key cannot be loaded for the algorithm
+    pub fn decoding_key_from_public_pem(
+        algorithm: Algorithm,
+        pem_public_key: &str,
+    ) -> Result<DecodingKey> {
+        match algorithm {
+            Algorithm::ES256 | Algorithm::ES384 => {
+                DecodingKey::from_ec_pem(pem_public_key.as_bytes())
+                    .context("Failed to create decoding key")
+            }
+            _ => DecodingKey::from_rsa_pem(pem_public_key.as_bytes())
+                .context("Failed to create decoding key"),
+        }
+    }
+
     /// Generates a key or key pair based on the specified JWT algorithm
     pub fn generate_key(&self, algorithm: Algorithm) -> Result<KeyPair> {
         match algorithm {
             // HMAC algorithms
             Algorithm::HS256 => self.generate_hmac_key(HmacHashFunction::Sha256, None),
-            Algorithm::HS384 => self.generate_hmac_key(HmacHashFunction::Sha256, None), // Using SHA256 as it's sufficient
+            Algorithm::HS384 => self.generate_hmac_key(HmacHashFunction::Sha384, None),
             Algorithm::HS512 => self.generate_hmac_key(HmacHashFunction::Sha512, None),
 
             // RSA algorithms
@@ -306,6 +551,43 @@ impl KeyBuilder {
             .map_err(|e| Error::msg(format!("Failed to sign JWT token: {}", e)))
     }
 
+    /// Converts a stored key string into the bytes expected by `create_jwt`
+    ///
+    /// HMAC secrets are stored hex-encoded while asymmetric keys are stored as PEM.
+    ///
+    /// # Errors
+    /// Returns an error if an HMAC secret is not valid hex
+    pub fn signing_key_from_str(algorithm: Algorithm, key: &str) -> Result<Vec<u8>> {
+        match algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                hex::decode(key).context("Failed to decode HMAC key")
+            }
+            _ => Ok(key.as_bytes().to_vec()),
+        }
+    }
+
+    /// Builds the key verifying tokens signed with a stored key string
+    ///
+    /// HMAC secrets verify their own signatures while the public key of asymmetric keys is
+    /// derived from the stored private key.
+    ///
+    /// # Errors
+    /// Returns an error if the stored key cannot be parsed
+    pub fn decoding_key_from_str(algorithm: Algorithm, key: &str) -> Result<DecodingKey> {
+        match algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => Ok(DecodingKey::from_secret(
+                &Self::signing_key_from_str(algorithm, key)?,
+            )),
+            _ => {
+                let public_pem = PKey::private_key_from_pem(key.as_bytes())
+                    .context("Failed to load private key from PEM")?
+                    .public_key_to_pem()
+                    .context("Failed to extract public key from private key")?;
+                DecodingKey::from_rsa_pem(&public_pem).context("Failed to create decoding key")
+            }
+        }
+    }
+
     /// Generates a key for a specific algorithm with a custom key length (for HMAC)
     pub fn generate_key_with_length(
         &self,
@@ -316,7 +598,7 @@ impl KeyBuilder {
             Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
                 let hash_function = match algorithm {
                     Algorithm::HS256 => HmacHashFunction::Sha256,
-                    Algorithm::HS384 => HmacHashFunction::Sha256, // Using SHA256 as it's sufficient
+                    Algorithm::HS384 => HmacHashFunction::Sha384,
                     Algorithm::HS512 => HmacHashFunction::Sha512,
                     _ => unreachable!(),
                 };
@@ -372,6 +654,18 @@ mod tests {
         assert!(key_pair.public_key.is_none());
         assert!(!key_pair.private_key.is_empty());
 
+        // Test HS384, generated at the 48 byte length of its hash output
+        let key_pair = builder.generate_key(Algorithm::HS384).unwrap();
+        assert_eq!(key_pair.private_key.len(), 48);
+        assert!(
+            KeyBuilder::import_key(
+                Algorithm::HS384,
+                &key_pair.private_key_str,
+                Some(KeyEncoding::Hex)
+            )
+            .is_ok()
+        );
+
         // Test HS512
         let result = builder.generate_key(Algorithm::HS512);
         assert!(result.is_ok());
@@ -413,6 +707,11 @@ mod tests {
         // Test with default key length
         let result = builder.generate_key_with_length(Algorithm::HS256, None);
         assert!(result.is_ok());
+
+        let key_pair = builder
+            .generate_key_with_length(Algorithm::HS384, None)
+            .unwrap();
+        assert_eq!(key_pair.private_key.len(), 48);
     }
 
     #[test]
@@ -463,6 +762,103 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_import_key_hmac() {
+        let secret = [7u8; 32];
+
+        let from_hex = KeyBuilder::import_key(
+            Algorithm::HS256,
+            &hex::encode(secret),
+            Some(KeyEncoding::Hex),
+        )
+        .unwrap();
+        assert_eq!(from_hex.private_key, secret);
+        assert_eq!(from_hex.private_key_str, hex::encode(secret));
+
+        let from_base64 = KeyBuilder::import_key(
+            Algorithm::HS256,
+            &STANDARD.encode(secret),
+            Some(KeyEncoding::Base64),
+        )
+        .unwrap();
+        assert_eq!(from_base64.private_key, secret);
+
+        // Detected, as it only decodes as base64
+        let from_base64 =
+            KeyBuilder::import_key(Algorithm::HS256, &STANDARD.encode(secret), None).unwrap();
+        assert_eq!(from_base64.private_key, secret);
+
+        let result = KeyBuilder::import_key(
+            Algorithm::HS512,
+            &hex::encode(secret),
+            Some(KeyEncoding::Hex),
+        );
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "HMAC secret for HS512 must be at least 512 bits"
+        );
+
+        let result = KeyBuilder::import_key(Algorithm::HS256, "not a secret!", None);
+        assert!(result.is_err());
+        let result = KeyBuilder::import_key(
+            Algorithm::HS256,
+            &STANDARD.encode(secret),
+            Some(KeyEncoding::Hex),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_import_key_hmac_rejects_ambiguous_encoding() {
+        // Made of hex digits, so it decodes to 32 bytes as hex and 48 bytes as base64
+        let secret = "deadbeef".repeat(8);
+
+        let result = KeyBuilder::import_key(Algorithm::HS256, &secret, None);
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "HMAC secret is ambiguous, set its encoding to hex, base64 or base64url"
+        );
+
+        let from_hex =
+            KeyBuilder::import_key(Algorithm::HS256, &secret, Some(KeyEncoding::Hex)).unwrap();
+        assert_eq!(from_hex.private_key.len(), 32);
+        let from_base64 =
+            KeyBuilder::import_key(Algorithm::HS256, &secret, Some(KeyEncoding::Base64)).unwrap();
+        assert_eq!(from_base64.private_key.len(), 48);
+    }
+
+    #[test]
+    fn test_import_key_rsa() {
+        let key_pair = KeyBuilder::new().generate_key(Algorithm::RS256).unwrap();
+
+        let imported =
+            KeyBuilder::import_key(Algorithm::PS256, &key_pair.private_key_str, None).unwrap();
+        assert_eq!(imported.private_key_str, key_pair.private_key_str);
+        assert_eq!(imported.public_key_str, key_pair.public_key_str);
+
+        let weak_key = PKey::from_rsa(openssl::rsa::Rsa::generate(1024).unwrap()).unwrap();
+        let weak_pem = String::from_utf8(weak_key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+        let result = KeyBuilder::import_key(Algorithm::RS256, &weak_pem, Some(KeyEncoding::Pem));
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "RSA key for RS256 must be at least 2048 bits"
+        );
+
+        let result = KeyBuilder::import_key(Algorithm::RS256, &hex::encode([7u8; 32]), None);
+        assert!(result.is_err());
+        let result = KeyBuilder::import_key(
+            Algorithm::RS256,
+            &key_pair.private_key_str,
+            Some(KeyEncoding::Hex),
+        );
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "RSA private keys must be PEM encoded"
+        );
+        let result = KeyBuilder::import_key(Algorithm::ES256, &key_pair.private_key_str, None);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_create_jwt_with_hmac() {
         let builder = KeyBuilder::new();
@@ -659,6 +1055,83 @@ mod tests {
         assert!(token_claims["jti"].as_str().is_some());
     }
 
+    #[test]
+    fn test_signing_key_from_str() {
+        let builder = KeyBuilder::new();
+
+        let hmac_pair = builder.generate_key(Algorithm::HS256).unwrap();
+        let hmac_key =
+            KeyBuilder::signing_key_from_str(Algorithm::HS256, &hmac_pair.private_key_str)
+                .unwrap();
+        assert_eq!(hmac_key, hmac_pair.private_key);
+
+        let rsa_pair = builder.generate_key(Algorithm::RS256).unwrap();
+        let rsa_key =
+            KeyBuilder::signing_key_from_str(Algorithm::RS256, &rsa_pair.private_key_str).unwrap();
+        assert_eq!(rsa_key, rsa_pair.private_key);
+
+        assert!(KeyBuilder::signing_key_from_str(Algorithm::HS256, "not hex").is_err());
+    }
+
+    #[test]
+    fn test_decoding_key_from_str() {
+        let builder = KeyBuilder::new();
+        let claims = Claims::new("user123", 3600);
+
+        for algorithm in [Algorithm::HS256, Algorithm::RS256] {
+            let key_pair = builder.generate_key(algorithm).unwrap();
+            let signing_key =
+                KeyBuilder::signing_key_from_str(algorithm, &key_pair.private_key_str).unwrap();
+            let token = builder
+                .create_jwt(&claims, &signing_key, algorithm)
+                .unwrap();
+
+            let decoding_key =
+                KeyBuilder::decoding_key_from_str(algorithm, &key_pair.private_key_str).unwrap();
+            let token_data = decode::<Claims>(&token, &decoding_key, &Validation::new(algorithm));
+            assert_eq!(token_data.unwrap().claims.sub, "user123");
+        }
+
+        assert!(KeyBuilder::decoding_key_from_str(Algorithm::RS256, "not a pem").is_err());
+    }
+
+    #[test]
+    fn test_claims_with_actor_chain() {
+        let actor = ActorClaim::new("service-b", Some(ActorClaim::new("service-a", None)));
+        let claims = Claims::new("user123", 3600).with_actor(actor);
+
+        let value = serde_json::to_value(&claims).unwrap();
+        assert_eq!(value["act"]["sub"], "service-b");
+        assert_eq!(value["act"]["act"]["sub"], "service-a");
+        assert!(value["act"]["act"].get("act").is_none());
+
+        let value = serde_json::to_value(Claims::new("user123", 3600)).unwrap();
+        assert!(value.get("act").is_none());
+    }
+
+    #[test]
+    fn test_public_key_from_pem() {
+        let rsa_pair = KeyBuilder::new().generate_key(Algorithm::RS256).unwrap();
+        let (key_type, pem) =
+            KeyBuilder::public_key_from_pem(rsa_pair.public_key_str.as_deref().unwrap()).unwrap();
+        assert_eq!(key_type, PublicKeyType::Rsa);
+        assert!(pem.starts_with("-----BEGIN PUBLIC KEY-----"));
+        assert!(key_type.supports(Algorithm::PS256));
+        assert!(!key_type.supports(Algorithm::ES256));
+        assert!(!key_type.supports(Algorithm::HS256));
+
+        let group = openssl::ec::EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ec_key = openssl::ec::EcKey::generate(&group).unwrap();
+        let ec_pem = String::from_utf8(ec_key.public_key_to_pem().unwrap()).unwrap();
+        let (key_type, _) = KeyBuilder::public_key_from_pem(&ec_pem).unwrap();
+        assert_eq!(key_type, PublicKeyType::EcP256);
+        assert!(key_type.supports(Algorithm::ES256));
+        assert!(!key_type.supports(Algorithm::ES384));
+
+        // Private keys are rejected
+        assert!(KeyBuilder::public_key_from_pem(&rsa_pair.private_key_str).is_err());
+    }
+
     #[test]
     fn test_create_jwt_with_invalid_key() {
         let builder = KeyBuilder::new();
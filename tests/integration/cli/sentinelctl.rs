@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use clap::Parser;
+use sentinel_guard::{
+    cli::{self, Cli, CliContext, Command, EXIT_FAILURE, EXIT_NOT_FOUND},
+    config::{AppConfig, SecurityConfig},
+    repositories::{base::Repository, project_access_repository::ProjectAccessRepository},
+};
+use serde_json::Value;
+use sqlx::PgPool;
+
+fn context(pool: PgPool) -> CliContext {
+    // With the master key the tests run with
+    let config = AppConfig {
+        security: SecurityConfig::default().with_env().unwrap(),
+        ..Default::default()
+    };
+    CliContext::new(Arc::new(pool), config).unwrap()
+}
+
+fn command(args: &[&str]) -> Command {
+    Cli::try_parse_from([&["sentinelctl"], args].concat())
+        .unwrap()
+        .command
+}
+
+async fn run(context: &CliContext, args: &[&str]) -> Value {
+    cli::execute(command(args), context).await.unwrap()
+}
+
+fn id(value: &Value) -> String {
+    value["id"].as_str().unwrap().to_string()
+}
+
+#[sqlx::test]
+async fn test_sentinelctl_sets_up_a_service_account_and_mints_a_token(pool: PgPool) {
+    let context = context(pool);
+
+    let project = run(&context, &["project", "create", "--name", "Billing"]).await;
+    let project_id = id(&project);
+    let environment = run(
+        &context,
+        &[
+            "environment",
+            "create",
+            "--project-id",
+            &project_id,
+            "--name",
+            "production",
+        ],
+    )
+    .await;
+    let environment_id = id(&environment);
+    for scope in ["invoices:read", "invoices:write"] {
+        run(
+            &context,
+            &[
+                "scope",
+                "create",
+                "--project-id",
+                &project_id,
+                "--scope",
+                scope,
+            ],
+        )
+        .await;
+    }
+
+    // The generated secret is only shown on creation
+    let service_account = run(
+        &context,
+        &[
+            "service-account",
+            "create",
+            "--name",
+            "billing-worker",
+            "--email",
+            "worker@example.com",
+        ],
+    )
+    .await;
+    let service_account_id = id(&service_account);
+    let secret = service_account["secret"].as_str().unwrap().to_string();
+    assert!(!secret.is_empty());
+    let service_accounts = run(&context, &["service-account", "list"]).await;
+    assert!(service_accounts[0].get("secret").is_none());
+
+    let access = run(
+        &context,
+        &[
+            "access",
+            "grant",
+            "--project-id",
+            &project_id,
+            "--service-account-id",
+            &service_account_id,
+            "--environment-id",
+            &environment_id,
+            "--scope",
+            "invoices:read",
+        ],
+    )
+    .await;
+    assert_eq!(access["scopes"], serde_json::json!(["invoices:read"]));
+
+    let key = run(
+        &context,
+        &["key", "create", "--environment-id", &environment_id],
+    )
+    .await;
+    let rotated = run(&context, &["key", "rotate", &id(&key)]).await;
+    assert_eq!(rotated["algorithm"], "RS256");
+    let keys = run(
+        &context,
+        &["key", "list", "--environment-id", &environment_id],
+    )
+    .await;
+    assert!(!keys.as_array().unwrap().is_empty());
+
+    let token = run(
+        &context,
+        &[
+            "token",
+            "mint",
+            "--client-id",
+            &service_account_id,
+            "--client-secret",
+            &secret,
+            "--environment-id",
+            &environment_id,
+        ],
+    )
+    .await;
+    assert_eq!(token["token_type"], "Bearer");
+    assert_eq!(token["scope"], "invoices:read");
+    assert!(!token["access_token"].as_str().unwrap().is_empty());
+}
+
+#[sqlx::test(fixtures(
+    "../fixtures/projects.sql",
+    "../fixtures/environments.sql",
+    "../fixtures/service_accounts.sql",
+    "../fixtures/project_scopes.sql"
+))]
+async fn test_sentinelctl_grant_with_unknown_scope_is_not_found(pool: PgPool) {
+    let context = context(pool.clone());
+    let projects = run(&context, &["project", "list"]).await;
+    let project_id = id(&projects[0]);
+    let environments = run(
+        &context,
+        &["environment", "list", "--project-id", &project_id],
+    )
+    .await;
+    let service_accounts = run(&context, &["service-account", "list"]).await;
+
+    let error = cli::execute(
+        command(&[
+            "access",
+            "grant",
+            "--project-id",
+            &project_id,
+            "--service-account-id",
+            &id(&service_accounts[0]),
+            "--environment-id",
+            &id(&environments[0]),
+            "--scope",
+            "does-not-exist",
+        ]),
+        &context,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(cli::exit_code(&error), EXIT_NOT_FOUND);
+
+    // Nothing is granted when a scope is missing
+    let access = ProjectAccessRepository::new(Arc::new(pool))
+        .find(Default::default(), None, None)
+        .await
+        .unwrap();
+    assert!(access.is_empty());
+}
+
+#[sqlx::test(fixtures(
+    "../fixtures/projects.sql",
+    "../fixtures/environments.sql",
+    "../fixtures/service_accounts.sql",
+    "../fixtures/project_scopes.sql"
+))]
+async fn test_sentinelctl_grant_with_failing_scope_grants_nothing(pool: PgPool) {
+    let context = context(pool.clone());
+
+    // The second insert of the same scope violates its unique index
+    let error = cli::execute(
+        command(&[
+            "access",
+            "grant",
+            "--project-id",
+            "123e4567-e89b-12d3-a456-426614174000",
+            "--service-account-id",
+            "123e4567-e89b-12d3-a456-426614174000",
+            "--environment-id",
+            "00000000-0000-0000-0000-000000000001",
+            "--scope",
+            "testa:read",
+            "--scope",
+            "testa:read",
+        ]),
+        &context,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(cli::exit_code(&error), EXIT_FAILURE);
+
+    let access = ProjectAccessRepository::new(Arc::new(pool))
+        .find(Default::default(), None, None)
+        .await
+        .unwrap();
+    assert!(access.is_empty());
+}
+
+#[sqlx::test]
+async fn test_sentinelctl_rejected_command_fails(pool: PgPool) {
+    let context = context(pool);
+    let args = [
+        "service-account",
+        "create",
+        "--name",
+        "worker",
+        "--email",
+        "worker@example.com",
+    ];
+    run(&context, &args).await;
+
+    // The email is already taken
+    let error = cli::execute(command(&args), &context).await.unwrap_err();
+    assert_eq!(cli::exit_code(&error), EXIT_FAILURE);
+}
@@ -0,0 +1,6 @@
+-- Claim Templates (loaded after token_policies.sql)
+INSERT INTO claim_templates (id, project_id, environment_id, project_access_id, claim, template, created_at, updated_at) VALUES
+('80000000-0000-0000-0000-000000000001', '10000000-0000-0000-0000-000000000001', NULL, NULL, 'tenant', '{{project.name}}', NOW(), NOW()),
+('80000000-0000-0000-0000-000000000002', '10000000-0000-0000-0000-000000000001', NULL, NULL, 'region', 'eu-west', NOW(), NOW()),
+('80000000-0000-0000-0000-000000000003', '10000000-0000-0000-0000-000000000001', '20000000-0000-0000-0000-000000000001', NULL, 'tenant', '{{project.name}}-{{environment.name}}', NOW(), NOW()),
+('80000000-0000-0000-0000-000000000004', '10000000-0000-0000-0000-000000000001', NULL, '40000000-0000-0000-0000-000000000001', 'owner', '{{service_account.email}}', NOW(), NOW());
@@ -0,0 +1,24 @@
+-- Projects
+INSERT INTO projects (id, name, description, enabled, created_at, updated_at) VALUES
+('123e4567-e89b-12d3-a456-426614174000', 'testa', 'test', true, NOW(), NOW());
+
+-- Service Accounts
+INSERT INTO service_account (id, name, email, secret, description, enabled, created_at, updated_at) VALUES
+('123e4567-e89b-12d3-a456-426614174000', 'Test Account 1', 'test1@example.com', 'secret1', 'Test Description 1', true, NOW(), NOW());
+
+-- Environments
+INSERT INTO environment (id, project_id, name, description, enabled, created_at, updated_at) VALUES
+('00000000-0000-0000-0000-000000000001', '123e4567-e89b-12d3-a456-426614174000', 'dev', 'Development environment', true, NOW(), NOW());
+
+-- Project Access
+INSERT INTO project_access (id, project_id, service_account_id, environment_id, enabled, created_at, updated_at) VALUES
+('00000000-0000-0000-0000-000000000101', '123e4567-e89b-12d3-a456-426614174000', '123e4567-e89b-12d3-a456-426614174000', '00000000-0000-0000-0000-000000000001', true, NOW(), NOW());
+
+-- Access Tokens
+INSERT INTO access_tokens (id, jti, project_access_id, algorithm, token, expires_at, active, created_at, updated_at) VALUES
+('11111111-1111-1111-1111-111111111111', 'jti-valid', '00000000-0000-0000-0000-000000000101', 'HS256', 'token1', NOW() + INTERVAL '1 day', true, NOW(), NOW()),
+('22222222-2222-2222-2222-222222222222', 'jti-revoked-valid', '00000000-0000-0000-0000-000000000101', 'HS256', 'token2', NOW() + INTERVAL '1 day', false, NOW(), NOW()),
+('33333333-3333-3333-3333-333333333333', 'jti-recently-expired', '00000000-0000-0000-0000-000000000101', 'HS256', 'token3', NOW() - INTERVAL '1 day', true, NOW(), NOW()),
+('44444444-4444-4444-4444-444444444444', 'jti-expired-1', '00000000-0000-0000-0000-000000000101', 'HS256', 'token4', NOW() - INTERVAL '30 days', true, NOW(), NOW()),
+('55555555-5555-5555-5555-555555555555', 'jti-expired-2', '00000000-0000-0000-0000-000000000101', 'HS256', 'token5', NOW() - INTERVAL '31 days', false, NOW(), NOW()),
+('66666666-6666-6666-6666-666666666666', 'jti-expired-3', '00000000-0000-0000-0000-000000000101', 'HS256', 'token6', NOW() - INTERVAL '32 days', true, NOW(), NOW());
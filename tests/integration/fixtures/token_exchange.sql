@@ -0,0 +1,9 @@
+-- Acting service account (loaded after token_policies.sql)
+INSERT INTO service_account (id, name, email, secret, description, enabled, created_at, updated_at) VALUES
+('30000000-0000-0000-0000-000000000003', 'ledger-service', 'ledger@example.com', 'secret', 'Ledger service', true, NOW(), NOW());
+
+INSERT INTO project_access (id, project_id, service_account_id, environment_id, enabled, created_at, updated_at) VALUES
+('40000000-0000-0000-0000-000000000004', '10000000-0000-0000-0000-000000000001', '30000000-0000-0000-0000-000000000003', '20000000-0000-0000-0000-000000000001', true, NOW(), NOW());
+
+INSERT INTO project_access_scopes (id, project_access_id, scope_id, enabled, created_at, updated_at) VALUES
+('60000000-0000-0000-0000-000000000005', '40000000-0000-0000-0000-000000000004', '50000000-0000-0000-0000-000000000001', true, NOW(), NOW());
@@ -0,0 +1,39 @@
+-- Projects
+INSERT INTO projects (id, name, description, enabled, created_at, updated_at) VALUES
+('10000000-0000-0000-0000-000000000001', 'payments', 'Payments project', true, NOW(), NOW()),
+('10000000-0000-0000-0000-000000000002', 'billing', 'Billing project', true, NOW(), NOW());
+
+-- Environments
+INSERT INTO environment (id, project_id, name, description, enabled, created_at, updated_at) VALUES
+('20000000-0000-0000-0000-000000000001', '10000000-0000-0000-0000-000000000001', 'dev', 'Development environment', true, NOW(), NOW()),
+('20000000-0000-0000-0000-000000000002', '10000000-0000-0000-0000-000000000001', 'prod', 'Production environment', true, NOW(), NOW()),
+('20000000-0000-0000-0000-000000000003', '10000000-0000-0000-0000-000000000002', 'dev', 'Development environment', true, NOW(), NOW());
+
+-- Service Accounts (secrets are encrypted by the tests that authenticate with them)
+INSERT INTO service_account (id, name, email, secret, description, enabled, created_at, updated_at) VALUES
+('30000000-0000-0000-0000-000000000001', 'payments-worker', 'worker@example.com', 'secret', 'Payments worker', true, NOW(), NOW()),
+('30000000-0000-0000-0000-000000000002', 'retired-worker', 'retired@example.com', 'secret', 'Retired worker', false, NOW(), NOW());
+
+-- Project Access
+INSERT INTO project_access (id, project_id, service_account_id, environment_id, enabled, created_at, updated_at) VALUES
+('40000000-0000-0000-0000-000000000001', '10000000-0000-0000-0000-000000000001', '30000000-0000-0000-0000-000000000001', '20000000-0000-0000-0000-000000000001', true, NOW(), NOW()),
+('40000000-0000-0000-0000-000000000002', '10000000-0000-0000-0000-000000000001', '30000000-0000-0000-0000-000000000001', '20000000-0000-0000-0000-000000000002', true, NOW(), NOW()),
+('40000000-0000-0000-0000-000000000003', '10000000-0000-0000-0000-000000000001', '30000000-0000-0000-0000-000000000002', '20000000-0000-0000-0000-000000000001', true, NOW(), NOW());
+
+-- Project Scopes
+INSERT INTO project_scopes (id, project_id, scope, description, enabled, created_at, updated_at) VALUES
+('50000000-0000-0000-0000-000000000001', '10000000-0000-0000-0000-000000000001', 'payments:read', 'Read payments', true, NOW(), NOW()),
+('50000000-0000-0000-0000-000000000002', '10000000-0000-0000-0000-000000000001', 'payments:write', 'Write payments', true, NOW(), NOW()),
+('50000000-0000-0000-0000-000000000003', '10000000-0000-0000-0000-000000000001', 'payments:admin', 'Administer payments', false, NOW(), NOW());
+
+-- Project Access Scopes
+INSERT INTO project_access_scopes (id, project_access_id, scope_id, enabled, created_at, updated_at) VALUES
+('60000000-0000-0000-0000-000000000001', '40000000-0000-0000-0000-000000000001', '50000000-0000-0000-0000-000000000001', true, NOW(), NOW()),
+('60000000-0000-0000-0000-000000000002', '40000000-0000-0000-0000-000000000001', '50000000-0000-0000-0000-000000000002', true, NOW(), NOW()),
+('60000000-0000-0000-0000-000000000003', '40000000-0000-0000-0000-000000000001', '50000000-0000-0000-0000-000000000003', true, NOW(), NOW()),
+('60000000-0000-0000-0000-000000000004', '40000000-0000-0000-0000-000000000002', '50000000-0000-0000-0000-000000000001', true, NOW(), NOW());
+
+-- Token Policies
+INSERT INTO token_policies (id, project_id, environment_id, max_ttl_seconds, default_ttl_seconds, audience, issuer, allowed_algorithms, allow_refresh_tokens, refresh_token_ttl_seconds, created_at, updated_at) VALUES
+('70000000-0000-0000-0000-000000000001', '10000000-0000-0000-0000-000000000001', NULL, 600, 300, ARRAY['payments-api'], 'https://sentinel-guard.test', ARRAY['HS256', 'RS256'], false, NULL, NOW(), NOW()),
+('70000000-0000-0000-0000-000000000002', '10000000-0000-0000-0000-000000000001', '20000000-0000-0000-0000-000000000002', 120, NULL, NULL, NULL, ARRAY['HS512'], true, 3600, NOW(), NOW());
@@ -0,0 +1,209 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use sentinel_guard::{
+    config::KeyRotationConfig,
+    jobs::key_rotation::{JOB_NAME, KeyRotationJob},
+    models::{
+        audit_event::{AUDIT_EVENT_ENVIRONMENT_KEY_ROTATED, AuditEventFilter},
+        environment_key::EnvironmentKeyCreatePayload,
+    },
+    repositories::{
+        audit_event_repository::AuditEventRepository, base::Repository,
+        environment_key_repository::EnvironmentKeyRepository,
+    },
+    utils::{key_store::file::FileKeyStore, metrics::METRICS},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::integration::secrets_manager;
+
+const ENVIRONMENT_ID: &str = "123e4567-e89b-12d3-a456-426614174000";
+const ACTIVE_KEY_ID: &str = "00000000-0000-0000-0000-000000000001";
+const INACTIVE_KEY_ID: &str = "00000000-0000-0000-0000-000000000002";
+
+fn job(pool: &PgPool, config: KeyRotationConfig) -> KeyRotationJob {
+    KeyRotationJob::new(
+        EnvironmentKeyRepository::new(Arc::new(pool.clone()), secrets_manager()),
+        config,
+    )
+}
+
+/// Schedules the rotation of a key in the past, making it due
+async fn make_due(pool: &PgPool, id: Uuid) {
+    sqlx::query(
+        "UPDATE environment_key SET rotation_interval_days = 30, next_rotation_at = now() - interval '1 minute' WHERE id = $1",
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_key_rotation_job_rotates_due_active_keys(pool: PgPool) {
+    let repository = EnvironmentKeyRepository::new(Arc::new(pool.clone()), secrets_manager());
+    let created = repository
+        .create(EnvironmentKeyCreatePayload {
+            environment_id: ENVIRONMENT_ID.to_string(),
+            algorithm: "HS512".to_string(),
+            active: true,
+            rotation_interval_days: Some(30),
+        })
+        .await
+        .unwrap();
+    let created_id = created.id.unwrap();
+    let (_, original_key) = repository
+        .get_active_key(
+            Uuid::parse_str(ENVIRONMENT_ID).unwrap(),
+            Some(created.algorithm),
+        )
+        .await
+        .unwrap();
+    for id in [ACTIVE_KEY_ID, INACTIVE_KEY_ID] {
+        make_due(&pool, Uuid::parse_str(id).unwrap()).await;
+    }
+    make_due(&pool, created_id).await;
+
+    let job = job(
+        &pool,
+        KeyRotationConfig {
+            batch_size: 1,
+            jitter_seconds: 600,
+            ..Default::default()
+        },
+    );
+    let runs = METRICS.job_runs_total.get(&[JOB_NAME, "success"]);
+    let rotations = METRICS.environment_key_rotations_total.get(&["scheduled"]);
+    let started_at = Utc::now();
+    let rotated = job.run_once().await.unwrap();
+
+    let mut rotated_ids: Vec<Uuid> = rotated.iter().map(|key| key.id.unwrap()).collect();
+    rotated_ids.sort();
+    assert_eq!(
+        rotated_ids,
+        vec![Uuid::parse_str(ACTIVE_KEY_ID).unwrap(), created_id]
+    );
+    for key in &rotated {
+        let next_rotation_at = key.next_rotation_at.unwrap();
+        assert!(next_rotation_at >= started_at + Duration::days(30));
+        assert!(next_rotation_at <= Utc::now() + Duration::days(30) + Duration::seconds(600));
+    }
+
+    let (_, rotated_key) = repository
+        .get_active_key(
+            Uuid::parse_str(ENVIRONMENT_ID).unwrap(),
+            Some(created.algorithm),
+        )
+        .await
+        .unwrap();
+    assert_ne!(rotated_key, original_key);
+
+    // The replaced key is retained for the longest token lifetime
+    let retained = sqlx::query!(
+        "SELECT previous_key, previous_key_expires_at FROM environment_key WHERE id = $1",
+        created_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(retained.previous_key.is_some());
+    assert!(retained.previous_key_expires_at.unwrap() > Utc::now());
+
+    let inactive = repository
+        .read(Uuid::parse_str(INACTIVE_KEY_ID).unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(inactive.next_rotation_at.unwrap() < Utc::now());
+
+    let events = AuditEventRepository::new(Arc::new(pool.clone()))
+        .find(
+            AuditEventFilter {
+                event_type: Some(AUDIT_EVENT_ENVIRONMENT_KEY_ROTATED.to_string()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 2);
+
+    assert!(METRICS.job_runs_total.get(&[JOB_NAME, "success"]) > runs);
+    // Only this test rotates keys on schedule, so the delta is exact
+    assert_eq!(
+        METRICS.environment_key_rotations_total.get(&["scheduled"]),
+        rotations + 2
+    );
+
+    // Rotated keys are not due anymore
+    assert!(job.run_once().await.unwrap().is_empty());
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_key_rotation_job_discards_generated_keys_when_a_rotation_fails(pool: PgPool) {
+    let directory = std::env::temp_dir().join(format!("sentinel-guard-keys-{}", Uuid::new_v4()));
+    let repository = EnvironmentKeyRepository::new(Arc::new(pool.clone()), secrets_manager())
+        .with_key_store(Arc::new(FileKeyStore::new(&directory)));
+    let created = repository
+        .create(EnvironmentKeyCreatePayload {
+            environment_id: ENVIRONMENT_ID.to_string(),
+            algorithm: "HS512".to_string(),
+            active: true,
+            rotation_interval_days: Some(30),
+        })
+        .await
+        .unwrap();
+    let created_id = created.id.unwrap();
+    // The created key is rotated first, then the rotation of a key with an unknown algorithm fails
+    make_due(&pool, created_id).await;
+    sqlx::query(
+        "UPDATE environment_key SET next_rotation_at = now() - interval '2 minutes' WHERE id = $1",
+    )
+    .bind(created_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    make_due(&pool, Uuid::parse_str(ACTIVE_KEY_ID).unwrap()).await;
+    sqlx::query("UPDATE environment_key SET algorithm = 'UNKNOWN' WHERE id = $1")
+        .bind(Uuid::parse_str(ACTIVE_KEY_ID).unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let job = KeyRotationJob::new(repository.clone(), KeyRotationConfig::default());
+    assert!(job.run_once().await.is_err());
+
+    // Only the material of the untouched created key is left
+    let (_, reference) = repository
+        .find_active_key(
+            Uuid::parse_str(ENVIRONMENT_ID).unwrap(),
+            Some(created.algorithm),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    let files: Vec<String> = std::fs::read_dir(directory.join(ENVIRONMENT_ID))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(files, vec![reference]);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_key_rotation_job_rejects_invalid_batch_size(pool: PgPool) {
+    let job = job(
+        &pool,
+        KeyRotationConfig {
+            batch_size: 0,
+            ..Default::default()
+        },
+    );
+
+    let failures = METRICS.job_runs_total.get(&[JOB_NAME, "failure"]);
+    assert!(job.run_once().await.is_err());
+    // Invalid settings are refused before a run is recorded
+    assert_eq!(METRICS.job_runs_total.get(&[JOB_NAME, "failure"]), failures);
+}
@@ -0,0 +1,3 @@
+pub mod key_rotation;
+pub mod service_account_lifecycle;
+pub mod token_cleanup;
@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    config::ServiceAccountLifecycleConfig,
+    jobs::service_account_lifecycle::ServiceAccountLifecycleJob,
+    models::{
+        audit_event::{
+            AUDIT_EVENT_SERVICE_ACCOUNT_EXPIRED, AUDIT_EVENT_SERVICE_ACCOUNT_INACTIVITY_DISABLED,
+            AUDIT_EVENT_SERVICE_ACCOUNT_INACTIVITY_WARNING, AuditEventFilter,
+        },
+        service_account::ServiceAccountUpdatePayload,
+    },
+    repositories::{
+        audit_event_repository::AuditEventRepository, base::Repository,
+        service_account_repository::ServiceAccountRepository,
+    },
+    utils::metrics::METRICS,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::integration::secrets_manager;
+
+const FIRST_ID: &str = "123e4567-e89b-12d3-a456-426614174000";
+const SECOND_ID: &str = "123e4567-e89b-12d3-a456-426614174001";
+
+fn job(pool: &PgPool, inactivity_days: i64) -> ServiceAccountLifecycleJob {
+    ServiceAccountLifecycleJob::new(
+        ServiceAccountRepository::new(Arc::new(pool.clone()), secrets_manager()),
+        ServiceAccountLifecycleConfig {
+            inactivity_days,
+            warning_days: 7,
+            ..Default::default()
+        },
+    )
+}
+
+/// Moves the creation of a service account, and so the start of its inactivity, into the past
+async fn created_days_ago(pool: &PgPool, id: &str, days: i32) {
+    sqlx::query(
+        "UPDATE service_account SET created_at = now() - make_interval(days => $1) WHERE id = $2",
+    )
+    .bind(days)
+    .bind(Uuid::parse_str(id).unwrap())
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Moves the inactivity warning of a service account into the past
+async fn warned_days_ago(pool: &PgPool, id: &str, days: i32) {
+    sqlx::query(
+        "UPDATE service_account SET inactivity_warned_at = now() - make_interval(days => $1) WHERE id = $2",
+    )
+    .bind(days)
+    .bind(Uuid::parse_str(id).unwrap())
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn event_subjects(pool: &PgPool, event_type: &str) -> Vec<String> {
+    AuditEventRepository::new(Arc::new(pool.clone()))
+        .find(
+            AuditEventFilter {
+                event_type: Some(event_type.to_string()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|event| event.subject)
+        .collect()
+}
+
+#[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
+async fn test_service_account_lifecycle_job_disables_expired_accounts(pool: PgPool) {
+    sqlx::query(
+        "UPDATE service_account SET expires_at = now() - interval '1 minute' WHERE id = $1",
+    )
+    .bind(Uuid::parse_str(FIRST_ID).unwrap())
+    .execute(&pool)
+    .await
+    .unwrap();
+    let job = job(&pool, 0);
+    let disabled = METRICS.service_accounts_disabled_total.get(&["expired"]);
+
+    let report = job.run_once().await.unwrap();
+    assert_eq!(report.expired, vec![Uuid::parse_str(FIRST_ID).unwrap()]);
+    assert!(report.inactive.is_empty());
+    assert!(report.warned.is_empty());
+    assert!(METRICS.service_accounts_disabled_total.get(&["expired"]) > disabled);
+
+    let service_account = ServiceAccountRepository::new(Arc::new(pool.clone()), secrets_manager())
+        .read(Uuid::parse_str(FIRST_ID).unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!service_account.enabled);
+    assert_eq!(
+        event_subjects(&pool, AUDIT_EVENT_SERVICE_ACCOUNT_EXPIRED).await,
+        vec![FIRST_ID]
+    );
+
+    // Already disabled accounts are not disabled, nor audited, again
+    let report = job.run_once().await.unwrap();
+    assert!(report.expired.is_empty());
+}
+
+#[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
+async fn test_service_account_lifecycle_job_warns_then_disables_inactive_accounts(pool: PgPool) {
+    created_days_ago(&pool, FIRST_ID, 100).await;
+    created_days_ago(&pool, SECOND_ID, 85).await;
+    let job = job(&pool, 90);
+    let warnings = METRICS.service_account_inactivity_warnings_total.get(&[]);
+
+    // Accounts past the inactivity cutoff are warned before being disabled
+    let report = job.run_once().await.unwrap();
+    assert!(report.inactive.is_empty());
+    let mut warned = report.warned.clone();
+    warned.sort();
+    assert_eq!(
+        warned,
+        vec![
+            Uuid::parse_str(FIRST_ID).unwrap(),
+            Uuid::parse_str(SECOND_ID).unwrap()
+        ]
+    );
+    assert!(METRICS.service_account_inactivity_warnings_total.get(&[]) > warnings);
+
+    // Each upcoming disablement is only warned of once
+    let report = job.run_once().await.unwrap();
+    assert!(report.inactive.is_empty());
+    assert!(report.warned.is_empty());
+
+    // Once the warning period has passed, only accounts also past the inactivity cutoff are disabled
+    warned_days_ago(&pool, FIRST_ID, 7).await;
+    warned_days_ago(&pool, SECOND_ID, 7).await;
+    let report = job.run_once().await.unwrap();
+    assert_eq!(report.inactive, vec![Uuid::parse_str(FIRST_ID).unwrap()]);
+    assert!(report.warned.is_empty());
+    assert_eq!(
+        event_subjects(&pool, AUDIT_EVENT_SERVICE_ACCOUNT_INACTIVITY_DISABLED).await,
+        vec![FIRST_ID]
+    );
+    let mut warned = event_subjects(&pool, AUDIT_EVENT_SERVICE_ACCOUNT_INACTIVITY_WARNING).await;
+    warned.sort();
+    assert_eq!(warned, vec![FIRST_ID, SECOND_ID]);
+}
+
+#[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
+async fn test_service_account_lifecycle_job_respects_recent_activity(pool: PgPool) {
+    created_days_ago(&pool, FIRST_ID, 100).await;
+    created_days_ago(&pool, SECOND_ID, 100).await;
+    let repository = ServiceAccountRepository::new(Arc::new(pool.clone()), secrets_manager());
+    repository
+        .record_token_issued(Uuid::parse_str(FIRST_ID).unwrap())
+        .await
+        .unwrap();
+    let job = job(&pool, 90);
+
+    let report = job.run_once().await.unwrap();
+    assert_eq!(report.warned, vec![Uuid::parse_str(SECOND_ID).unwrap()]);
+    warned_days_ago(&pool, SECOND_ID, 7).await;
+    let report = job.run_once().await.unwrap();
+    assert_eq!(report.inactive, vec![Uuid::parse_str(SECOND_ID).unwrap()]);
+
+    // Enabling the account again restarts its inactivity period
+    repository
+        .update(
+            Uuid::parse_str(SECOND_ID).unwrap(),
+            ServiceAccountUpdatePayload {
+                name: None,
+                email: None,
+                secret: None,
+                description: None,
+                enabled: Some(true),
+                allowed_cidrs: None,
+                expires_at: None,
+            },
+        )
+        .await
+        .unwrap();
+    let report = job.run_once().await.unwrap();
+    assert!(report.inactive.is_empty());
+    assert!(report.warned.is_empty());
+}
+
+#[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
+async fn test_service_account_lifecycle_job_without_inactivity_policy_keeps_idle_accounts(
+    pool: PgPool,
+) {
+    created_days_ago(&pool, FIRST_ID, 1000).await;
+
+    let report = job(&pool, 0).run_once().await.unwrap();
+    assert!(report.inactive.is_empty());
+    assert!(report.warned.is_empty());
+}
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    config::{TokenCleanupConfig, TokenCleanupMode},
+    jobs::token_cleanup::{JOB_NAME, TokenCleanupJob},
+    models::access_token::AccessTokenFilter,
+    repositories::{
+        access_token_repository::AccessTokenRepository,
+        authentication_failure_repository::AuthenticationFailureRepository, base::Repository,
+    },
+    utils::metrics::METRICS,
+};
+use sqlx::PgPool;
+
+fn job(pool: &PgPool, config: TokenCleanupConfig) -> TokenCleanupJob {
+    TokenCleanupJob::new(AccessTokenRepository::new(Arc::new(pool.clone())), config)
+}
+
+#[sqlx::test(fixtures("../fixtures/token_cleanup.sql"))]
+async fn test_token_cleanup_job_run_once_removes_tokens_past_retention(pool: PgPool) {
+    let job = job(
+        &pool,
+        TokenCleanupConfig {
+            batch_size: 2,
+            ..Default::default()
+        },
+    );
+
+    let runs = METRICS.job_runs_total.get(&[JOB_NAME, "success"]);
+    let removed = METRICS.access_tokens_removed_total.get(&["delete"]);
+
+    let report = job.run_once().await.unwrap();
+    assert_eq!(report.removed, 3);
+    assert_eq!(report.batches, 2);
+    assert_eq!(report.mode, TokenCleanupMode::Delete);
+
+    assert!(METRICS.job_runs_total.get(&[JOB_NAME, "success"]) > runs);
+    assert!(METRICS.access_tokens_removed_total.get(&["delete"]) >= removed + 3);
+
+    let remaining = AccessTokenRepository::new(Arc::new(pool))
+        .find(AccessTokenFilter::default(), None, None)
+        .await
+        .unwrap();
+    let mut jtis: Vec<String> = remaining.into_iter().map(|token| token.jti).collect();
+    jtis.sort();
+    assert_eq!(
+        jtis,
+        vec!["jti-recently-expired", "jti-revoked-valid", "jti-valid"]
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_cleanup.sql"))]
+async fn test_token_cleanup_job_run_once_with_zero_retention_keeps_unexpired(pool: PgPool) {
+    let job = job(
+        &pool,
+        TokenCleanupConfig {
+            retention_days: 0,
+            ..Default::default()
+        },
+    );
+
+    let report = job.run_once().await.unwrap();
+    assert_eq!(report.removed, 4);
+}
+
+#[sqlx::test]
+async fn test_token_cleanup_job_run_once_with_invalid_batch_size_fails(pool: PgPool) {
+    let job = job(
+        &pool,
+        TokenCleanupConfig {
+            batch_size: 0,
+            ..Default::default()
+        },
+    );
+
+    let failures = METRICS.job_runs_total.get(&[JOB_NAME, "failure"]);
+    let result = job.run_once().await;
+    assert!(result.is_err());
+    // Invalid settings are refused before a run is recorded
+    assert_eq!(METRICS.job_runs_total.get(&[JOB_NAME, "failure"]), failures);
+}
+
+#[sqlx::test]
+async fn test_token_cleanup_job_run_once_removes_stale_authentication_failures(pool: PgPool) {
+    sqlx::query(
+        "INSERT INTO authentication_failures (subject_type, subject, failure_count, last_failure_at, blocked_until) VALUES \
+         ('ip', '203.0.113.1', 2, now() - interval '2 hours', NULL), \
+         ('ip', '203.0.113.2', 10, now() - interval '2 hours', now() + interval '1 hour'), \
+         ('ip', '203.0.113.3', 1, now(), NULL)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let job = job(&pool, TokenCleanupConfig::default()).with_authentication_failures(
+        AuthenticationFailureRepository::new(Arc::new(pool.clone())),
+        900,
+    );
+
+    let report = job.run_once().await.unwrap();
+    assert_eq!(report.authentication_failures_removed, 1);
+
+    let mut subjects: Vec<String> =
+        sqlx::query_scalar("SELECT subject FROM authentication_failures")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    subjects.sort();
+    // Still blocked, or failed within the window
+    assert_eq!(subjects, vec!["203.0.113.2", "203.0.113.3"]);
+}
@@ -1,5 +1,20 @@
+pub mod cli;
+pub mod jobs;
 pub mod repositories;
 pub mod routes;
+pub mod services;
+
+use sentinel_guard::config::SecurityConfig;
+use sentinel_guard::utils::security::SecretsManager;
+
+/// Secrets manager of the master key the tests run with, `SENTINEL_GUARD_MASTER_KEY`
+pub fn secrets_manager() -> SecretsManager {
+    dotenvy::dotenv().ok();
+    SecurityConfig::default()
+        .with_env()
+        .and_then(|config| config.secrets_manager())
+        .unwrap()
+}
 
 // Define the macro here instead of including it from a file
 #[macro_export]
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use sentinel_guard::{
+    config::TokenCleanupMode,
     models::{
         access_token::{AccessTokenCreatePayloadWithAccessToken, AccessTokenFilter, AccessTokenUpdatePayload},
         pagination::Pagination,
@@ -18,6 +19,8 @@ async fn test_access_token_repository_create_with_valid_data_succeeds(pool: PgPo
         algorithm: "HS512".to_string(),
         expires_at: "2031-01-01T00:00:00Z".to_string(),
         access_token: "test-token".to_string(),
+        jti: None,
+        token_use: "access".to_string(),
     };
     let access_token = repository.create(payload.clone()).await.unwrap();
     assert_eq!(
@@ -36,6 +39,8 @@ async fn test_access_token_repository_create_with_missing_project_access_id_fail
         algorithm: "HS256".to_string(),
         expires_at: "2031-01-01T00:00:00Z".to_string(),
         access_token: "test-token".to_string(),
+        jti: None,
+        token_use: "access".to_string(),
     };
     let result = repository.create(payload).await;
     assert!(result.is_err());
@@ -181,3 +186,106 @@ async fn test_access_token_repository_find_with_active_filter(pool: PgPool) {
     let access_tokens = repository.find(filter, sort, pagination).await.unwrap();
     assert_eq!(access_tokens.len(), 4);
 }
+
+#[sqlx::test(fixtures("../fixtures/revocation_lists.sql"))]
+async fn test_access_token_repository_find_revoked_by_environment_skips_active_and_expired(
+    pool: PgPool,
+) {
+    let repository = AccessTokenRepository::new(Arc::new(pool));
+    let environment_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap();
+    let revoked = repository
+        .find_revoked_by_environment(environment_id)
+        .await
+        .unwrap();
+    assert_eq!(revoked.len(), 1);
+    assert_eq!(revoked[0].jti, "jti-revoked");
+}
+
+#[sqlx::test]
+async fn test_access_token_repository_find_revoked_by_unknown_environment_is_empty(pool: PgPool) {
+    let repository = AccessTokenRepository::new(Arc::new(pool));
+    let revoked = repository
+        .find_revoked_by_environment(Uuid::new_v4())
+        .await
+        .unwrap();
+    assert!(revoked.is_empty());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_cleanup.sql"))]
+async fn test_access_token_repository_purge_expired_delete_respects_cutoff_and_batch(
+    pool: PgPool,
+) {
+    let repository = AccessTokenRepository::new(Arc::new(pool));
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(7);
+
+    let removed = repository
+        .purge_expired(cutoff, 2, TokenCleanupMode::Delete)
+        .await
+        .unwrap();
+    assert_eq!(removed, 2);
+
+    let removed = repository
+        .purge_expired(cutoff, 2, TokenCleanupMode::Delete)
+        .await
+        .unwrap();
+    assert_eq!(removed, 1);
+
+    let remaining = repository
+        .find(AccessTokenFilter::default(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 3);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_cleanup.sql"))]
+async fn test_access_token_repository_purge_expired_archive_moves_tokens(pool: PgPool) {
+    let pool = Arc::new(pool);
+    let repository = AccessTokenRepository::new(pool.clone());
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(7);
+
+    let removed = repository
+        .purge_expired(cutoff, 100, TokenCleanupMode::Archive)
+        .await
+        .unwrap();
+    assert_eq!(removed, 3);
+
+    let archived: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM access_tokens_archive")
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+    assert_eq!(archived, 3);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_cleanup.sql"))]
+async fn test_access_token_repository_purge_expired_archive_counts_tokens_already_archived(
+    pool: PgPool,
+) {
+    let pool = Arc::new(pool);
+    let repository = AccessTokenRepository::new(pool.clone());
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(7);
+    // Archived by an earlier run whose delete was rolled back
+    sqlx::query(
+        "INSERT INTO access_tokens_archive (id, jti, project_access_id, algorithm, token, token_use, expires_at, active, created_at, updated_at) SELECT id, jti, project_access_id, algorithm, token, token_use, expires_at, active, created_at, updated_at FROM access_tokens WHERE expires_at < $1 ORDER BY expires_at LIMIT 1",
+    )
+    .bind(cutoff)
+    .execute(&*pool)
+    .await
+    .unwrap();
+
+    let removed = repository
+        .purge_expired(cutoff, 100, TokenCleanupMode::Archive)
+        .await
+        .unwrap();
+    assert_eq!(removed, 3);
+
+    let remaining = repository
+        .find(AccessTokenFilter::default(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 3);
+    let archived: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM access_tokens_archive")
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+    assert_eq!(archived, 3);
+}
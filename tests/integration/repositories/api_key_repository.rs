@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    models::api_key::{ApiKeyCreatePayload, ApiKeyFilter, ApiKeyUpdatePayload},
+    repositories::{api_key_repository::ApiKeyRepository, base::Repository},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const PROJECT_ACCESS_ID: &str = "40000000-0000-0000-0000-000000000001";
+
+fn payload(scopes: Option<Vec<&str>>) -> ApiKeyCreatePayload {
+    ApiKeyCreatePayload {
+        project_access_id: PROJECT_ACCESS_ID.to_string(),
+        name: "webhook".to_string(),
+        scopes: scopes.map(|scopes| scopes.into_iter().map(String::from).collect()),
+        expires_at: None,
+    }
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_repository_create_defaults_to_granted_scopes(pool: PgPool) {
+    let repository = ApiKeyRepository::new(Arc::new(pool));
+    let payload = payload(None).with_key();
+    let key = payload.key.clone();
+
+    let api_key = repository.create(payload).await.unwrap();
+    assert!(api_key.id.is_some());
+    assert!(api_key.enabled);
+    assert!(key.contains(&api_key.prefix));
+    assert_ne!(api_key.key_hash, key);
+    assert_eq!(api_key.scopes, vec!["payments:read", "payments:write"]);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_repository_create_with_ungranted_scope_fails(pool: PgPool) {
+    let repository = ApiKeyRepository::new(Arc::new(pool));
+
+    let result = repository
+        .create(payload(Some(vec!["payments:admin"])).with_key())
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Scope payments:admin is not granted to the project access"
+    );
+}
+
+#[sqlx::test]
+async fn test_api_key_repository_create_with_missing_project_access_fails(pool: PgPool) {
+    let repository = ApiKeyRepository::new(Arc::new(pool));
+
+    let result = repository.create(payload(None).with_key()).await;
+    assert_eq!(result.unwrap_err().to_string(), "Project access not found");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_repository_verify_resolves_key(pool: PgPool) {
+    let repository = ApiKeyRepository::new(Arc::new(pool));
+    let payload = payload(Some(vec!["payments:read"])).with_key();
+    let key = payload.key.clone();
+    let api_key = repository.create(payload).await.unwrap();
+
+    let verification = repository.verify(&key).await.unwrap().unwrap();
+    assert_eq!(verification.api_key_id, api_key.id.unwrap().to_string());
+    assert_eq!(verification.project_access_id, PROJECT_ACCESS_ID);
+    assert_eq!(verification.project_name, "payments");
+    assert_eq!(verification.environment_name, "dev");
+    assert_eq!(verification.scopes, vec!["payments:read"]);
+
+    let api_key = repository.read(api_key.id.unwrap()).await.unwrap().unwrap();
+    assert!(api_key.last_used_at.is_some());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_repository_verify_rejects_wrong_or_disabled_keys(pool: PgPool) {
+    let repository = ApiKeyRepository::new(Arc::new(pool));
+    let payload = payload(None).with_key();
+    let key = payload.key.clone();
+    let api_key = repository.create(payload).await.unwrap();
+
+    let tampered = format!("{}x", key);
+    assert!(repository.verify(&tampered).await.unwrap().is_none());
+    assert!(repository.verify("not-a-key").await.unwrap().is_none());
+
+    repository
+        .update(
+            api_key.id.unwrap(),
+            ApiKeyUpdatePayload {
+                enabled: Some(false),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(repository.verify(&key).await.unwrap().is_none());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_repository_verify_drops_revoked_scopes(pool: PgPool) {
+    let repository = ApiKeyRepository::new(Arc::new(pool.clone()));
+    let payload = payload(None).with_key();
+    let key = payload.key.clone();
+    repository.create(payload).await.unwrap();
+
+    sqlx::query("UPDATE project_access_scopes SET enabled = false WHERE id = $1")
+        .bind(Uuid::parse_str("60000000-0000-0000-0000-000000000002").unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let verification = repository.verify(&key).await.unwrap().unwrap();
+    assert_eq!(verification.scopes, vec!["payments:read"]);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_repository_find_and_delete(pool: PgPool) {
+    let repository = ApiKeyRepository::new(Arc::new(pool));
+    let api_key = repository.create(payload(None).with_key()).await.unwrap();
+
+    let api_keys = repository
+        .find(
+            ApiKeyFilter {
+                project_access_id: Some(PROJECT_ACCESS_ID.to_string()),
+                enabled: Some(true),
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(api_keys.len(), 1);
+
+    assert!(repository.delete(api_key.id.unwrap()).await.unwrap());
+    assert_eq!(
+        repository
+            .delete(api_key.id.unwrap())
+            .await
+            .unwrap_err()
+            .to_string(),
+        "API key not found"
+    );
+}
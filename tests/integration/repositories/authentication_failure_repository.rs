@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    config::LockoutConfig,
+    models::{
+        audit_event::{
+            AUDIT_EVENT_AUTHENTICATION_LOCKOUT, AUDIT_EVENT_AUTHENTICATION_UNLOCK, AuditEventFilter,
+        },
+        authentication_failure::{AuthenticationFailureFilter, AuthenticationSubjectType},
+    },
+    repositories::{
+        audit_event_repository::AuditEventRepository,
+        authentication_failure_repository::AuthenticationFailureRepository,
+    },
+};
+use sqlx::PgPool;
+
+const SUBJECT: &str = "30000000-0000-0000-0000-000000000001";
+
+fn config() -> LockoutConfig {
+    LockoutConfig {
+        max_failures: 3,
+        backoff_after_failures: 1,
+        ..Default::default()
+    }
+}
+
+fn subjects() -> Vec<(AuthenticationSubjectType, String)> {
+    vec![
+        (
+            AuthenticationSubjectType::ServiceAccount,
+            SUBJECT.to_string(),
+        ),
+        (AuthenticationSubjectType::Ip, "203.0.113.7".to_string()),
+    ]
+}
+
+#[sqlx::test]
+async fn test_authentication_failure_repository_backs_off_then_locks(pool: PgPool) {
+    let repository = AuthenticationFailureRepository::new(Arc::new(pool.clone()));
+    let subject_type = AuthenticationSubjectType::ServiceAccount;
+
+    let failure = repository
+        .record_failure(subject_type, SUBJECT, &config())
+        .await
+        .unwrap();
+    assert_eq!(failure.failure_count, 1);
+    assert!(!failure.is_locked());
+    assert!(
+        repository
+            .find_blocked_until(&subjects())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let failure = repository
+        .record_failure(subject_type, SUBJECT, &config())
+        .await
+        .unwrap();
+    assert_eq!(failure.failure_count, 2);
+    assert!(!failure.is_locked());
+    assert!(
+        repository
+            .find_blocked_until(&subjects())
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    let failure = repository
+        .record_failure(subject_type, SUBJECT, &config())
+        .await
+        .unwrap();
+    assert_eq!(failure.failure_count, 3);
+    assert!(failure.is_locked());
+
+    let events = AuditEventRepository::new(Arc::new(pool))
+        .find(
+            AuditEventFilter {
+                event_type: Some(AUDIT_EVENT_AUTHENTICATION_LOCKOUT.to_string()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].subject_type, "service_account");
+    assert_eq!(events[0].subject, SUBJECT);
+}
+
+#[sqlx::test]
+async fn test_authentication_failure_repository_forgets_failures_outside_window(pool: PgPool) {
+    let repository = AuthenticationFailureRepository::new(Arc::new(pool.clone()));
+    let subject_type = AuthenticationSubjectType::Ip;
+
+    repository
+        .record_failure(subject_type, "203.0.113.7", &config())
+        .await
+        .unwrap();
+    sqlx::query("UPDATE authentication_failures SET last_failure_at = now() - interval '1 hour'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let failure = repository
+        .record_failure(subject_type, "203.0.113.7", &config())
+        .await
+        .unwrap();
+    assert_eq!(failure.failure_count, 1);
+}
+
+#[sqlx::test]
+async fn test_authentication_failure_repository_unlock_records_audit_event(pool: PgPool) {
+    let repository = AuthenticationFailureRepository::new(Arc::new(pool.clone()));
+    let subject_type = AuthenticationSubjectType::ServiceAccount;
+    for _ in 0..3 {
+        repository
+            .record_failure(subject_type, SUBJECT, &config())
+            .await
+            .unwrap();
+    }
+
+    repository.unlock(subject_type, SUBJECT).await.unwrap();
+
+    assert!(
+        repository
+            .find_blocked_until(&subjects())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let events = AuditEventRepository::new(Arc::new(pool))
+        .find(
+            AuditEventFilter {
+                event_type: Some(AUDIT_EVENT_AUTHENTICATION_UNLOCK.to_string()),
+                subject: Some(SUBJECT.to_string()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+
+    let result = repository.unlock(subject_type, SUBJECT).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Authentication failures not found"
+    );
+}
+
+#[sqlx::test]
+async fn test_authentication_failure_repository_find_filters_locked(pool: PgPool) {
+    let repository = AuthenticationFailureRepository::new(Arc::new(pool));
+    for _ in 0..3 {
+        repository
+            .record_failure(
+                AuthenticationSubjectType::ServiceAccount,
+                SUBJECT,
+                &config(),
+            )
+            .await
+            .unwrap();
+    }
+    repository
+        .record_failure(AuthenticationSubjectType::Ip, "203.0.113.7", &config())
+        .await
+        .unwrap();
+
+    let locked = repository
+        .find(
+            AuthenticationFailureFilter {
+                locked: Some(true),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(locked.len(), 1);
+    assert_eq!(locked[0].subject, SUBJECT);
+
+    let ips = repository
+        .find(
+            AuthenticationFailureFilter {
+                subject_type: Some("ip".to_string()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(ips.len(), 1);
+    assert_eq!(ips[0].subject, "203.0.113.7");
+
+    let result = repository
+        .find(
+            AuthenticationFailureFilter {
+                subject_type: Some("user".to_string()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    models::claim_template::{
+        ClaimTemplateCreatePayload, ClaimTemplateFilter, ClaimTemplateUpdatePayload,
+    },
+    repositories::{base::Repository, claim_template_repository::ClaimTemplateRepository},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const PROJECT_ID: &str = "10000000-0000-0000-0000-000000000001";
+const DEV_ENVIRONMENT_ID: &str = "20000000-0000-0000-0000-000000000001";
+const OTHER_ENVIRONMENT_ID: &str = "20000000-0000-0000-0000-000000000003";
+const DEV_ACCESS_ID: &str = "40000000-0000-0000-0000-000000000001";
+const PROD_ACCESS_ID: &str = "40000000-0000-0000-0000-000000000002";
+const PROJECT_TEMPLATE_ID: &str = "80000000-0000-0000-0000-000000000001";
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_claim_template_repository_create_access_template_succeeds(pool: PgPool) {
+    let repository = ClaimTemplateRepository::new(Arc::new(pool));
+    let payload = ClaimTemplateCreatePayload {
+        project_id: PROJECT_ID.to_string(),
+        project_access_id: Some(PROD_ACCESS_ID.to_string()),
+        claim: "tenant".to_string(),
+        template: "acme".to_string(),
+        ..Default::default()
+    };
+
+    let claim_template = repository.create(payload).await.unwrap();
+    assert!(claim_template.id.is_some());
+    assert_eq!(
+        claim_template.project_access_id,
+        Some(Uuid::parse_str(PROD_ACCESS_ID).unwrap())
+    );
+    assert_eq!(claim_template.precedence(), 2);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_claim_template_repository_create_duplicate_fails(pool: PgPool) {
+    let repository = ClaimTemplateRepository::new(Arc::new(pool));
+    let payload = ClaimTemplateCreatePayload {
+        project_id: PROJECT_ID.to_string(),
+        claim: "tenant".to_string(),
+        template: "acme".to_string(),
+        ..Default::default()
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Claim template already exists"
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_claim_template_repository_create_reserved_claim_fails(pool: PgPool) {
+    let repository = ClaimTemplateRepository::new(Arc::new(pool));
+    let payload = ClaimTemplateCreatePayload {
+        project_id: PROJECT_ID.to_string(),
+        claim: "exp".to_string(),
+        template: "0".to_string(),
+        ..Default::default()
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Claim exp is reserved and cannot be templated"
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_claim_template_repository_create_with_foreign_environment_fails(pool: PgPool) {
+    let repository = ClaimTemplateRepository::new(Arc::new(pool));
+    let payload = ClaimTemplateCreatePayload {
+        project_id: PROJECT_ID.to_string(),
+        environment_id: Some(OTHER_ENVIRONMENT_ID.to_string()),
+        claim: "tenant".to_string(),
+        template: "acme".to_string(),
+        ..Default::default()
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Environment does not belong to the project"
+    );
+}
+
+#[sqlx::test]
+async fn test_claim_template_repository_create_with_missing_project_fails(pool: PgPool) {
+    let repository = ClaimTemplateRepository::new(Arc::new(pool));
+    let payload = ClaimTemplateCreatePayload {
+        project_id: Uuid::new_v4().to_string(),
+        claim: "tenant".to_string(),
+        template: "acme".to_string(),
+        ..Default::default()
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(result.unwrap_err().to_string(), "Project not found");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_claim_template_repository_update_template_succeeds(pool: PgPool) {
+    let repository = ClaimTemplateRepository::new(Arc::new(pool));
+    let id = Uuid::parse_str(PROJECT_TEMPLATE_ID).unwrap();
+    let update = ClaimTemplateUpdatePayload {
+        template: Some("{{project.id}}".to_string()),
+        ..Default::default()
+    };
+
+    let claim_template = repository.update(id, update).await.unwrap();
+    assert_eq!(claim_template.claim, "tenant");
+    assert_eq!(claim_template.template, "{{project.id}}");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_claim_template_repository_update_with_unknown_placeholder_fails(pool: PgPool) {
+    let repository = ClaimTemplateRepository::new(Arc::new(pool));
+    let id = Uuid::parse_str(PROJECT_TEMPLATE_ID).unwrap();
+    let update = ClaimTemplateUpdatePayload {
+        template: Some("{{service_account.secret}}".to_string()),
+        ..Default::default()
+    };
+
+    let result = repository.update(id, update).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Unknown placeholder: service_account.secret"
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_claim_template_repository_delete_existing_succeeds(pool: PgPool) {
+    let repository = ClaimTemplateRepository::new(Arc::new(pool));
+    let id = Uuid::parse_str(PROJECT_TEMPLATE_ID).unwrap();
+
+    assert!(repository.delete(id).await.unwrap());
+    let result = repository.read(id).await;
+    assert_eq!(result.unwrap_err().to_string(), "Claim template not found");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_claim_template_repository_find_with_filters(pool: PgPool) {
+    let repository = ClaimTemplateRepository::new(Arc::new(pool));
+    let filter = ClaimTemplateFilter {
+        project_id: Some(PROJECT_ID.to_string()),
+        claim: Some("tenant".to_string()),
+        ..Default::default()
+    };
+    let claim_templates = repository.find(filter, None, None).await.unwrap();
+    assert_eq!(claim_templates.len(), 2);
+
+    let filter = ClaimTemplateFilter {
+        environment_id: Some(DEV_ENVIRONMENT_ID.to_string()),
+        ..Default::default()
+    };
+    let claim_templates = repository.find(filter, None, None).await.unwrap();
+    assert_eq!(claim_templates.len(), 1);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_claim_template_repository_find_applicable_renders_by_precedence(pool: PgPool) {
+    let repository = ClaimTemplateRepository::new(Arc::new(pool));
+
+    let project_access_id = Uuid::parse_str(DEV_ACCESS_ID).unwrap();
+    let templates = repository.find_applicable(project_access_id).await.unwrap();
+    assert_eq!(templates.len(), 4);
+    let context = repository.context(project_access_id).await.unwrap();
+    let claims = context.render_claims(&templates).unwrap();
+    assert_eq!(claims.get("tenant"), Some(&"payments-dev".to_string()));
+    assert_eq!(claims.get("region"), Some(&"eu-west".to_string()));
+    assert_eq!(claims.get("owner"), Some(&"worker@example.com".to_string()));
+
+    let project_access_id = Uuid::parse_str(PROD_ACCESS_ID).unwrap();
+    let templates = repository.find_applicable(project_access_id).await.unwrap();
+    assert_eq!(templates.len(), 2);
+    let context = repository.context(project_access_id).await.unwrap();
+    let claims = context.render_claims(&templates).unwrap();
+    assert_eq!(claims.get("tenant"), Some(&"payments".to_string()));
+    assert!(!claims.contains_key("owner"));
+}
@@ -1,23 +1,29 @@
 use std::sync::Arc;
 
 use sentinel_guard::models::environment_key::{
-    EnvironmentKeyCreatePayload, EnvironmentKeyFilter, EnvironmentKeySortOrder,
-    EnvironmentKeySortableFields, EnvironmentKeyUpdatePayload,
+    EnvironmentKeyCreatePayload, EnvironmentKeyFilter, EnvironmentKeyImportPayload,
+    EnvironmentKeySortOrder, EnvironmentKeySortableFields, EnvironmentKeyUpdatePayload,
 };
 use sentinel_guard::models::sort::SortOrder;
 use sentinel_guard::repositories::base::Repository;
 use sentinel_guard::repositories::environment_key_repository::EnvironmentKeyRepository;
+use sentinel_guard::utils::key_store::file::FileKeyStore;
+use sentinel_guard::utils::metrics::METRICS;
+use sentinel_guard::utils::tokens::key_builder::{Claims, KeyBuilder, KeyEncoding};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::integration::secrets_manager;
+
 // CREATE
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_create_environment_key_valid(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let payload = EnvironmentKeyCreatePayload {
         environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
         algorithm: "HS384".to_string(),
         active: true,
+        rotation_interval_days: None,
     };
     let created = repo.create(payload).await.unwrap();
     assert_eq!(
@@ -30,11 +36,12 @@ async fn test_create_environment_key_valid(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_create_environment_key_duplicate_fails(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let payload = EnvironmentKeyCreatePayload {
         environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
         algorithm: "HS256".to_string(), // already exists in fixture
         active: true,
+        rotation_interval_days: None,
     };
     let result = repo.create(payload).await;
     dbg!(&result);
@@ -47,11 +54,12 @@ async fn test_create_environment_key_duplicate_fails(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_create_environment_key_missing_env_fails(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let payload = EnvironmentKeyCreatePayload {
         environment_id: Uuid::new_v4().to_string(), // non-existent
         algorithm: "HS512".to_string(),
         active: true,
+        rotation_interval_days: None,
     };
     let result = repo.create(payload).await;
     assert!(result.is_err());
@@ -64,7 +72,7 @@ async fn test_create_environment_key_missing_env_fails(pool: PgPool) {
 // READ
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_read_environment_key_existing(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let key = repo
         .read(Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap())
         .await
@@ -80,7 +88,7 @@ async fn test_read_environment_key_existing(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_read_environment_key_nonexistent(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let result = repo.read(Uuid::new_v4()).await;
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().to_string(), "Environment key not found");
@@ -89,9 +97,10 @@ async fn test_read_environment_key_nonexistent(pool: PgPool) {
 // UPDATE
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_update_environment_key_active(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let update = EnvironmentKeyUpdatePayload {
         active: Some(false),
+        rotation_interval_days: None,
     };
     let updated = repo
         .update(
@@ -110,8 +119,11 @@ async fn test_update_environment_key_active(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_update_environment_key_no_changes(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
-    let update = EnvironmentKeyUpdatePayload { active: None };
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let update = EnvironmentKeyUpdatePayload {
+        active: None,
+        rotation_interval_days: None,
+    };
     let result = repo
         .update(
             Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap(),
@@ -124,8 +136,11 @@ async fn test_update_environment_key_no_changes(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_update_environment_key_nonexistent(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
-    let update = EnvironmentKeyUpdatePayload { active: Some(true) };
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let update = EnvironmentKeyUpdatePayload {
+        active: Some(true),
+        rotation_interval_days: None,
+    };
     let result = repo.update(Uuid::new_v4(), update).await;
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().to_string(), "Environment key not found");
@@ -134,7 +149,7 @@ async fn test_update_environment_key_nonexistent(pool: PgPool) {
 // DELETE
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_delete_environment_key_existing(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let deleted = repo
         .delete(Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap())
         .await
@@ -144,7 +159,7 @@ async fn test_delete_environment_key_existing(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_delete_environment_key_nonexistent(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let result = repo.delete(Uuid::new_v4()).await;
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().to_string(), "Environment key not found");
@@ -153,7 +168,7 @@ async fn test_delete_environment_key_nonexistent(pool: PgPool) {
 // FIND
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_find_environment_keys_by_env(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let filter = EnvironmentKeyFilter {
         environment_id: Some("123e4567-e89b-12d3-a456-426614174000".to_string()),
         algorithm: None,
@@ -170,7 +185,7 @@ async fn test_find_environment_keys_by_env(pool: PgPool) {
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_find_environment_keys_with_pagination(pool: PgPool) {
     use sentinel_guard::models::pagination::Pagination;
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let filter = EnvironmentKeyFilter::default();
     let sort = None;
     let pagination = Some(Pagination {
@@ -183,7 +198,7 @@ async fn test_find_environment_keys_with_pagination(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_get_environment_key_rsa(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let environment_id = uuid::Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap();
     let algorithm = jsonwebtoken::Algorithm::RS512;
 
@@ -192,6 +207,7 @@ async fn test_get_environment_key_rsa(pool: PgPool) {
             environment_id: environment_id.to_string(),
             algorithm: format!("{:?}", algorithm),
             active: true,
+            rotation_interval_days: None,
         })
         .await
         .unwrap();
@@ -206,7 +222,7 @@ async fn test_get_environment_key_rsa(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_get_environment_key_hmac(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let environment_id = uuid::Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap();
     let algorithm = jsonwebtoken::Algorithm::HS384;
 
@@ -215,6 +231,7 @@ async fn test_get_environment_key_hmac(pool: PgPool) {
             environment_id: environment_id.to_string(),
             algorithm: format!("{:?}", algorithm),
             active: true,
+            rotation_interval_days: None,
         })
         .await
         .unwrap();
@@ -228,7 +245,7 @@ async fn test_get_environment_key_hmac(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_rotate_key_existing_active(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let id = Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap();
 
     // Get the original key and updated_at
@@ -243,7 +260,9 @@ async fn test_rotate_key_existing_active(pool: PgPool) {
     let original_updated_at = row.updated_at;
 
     // Rotate the key
+    let rotations = METRICS.environment_key_rotations_total.get(&["manual"]);
     let rotated = repo.clone().rotate_key(id).await.unwrap();
+    assert!(METRICS.environment_key_rotations_total.get(&["manual"]) > rotations);
     assert_eq!(rotated.id, Some(id));
     assert_eq!(
         rotated.environment_id,
@@ -265,7 +284,7 @@ async fn test_rotate_key_existing_active(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_rotate_key_nonexistent(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let id = Uuid::new_v4();
     let result = repo.clone().rotate_key(id).await;
     assert!(result.is_err());
@@ -274,7 +293,7 @@ async fn test_rotate_key_nonexistent(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_rotate_key_inactive(pool: PgPool) {
-    let repo = EnvironmentKeyRepository::new(Arc::new(pool));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
     let id = Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap(); // inactive key
 
     // Get the original key and updated_at
@@ -308,3 +327,369 @@ async fn test_rotate_key_inactive(pool: PgPool) {
     assert_ne!(row.key, original_key);
     assert!(row.updated_at > original_updated_at);
 }
+
+// ACTIVE KEY
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_get_active_key_returns_decrypted_key(pool: PgPool) {
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let environment_id = Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap();
+    let created = repo
+        .create(EnvironmentKeyCreatePayload {
+            environment_id: environment_id.to_string(),
+            algorithm: "HS512".to_string(),
+            active: true,
+            rotation_interval_days: None,
+        })
+        .await
+        .unwrap();
+
+    let (key, secret) = repo
+        .get_active_key(environment_id, Some(jsonwebtoken::Algorithm::HS512))
+        .await
+        .unwrap();
+    assert_eq!(key.id, created.id);
+    assert_eq!(secret.len(), 128);
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_get_active_key_skips_inactive_keys(pool: PgPool) {
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let environment_id = Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap();
+    let result = repo
+        .get_active_key(environment_id, Some(jsonwebtoken::Algorithm::RS256))
+        .await;
+    assert!(result.is_err());
+    assert_eq!(result.unwrap_err().to_string(), "Environment key not found");
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_find_active_key_returns_none_without_active_key(pool: PgPool) {
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let environment_id = Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap();
+    let found = repo
+        .find_active_key(environment_id, Some(jsonwebtoken::Algorithm::RS256))
+        .await
+        .unwrap();
+    assert!(found.is_none());
+}
+
+// IMPORT
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_import_environment_key_hmac_secret(pool: PgPool) {
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let environment_id = Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap();
+    let secret = "ab".repeat(64);
+
+    let imported = repo
+        .import_key(EnvironmentKeyImportPayload {
+            environment_id: environment_id.to_string(),
+            algorithm: "HS512".to_string(),
+            key: secret.clone(),
+            encoding: Some(KeyEncoding::Hex),
+            active: true,
+            rotation_interval_days: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(format!("{:?}", imported.algorithm), "HS512");
+
+    let row = sqlx::query!("SELECT key FROM environment_key WHERE id = $1", imported.id)
+        .fetch_one(&*repo.pool)
+        .await
+        .unwrap();
+    assert_ne!(row.key, secret);
+
+    let key = repo
+        .get_environment_key(environment_id, jsonwebtoken::Algorithm::HS512)
+        .await
+        .unwrap();
+    assert_eq!(key, secret);
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_import_environment_key_rsa_private_key(pool: PgPool) {
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let environment_id = Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap();
+    let key_pair = KeyBuilder::new()
+        .generate_key(jsonwebtoken::Algorithm::RS512)
+        .unwrap();
+
+    repo.import_key(EnvironmentKeyImportPayload {
+        environment_id: environment_id.to_string(),
+        algorithm: "RS512".to_string(),
+        key: key_pair.private_key_str.clone(),
+        encoding: None,
+        active: true,
+        rotation_interval_days: None,
+    })
+    .await
+    .unwrap();
+
+    let key = repo
+        .get_environment_key(environment_id, jsonwebtoken::Algorithm::RS512)
+        .await
+        .unwrap();
+    assert_eq!(key, key_pair.private_key_str);
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_import_environment_key_mismatched_algorithm_fails(pool: PgPool) {
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let result = repo
+        .import_key(EnvironmentKeyImportPayload {
+            environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            algorithm: "HS384".to_string(),
+            key: "ab".repeat(32),
+            encoding: Some(KeyEncoding::Hex),
+            active: true,
+            rotation_interval_days: None,
+        })
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "HMAC secret for HS384 must be at least 384 bits"
+    );
+
+    let result = repo
+        .import_key(EnvironmentKeyImportPayload {
+            environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            algorithm: "PS256".to_string(),
+            key: "ab".repeat(64),
+            encoding: None,
+            active: true,
+            rotation_interval_days: None,
+        })
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Private key must be a PEM encoded RSA key"
+    );
+}
+
+// ROTATION SCHEDULE
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_create_environment_key_with_rotation_schedule(pool: PgPool) {
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let created = repo
+        .create(EnvironmentKeyCreatePayload {
+            environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            algorithm: "HS512".to_string(),
+            active: true,
+            rotation_interval_days: Some(90),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(created.rotation_interval_days, Some(90));
+    let until_rotation = created.next_rotation_at.unwrap() - chrono::Utc::now();
+    assert!(until_rotation > chrono::Duration::days(89));
+    assert!(until_rotation <= chrono::Duration::days(90));
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_create_environment_key_jitters_first_rotation(pool: PgPool) {
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager())
+        .with_rotation_jitter(86400);
+    let mut jitters = Vec::new();
+    for algorithm in ["HS384", "HS512", "RS384"] {
+        let started_at = chrono::Utc::now();
+        let created = repo
+            .create(EnvironmentKeyCreatePayload {
+                environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                algorithm: algorithm.to_string(),
+                active: true,
+                rotation_interval_days: Some(30),
+            })
+            .await
+            .unwrap();
+        let jitter = created.next_rotation_at.unwrap() - started_at - chrono::Duration::days(30);
+        assert!(jitter >= chrono::Duration::zero());
+        assert!(jitter <= chrono::Duration::days(1) + chrono::Duration::seconds(1));
+        jitters.push(jitter);
+    }
+
+    // Keys created together do not come due together
+    assert!(
+        jitters
+            .iter()
+            .any(|jitter| *jitter > chrono::Duration::seconds(1))
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_update_environment_key_rotation_schedule(pool: PgPool) {
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let id = Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap();
+
+    let updated = repo
+        .update(
+            id,
+            EnvironmentKeyUpdatePayload {
+                active: None,
+                rotation_interval_days: Some(30),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.rotation_interval_days, Some(30));
+    assert!(updated.next_rotation_at.unwrap() > chrono::Utc::now() + chrono::Duration::days(29));
+
+    let rotated = repo.rotate_key(id).await.unwrap();
+    assert!(rotated.next_rotation_at.unwrap() >= updated.next_rotation_at.unwrap());
+
+    let updated = repo
+        .update(
+            id,
+            EnvironmentKeyUpdatePayload {
+                active: None,
+                rotation_interval_days: Some(0),
+            },
+        )
+        .await
+        .unwrap();
+    assert!(updated.rotation_interval_days.is_none());
+    assert!(updated.next_rotation_at.is_none());
+
+    let result = repo
+        .update(
+            id,
+            EnvironmentKeyUpdatePayload {
+                active: None,
+                rotation_interval_days: Some(-1),
+            },
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Rotation interval days must not be negative"
+    );
+}
+
+// KEY STORE
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_file_key_store_keeps_key_material_out_of_the_database(pool: PgPool) {
+    let directory = std::env::temp_dir().join(format!("sentinel-guard-keys-{}", Uuid::new_v4()));
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager())
+        .with_key_store(Arc::new(FileKeyStore::new(&directory)));
+    let environment_id = Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap();
+    let created = repo
+        .create(EnvironmentKeyCreatePayload {
+            environment_id: environment_id.to_string(),
+            algorithm: "RS384".to_string(),
+            active: true,
+            rotation_interval_days: None,
+        })
+        .await
+        .unwrap();
+
+    let (key, reference) = repo
+        .find_active_key(environment_id, Some(jsonwebtoken::Algorithm::RS384))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(key.id, created.id);
+    let path = directory.join(environment_id.to_string()).join(&reference);
+    let material = std::fs::read_to_string(&path).unwrap();
+    assert!(material.contains("PRIVATE KEY"));
+
+    let claims = Claims::new("worker", 300).with_jti("jti-1");
+    let token = repo.sign_jwt(&key, &reference, &claims).await.unwrap();
+    assert_eq!(
+        jsonwebtoken::decode_header(&token).unwrap().kid,
+        created.id.map(|id| id.to_string())
+    );
+    let validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::RS384);
+    let decoded: Claims = repo
+        .verify_jwt(
+            environment_id,
+            jsonwebtoken::Algorithm::RS384,
+            &token,
+            &validation,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(decoded.jti.as_deref(), Some("jti-1"));
+
+    // Tokens of the replaced key keep verifying until they expire
+    repo.rotate_key(created.id.unwrap()).await.unwrap();
+    assert!(path.exists());
+    let decoded: Option<Claims> = repo
+        .verify_jwt(
+            environment_id,
+            jsonwebtoken::Algorithm::RS384,
+            &token,
+            &validation,
+        )
+        .await
+        .unwrap();
+    assert!(decoded.is_some());
+    assert_eq!(repo.discard_expired_previous_keys().await.unwrap(), 0);
+
+    // Once the longest token lifetime elapsed, the replaced key file is discarded
+    sqlx::query!(
+        "UPDATE environment_key SET previous_key_expires_at = now() - interval '1 second' WHERE id = $1",
+        created.id.unwrap()
+    )
+    .execute(&*repo.pool)
+    .await
+    .unwrap();
+    let decoded: Option<Claims> = repo
+        .verify_jwt(
+            environment_id,
+            jsonwebtoken::Algorithm::RS384,
+            &token,
+            &validation,
+        )
+        .await
+        .unwrap();
+    assert!(decoded.is_none());
+    assert_eq!(repo.discard_expired_previous_keys().await.unwrap(), 1);
+    assert!(!path.exists());
+
+    let (_, reference) = repo
+        .find_active_key(environment_id, Some(jsonwebtoken::Algorithm::RS384))
+        .await
+        .unwrap()
+        .unwrap();
+    let path = directory.join(environment_id.to_string()).join(&reference);
+    assert!(path.exists());
+
+    repo.delete(created.id.unwrap()).await.unwrap();
+    assert!(!path.exists());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_ensure_key_store_holds_keys_detects_keys_of_another_store(pool: PgPool) {
+    sqlx::query("DELETE FROM environment_key")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let repo = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let created = repo
+        .create(EnvironmentKeyCreatePayload {
+            environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            algorithm: "HS256".to_string(),
+            active: true,
+            rotation_interval_days: None,
+        })
+        .await
+        .unwrap();
+    repo.ensure_key_store_holds_keys().await.unwrap();
+
+    // Switching to the file store leaves the key encrypted in the database
+    let directory = std::env::temp_dir().join(format!("sentinel-guard-keys-{}", Uuid::new_v4()));
+    let result = repo
+        .with_key_store(Arc::new(FileKeyStore::new(&directory)))
+        .ensure_key_store_holds_keys()
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        format!(
+            "Environment keys {} are not kept in the configured key store, migrate them before changing security.key_store.backend",
+            created.id.unwrap()
+        )
+    );
+}
@@ -1,8 +1,14 @@
 pub mod access_token_repository;
+pub mod api_key_repository;
+pub mod authentication_failure_repository;
+pub mod claim_template_repository;
 pub mod environment_key_repository;
 pub mod environment_repository;
 pub mod project_access_repository;
 pub mod project_access_scopes_repository;
 pub mod project_repository;
 pub mod project_scope_repository;
+pub mod service_account_credential_repository;
+pub mod service_account_key_repository;
 pub mod service_account_repository;
+pub mod token_policy_repository;
@@ -20,6 +20,7 @@ async fn test_project_access_repository_create_with_valid_data_succeeds(pool: Pg
         service_account_id: "123e4567-e89b-12d3-a456-426614179998".to_string(),
         environment_id: "00000000-0000-0000-0000-000000009999".to_string(),
         enabled: true,
+        allowed_cidrs: None,
     };
     let project_access = repository.create(payload.clone()).await.unwrap();
     assert_eq!(
@@ -45,6 +46,7 @@ async fn test_project_access_repository_create_with_missing_project_id_fails(poo
         service_account_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
         environment_id: "00000000-0000-0000-0000-000000000001".to_string(),
         enabled: true,
+        allowed_cidrs: None,
     };
     let result = repository.create(payload).await;
     assert!(result.is_err());
@@ -60,6 +62,7 @@ async fn test_project_access_repository_create_with_duplicate_fails(pool: PgPool
         service_account_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
         environment_id: "00000000-0000-0000-0000-000000000001".to_string(),
         enabled: true,
+        allowed_cidrs: None,
     };
     let result = repository.create(payload).await;
     assert!(result.is_err());
@@ -100,6 +103,7 @@ async fn test_project_access_repository_update_enabled_false_succeeds(pool: PgPo
     let id = Uuid::parse_str("00000000-0000-0000-0000-000000000101").unwrap();
     let update = ProjectAccessUpdatePayload {
         enabled: Some(false),
+        allowed_cidrs: None,
     };
     let project_access = repository.update(id, update).await.unwrap();
     assert!(!project_access.enabled);
@@ -111,6 +115,7 @@ async fn test_project_access_repository_update_enabled_true_succeeds(pool: PgPoo
     let id = Uuid::parse_str("00000000-0000-0000-0000-000000000102").unwrap();
     let update = ProjectAccessUpdatePayload {
         enabled: Some(true),
+        allowed_cidrs: None,
     };
     let project_access = repository.update(id, update).await.unwrap();
     assert!(project_access.enabled);
@@ -224,3 +229,33 @@ async fn test_project_access_find_with_enabled_filter(pool: PgPool) {
     let project_accesses = repository.find(filter, sort, pagination).await.unwrap();
     assert_eq!(project_accesses.len(), 1);
 }
+
+#[sqlx::test(fixtures("../fixtures/project_access.sql"))]
+async fn test_project_access_repository_update_allowed_cidrs_succeeds(pool: PgPool) {
+    let repository = ProjectAccessRepository::new(Arc::new(pool));
+    let id = Uuid::parse_str("00000000-0000-0000-0000-000000000101").unwrap();
+
+    let update = ProjectAccessUpdatePayload {
+        enabled: None,
+        allowed_cidrs: Some(vec!["10.1.0.0/16".to_string()]),
+    };
+    let project_access = repository.update(id, update).await.unwrap();
+    assert_eq!(project_access.allowed_cidrs, vec!["10.1.0.0/16"]);
+
+    let update = ProjectAccessUpdatePayload {
+        enabled: None,
+        allowed_cidrs: Some(vec![]),
+    };
+    let project_access = repository.update(id, update).await.unwrap();
+    assert!(project_access.allowed_cidrs.is_empty());
+
+    let update = ProjectAccessUpdatePayload {
+        enabled: None,
+        allowed_cidrs: Some(vec!["everywhere".to_string()]),
+    };
+    let result = repository.update(id, update).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid CIDR range: everywhere"
+    );
+}
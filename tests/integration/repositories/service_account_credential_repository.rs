@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use sentinel_guard::{
+    models::service_account_credential::{
+        ServiceAccountCredentialCreatePayload, ServiceAccountCredentialCreatePayloadWithSecret,
+        ServiceAccountCredentialFilter, ServiceAccountCredentialRotatePayload,
+        ServiceAccountCredentialUpdatePayload,
+    },
+    repositories::{
+        base::Repository, service_account_credential_repository::ServiceAccountCredentialRepository,
+    },
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const SERVICE_ACCOUNT_ID: &str = "30000000-0000-0000-0000-000000000001";
+
+fn payload(label: &str) -> ServiceAccountCredentialCreatePayloadWithSecret {
+    ServiceAccountCredentialCreatePayload {
+        label: label.to_string(),
+        expires_at: None,
+    }
+    .with_secret(Uuid::parse_str(SERVICE_ACCOUNT_ID).unwrap())
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_repository_create_stores_secret_hash(pool: PgPool) {
+    let repository = ServiceAccountCredentialRepository::new(Arc::new(pool));
+    let payload = payload("ci");
+    let secret = payload.secret.clone();
+
+    let credential = repository.create(payload).await.unwrap();
+    assert!(credential.id.is_some());
+    assert_eq!(credential.label, "ci");
+    assert_ne!(credential.secret_hash, secret);
+    assert!(credential.expires_at.is_none());
+    assert!(credential.last_used_at.is_none());
+}
+
+#[sqlx::test]
+async fn test_service_account_credential_repository_create_with_missing_service_account_fails(
+    pool: PgPool,
+) {
+    let repository = ServiceAccountCredentialRepository::new(Arc::new(pool));
+
+    let result = repository.create(payload("ci")).await;
+    assert_eq!(result.unwrap_err().to_string(), "Service account not found");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_repository_allows_several_credentials(pool: PgPool) {
+    let repository = ServiceAccountCredentialRepository::new(Arc::new(pool));
+    repository.create(payload("ci")).await.unwrap();
+    repository.create(payload("ci")).await.unwrap();
+
+    let credentials = repository
+        .find(
+            ServiceAccountCredentialFilter {
+                service_account_id: Some(SERVICE_ACCOUNT_ID.to_string()),
+                active: Some(true),
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(credentials.len(), 2);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_repository_verify_records_use(pool: PgPool) {
+    let repository = ServiceAccountCredentialRepository::new(Arc::new(pool));
+    let payload = payload("ci");
+    let secret = payload.secret.clone();
+    let service_account_id = Uuid::parse_str(SERVICE_ACCOUNT_ID).unwrap();
+    repository.create(payload).await.unwrap();
+
+    let credential = repository
+        .verify(service_account_id, &secret)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(credential.last_used_at.is_some());
+
+    assert!(
+        repository
+            .verify(service_account_id, "wrong-secret")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        repository
+            .verify(Uuid::new_v4(), &secret)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_repository_rotate_keeps_old_credential_for_grace_period(
+    pool: PgPool,
+) {
+    let repository = ServiceAccountCredentialRepository::new(Arc::new(pool));
+    let old = repository.create(payload("ci")).await.unwrap();
+
+    let new = repository
+        .rotate(
+            old.id.unwrap(),
+            "new-secret",
+            ServiceAccountCredentialRotatePayload {
+                grace_period_seconds: Some(600),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_ne!(new.id, old.id);
+    assert_eq!(new.label, "ci");
+    assert!(new.expires_at.is_none());
+
+    let old = repository.read(old.id.unwrap()).await.unwrap().unwrap();
+    let expires_at = old.expires_at.unwrap();
+    assert!(old.is_active());
+    assert!(expires_at <= Utc::now() + Duration::seconds(600));
+    assert!(expires_at > Utc::now() + Duration::seconds(590));
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_repository_rotate_keeps_earlier_expiry(pool: PgPool) {
+    let repository = ServiceAccountCredentialRepository::new(Arc::new(pool));
+    let expires_at = Utc::now() + Duration::minutes(5);
+    let old = repository
+        .create(ServiceAccountCredentialCreatePayloadWithSecret {
+            expires_at: Some(expires_at.to_rfc3339()),
+            ..payload("ci")
+        })
+        .await
+        .unwrap();
+
+    repository
+        .rotate(
+            old.id.unwrap(),
+            "new-secret",
+            ServiceAccountCredentialRotatePayload::default(),
+        )
+        .await
+        .unwrap();
+
+    let old = repository.read(old.id.unwrap()).await.unwrap().unwrap();
+    assert_eq!(
+        old.expires_at.unwrap().timestamp_micros(),
+        expires_at.timestamp_micros()
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_repository_update_succeeds(pool: PgPool) {
+    let repository = ServiceAccountCredentialRepository::new(Arc::new(pool));
+    let credential = repository.create(payload("ci")).await.unwrap();
+
+    let updated = repository
+        .update(
+            credential.id.unwrap(),
+            ServiceAccountCredentialUpdatePayload {
+                label: Some("deploy".to_string()),
+                expires_at: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.label, "deploy");
+    assert_eq!(updated.secret_hash, credential.secret_hash);
+
+    let result = repository
+        .update(
+            credential.id.unwrap(),
+            ServiceAccountCredentialUpdatePayload::default(),
+        )
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), "No changes to update");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_repository_delete_succeeds(pool: PgPool) {
+    let repository = ServiceAccountCredentialRepository::new(Arc::new(pool));
+    let credential = repository.create(payload("ci")).await.unwrap();
+
+    assert!(repository.delete(credential.id.unwrap()).await.unwrap());
+    assert_eq!(
+        repository
+            .read(credential.id.unwrap())
+            .await
+            .unwrap_err()
+            .to_string(),
+        "Service account credential not found"
+    );
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use jsonwebtoken::Algorithm;
+use sentinel_guard::{
+    models::service_account_key::{
+        ServiceAccountKeyCreatePayload, ServiceAccountKeyFilter, ServiceAccountKeyUpdatePayload,
+    },
+    repositories::{base::Repository, service_account_key_repository::ServiceAccountKeyRepository},
+    utils::tokens::key_builder::KeyBuilder,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const SERVICE_ACCOUNT_ID: &str = "30000000-0000-0000-0000-000000000001";
+
+fn payload(name: &str) -> ServiceAccountKeyCreatePayload {
+    let key_pair = KeyBuilder::new().generate_key(Algorithm::RS256).unwrap();
+    ServiceAccountKeyCreatePayload {
+        service_account_id: SERVICE_ACCOUNT_ID.to_string(),
+        name: name.to_string(),
+        algorithm: "RS256".to_string(),
+        public_key: key_pair.public_key_str.unwrap(),
+        enabled: None,
+    }
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_key_repository_create_succeeds(pool: PgPool) {
+    let repository = ServiceAccountKeyRepository::new(Arc::new(pool));
+
+    let service_account_key = repository.create(payload("deployer")).await.unwrap();
+    assert!(service_account_key.id.is_some());
+    assert_eq!(service_account_key.algorithm, "RS256");
+    assert!(service_account_key.enabled);
+    assert!(
+        service_account_key
+            .public_key
+            .starts_with("-----BEGIN PUBLIC KEY-----")
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_key_repository_create_duplicate_name_fails(pool: PgPool) {
+    let repository = ServiceAccountKeyRepository::new(Arc::new(pool));
+    repository.create(payload("deployer")).await.unwrap();
+
+    let result = repository.create(payload("deployer")).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Service account key already exists"
+    );
+}
+
+#[sqlx::test]
+async fn test_service_account_key_repository_create_with_missing_service_account_fails(
+    pool: PgPool,
+) {
+    let repository = ServiceAccountKeyRepository::new(Arc::new(pool));
+    let payload = ServiceAccountKeyCreatePayload {
+        service_account_id: Uuid::new_v4().to_string(),
+        ..payload("deployer")
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(result.unwrap_err().to_string(), "Service account not found");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_key_repository_create_with_invalid_key_fails(pool: PgPool) {
+    let repository = ServiceAccountKeyRepository::new(Arc::new(pool));
+    let payload = ServiceAccountKeyCreatePayload {
+        public_key: "not a key".to_string(),
+        ..payload("deployer")
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(result.unwrap_err().to_string(), "Invalid public key");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_key_repository_disabled_key_is_not_used(pool: PgPool) {
+    let repository = ServiceAccountKeyRepository::new(Arc::new(pool));
+    let service_account_key = repository.create(payload("deployer")).await.unwrap();
+    let service_account_id = Uuid::parse_str(SERVICE_ACCOUNT_ID).unwrap();
+
+    let keys = repository
+        .find_enabled_by_service_account(service_account_id, "RS256")
+        .await
+        .unwrap();
+    assert_eq!(keys.len(), 1);
+
+    let update = ServiceAccountKeyUpdatePayload {
+        enabled: Some(false),
+        ..Default::default()
+    };
+    let updated = repository
+        .update(service_account_key.id.unwrap(), update)
+        .await
+        .unwrap();
+    assert!(!updated.enabled);
+    assert_eq!(updated.name, "deployer");
+
+    let keys = repository
+        .find_enabled_by_service_account(service_account_id, "RS256")
+        .await
+        .unwrap();
+    assert!(keys.is_empty());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_key_repository_find_with_filters(pool: PgPool) {
+    let repository = ServiceAccountKeyRepository::new(Arc::new(pool));
+    repository.create(payload("deployer")).await.unwrap();
+    repository
+        .create(ServiceAccountKeyCreatePayload {
+            enabled: Some(false),
+            ..payload("retired")
+        })
+        .await
+        .unwrap();
+
+    let filter = ServiceAccountKeyFilter {
+        service_account_id: Some(SERVICE_ACCOUNT_ID.to_string()),
+        ..Default::default()
+    };
+    assert_eq!(repository.find(filter, None, None).await.unwrap().len(), 2);
+
+    let filter = ServiceAccountKeyFilter {
+        enabled: Some(true),
+        ..Default::default()
+    };
+    let keys = repository.find(filter, None, None).await.unwrap();
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].name, "deployer");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_key_repository_record_assertion_jti_rejects_replay(pool: PgPool) {
+    let repository = ServiceAccountKeyRepository::new(Arc::new(pool));
+    let service_account_id = Uuid::parse_str(SERVICE_ACCOUNT_ID).unwrap();
+    let expires_at = Utc::now() + Duration::seconds(60);
+
+    assert!(
+        repository
+            .record_assertion_jti(service_account_id, "assertion-1", expires_at)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repository
+            .record_assertion_jti(service_account_id, "assertion-1", expires_at)
+            .await
+            .unwrap()
+    );
+    assert!(
+        repository
+            .record_assertion_jti(service_account_id, "assertion-2", expires_at)
+            .await
+            .unwrap()
+    );
+}
@@ -10,14 +10,15 @@ use sentinel_guard::{
         sort::SortOrder,
     },
     repositories::{base::Repository, service_account_repository::ServiceAccountRepository},
-    utils::security::SecretsManager,
 };
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::integration::secrets_manager;
+
 #[sqlx::test]
 async fn test_service_account_repository_create_with_valid_data_succeeds(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let payload = ServiceAccountCreatePayload {
         name: "Test Service Account".to_string(),
@@ -25,11 +26,13 @@ async fn test_service_account_repository_create_with_valid_data_succeeds(pool: P
         secret: "supersecret".to_string(),
         description: "Test Description".to_string(),
         enabled: true,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let service_account = repository.create(payload.clone()).await.unwrap();
 
-    let secrets_manager = SecretsManager::new(true).unwrap();
+    let secrets_manager = secrets_manager();
     let decrypt_password = secrets_manager
         .decrypt(&service_account.secret, &service_account.id.unwrap())
         .unwrap();
@@ -43,7 +46,7 @@ async fn test_service_account_repository_create_with_valid_data_succeeds(pool: P
 
 #[sqlx::test]
 async fn test_service_account_repository_create_with_duplicate_name_fails(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let mut payload = ServiceAccountCreatePayload {
         name: "Test Service Account".to_string(),
@@ -51,6 +54,8 @@ async fn test_service_account_repository_create_with_duplicate_name_fails(pool:
         secret: "supersecret".to_string(),
         description: "Test Description".to_string(),
         enabled: true,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     // First create should succeed
@@ -68,7 +73,7 @@ async fn test_service_account_repository_create_with_duplicate_name_fails(pool:
 
 #[sqlx::test]
 async fn test_service_account_repository_create_with_duplicate_email_fails(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let mut payload = ServiceAccountCreatePayload {
         name: "Test Service Account".to_string(),
@@ -76,6 +81,8 @@ async fn test_service_account_repository_create_with_duplicate_email_fails(pool:
         secret: "supersecret".to_string(),
         description: "Test Description".to_string(),
         enabled: true,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     // First create should succeed
@@ -93,7 +100,7 @@ async fn test_service_account_repository_create_with_duplicate_email_fails(pool:
 
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_read_existing_account_succeeds(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let service_account = repository
         .read(Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap())
@@ -110,7 +117,7 @@ async fn test_service_account_repository_read_existing_account_succeeds(pool: Pg
 
 #[sqlx::test]
 async fn test_service_account_repository_read_nonexistent_account_returns_error(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let result = repository
         .read(Uuid::parse_str("123e4567-e89b-12d3-a456-426614174999").unwrap())
@@ -120,6 +127,18 @@ async fn test_service_account_repository_read_nonexistent_account_returns_error(
     assert_eq!(result.unwrap_err().to_string(), "Service account not found");
 }
 
+#[sqlx::test]
+async fn test_service_account_repository_find_by_id_nonexistent_account_returns_none(pool: PgPool) {
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
+
+    let service_account = repository
+        .find_by_id(Uuid::parse_str("123e4567-e89b-12d3-a456-426614174999").unwrap())
+        .await
+        .unwrap();
+
+    assert!(service_account.is_none());
+}
+
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_update_name_field_succeeds(pool: PgPool) {
     test_service_account_repository_update_helper(
@@ -130,6 +149,8 @@ async fn test_service_account_repository_update_name_field_succeeds(pool: PgPool
             secret: None,
             description: None,
             enabled: None,
+            allowed_cidrs: None,
+            expires_at: None,
         },
         |account| assert_eq!(account.name, "Updated Name"),
     )
@@ -146,6 +167,8 @@ async fn test_service_account_repository_update_email_field_succeeds(pool: PgPoo
             secret: None,
             description: None,
             enabled: None,
+            allowed_cidrs: None,
+            expires_at: None,
         },
         |account| assert_eq!(account.email, "updated@example.com"),
     )
@@ -162,9 +185,11 @@ async fn test_service_account_repository_update_secret_field_succeeds(pool: PgPo
             secret: Some("new-secret".to_string()),
             description: None,
             enabled: None,
+            allowed_cidrs: None,
+            expires_at: None,
         },
         |account| {
-            let secrets_manager = SecretsManager::new(true).unwrap();
+            let secrets_manager = secrets_manager();
             let decrypt_password = secrets_manager
                 .decrypt(&account.secret, &account.id.unwrap())
                 .unwrap();
@@ -184,15 +209,100 @@ async fn test_service_account_repository_update_description_field_succeeds(pool:
             secret: None,
             description: Some("Updated Description".to_string()),
             enabled: None,
+            allowed_cidrs: None,
+            expires_at: None,
         },
         |account| assert_eq!(account.description, "Updated Description"),
     )
     .await;
 }
 
+#[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
+async fn test_service_account_repository_update_allowed_cidrs_normalizes_ranges(pool: PgPool) {
+    test_service_account_repository_update_helper(
+        pool,
+        ServiceAccountUpdatePayload {
+            name: None,
+            email: None,
+            secret: None,
+            description: None,
+            enabled: None,
+            allowed_cidrs: Some(vec!["10.1.2.3/16".to_string(), "192.0.2.1".to_string()]),
+            expires_at: None,
+        },
+        |account| assert_eq!(account.allowed_cidrs, vec!["10.1.0.0/16", "192.0.2.1/32"]),
+    )
+    .await;
+}
+
+#[sqlx::test]
+async fn test_service_account_repository_create_with_invalid_cidr_fails(pool: PgPool) {
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
+
+    let payload = ServiceAccountCreatePayload {
+        name: "Test Service Account".to_string(),
+        email: "test@example.com".to_string(),
+        secret: "supersecret".to_string(),
+        description: "Test Description".to_string(),
+        enabled: true,
+        allowed_cidrs: Some(vec!["10.0.0.0/40".to_string()]),
+        expires_at: None,
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid CIDR range: 10.0.0.0/40"
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
+async fn test_service_account_repository_update_expires_at_succeeds(pool: PgPool) {
+    test_service_account_repository_update_helper(
+        pool,
+        ServiceAccountUpdatePayload {
+            name: None,
+            email: None,
+            secret: None,
+            description: None,
+            enabled: None,
+            allowed_cidrs: None,
+            expires_at: Some("2999-01-01T00:00:00Z".to_string()),
+        },
+        |account| {
+            assert_eq!(
+                account.expires_at.unwrap().to_rfc3339(),
+                "2999-01-01T00:00:00+00:00"
+            )
+        },
+    )
+    .await;
+}
+
+#[sqlx::test]
+async fn test_service_account_repository_create_with_past_expiration_fails(pool: PgPool) {
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
+
+    let payload = ServiceAccountCreatePayload {
+        name: "Test Service Account".to_string(),
+        email: "test@example.com".to_string(),
+        secret: "supersecret".to_string(),
+        description: "Test Description".to_string(),
+        enabled: true,
+        allowed_cidrs: None,
+        expires_at: Some("2000-01-01T00:00:00Z".to_string()),
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Expiration date must be in the future"
+    );
+}
+
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_update_duplicate_name_fails(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let payload = ServiceAccountUpdatePayload {
         name: Some("Test Account 2".to_string()),
@@ -200,6 +310,8 @@ async fn test_service_account_repository_update_duplicate_name_fails(pool: PgPoo
         secret: None,
         description: None,
         enabled: None,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     // Second update with same name should fail
@@ -217,7 +329,7 @@ async fn test_service_account_repository_update_duplicate_name_fails(pool: PgPoo
 
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_update_duplicate_email_fails(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let payload = ServiceAccountUpdatePayload {
         name: None,
@@ -225,6 +337,8 @@ async fn test_service_account_repository_update_duplicate_email_fails(pool: PgPo
         secret: None,
         description: None,
         enabled: None,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     // Second update with same name should fail
@@ -250,6 +364,8 @@ async fn test_service_account_repository_update_enabled_to_false_succeeds(pool:
             secret: None,
             description: None,
             enabled: Some(false),
+            allowed_cidrs: None,
+            expires_at: None,
         },
         |account| assert!(!account.enabled),
     )
@@ -258,7 +374,7 @@ async fn test_service_account_repository_update_enabled_to_false_succeeds(pool:
 
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_update_nonexistent_account_returns_error(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let result = repository
         .update(
@@ -269,6 +385,8 @@ async fn test_service_account_repository_update_nonexistent_account_returns_erro
                 secret: None,
                 description: None,
                 enabled: None,
+                allowed_cidrs: None,
+                expires_at: None,
             },
         )
         .await;
@@ -279,7 +397,7 @@ async fn test_service_account_repository_update_nonexistent_account_returns_erro
 
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_delete_existing_account_succeeds(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let is_deleted = repository
         .delete(Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap())
@@ -291,7 +409,7 @@ async fn test_service_account_repository_delete_existing_account_succeeds(pool:
 
 #[sqlx::test]
 async fn test_service_account_repository_delete_nonexistent_account_returns_false(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let result = repository
         .delete(Uuid::parse_str("123e4567-e89b-12d3-a456-426614174999").unwrap())
@@ -308,7 +426,7 @@ async fn test_service_account_repository_update_helper<F>(
 ) where
     F: FnOnce(&sentinel_guard::models::service_account::ServiceAccount),
 {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let service_account = repository
         .update(
@@ -323,7 +441,7 @@ async fn test_service_account_repository_update_helper<F>(
 
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_find_with_no_filters_returns_all_accounts(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let filter = ServiceAccountFilter::default();
     let sort = None;
@@ -336,7 +454,7 @@ async fn test_service_account_repository_find_with_no_filters_returns_all_accoun
 
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_find_with_name_filter(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let filter = ServiceAccountFilter {
         name: Some("Test Account 1".to_string()),
@@ -353,7 +471,7 @@ async fn test_service_account_repository_find_with_name_filter(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_find_with_email_filter(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let filter = ServiceAccountFilter {
         email: Some("test2@example.com".to_string()),
@@ -370,7 +488,7 @@ async fn test_service_account_repository_find_with_email_filter(pool: PgPool) {
 
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_find_with_enabled_filter(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let filter = ServiceAccountFilter {
         enabled: Some(true),
@@ -388,7 +506,7 @@ async fn test_service_account_repository_find_with_enabled_filter(pool: PgPool)
 
 #[sqlx::test(fixtures("../fixtures/sort_service_accounts.sql"))]
 async fn test_service_account_repository_find_with_name_ascending_sort(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let filter = ServiceAccountFilter::default();
     let sort = Some(vec![ServiceAccountSortOrder::new(
@@ -407,7 +525,7 @@ async fn test_service_account_repository_find_with_name_ascending_sort(pool: PgP
 
 #[sqlx::test(fixtures("../fixtures/sort_service_accounts.sql"))]
 async fn test_service_account_repository_find_with_name_descending_sort(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let filter = ServiceAccountFilter::default();
     let sort = Some(vec![ServiceAccountSortOrder::new(
@@ -426,7 +544,7 @@ async fn test_service_account_repository_find_with_name_descending_sort(pool: Pg
 
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_find_with_limit_pagination(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let filter = ServiceAccountFilter::default();
     let sort = None;
@@ -442,7 +560,7 @@ async fn test_service_account_repository_find_with_limit_pagination(pool: PgPool
 
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_repository_find_with_offset_pagination(pool: PgPool) {
-    let repository = ServiceAccountRepository::new(Arc::new(pool));
+    let repository = ServiceAccountRepository::new(Arc::new(pool), secrets_manager());
 
     let filter = ServiceAccountFilter::default();
     let sort = None;
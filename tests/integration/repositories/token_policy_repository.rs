@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use jsonwebtoken::Algorithm;
+use sentinel_guard::{
+    models::{
+        environment_key::EnvironmentKeyCreatePayload,
+        token_policy::{
+            EffectiveTokenPolicy, TokenPolicyCreatePayload, TokenPolicyFilter,
+            TokenPolicyUpdatePayload,
+        },
+    },
+    repositories::{
+        base::Repository, environment_key_repository::EnvironmentKeyRepository,
+        token_policy_repository::TokenPolicyRepository,
+    },
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::integration::secrets_manager;
+
+const PROJECT_ID: &str = "10000000-0000-0000-0000-000000000001";
+const OTHER_PROJECT_ID: &str = "10000000-0000-0000-0000-000000000002";
+const DEV_ENVIRONMENT_ID: &str = "20000000-0000-0000-0000-000000000001";
+const PROD_ENVIRONMENT_ID: &str = "20000000-0000-0000-0000-000000000002";
+const OTHER_ENVIRONMENT_ID: &str = "20000000-0000-0000-0000-000000000003";
+const PROJECT_POLICY_ID: &str = "70000000-0000-0000-0000-000000000001";
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_create_project_policy_succeeds(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let payload = TokenPolicyCreatePayload {
+        project_id: OTHER_PROJECT_ID.to_string(),
+        max_ttl_seconds: Some(1800),
+        allowed_algorithms: Some(vec!["RS256".to_string()]),
+        ..Default::default()
+    };
+
+    let token_policy = repository.create(payload).await.unwrap();
+    assert!(token_policy.id.is_some());
+    assert!(token_policy.environment_id.is_none());
+    assert_eq!(token_policy.max_ttl_seconds, Some(1800));
+    assert_eq!(
+        token_policy.allowed_algorithms,
+        Some(vec!["RS256".to_string()])
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_create_duplicate_project_policy_fails(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let payload = TokenPolicyCreatePayload {
+        project_id: PROJECT_ID.to_string(),
+        ..Default::default()
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Token policy already exists"
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_create_with_foreign_environment_fails(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let payload = TokenPolicyCreatePayload {
+        project_id: PROJECT_ID.to_string(),
+        environment_id: Some(OTHER_ENVIRONMENT_ID.to_string()),
+        ..Default::default()
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Environment does not belong to the project"
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_create_with_invalid_algorithm_fails(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let payload = TokenPolicyCreatePayload {
+        project_id: OTHER_PROJECT_ID.to_string(),
+        allowed_algorithms: Some(vec!["none".to_string()]),
+        ..Default::default()
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(result.unwrap_err().to_string(), "Invalid algorithm: none");
+}
+
+#[sqlx::test]
+async fn test_token_policy_repository_create_with_missing_project_fails(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let payload = TokenPolicyCreatePayload {
+        project_id: Uuid::new_v4().to_string(),
+        ..Default::default()
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(result.unwrap_err().to_string(), "Project not found");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_update_keeps_unset_fields(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let id = Uuid::parse_str(PROJECT_POLICY_ID).unwrap();
+    let update = TokenPolicyUpdatePayload {
+        issuer: Some("https://issuer.test".to_string()),
+        ..Default::default()
+    };
+
+    let token_policy = repository.update(id, update).await.unwrap();
+    assert_eq!(token_policy.issuer, Some("https://issuer.test".to_string()));
+    assert_eq!(token_policy.max_ttl_seconds, Some(600));
+    assert_eq!(
+        token_policy.audience,
+        Some(vec!["payments-api".to_string()])
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_update_default_above_stored_max_fails(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let id = Uuid::parse_str(PROJECT_POLICY_ID).unwrap();
+    let update = TokenPolicyUpdatePayload {
+        default_ttl_seconds: Some(900),
+        ..Default::default()
+    };
+
+    let result = repository.update(id, update).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "default_ttl_seconds must not exceed max_ttl_seconds"
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_update_without_changes_fails(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let id = Uuid::parse_str(PROJECT_POLICY_ID).unwrap();
+
+    let result = repository
+        .update(id, TokenPolicyUpdatePayload::default())
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), "No changes to update");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_delete_existing_succeeds(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let id = Uuid::parse_str(PROJECT_POLICY_ID).unwrap();
+
+    assert!(repository.delete(id).await.unwrap());
+    let result = repository.read(id).await;
+    assert_eq!(result.unwrap_err().to_string(), "Token policy not found");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_find_with_environment_filter(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let filter = TokenPolicyFilter {
+        environment_id: Some(PROD_ENVIRONMENT_ID.to_string()),
+        ..Default::default()
+    };
+
+    let token_policies = repository.find(filter, None, None).await.unwrap();
+    assert_eq!(token_policies.len(), 1);
+
+    let filter = TokenPolicyFilter {
+        project_id: Some(PROJECT_ID.to_string()),
+        ..Default::default()
+    };
+    let token_policies = repository.find(filter, None, None).await.unwrap();
+    assert_eq!(token_policies.len(), 2);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_resolve_uses_project_policy(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let environment_id = Uuid::parse_str(DEV_ENVIRONMENT_ID).unwrap();
+
+    let policy = repository.resolve(environment_id).await.unwrap();
+    assert_eq!(policy.max_ttl_seconds, 600);
+    assert_eq!(policy.default_ttl_seconds, 300);
+    assert_eq!(policy.audience, vec!["payments-api".to_string()]);
+    assert_eq!(
+        policy.issuer,
+        Some("https://sentinel-guard.test".to_string())
+    );
+    assert_eq!(
+        policy.allowed_algorithms,
+        vec![Algorithm::HS256, Algorithm::RS256]
+    );
+    assert!(!policy.allow_refresh_tokens);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_resolve_applies_environment_override(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let environment_id = Uuid::parse_str(PROD_ENVIRONMENT_ID).unwrap();
+
+    let policy = repository.resolve(environment_id).await.unwrap();
+    assert_eq!(policy.max_ttl_seconds, 120);
+    assert_eq!(policy.default_ttl_seconds, 120);
+    assert_eq!(policy.audience, vec!["payments-api".to_string()]);
+    assert_eq!(policy.allowed_algorithms, vec![Algorithm::HS512]);
+    assert!(policy.allow_refresh_tokens);
+    assert_eq!(policy.refresh_token_ttl_seconds, 3600);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_repository_resolve_without_policy_returns_defaults(pool: PgPool) {
+    let repository = TokenPolicyRepository::new(Arc::new(pool));
+    let environment_id = Uuid::parse_str(OTHER_ENVIRONMENT_ID).unwrap();
+
+    let policy = repository.resolve(environment_id).await.unwrap();
+    assert_eq!(policy, EffectiveTokenPolicy::default());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_environment_key_repository_create_rejects_algorithm_outside_policy(pool: PgPool) {
+    let repository = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let payload = EnvironmentKeyCreatePayload {
+        environment_id: PROD_ENVIRONMENT_ID.to_string(),
+        algorithm: "HS256".to_string(),
+        active: true,
+        rotation_interval_days: None,
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Algorithm HS256 is not allowed by the token policy"
+    );
+
+    let payload = EnvironmentKeyCreatePayload {
+        environment_id: PROD_ENVIRONMENT_ID.to_string(),
+        algorithm: "HS512".to_string(),
+        active: true,
+        rotation_interval_days: None,
+    };
+    assert!(repository.create(payload).await.is_ok());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_environment_key_repository_create_with_invalid_algorithm_fails(pool: PgPool) {
+    let repository = EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager());
+    let payload = EnvironmentKeyCreatePayload {
+        environment_id: DEV_ENVIRONMENT_ID.to_string(),
+        algorithm: "XX256".to_string(),
+        active: true,
+        rotation_interval_days: None,
+    };
+
+    let result = repository.create(payload).await;
+    assert_eq!(result.unwrap_err().to_string(), "Invalid algorithm: XX256");
+}
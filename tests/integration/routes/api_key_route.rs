@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    models::api_key::{
+        ApiKeyCreatePayload, ApiKeyResponse, ApiKeyUpdatePayload, ApiKeyVerification,
+        ApiKeyVerifyPayload,
+    },
+    repositories::{api_key_repository::ApiKeyRepository, base::Repository},
+    routes::api_key_route,
+};
+use sqlx::PgPool;
+
+use crate::create_test_app;
+
+const PROJECT_ACCESS_ID: &str = "40000000-0000-0000-0000-000000000001";
+
+fn repositories(pool: PgPool) -> ApiKeyRepository {
+    ApiKeyRepository::new(Arc::new(pool))
+}
+
+fn routes() -> fn(&mut actix_web::web::ServiceConfig) {
+    api_key_route::configure_routes
+}
+
+fn payload() -> ApiKeyCreatePayload {
+    ApiKeyCreatePayload {
+        project_access_id: PROJECT_ACCESS_ID.to_string(),
+        name: "webhook".to_string(),
+        scopes: Some(vec!["payments:read".to_string()]),
+        expires_at: None,
+    }
+}
+
+fn verify_request(api_key: &str) -> actix_web::test::TestRequest {
+    actix_web::test::TestRequest::post()
+        .uri("/api-keys/verify")
+        .set_json(ApiKeyVerifyPayload {
+            api_key: api_key.to_string(),
+        })
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_route_create_returns_key_that_verifies(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/api-keys")
+        .set_json(payload())
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::CREATED);
+    let api_key: ApiKeyResponse = actix_web::test::read_body_json(response).await;
+    let key = api_key.key.unwrap();
+    assert!(key.starts_with(&format!("sg_live_{}_", api_key.prefix)));
+    assert_eq!(api_key.scopes, vec!["payments:read"]);
+
+    let response = verify_request(&key).send_request(&app).await;
+    assert!(response.status().is_success());
+    assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+    let verification: ApiKeyVerification = actix_web::test::read_body_json(response).await;
+    assert_eq!(verification.api_key_id, api_key.id);
+    assert_eq!(
+        verification.project_id,
+        "10000000-0000-0000-0000-000000000001"
+    );
+    assert_eq!(
+        verification.environment_id,
+        "20000000-0000-0000-0000-000000000001"
+    );
+    assert_eq!(verification.scopes, vec!["payments:read"]);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_route_create_with_ungranted_scope_returns_bad_request(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/api-keys")
+        .set_json(ApiKeyCreatePayload {
+            scopes: Some(vec!["payments:admin".to_string()]),
+            ..payload()
+        })
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+async fn test_api_key_route_create_with_missing_project_access_returns_not_found(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/api-keys")
+        .set_json(ApiKeyCreatePayload {
+            scopes: None,
+            ..payload()
+        })
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_route_get_does_not_return_key(pool: PgPool) {
+    let api_key = repositories(pool.clone())
+        .create(payload().with_key())
+        .await
+        .unwrap();
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!("/api-keys/{}", api_key.id.unwrap()))
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let api_key: ApiKeyResponse = actix_web::test::read_body_json(response).await;
+    assert!(api_key.key.is_none());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_route_verify_disabled_key_returns_unauthorized(pool: PgPool) {
+    let payload = payload().with_key();
+    let key = payload.key.clone();
+    let api_key = repositories(pool.clone()).create(payload).await.unwrap();
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::patch()
+        .uri(&format!("/api-keys/{}", api_key.id.unwrap()))
+        .set_json(ApiKeyUpdatePayload {
+            enabled: Some(false),
+            ..Default::default()
+        })
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+
+    let response = verify_request(&key).send_request(&app).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_route_verify_expired_key_returns_unauthorized(pool: PgPool) {
+    let payload = payload().with_key();
+    let key = payload.key.clone();
+    let api_key = repositories(pool.clone()).create(payload).await.unwrap();
+    sqlx::query("UPDATE api_keys SET expires_at = now() - interval '1 minute' WHERE id = $1")
+        .bind(api_key.id.unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = verify_request(&key).send_request(&app).await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_api_key_route_delete_nonexistent_returns_not_found(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::delete()
+        .uri(&format!("/api-keys/{}", uuid::Uuid::new_v4()))
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    config::LockoutConfig,
+    models::{
+        audit_event::AuditEventResponse, authentication_failure::AuthenticationFailureResponse,
+        authentication_failure::AuthenticationSubjectType,
+    },
+    repositories::{
+        audit_event_repository::AuditEventRepository,
+        authentication_failure_repository::AuthenticationFailureRepository,
+    },
+    routes::{audit_event_route, authentication_lockout_route},
+};
+use sqlx::PgPool;
+
+const SUBJECT: &str = "30000000-0000-0000-0000-000000000001";
+
+fn routes(config: &mut actix_web::web::ServiceConfig) {
+    authentication_lockout_route::configure_routes(config);
+    audit_event_route::configure_routes(config);
+}
+
+async fn lock_out(repository: &AuthenticationFailureRepository) {
+    let config = LockoutConfig {
+        max_failures: 2,
+        ..Default::default()
+    };
+    for _ in 0..2 {
+        repository
+            .record_failure(AuthenticationSubjectType::ServiceAccount, SUBJECT, &config)
+            .await
+            .unwrap();
+    }
+}
+
+#[sqlx::test]
+async fn test_authentication_lockout_route_lists_and_unlocks(pool: PgPool) {
+    let repository = AuthenticationFailureRepository::new(Arc::new(pool.clone()));
+    lock_out(&repository).await;
+    let app = actix_web::test::init_service(
+        actix_web::App::new()
+            .app_data(actix_web::web::Data::new(repository))
+            .app_data(actix_web::web::Data::new(AuditEventRepository::new(
+                Arc::new(pool),
+            )))
+            .configure(routes),
+    )
+    .await;
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/authentication-lockouts?locked=true")
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+    let lockouts: Vec<AuthenticationFailureResponse> =
+        actix_web::test::read_body_json(response).await;
+    assert_eq!(lockouts.len(), 1);
+    assert_eq!(lockouts[0].subject, SUBJECT);
+    assert!(lockouts[0].locked);
+
+    let response = actix_web::test::TestRequest::delete()
+        .uri(&format!(
+            "/authentication-lockouts/service_account/{}",
+            SUBJECT
+        ))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::NO_CONTENT);
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/authentication-lockouts")
+        .send_request(&app)
+        .await;
+    let lockouts: Vec<AuthenticationFailureResponse> =
+        actix_web::test::read_body_json(response).await;
+    assert!(lockouts.is_empty());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!("/audit-events?subject={}", SUBJECT))
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+    let events: Vec<AuditEventResponse> = actix_web::test::read_body_json(response).await;
+    let event_types: Vec<&str> = events
+        .iter()
+        .map(|event| event.event_type.as_str())
+        .collect();
+    assert_eq!(
+        event_types,
+        vec!["authentication_unlock", "authentication_lockout"]
+    );
+}
+
+#[sqlx::test]
+async fn test_authentication_lockout_route_unlock_unknown_subject_returns_not_found(pool: PgPool) {
+    let app = crate::create_test_app!(
+        AuthenticationFailureRepository::new(Arc::new(pool)),
+        authentication_lockout_route::configure_routes
+    );
+
+    let response = actix_web::test::TestRequest::delete()
+        .uri("/authentication-lockouts/ip/203.0.113.7")
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+    let response = actix_web::test::TestRequest::delete()
+        .uri("/authentication-lockouts/user/203.0.113.7")
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
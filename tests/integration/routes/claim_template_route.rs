@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    models::claim_template::{
+        ClaimTemplateCreatePayload, ClaimTemplateResponse, ClaimTemplateUpdatePayload,
+    },
+    repositories::claim_template_repository::ClaimTemplateRepository,
+    routes::claim_template_route,
+};
+use sqlx::PgPool;
+
+use crate::create_test_app;
+
+fn repositories(pool: PgPool) -> ClaimTemplateRepository {
+    ClaimTemplateRepository::new(Arc::new(pool))
+}
+
+fn routes() -> fn(&mut actix_web::web::ServiceConfig) {
+    claim_template_route::configure_routes
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_claim_template_route_create_with_valid_data_succeeds(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let payload = ClaimTemplateCreatePayload {
+        project_id: "10000000-0000-0000-0000-000000000001".to_string(),
+        environment_id: Some("20000000-0000-0000-0000-000000000002".to_string()),
+        claim: "tenant".to_string(),
+        template: "{{project.name}}-{{environment.name}}".to_string(),
+        ..Default::default()
+    };
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/claim-templates")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::CREATED);
+    let claim_template: ClaimTemplateResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(claim_template.claim, "tenant");
+    assert_eq!(
+        claim_template.environment_id,
+        Some("20000000-0000-0000-0000-000000000002".to_string())
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_claim_template_route_create_reserved_claim_returns_bad_request(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let payload = ClaimTemplateCreatePayload {
+        project_id: "10000000-0000-0000-0000-000000000001".to_string(),
+        claim: "sub".to_string(),
+        template: "{{service_account.email}}".to_string(),
+        ..Default::default()
+    };
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/claim-templates")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_claim_template_route_create_duplicate_returns_conflict(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let payload = ClaimTemplateCreatePayload {
+        project_id: "10000000-0000-0000-0000-000000000001".to_string(),
+        claim: "region".to_string(),
+        template: "us-east".to_string(),
+        ..Default::default()
+    };
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/claim-templates")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::CONFLICT);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_claim_template_route_patch_updates_template(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let payload = ClaimTemplateUpdatePayload {
+        template: Some("us-east".to_string()),
+        ..Default::default()
+    };
+
+    let response = actix_web::test::TestRequest::patch()
+        .uri("/claim-templates/80000000-0000-0000-0000-000000000002")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let claim_template: ClaimTemplateResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(claim_template.template, "us-east");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_claim_template_route_delete_nonexistent_returns_not_found(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::delete()
+        .uri(&format!("/claim-templates/{}", uuid::Uuid::new_v4()))
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_claim_template_route_list_filters_by_project_access(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/claim-templates?project_access_id=40000000-0000-0000-0000-000000000001")
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let claim_templates: Vec<ClaimTemplateResponse> =
+        actix_web::test::read_body_json(response).await;
+    assert_eq!(claim_templates.len(), 1);
+    assert_eq!(claim_templates[0].claim, "owner");
+}
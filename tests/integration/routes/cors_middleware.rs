@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::middleware::{Condition, from_fn};
+use sentinel_guard::{
+    config::{CorsConfig, RateLimitConfig, RateLimitRule},
+    middleware::{cors::cors, rate_limit::rate_limit},
+    repositories::project_repository::ProjectRepository,
+    routes::project_route,
+    utils::rate_limit::RateLimiter,
+};
+use sqlx::PgPool;
+
+const CONSOLE: &str = "https://console.example.com";
+const PEER: &str = "192.0.2.1:40000";
+
+macro_rules! app {
+    ($pool:expr, $config:expr) => {{
+        let config: CorsConfig = $config;
+        actix_web::test::init_service(
+            actix_web::App::new()
+                .wrap(from_fn(rate_limit))
+                .wrap(Condition::new(config.is_enabled(), cors(&config)))
+                .app_data(actix_web::web::Data::new(RateLimiter::new(
+                    RateLimitConfig {
+                        admin: RateLimitRule {
+                            capacity: 1,
+                            refill_per_second: 0.01,
+                        },
+                        ..Default::default()
+                    },
+                )))
+                .app_data(actix_web::web::Data::new(ProjectRepository::new(Arc::new(
+                    $pool,
+                ))))
+                .configure(project_route::configure_routes),
+        )
+        .await
+    }};
+}
+
+fn console() -> CorsConfig {
+    CorsConfig {
+        allowed_origins: vec![CONSOLE.to_string()],
+        ..Default::default()
+    }
+}
+
+#[sqlx::test]
+async fn test_cors_middleware_answers_preflight_requests(pool: PgPool) {
+    let app = app!(pool, console());
+
+    let response = actix_web::test::TestRequest::default()
+        .method(actix_web::http::Method::OPTIONS)
+        .uri("/projects")
+        .peer_addr(PEER.parse().unwrap())
+        .insert_header(("Origin", CONSOLE))
+        .insert_header(("Access-Control-Request-Method", "POST"))
+        .insert_header((
+            "Access-Control-Request-Headers",
+            "authorization, content-type",
+        ))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let headers = response.headers();
+    assert_eq!(headers.get("access-control-allow-origin").unwrap(), CONSOLE);
+    assert_eq!(headers.get("access-control-max-age").unwrap(), "3600");
+    assert!(
+        headers
+            .get("access-control-allow-methods")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("POST")
+    );
+    assert!(headers.get("access-control-allow-credentials").is_none());
+    // Preflight requests do not take from the rate limit
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(PEER.parse().unwrap())
+        .insert_header(("Origin", CONSOLE))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = actix_web::test::TestRequest::default()
+        .method(actix_web::http::Method::OPTIONS)
+        .uri("/projects")
+        .peer_addr(PEER.parse().unwrap())
+        .insert_header(("Origin", CONSOLE))
+        .insert_header(("Access-Control-Request-Method", "TRACE"))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+async fn test_cors_middleware_only_allows_configured_origins(pool: PgPool) {
+    let app = app!(pool, console());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(PEER.parse().unwrap())
+        .insert_header(("Origin", CONSOLE))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .unwrap(),
+        CONSOLE
+    );
+    let exposed = response
+        .headers()
+        .get("access-control-expose-headers")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_lowercase();
+    assert!(exposed.contains("ratelimit-remaining"));
+    assert!(exposed.contains("x-request-id"));
+
+    // Rejections stay readable by the browser application
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(PEER.parse().unwrap())
+        .insert_header(("Origin", CONSOLE))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .unwrap(),
+        CONSOLE
+    );
+
+    // Other origins are served without CORS headers, browsers then hide the response
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(PEER.parse().unwrap())
+        .insert_header(("Origin", "https://attacker.example"))
+        .peer_addr("192.0.2.2:40000".parse().unwrap())
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .is_none()
+    );
+}
+
+#[sqlx::test]
+async fn test_cors_middleware_with_any_origin_and_credentials(pool: PgPool) {
+    let app = app!(
+        pool.clone(),
+        CorsConfig {
+            allowed_origins: vec!["*".to_string()],
+            ..Default::default()
+        }
+    );
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(PEER.parse().unwrap())
+        .insert_header(("Origin", "https://anywhere.example"))
+        .send_request(&app)
+        .await;
+    assert_eq!(
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .unwrap(),
+        "*"
+    );
+
+    let app = app!(
+        pool,
+        CorsConfig {
+            allow_credentials: true,
+            ..console()
+        }
+    );
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(PEER.parse().unwrap())
+        .insert_header(("Origin", CONSOLE))
+        .send_request(&app)
+        .await;
+    assert_eq!(
+        response
+            .headers()
+            .get("access-control-allow-credentials")
+            .unwrap(),
+        "true"
+    );
+}
+
+#[sqlx::test]
+async fn test_cors_middleware_disabled_without_origins(pool: PgPool) {
+    let app = app!(pool, CorsConfig::default());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(PEER.parse().unwrap())
+        .insert_header(("Origin", CONSOLE))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .is_none()
+    );
+}
@@ -4,14 +4,18 @@ use sqlx::PgPool;
 
 use sentinel_guard::{
     models::environment_key::{
-        EnvironmentKeyCreatePayload, EnvironmentKeyResponse, EnvironmentKeyUpdatePayload,
+        EnvironmentKeyCreatePayload, EnvironmentKeyImportPayload, EnvironmentKeyResponse,
+        EnvironmentKeyUpdatePayload,
     },
     repositories::environment_key_repository::EnvironmentKeyRepository,
     routes::environment_key_route,
+    utils::tokens::key_builder::KeyEncoding,
 };
 
+use crate::integration::secrets_manager;
+
 fn repositories(pool: PgPool) -> EnvironmentKeyRepository {
-    EnvironmentKeyRepository::new(Arc::new(pool))
+    EnvironmentKeyRepository::new(Arc::new(pool), secrets_manager())
 }
 
 fn routes() -> fn(&mut actix_web::web::ServiceConfig) {
@@ -27,6 +31,7 @@ async fn test_environment_key_route_create_valid(pool: PgPool) {
         environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
         algorithm: "RS512".to_string(),
         active: true,
+        rotation_interval_days: None,
     };
     let response = actix_web::test::TestRequest::post()
         .uri("/environment-keys")
@@ -48,6 +53,7 @@ async fn test_environment_key_route_create_duplicate_fails(pool: PgPool) {
         environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
         algorithm: "HS256".to_string(),
         active: true,
+        rotation_interval_days: None,
     };
     let response = actix_web::test::TestRequest::post()
         .uri("/environment-keys")
@@ -84,6 +90,7 @@ async fn test_environment_key_route_patch_active_succeeds(pool: PgPool) {
     let app = create_test_app!(repositories(pool), routes());
     let payload = EnvironmentKeyUpdatePayload {
         active: Some(false),
+        rotation_interval_days: None,
     };
     let response = actix_web::test::TestRequest::patch()
         .uri("/environment-keys/00000000-0000-0000-0000-000000000001")
@@ -100,6 +107,7 @@ async fn test_environment_key_route_patch_not_found(pool: PgPool) {
     let app = create_test_app!(repositories(pool), routes());
     let payload = EnvironmentKeyUpdatePayload {
         active: Some(false),
+        rotation_interval_days: None,
     };
     let response = actix_web::test::TestRequest::patch()
         .uri("/environment-keys/00000000-0000-0000-0000-00000000dead")
@@ -112,7 +120,10 @@ async fn test_environment_key_route_patch_not_found(pool: PgPool) {
 #[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
 async fn test_environment_key_route_patch_empty_payload(pool: PgPool) {
     let app = create_test_app!(repositories(pool), routes());
-    let payload = EnvironmentKeyUpdatePayload { active: None };
+    let payload = EnvironmentKeyUpdatePayload {
+        active: None,
+        rotation_interval_days: None,
+    };
     let response = actix_web::test::TestRequest::patch()
         .uri("/environment-keys/00000000-0000-0000-0000-000000000001")
         .set_json(&payload)
@@ -192,3 +203,84 @@ async fn test_environment_key_route_rotate_key_not_found(pool: PgPool) {
         .await;
     assert!(response.status().is_client_error());
 }
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_environment_key_route_import_succeeds(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+    let payload = EnvironmentKeyImportPayload {
+        environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+        algorithm: "HS512".to_string(),
+        key: "ab".repeat(64),
+        encoding: Some(KeyEncoding::Hex),
+        active: true,
+        rotation_interval_days: None,
+    };
+    let response = actix_web::test::TestRequest::post()
+        .uri("/environment-keys/import")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::CREATED);
+    let imported: EnvironmentKeyResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(imported.environment_id, payload.environment_id);
+    assert_eq!(imported.algorithm, payload.algorithm);
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_environment_key_route_import_weak_key_fails(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+    let payload = EnvironmentKeyImportPayload {
+        environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+        algorithm: "HS512".to_string(),
+        key: "ab".repeat(16),
+        encoding: Some(KeyEncoding::Hex),
+        active: true,
+        rotation_interval_days: None,
+    };
+    let response = actix_web::test::TestRequest::post()
+        .uri("/environment-keys/import")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_environment_key_route_import_ambiguous_secret_fails(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+    // Valid as hex and as base64, without an encoding to tell them apart
+    let payload = serde_json::json!({
+        "environment_id": "123e4567-e89b-12d3-a456-426614174000",
+        "algorithm": "HS512",
+        "key": "ab".repeat(128),
+        "active": true,
+    });
+    let response = actix_web::test::TestRequest::post()
+        .uri("/environment-keys/import")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("../fixtures/environment_keys.sql"))]
+async fn test_environment_key_route_create_with_rotation_schedule(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+    let payload = EnvironmentKeyCreatePayload {
+        environment_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+        algorithm: "HS512".to_string(),
+        active: true,
+        rotation_interval_days: Some(90),
+    };
+    let response = actix_web::test::TestRequest::post()
+        .uri("/environment-keys")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::CREATED);
+    let created: EnvironmentKeyResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(created.rotation_interval_days, Some(90));
+    assert!(created.next_rotation_at.is_some());
+}
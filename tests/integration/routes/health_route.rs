@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    models::health::HealthResponse, routes::health_route, services::health_service::HealthService,
+};
+use sqlx::PgPool;
+
+use crate::create_test_app;
+use crate::integration::secrets_manager;
+
+fn routes() -> fn(&mut actix_web::web::ServiceConfig) {
+    health_route::configure_routes
+}
+
+fn check<'a>(response: &'a HealthResponse, name: &str) -> &'a str {
+    response
+        .checks
+        .iter()
+        .find(|check| check.name == name)
+        .map(|check| check.status.as_str())
+        .unwrap()
+}
+
+#[sqlx::test]
+async fn test_healthz_route_reports_process_up(pool: PgPool) {
+    let service = HealthService::new(Arc::new(pool), secrets_manager());
+    let app = create_test_app!(service, routes());
+
+    let request = actix_web::test::TestRequest::get()
+        .uri("/healthz")
+        .to_request();
+    let response: HealthResponse = actix_web::test::call_and_read_body_json(&app, request).await;
+    assert_eq!(response.status, "ok");
+    assert!(response.checks.is_empty());
+}
+
+#[sqlx::test]
+async fn test_readyz_route_reports_every_check(pool: PgPool) {
+    let service = HealthService::new(Arc::new(pool), secrets_manager());
+    let app = create_test_app!(service, routes());
+
+    let request = actix_web::test::TestRequest::get()
+        .uri("/readyz")
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+
+    let response: HealthResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(response.status, "ok");
+    for name in ["shutdown", "database", "migrations", "secrets_manager"] {
+        assert_eq!(check(&response, name), "ok", "{} check failed", name);
+    }
+}
+
+#[sqlx::test]
+async fn test_readyz_route_fails_when_migrations_are_missing(pool: PgPool) {
+    sqlx::query(
+        "DELETE FROM _sqlx_migrations WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let service = HealthService::new(Arc::new(pool), secrets_manager());
+    let app = create_test_app!(service, routes());
+
+    let request = actix_web::test::TestRequest::get()
+        .uri("/readyz")
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+    assert_eq!(
+        response.status(),
+        actix_web::http::StatusCode::SERVICE_UNAVAILABLE
+    );
+
+    let response: HealthResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(response.status, "failing");
+    assert_eq!(check(&response, "migrations"), "failing");
+    assert_eq!(check(&response, "database"), "ok");
+}
+
+#[sqlx::test]
+async fn test_readyz_route_fails_during_shutdown(pool: PgPool) {
+    let service = HealthService::new(Arc::new(pool), secrets_manager());
+    let app = create_test_app!(service.clone(), routes());
+
+    service.begin_shutdown();
+
+    let request = actix_web::test::TestRequest::get()
+        .uri("/readyz")
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+    assert_eq!(
+        response.status(),
+        actix_web::http::StatusCode::SERVICE_UNAVAILABLE
+    );
+    let response: HealthResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(check(&response, "shutdown"), "failing");
+
+    // The process itself is still alive while draining
+    let request = actix_web::test::TestRequest::get()
+        .uri("/healthz")
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+}
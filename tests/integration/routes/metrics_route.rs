@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use actix_web::middleware::from_fn;
+use sentinel_guard::{
+    middleware::request_metrics::request_metrics,
+    routes::{health_route, metrics_route},
+    services::{health_service::HealthService, metrics_service::MetricsService},
+    utils::metrics::METRICS,
+};
+use sqlx::PgPool;
+
+use crate::integration::secrets_manager;
+
+#[sqlx::test]
+async fn test_metrics_route_exposes_request_and_pool_metrics(pool: PgPool) {
+    let pool = Arc::new(pool);
+    let app = actix_web::test::init_service(
+        actix_web::App::new()
+            .wrap(from_fn(request_metrics))
+            .app_data(actix_web::web::Data::new(HealthService::new(
+                pool.clone(),
+                secrets_manager(),
+            )))
+            .app_data(actix_web::web::Data::new(MetricsService::new(pool)))
+            .configure(health_route::configure_routes)
+            .configure(metrics_route::configure_routes),
+    )
+    .await;
+
+    for uri in ["/healthz", "/does-not-exist"] {
+        let request = actix_web::test::TestRequest::get().uri(uri).to_request();
+        actix_web::test::call_service(&app, request).await;
+    }
+
+    // Metrics are exposed once recorded
+    METRICS.record_job_run("token_cleanup", true);
+    METRICS.access_tokens_removed_total.inc_by(&["delete"], 0);
+
+    let request = actix_web::test::TestRequest::get()
+        .uri("/metrics")
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+    assert!(
+        response
+            .headers()
+            .get("content-type")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain; version=0.0.4")
+    );
+
+    let body = actix_web::test::read_body(response).await;
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.contains(
+        "sentinel_guard_http_requests_total{method=\"GET\",route=\"/healthz\",status=\"200\"}"
+    ));
+    assert!(body.contains(
+        "sentinel_guard_http_requests_total{method=\"GET\",route=\"unmatched\",status=\"404\"}"
+    ));
+    assert!(body.contains(
+        "sentinel_guard_http_request_duration_seconds_bucket{method=\"GET\",route=\"/healthz\",le=\"+Inf\"}"
+    ));
+    assert!(body.contains("# TYPE sentinel_guard_db_pool_connections gauge"));
+    assert!(body.contains("# TYPE sentinel_guard_db_pool_max_connections gauge"));
+    assert!(body.contains("# TYPE sentinel_guard_job_runs_total counter"));
+    assert!(body.contains("# TYPE sentinel_guard_access_tokens_removed_total counter"));
+}
@@ -28,6 +28,7 @@ async fn test_project_access_route_create_valid(pool: PgPool) {
         service_account_id: "123e4567-e89b-12d3-a456-426614174001".to_string(),
         environment_id: "00000000-0000-0000-0000-000000000001".to_string(),
         enabled: true,
+        allowed_cidrs: None,
     };
     let response = actix_web::test::TestRequest::post()
         .uri("/project-access")
@@ -50,6 +51,7 @@ async fn test_project_access_route_create_duplicate_fails(pool: PgPool) {
         service_account_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
         environment_id: "00000000-0000-0000-0000-000000000001".to_string(),
         enabled: true,
+        allowed_cidrs: None,
     };
     let response = actix_web::test::TestRequest::post()
         .uri("/project-access")
@@ -88,6 +90,7 @@ async fn test_project_access_route_patch_enabled_succeeds(pool: PgPool) {
     let app = create_test_app!(repositories(pool), routes());
     let payload = ProjectAccessUpdatePayload {
         enabled: Some(false),
+        allowed_cidrs: None,
     };
     let response = actix_web::test::TestRequest::patch()
         .uri("/project-access/00000000-0000-0000-0000-000000000101")
@@ -104,6 +107,7 @@ async fn test_project_access_route_patch_not_found(pool: PgPool) {
     let app = create_test_app!(repositories(pool), routes());
     let payload = ProjectAccessUpdatePayload {
         enabled: Some(false),
+        allowed_cidrs: None,
     };
     let response = actix_web::test::TestRequest::patch()
         .uri("/project-access/00000000-0000-0000-0000-00000000dead")
@@ -116,7 +120,10 @@ async fn test_project_access_route_patch_not_found(pool: PgPool) {
 #[sqlx::test(fixtures("../fixtures/project_access.sql"))]
 async fn test_project_access_route_patch_empty_payload(pool: PgPool) {
     let app = create_test_app!(repositories(pool), routes());
-    let payload = ProjectAccessUpdatePayload { enabled: None };
+    let payload = ProjectAccessUpdatePayload {
+        enabled: None,
+        allowed_cidrs: None,
+    };
     let response = actix_web::test::TestRequest::patch()
         .uri("/project-access/00000000-0000-0000-0000-000000000101")
         .set_json(&payload)
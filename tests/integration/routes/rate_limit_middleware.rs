@@ -0,0 +1,250 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::middleware::from_fn;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sentinel_guard::{
+    config::{RateLimitConfig, RateLimitRule},
+    middleware::rate_limit::rate_limit,
+    repositories::{
+        environment_key_repository::EnvironmentKeyRepository, project_repository::ProjectRepository,
+    },
+    routes::{environment_key_route, health_route, project_route},
+    services::health_service::HealthService,
+    utils::rate_limit::{
+        RateLimitCategory, RateLimitStore, RateLimiter, postgres::PostgresRateLimitStore,
+    },
+};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::integration::secrets_manager;
+
+fn rule(capacity: u32) -> RateLimitRule {
+    RateLimitRule {
+        capacity,
+        refill_per_second: 0.01,
+    }
+}
+
+fn limiter() -> RateLimiter {
+    RateLimiter::new(RateLimitConfig {
+        admin: rule(2),
+        key_generation: rule(1),
+        ..Default::default()
+    })
+}
+
+macro_rules! app {
+    ($pool:expr, $limiter:expr) => {{
+        let pool = Arc::new($pool);
+        actix_web::test::init_service(
+            actix_web::App::new()
+                .wrap(from_fn(rate_limit))
+                .app_data(actix_web::web::Data::new($limiter))
+                .app_data(actix_web::web::Data::new(ProjectRepository::new(
+                    pool.clone(),
+                )))
+                .app_data(actix_web::web::Data::new(EnvironmentKeyRepository::new(
+                    pool.clone(),
+                    secrets_manager(),
+                )))
+                .app_data(actix_web::web::Data::new(HealthService::new(
+                    pool,
+                    secrets_manager(),
+                )))
+                .configure(health_route::configure_routes)
+                .configure(project_route::configure_routes)
+                .configure(environment_key_route::configure_routes),
+        )
+        .await
+    }};
+}
+
+fn from(ip: &str) -> SocketAddr {
+    format!("{}:40000", ip).parse().unwrap()
+}
+
+#[sqlx::test]
+async fn test_rate_limit_middleware_limits_requests_per_ip(pool: PgPool) {
+    let app = app!(pool, limiter());
+
+    for remaining in ["1", "0"] {
+        let response = actix_web::test::TestRequest::get()
+            .uri("/projects")
+            .peer_addr(from("192.0.2.1"))
+            .send_request(&app)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("ratelimit-limit").unwrap(), "2");
+        assert_eq!(
+            response.headers().get("ratelimit-remaining").unwrap(),
+            remaining
+        );
+    }
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(from("192.0.2.1"))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response
+        .headers()
+        .get("retry-after")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(retry_after, 100);
+    assert_eq!(response.headers().get("ratelimit-remaining").unwrap(), "0");
+    assert_eq!(response.headers().get("ratelimit-reset").unwrap(), "200");
+    let body: Value = actix_web::test::read_body_json(response).await;
+    assert_eq!(body["error"], "rate_limited");
+
+    // Other addresses have their own bucket, health probes are not limited
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(from("192.0.2.2"))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = actix_web::test::TestRequest::get()
+        .uri("/healthz")
+        .peer_addr(from("192.0.2.1"))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("ratelimit-limit").is_none());
+}
+
+#[sqlx::test]
+async fn test_rate_limit_middleware_limits_key_rotation_separately(pool: PgPool) {
+    let app = app!(pool, limiter());
+    let rotate = format!("/environment-keys/{}/rotate", Uuid::new_v4());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri(&rotate)
+        .peer_addr(from("192.0.2.1"))
+        .send_request(&app)
+        .await;
+    assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers().get("ratelimit-limit").unwrap(), "1");
+
+    let response = actix_web::test::TestRequest::post()
+        .uri(&rotate)
+        .peer_addr(from("192.0.2.1"))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Reading keys falls under the admin rule
+    let response = actix_web::test::TestRequest::get()
+        .uri("/environment-keys")
+        .peer_addr(from("192.0.2.1"))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[sqlx::test]
+async fn test_rate_limit_middleware_ignores_claimed_clients(pool: PgPool) {
+    let app = app!(pool, limiter());
+    // Anyone can claim a client in an unverified Authorization header
+    let authorization = format!("Basic {}", STANDARD.encode("client-a:secret"));
+
+    for ip in ["192.0.2.1", "192.0.2.2", "192.0.2.3"] {
+        let response = actix_web::test::TestRequest::get()
+            .uri("/projects")
+            .peer_addr(from(ip))
+            .insert_header(("Authorization", authorization.as_str()))
+            .send_request(&app)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("ratelimit-remaining").unwrap(), "1");
+    }
+}
+
+#[sqlx::test]
+async fn test_rate_limit_postgres_store_is_shared_by_replicas(pool: PgPool) {
+    let pool = Arc::new(pool);
+    let replicas = [
+        limiter().with_store(Arc::new(PostgresRateLimitStore::new(pool.clone()))),
+        limiter().with_store(Arc::new(PostgresRateLimitStore::new(pool.clone()))),
+    ];
+    let ip = Some("192.0.2.1".parse().unwrap());
+
+    let first = replicas[0]
+        .check(RateLimitCategory::Admin, ip, None)
+        .await
+        .unwrap();
+    let second = replicas[1]
+        .check(RateLimitCategory::Admin, ip, None)
+        .await
+        .unwrap();
+    let third = replicas[0]
+        .check(RateLimitCategory::Admin, ip, None)
+        .await
+        .unwrap();
+    assert!(first.allowed && second.allowed);
+    assert_eq!(second.remaining, 0);
+    assert!(!third.allowed);
+    assert_eq!(third.retry_after, 100);
+
+    // Concurrent requests never take more than the capacity
+    let store = Arc::new(PostgresRateLimitStore::new(pool.clone()));
+    let mut requests = tokio::task::JoinSet::new();
+    for _ in 0..10 {
+        let store = store.clone();
+        requests.spawn(async move { store.acquire("admin:ip:192.0.2.2", &rule(5)).await });
+    }
+    let allowed = requests
+        .join_all()
+        .await
+        .into_iter()
+        .filter(|decision| decision.as_ref().unwrap().allowed)
+        .count();
+    assert_eq!(allowed, 5);
+}
+
+#[sqlx::test]
+async fn test_rate_limit_postgres_store_prunes_full_buckets(pool: PgPool) {
+    let pool = Arc::new(pool);
+    let store = PostgresRateLimitStore::new(pool.clone());
+    store.acquire("admin:ip:192.0.2.1", &rule(2)).await.unwrap();
+    store.acquire("admin:ip:192.0.2.2", &rule(2)).await.unwrap();
+    assert_eq!(store.prune().await.unwrap(), 0);
+
+    sqlx::query("UPDATE rate_limit_buckets SET full_at = now() - interval '1 second' WHERE key = 'admin:ip:192.0.2.1'")
+        .execute(&*pool)
+        .await
+        .unwrap();
+    assert_eq!(store.prune().await.unwrap(), 1);
+}
+
+#[sqlx::test]
+async fn test_rate_limit_middleware_lets_requests_through_when_store_fails(pool: PgPool) {
+    let store_pool = Arc::new(pool.clone());
+    let limiter = limiter().with_store(Arc::new(PostgresRateLimitStore::new(store_pool.clone())));
+    store_pool.close().await;
+    let app = app!(
+        PgPool::connect_with((*pool.connect_options()).clone())
+            .await
+            .unwrap(),
+        limiter
+    );
+
+    for _ in 0..3 {
+        let response = actix_web::test::TestRequest::get()
+            .uri("/projects")
+            .peer_addr(from("192.0.2.1"))
+            .send_request(&app)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("ratelimit-limit").is_none());
+    }
+}
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use actix_web::middleware::from_fn;
+use sentinel_guard::{
+    config::{LogFormat, LoggingConfig, TracingConfig},
+    middleware::request_id::request_id,
+    routes::health_route,
+    services::health_service::HealthService,
+    utils::logging,
+};
+use serde_json::Value;
+use sqlx::PgPool;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+use crate::integration::secrets_manager;
+
+#[sqlx::test]
+async fn test_request_id_middleware_propagates_or_generates_request_ids(pool: PgPool) {
+    let app = actix_web::test::init_service(
+        actix_web::App::new()
+            .wrap(from_fn(request_id))
+            .app_data(actix_web::web::Data::new(HealthService::new(
+                Arc::new(pool),
+                secrets_manager(),
+            )))
+            .configure(health_route::configure_routes),
+    )
+    .await;
+
+    let request = actix_web::test::TestRequest::get()
+        .uri("/healthz")
+        .insert_header(("X-Request-Id", "edge-7f3c.1_a"))
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+    assert_eq!(
+        response.headers().get("x-request-id").unwrap(),
+        "edge-7f3c.1_a"
+    );
+
+    let request = actix_web::test::TestRequest::get()
+        .uri("/healthz")
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+    let generated = response.headers().get("x-request-id").unwrap();
+    assert!(Uuid::parse_str(generated.to_str().unwrap()).is_ok());
+
+    let request = actix_web::test::TestRequest::get()
+        .uri("/does-not-exist")
+        .insert_header(("X-Request-Id", "id with spaces\tand tabs"))
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+    let replaced = response.headers().get("x-request-id").unwrap();
+    assert!(Uuid::parse_str(replaced.to_str().unwrap()).is_ok());
+}
+
+/// Accepts one OTLP/HTTP export request, answering like a collector, and returns its body
+async fn receive_export(listener: TcpListener) -> Value {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut received = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        let read = stream.read(&mut buffer).await.unwrap();
+        received.extend_from_slice(&buffer[..read]);
+        let text = String::from_utf8_lossy(&received);
+        if let Some(headers_end) = text.find("\r\n\r\n") {
+            let content_length = text[..headers_end]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().unwrap())
+                })
+                .unwrap();
+            if received.len() >= headers_end + 4 + content_length {
+                assert!(text.starts_with("POST /v1/traces "));
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n{}")
+                    .await
+                    .unwrap();
+                return serde_json::from_slice(&received[headers_end + 4..]).unwrap();
+            }
+        }
+    }
+}
+
+#[sqlx::test]
+async fn test_request_id_middleware_exports_spans_of_the_caller_trace(pool: PgPool) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = TracingConfig {
+        otlp_endpoint: Some(format!("http://{}", listener.local_addr().unwrap())),
+        sample_ratio: 0.0,
+        ..Default::default()
+    };
+    let collector = tokio::spawn(receive_export(listener));
+    let (logger, provider) =
+        logging::subscriber(&LoggingConfig::default(), &config, std::io::sink).unwrap();
+    let _default = tracing::subscriber::set_default(logger);
+
+    let app = actix_web::test::init_service(
+        actix_web::App::new()
+            .wrap(from_fn(request_id))
+            .app_data(actix_web::web::Data::new(HealthService::new(
+                Arc::new(pool),
+                secrets_manager(),
+            )))
+            .configure(health_route::configure_routes),
+    )
+    .await;
+
+    let request = actix_web::test::TestRequest::get()
+        .uri("/healthz")
+        .insert_header((
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ))
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+
+    // Not sampled, the ratio of 0.0 applies to traces started here
+    let request = actix_web::test::TestRequest::get()
+        .uri("/healthz")
+        .to_request();
+    actix_web::test::call_service(&app, request).await;
+
+    // The batch processor exports from its own thread, blocking until the collector answered
+    tokio::task::spawn_blocking(move || provider.shutdown().unwrap())
+        .await
+        .unwrap();
+    let export = collector.await.unwrap();
+
+    let resource = &export["resourceSpans"][0];
+    let service_name = resource["resource"]["attributes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|attribute| attribute["key"] == "service.name")
+        .unwrap();
+    assert_eq!(service_name["value"]["stringValue"], "sentinel-guard");
+    let spans = resource["scopeSpans"][0]["spans"].as_array().unwrap();
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0]["traceId"], "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(spans[0]["parentSpanId"], "00f067aa0ba902b7");
+    assert_eq!(spans[0]["name"], "GET /healthz");
+    assert_eq!(spans[0]["kind"], 2);
+}
+
+/// Collects the log lines written by a logger
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        std::io::Write::write(&mut *self.0.lock().unwrap(), bytes)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[actix_web::test]
+async fn test_request_id_middleware_logs_the_error_of_server_errors() {
+    let buffer = LogBuffer::default();
+    let logging = LoggingConfig {
+        format: LogFormat::Json,
+        ..Default::default()
+    };
+    let writer = buffer.clone();
+    let (logger, _) =
+        logging::subscriber(&logging, &TracingConfig::default(), move || writer.clone()).unwrap();
+    let _default = tracing::subscriber::set_default(logger);
+
+    let app = actix_web::test::init_service(actix_web::App::new().wrap(from_fn(request_id)).route(
+        "/failing",
+        actix_web::web::get().to(|| async {
+            Err::<String, _>(actix_web::error::ErrorInternalServerError(
+                "Connection pool timed out",
+            ))
+        }),
+    ))
+    .await;
+    let request = actix_web::test::TestRequest::get()
+        .uri("/failing")
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+    assert_eq!(
+        response.status(),
+        actix_web::http::StatusCode::INTERNAL_SERVER_ERROR
+    );
+
+    let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let line: Value = serde_json::from_str(logs.lines().last().unwrap()).unwrap();
+    assert_eq!(line["level"], "ERROR");
+    assert_eq!(line["message"], "Request failed");
+    assert_eq!(line["fields"]["status"], "500");
+    assert_eq!(line["fields"]["error"], "Connection pool timed out");
+}
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use sentinel_guard::{
+    models::{environment_key::EnvironmentKeyCreatePayload, revocation_list::RevocationListClaims},
+    repositories::{
+        access_token_repository::AccessTokenRepository, base::Repository,
+        environment_key_repository::EnvironmentKeyRepository,
+    },
+    routes::revocation_list_route,
+    utils::tokens::key_builder::KeyBuilder,
+};
+
+use crate::integration::secrets_manager;
+
+const ENVIRONMENT_ID: &str = "00000000-0000-0000-0000-000000000001";
+
+macro_rules! create_revocation_list_app {
+    ($pool:expr) => {{
+        let pool = Arc::new($pool);
+        actix_web::test::init_service(
+            actix_web::App::new()
+                .app_data(actix_web::web::Data::new(AccessTokenRepository::new(
+                    pool.clone(),
+                )))
+                .app_data(actix_web::web::Data::new(EnvironmentKeyRepository::new(
+                    pool.clone(),
+                    secrets_manager(),
+                )))
+                .configure(revocation_list_route::configure_routes),
+        )
+        .await
+    }};
+}
+
+async fn create_environment_key(pool: &PgPool) -> Vec<u8> {
+    let repository = EnvironmentKeyRepository::new(Arc::new(pool.clone()), secrets_manager());
+    repository
+        .create(EnvironmentKeyCreatePayload {
+            environment_id: ENVIRONMENT_ID.to_string(),
+            algorithm: "HS256".to_string(),
+            active: true,
+            rotation_interval_days: None,
+        })
+        .await
+        .unwrap();
+    let (_, key) = repository
+        .get_active_key(Uuid::parse_str(ENVIRONMENT_ID).unwrap(), None)
+        .await
+        .unwrap();
+    KeyBuilder::signing_key_from_str(Algorithm::HS256, &key).unwrap()
+}
+
+#[sqlx::test(fixtures("../fixtures/revocation_lists.sql"))]
+async fn test_revocation_list_route_get_returns_signed_list(pool: PgPool) {
+    let signing_key = create_environment_key(&pool).await;
+    let app = create_revocation_list_app!(pool);
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!("/revocation-lists/{}", ENVIRONMENT_ID))
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+    assert!(response.headers().contains_key("etag"));
+    let body = actix_web::test::read_body(response).await;
+    let token = String::from_utf8(body.to_vec()).unwrap();
+
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.sub = Some(ENVIRONMENT_ID.to_string());
+    let claims = decode::<RevocationListClaims>(
+        &token,
+        &DecodingKey::from_secret(&signing_key),
+        &validation,
+    )
+    .unwrap()
+    .claims;
+    assert_eq!(claims.revoked.len(), 1);
+    assert_eq!(claims.revoked[0].jti, "jti-revoked");
+}
+
+#[sqlx::test(fixtures("../fixtures/revocation_lists.sql"))]
+async fn test_revocation_list_route_get_with_matching_etag_returns_not_modified(pool: PgPool) {
+    create_environment_key(&pool).await;
+    let app = create_revocation_list_app!(pool);
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!("/revocation-lists/{}", ENVIRONMENT_ID))
+        .send_request(&app)
+        .await;
+    let etag = response.headers().get("etag").unwrap().clone();
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!("/revocation-lists/{}", ENVIRONMENT_ID))
+        .insert_header(("If-None-Match", etag.clone()))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers().get("etag").unwrap(), &etag);
+    // Caches keep revalidating, as for the full response
+    assert_eq!(response.headers().get("cache-control").unwrap(), "no-cache");
+}
+
+#[sqlx::test(fixtures("../fixtures/revocation_lists.sql"))]
+async fn test_revocation_list_route_etag_changes_after_revocation(pool: PgPool) {
+    create_environment_key(&pool).await;
+    let access_token_repository = AccessTokenRepository::new(Arc::new(pool.clone()));
+    let app = create_revocation_list_app!(pool);
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!("/revocation-lists/{}", ENVIRONMENT_ID))
+        .send_request(&app)
+        .await;
+    let etag = response.headers().get("etag").unwrap().clone();
+
+    access_token_repository
+        .update(
+            Uuid::parse_str("11111111-1111-1111-1111-111111111111").unwrap(),
+            sentinel_guard::models::access_token::AccessTokenUpdatePayload {
+                active: Some(false),
+            },
+        )
+        .await
+        .unwrap();
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!("/revocation-lists/{}", ENVIRONMENT_ID))
+        .insert_header(("If-None-Match", etag.clone()))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+    assert_ne!(response.headers().get("etag").unwrap(), &etag);
+}
+
+#[sqlx::test(fixtures("../fixtures/revocation_lists.sql"))]
+async fn test_revocation_list_route_get_without_key_not_found(pool: PgPool) {
+    let app = create_revocation_list_app!(pool);
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!("/revocation-lists/{}", ENVIRONMENT_ID))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("../fixtures/revocation_lists.sql"))]
+async fn test_revocation_list_route_get_with_invalid_algorithm_fails(pool: PgPool) {
+    let app = create_revocation_list_app!(pool);
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!(
+            "/revocation-lists/{}?algorithm=XX999",
+            ENVIRONMENT_ID
+        ))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
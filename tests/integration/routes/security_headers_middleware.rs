@@ -0,0 +1,155 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use actix_web::HttpResponse;
+use actix_web::http::StatusCode;
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::middleware::from_fn;
+use sentinel_guard::{
+    config::SecurityHeadersConfig,
+    middleware::security_headers::{SecurityHeaders, security_headers},
+    repositories::project_repository::ProjectRepository,
+    routes::project_route,
+};
+use sqlx::PgPool;
+
+fn from(ip: &str) -> SocketAddr {
+    format!("{}:40000", ip).parse().unwrap()
+}
+
+/// Security headers trusting the proxies of 10.0.0.0/8
+fn security_headers_behind_proxy(config: SecurityHeadersConfig) -> SecurityHeaders {
+    SecurityHeaders::new(config).with_trusted_proxies(vec!["10.0.0.0/8".parse().unwrap()])
+}
+
+macro_rules! app {
+    ($pool:expr, $config:expr) => {{
+        let app = actix_web::App::new()
+            .wrap(from_fn(security_headers))
+            .app_data(actix_web::web::Data::new(ProjectRepository::new(Arc::new(
+                $pool,
+            ))))
+            .configure(project_route::configure_routes)
+            .route(
+                "/cached",
+                actix_web::web::get().to(|| async {
+                    HttpResponse::Ok()
+                        .insert_header((CACHE_CONTROL, "max-age=60"))
+                        .finish()
+                }),
+            );
+        let app = match $config {
+            Some(config) => app.app_data(actix_web::web::Data::new(config)),
+            None => app,
+        };
+        actix_web::test::init_service(app).await
+    }};
+}
+
+#[sqlx::test]
+async fn test_security_headers_middleware_adds_headers(pool: PgPool) {
+    let app = app!(
+        pool,
+        Some(security_headers_behind_proxy(
+            SecurityHeadersConfig::default()
+        ))
+    );
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let headers = response.headers();
+    assert_eq!(headers.get("x-content-type-options").unwrap(), "nosniff");
+    assert_eq!(headers.get("x-frame-options").unwrap(), "DENY");
+    assert_eq!(headers.get("referrer-policy").unwrap(), "no-referrer");
+    assert_eq!(headers.get("cache-control").unwrap(), "no-store");
+    // Plain HTTP, browsers would ignore it
+    assert!(headers.get("strict-transport-security").is_none());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(from("10.0.0.1"))
+        .insert_header(("X-Forwarded-Proto", "https"))
+        .send_request(&app)
+        .await;
+    assert_eq!(
+        response.headers().get("strict-transport-security").unwrap(),
+        "max-age=31536000"
+    );
+
+    // Clients reaching the server directly cannot claim HTTPS
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(from("192.0.2.1"))
+        .insert_header(("X-Forwarded-Proto", "https"))
+        .send_request(&app)
+        .await;
+    assert!(
+        response
+            .headers()
+            .get("strict-transport-security")
+            .is_none()
+    );
+
+    // Responses choosing their own caching keep it
+    let response = actix_web::test::TestRequest::get()
+        .uri("/cached")
+        .send_request(&app)
+        .await;
+    assert_eq!(
+        response.headers().get("cache-control").unwrap(),
+        "max-age=60"
+    );
+    assert_eq!(
+        response.headers().get("x-content-type-options").unwrap(),
+        "nosniff"
+    );
+}
+
+#[sqlx::test]
+async fn test_security_headers_middleware_without_hsts(pool: PgPool) {
+    let app = app!(
+        pool,
+        Some(security_headers_behind_proxy(SecurityHeadersConfig {
+            hsts_max_age_seconds: 0,
+            ..Default::default()
+        }))
+    );
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .peer_addr(from("10.0.0.1"))
+        .insert_header(("X-Forwarded-Proto", "https"))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response
+            .headers()
+            .get("strict-transport-security")
+            .is_none()
+    );
+    assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+}
+
+#[sqlx::test]
+async fn test_security_headers_middleware_passes_through_when_disabled(pool: PgPool) {
+    let app = app!(pool, None::<SecurityHeaders>);
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/projects")
+        .insert_header(("X-Forwarded-Proto", "https"))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("x-content-type-options").is_none());
+    assert!(
+        response
+            .headers()
+            .get("strict-transport-security")
+            .is_none()
+    );
+    assert!(response.headers().get("cache-control").is_none());
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    models::service_account_credential::{
+        ServiceAccountCredentialCreatePayload, ServiceAccountCredentialResponse,
+        ServiceAccountCredentialRotatePayload, ServiceAccountCredentialUpdatePayload,
+    },
+    repositories::{
+        base::Repository, service_account_credential_repository::ServiceAccountCredentialRepository,
+    },
+    routes::service_account_credential_route,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::create_test_app;
+
+const SERVICE_ACCOUNT_ID: &str = "30000000-0000-0000-0000-000000000001";
+const OTHER_SERVICE_ACCOUNT_ID: &str = "30000000-0000-0000-0000-000000000002";
+
+fn repositories(pool: PgPool) -> ServiceAccountCredentialRepository {
+    ServiceAccountCredentialRepository::new(Arc::new(pool))
+}
+
+fn routes() -> fn(&mut actix_web::web::ServiceConfig) {
+    service_account_credential_route::configure_routes
+}
+
+fn payload() -> ServiceAccountCredentialCreatePayload {
+    ServiceAccountCredentialCreatePayload {
+        label: "ci".to_string(),
+        expires_at: None,
+    }
+}
+
+async fn create_credential(pool: &PgPool) -> Uuid {
+    repositories(pool.clone())
+        .create(payload().with_secret(Uuid::parse_str(SERVICE_ACCOUNT_ID).unwrap()))
+        .await
+        .unwrap()
+        .id
+        .unwrap()
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_route_create_returns_secret_once(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri(&format!(
+            "/service-accounts/{}/credentials",
+            SERVICE_ACCOUNT_ID
+        ))
+        .set_json(payload())
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::CREATED);
+    let credential: ServiceAccountCredentialResponse =
+        actix_web::test::read_body_json(response).await;
+    assert_eq!(credential.label, "ci");
+    assert_eq!(credential.service_account_id, SERVICE_ACCOUNT_ID);
+    assert!(credential.active);
+    assert!(credential.secret.is_some());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!(
+            "/service-accounts/{}/credentials/{}",
+            SERVICE_ACCOUNT_ID, credential.id
+        ))
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let credential: ServiceAccountCredentialResponse =
+        actix_web::test::read_body_json(response).await;
+    assert!(credential.secret.is_none());
+}
+
+#[sqlx::test]
+async fn test_service_account_credential_route_create_with_missing_service_account_returns_not_found(
+    pool: PgPool,
+) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri(&format!(
+            "/service-accounts/{}/credentials",
+            SERVICE_ACCOUNT_ID
+        ))
+        .set_json(payload())
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_route_create_with_past_expiry_returns_bad_request(
+    pool: PgPool,
+) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri(&format!(
+            "/service-accounts/{}/credentials",
+            SERVICE_ACCOUNT_ID
+        ))
+        .set_json(ServiceAccountCredentialCreatePayload {
+            expires_at: Some("2020-01-01T00:00:00Z".to_string()),
+            ..payload()
+        })
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_route_list_returns_credentials_of_service_account(
+    pool: PgPool,
+) {
+    create_credential(&pool).await;
+    create_credential(&pool).await;
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!(
+            "/service-accounts/{}/credentials",
+            SERVICE_ACCOUNT_ID
+        ))
+        .send_request(&app)
+        .await;
+    let credentials: Vec<ServiceAccountCredentialResponse> =
+        actix_web::test::read_body_json(response).await;
+    assert_eq!(credentials.len(), 2);
+    assert!(
+        credentials
+            .iter()
+            .all(|credential| credential.secret.is_none())
+    );
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!(
+            "/service-accounts/{}/credentials",
+            OTHER_SERVICE_ACCOUNT_ID
+        ))
+        .send_request(&app)
+        .await;
+    let credentials: Vec<ServiceAccountCredentialResponse> =
+        actix_web::test::read_body_json(response).await;
+    assert!(credentials.is_empty());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_route_hides_credentials_of_other_service_accounts(
+    pool: PgPool,
+) {
+    let id = create_credential(&pool).await;
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::delete()
+        .uri(&format!(
+            "/service-accounts/{}/credentials/{}",
+            OTHER_SERVICE_ACCOUNT_ID, id
+        ))
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_route_patch_updates_label(pool: PgPool) {
+    let id = create_credential(&pool).await;
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::patch()
+        .uri(&format!(
+            "/service-accounts/{}/credentials/{}",
+            SERVICE_ACCOUNT_ID, id
+        ))
+        .set_json(ServiceAccountCredentialUpdatePayload {
+            label: Some("deploy".to_string()),
+            ..Default::default()
+        })
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let credential: ServiceAccountCredentialResponse =
+        actix_web::test::read_body_json(response).await;
+    assert_eq!(credential.label, "deploy");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_route_rotate_returns_new_credential(pool: PgPool) {
+    let id = create_credential(&pool).await;
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri(&format!(
+            "/service-accounts/{}/credentials/{}/rotate",
+            SERVICE_ACCOUNT_ID, id
+        ))
+        .set_json(ServiceAccountCredentialRotatePayload {
+            label: Some("ci-2".to_string()),
+            grace_period_seconds: Some(600),
+            expires_at: None,
+        })
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::CREATED);
+    let credential: ServiceAccountCredentialResponse =
+        actix_web::test::read_body_json(response).await;
+    assert_ne!(credential.id, id.to_string());
+    assert_eq!(credential.label, "ci-2");
+    assert!(credential.secret.is_some());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri(&format!(
+            "/service-accounts/{}/credentials/{}",
+            SERVICE_ACCOUNT_ID, id
+        ))
+        .send_request(&app)
+        .await;
+    let rotated: ServiceAccountCredentialResponse = actix_web::test::read_body_json(response).await;
+    assert!(rotated.active);
+    assert!(rotated.expires_at.is_some());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_credential_route_rotate_expired_credential_returns_bad_request(
+    pool: PgPool,
+) {
+    let id = create_credential(&pool).await;
+    sqlx::query(
+        "UPDATE service_account_credentials SET expires_at = now() - interval '1 minute' WHERE id = $1",
+    )
+    .bind(id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri(&format!(
+            "/service-accounts/{}/credentials/{}/rotate",
+            SERVICE_ACCOUNT_ID, id
+        ))
+        .set_json(ServiceAccountCredentialRotatePayload::default())
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
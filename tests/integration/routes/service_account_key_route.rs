@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use jsonwebtoken::Algorithm;
+use sentinel_guard::{
+    models::service_account_key::{
+        ServiceAccountKeyCreatePayload, ServiceAccountKeyResponse, ServiceAccountKeyUpdatePayload,
+    },
+    repositories::{base::Repository, service_account_key_repository::ServiceAccountKeyRepository},
+    routes::service_account_key_route,
+    utils::tokens::key_builder::KeyBuilder,
+};
+use sqlx::PgPool;
+
+use crate::create_test_app;
+
+fn repositories(pool: PgPool) -> ServiceAccountKeyRepository {
+    ServiceAccountKeyRepository::new(Arc::new(pool))
+}
+
+fn routes() -> fn(&mut actix_web::web::ServiceConfig) {
+    service_account_key_route::configure_routes
+}
+
+fn payload() -> ServiceAccountKeyCreatePayload {
+    let key_pair = KeyBuilder::new().generate_key(Algorithm::RS256).unwrap();
+    ServiceAccountKeyCreatePayload {
+        service_account_id: "30000000-0000-0000-0000-000000000001".to_string(),
+        name: "deployer".to_string(),
+        algorithm: "RS256".to_string(),
+        public_key: key_pair.public_key_str.unwrap(),
+        enabled: None,
+    }
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_key_route_create_with_valid_data_succeeds(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/service-account-keys")
+        .set_json(payload())
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::CREATED);
+    let service_account_key: ServiceAccountKeyResponse =
+        actix_web::test::read_body_json(response).await;
+    assert_eq!(service_account_key.name, "deployer");
+    assert!(service_account_key.enabled);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_key_route_create_with_mismatched_algorithm_returns_bad_request(
+    pool: PgPool,
+) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let payload = ServiceAccountKeyCreatePayload {
+        algorithm: "ES256".to_string(),
+        ..payload()
+    };
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/service-account-keys")
+        .set_json(payload)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+async fn test_service_account_key_route_create_with_missing_service_account_returns_not_found(
+    pool: PgPool,
+) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/service-account-keys")
+        .set_json(payload())
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_key_route_patch_disables_key(pool: PgPool) {
+    let repository = repositories(pool.clone());
+    let service_account_key = repository.create(payload()).await.unwrap();
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::patch()
+        .uri(&format!(
+            "/service-account-keys/{}",
+            service_account_key.id.unwrap()
+        ))
+        .set_json(ServiceAccountKeyUpdatePayload {
+            enabled: Some(false),
+            ..Default::default()
+        })
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let service_account_key: ServiceAccountKeyResponse =
+        actix_web::test::read_body_json(response).await;
+    assert!(!service_account_key.enabled);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_service_account_key_route_delete_nonexistent_returns_not_found(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::delete()
+        .uri(&format!("/service-account-keys/{}", uuid::Uuid::new_v4()))
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+}
@@ -11,9 +11,10 @@ use sentinel_guard::{
 };
 
 use crate::create_test_app;
+use crate::integration::secrets_manager;
 
 fn repositories(pool: PgPool) -> ServiceAccountRepository {
-    ServiceAccountRepository::new(Arc::new(pool))
+    ServiceAccountRepository::new(Arc::new(pool), secrets_manager())
 }
 
 fn routes() -> fn(&mut actix_web::web::ServiceConfig) {
@@ -30,6 +31,8 @@ async fn test_service_account_route_create_service_account_with_valid_data_succe
         enabled: true,
         email: "test@example.com".to_string(),
         secret: "test".to_string(),
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let response = actix_web::test::TestRequest::post()
@@ -57,6 +60,8 @@ async fn test_service_account_route_create_service_account_with_duplicate_name_f
         enabled: true,
         email: "test@example.com".to_string(),
         secret: "something".to_string(),
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let response = actix_web::test::TestRequest::post()
@@ -82,6 +87,8 @@ async fn test_service_account_route_create_service_account_with_duplicate_email_
         enabled: true,
         email: "test@example.com".to_string(),
         secret: "something".to_string(),
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let response = actix_web::test::TestRequest::post()
@@ -139,6 +146,8 @@ async fn test_service_account_route_patch_service_account_name_succeeds(pool: Pg
         enabled: None,
         email: None,
         secret: None,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let response = actix_web::test::TestRequest::patch()
@@ -160,6 +169,8 @@ async fn test_service_account_route_patch_service_account_description_succeeds(p
         enabled: None,
         email: None,
         secret: None,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let response = actix_web::test::TestRequest::patch()
@@ -183,6 +194,8 @@ async fn test_service_account_route_patch_service_account_enabled_succeeds(pool:
         enabled: Some(false),
         email: None,
         secret: None,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let response = actix_web::test::TestRequest::patch()
@@ -204,6 +217,8 @@ async fn test_service_account_route_patch_service_account_empty_payload(pool: Pg
         enabled: None,
         email: None,
         secret: None,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let response = actix_web::test::TestRequest::patch()
@@ -226,6 +241,8 @@ async fn test_service_account_route_patch_service_account_not_found(pool: PgPool
         enabled: None,
         email: None,
         secret: None,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let response = actix_web::test::TestRequest::patch()
@@ -248,6 +265,8 @@ async fn test_service_account_route_patch_service_account_duplicate_name_error(p
         enabled: None,
         email: None,
         secret: None,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let response = actix_web::test::TestRequest::patch()
@@ -270,6 +289,8 @@ async fn test_service_account_route_patch_service_account_duplicate_email_error(
         enabled: None,
         email: Some("test3@example.com".to_string()),
         secret: None,
+        allowed_cidrs: None,
+        expires_at: None,
     };
 
     let response = actix_web::test::TestRequest::patch()
@@ -282,6 +303,29 @@ async fn test_service_account_route_patch_service_account_duplicate_email_error(
     assert_eq!(response.status(), actix_web::http::StatusCode::CONFLICT);
 }
 
+#[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
+async fn test_service_account_route_patch_service_account_invalid_cidr_error(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let payload = ServiceAccountUpdatePayload {
+        name: None,
+        description: None,
+        enabled: None,
+        email: None,
+        secret: None,
+        allowed_cidrs: Some(vec!["10.0.0.0/99".to_string()]),
+        expires_at: None,
+    };
+
+    let response = actix_web::test::TestRequest::patch()
+        .uri("/service-accounts/123e4567-e89b-12d3-a456-426614174000")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
 #[sqlx::test(fixtures("../fixtures/service_accounts.sql"))]
 async fn test_service_account_route_delete_service_account_succeeds(pool: PgPool) {
     let app = create_test_app!(repositories(pool), routes());
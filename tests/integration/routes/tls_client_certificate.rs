@@ -0,0 +1,175 @@
+use actix_web::{HttpRequest, HttpResponse, HttpServer, web};
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::extension::{BasicConstraints, SubjectAlternativeName};
+use openssl::x509::{X509, X509NameBuilder};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+use sentinel_guard::{
+    config::{ClientAuthMode, TlsConfig},
+    utils::tls::{ClientCertificate, TlsReloader, capture_client_certificate},
+};
+use std::fs;
+use std::io::{Read, Write};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Issues a P-256 certificate, self-signed when no issuer is given
+fn issue(common_name: &str, issuer: Option<&(X509, PKey<Private>)>) -> (X509, PKey<Private>) {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, common_name)
+        .unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = BigNum::from_u32(rand::random::<u32>()).unwrap();
+    builder
+        .set_serial_number(&serial.to_asn1_integer().unwrap())
+        .unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder
+        .set_issuer_name(issuer.map_or(&name, |(certificate, _)| certificate.subject_name()))
+        .unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    match issuer {
+        None => builder
+            .append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+            .unwrap(),
+        Some((certificate, _)) => {
+            let san = SubjectAlternativeName::new()
+                .dns(common_name)
+                .build(&builder.x509v3_context(Some(certificate), None))
+                .unwrap();
+            builder.append_extension(san).unwrap();
+        }
+    }
+    builder
+        .sign(issuer.map_or(&key, |(_, key)| key), MessageDigest::sha256())
+        .unwrap();
+    (builder.build(), key)
+}
+
+/// Requests `/certificate` over TLS, with a client certificate when given, returning the
+/// raw HTTP response
+async fn get_certificate(
+    port: u16,
+    server_ca: &X509,
+    identity: Option<(Vec<CertificateDer<'static>>, Vec<u8>)>,
+) -> String {
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from(server_ca.to_der().unwrap()))
+        .unwrap();
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots);
+    let config = match identity {
+        Some((chain, key)) => builder
+            .with_client_auth_cert(chain, PrivateKeyDer::try_from(key).unwrap())
+            .unwrap(),
+        None => builder.with_no_client_auth(),
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let connection =
+            ClientConnection::new(Arc::new(config), "localhost".try_into().unwrap()).unwrap();
+        let socket = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut stream = StreamOwned::new(connection, socket);
+        stream
+            .write_all(b"GET /certificate HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        response
+    })
+    .await
+    .unwrap()
+}
+
+async fn client_certificate(request: HttpRequest) -> HttpResponse {
+    match request.conn_data::<ClientCertificate>() {
+        Some(certificate) => HttpResponse::Ok().body(certificate.thumbprint_sha256()),
+        None => HttpResponse::Unauthorized().finish(),
+    }
+}
+
+#[actix_web::test]
+async fn test_tls_server_exposes_verified_client_certificate() {
+    let directory = std::env::temp_dir().join(format!("sentinel-guard-tls-{}", Uuid::new_v4()));
+    fs::create_dir_all(&directory).unwrap();
+    let server_ca = issue("Server CA", None);
+    let client_ca = issue("Client CA", None);
+    let (server_certificate, server_key) = issue("localhost", Some(&server_ca));
+    let (client_x509, client_key) = issue("client", Some(&client_ca));
+    fs::write(
+        directory.join("tls.crt"),
+        server_certificate.to_pem().unwrap(),
+    )
+    .unwrap();
+    fs::write(
+        directory.join("tls.key"),
+        server_key.private_key_to_pem_pkcs8().unwrap(),
+    )
+    .unwrap();
+    fs::write(directory.join("ca.crt"), client_ca.0.to_pem().unwrap()).unwrap();
+
+    let reloader = TlsReloader::load(&TlsConfig {
+        cert_path: Some(directory.join("tls.crt")),
+        key_path: Some(directory.join("tls.key")),
+        client_ca_path: Some(directory.join("ca.crt")),
+        client_auth: ClientAuthMode::Optional,
+        reload_interval_seconds: 0,
+    })
+    .unwrap();
+    let server = HttpServer::new(|| {
+        actix_web::App::new().route("/certificate", web::get().to(client_certificate))
+    })
+    .on_connect(capture_client_certificate)
+    .workers(1)
+    .bind_rustls_0_23(("127.0.0.1", 0), reloader.server_config().unwrap())
+    .unwrap();
+    let port = server.addrs()[0].port();
+    let server = server.run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+
+    let client_chain = vec![client_x509.to_der().unwrap().into()];
+    let client_key_der = client_key.private_key_to_pkcs8().unwrap();
+    let response = get_certificate(
+        port,
+        &server_ca.0,
+        Some((client_chain.clone(), client_key_der)),
+    )
+    .await;
+    let expected = ClientCertificate {
+        chain: client_chain,
+    };
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(
+        response.ends_with(&expected.thumbprint_sha256()),
+        "{}",
+        response
+    );
+
+    // Client authentication is optional, anonymous clients reach the handler without one
+    let response = get_certificate(port, &server_ca.0, None).await;
+    assert!(response.starts_with("HTTP/1.1 401"), "{}", response);
+
+    handle.stop(false).await;
+    let _ = fs::remove_dir_all(&directory);
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    models::token_policy::{
+        EffectiveTokenPolicy, TokenPolicyCreatePayload, TokenPolicyResponse,
+        TokenPolicyUpdatePayload,
+    },
+    repositories::token_policy_repository::TokenPolicyRepository,
+    routes::token_policy_route,
+};
+use sqlx::PgPool;
+
+use crate::create_test_app;
+
+fn repositories(pool: PgPool) -> TokenPolicyRepository {
+    TokenPolicyRepository::new(Arc::new(pool))
+}
+
+fn routes() -> fn(&mut actix_web::web::ServiceConfig) {
+    token_policy_route::configure_routes
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_route_create_with_valid_data_succeeds(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let payload = TokenPolicyCreatePayload {
+        project_id: "10000000-0000-0000-0000-000000000002".to_string(),
+        max_ttl_seconds: Some(900),
+        issuer: Some("https://billing.test".to_string()),
+        ..Default::default()
+    };
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token-policies")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::CREATED);
+    let token_policy: TokenPolicyResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(token_policy.max_ttl_seconds, Some(900));
+    assert_eq!(
+        token_policy.issuer,
+        Some("https://billing.test".to_string())
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_route_create_duplicate_returns_conflict(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let payload = TokenPolicyCreatePayload {
+        project_id: "10000000-0000-0000-0000-000000000001".to_string(),
+        environment_id: Some("20000000-0000-0000-0000-000000000002".to_string()),
+        ..Default::default()
+    };
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token-policies")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::CONFLICT);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_route_create_with_invalid_ttl_returns_bad_request(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let payload = TokenPolicyCreatePayload {
+        project_id: "10000000-0000-0000-0000-000000000002".to_string(),
+        max_ttl_seconds: Some(60),
+        default_ttl_seconds: Some(120),
+        ..Default::default()
+    };
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token-policies")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_route_patch_updates_policy(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let payload = TokenPolicyUpdatePayload {
+        allow_refresh_tokens: Some(true),
+        ..Default::default()
+    };
+
+    let response = actix_web::test::TestRequest::patch()
+        .uri("/token-policies/70000000-0000-0000-0000-000000000001")
+        .set_json(&payload)
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let token_policy: TokenPolicyResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(token_policy.allow_refresh_tokens, Some(true));
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_route_delete_nonexistent_returns_not_found(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::delete()
+        .uri(&format!("/token-policies/{}", uuid::Uuid::new_v4()))
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_route_list_filters_by_project(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/token-policies?project_id=10000000-0000-0000-0000-000000000001")
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let token_policies: Vec<TokenPolicyResponse> = actix_web::test::read_body_json(response).await;
+    assert_eq!(token_policies.len(), 2);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_policy_route_effective_merges_environment_override(pool: PgPool) {
+    let app = create_test_app!(repositories(pool), routes());
+
+    let response = actix_web::test::TestRequest::get()
+        .uri("/token-policies/effective/20000000-0000-0000-0000-000000000002")
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let policy: EffectiveTokenPolicy = actix_web::test::read_body_json(response).await;
+    assert_eq!(policy.max_ttl_seconds, 120);
+    assert_eq!(
+        policy.issuer,
+        Some("https://sentinel-guard.test".to_string())
+    );
+    assert!(policy.allow_refresh_tokens);
+}
@@ -0,0 +1,1309 @@
+use std::sync::Arc;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
+use sentinel_guard::{
+    config::{LockoutConfig, RateLimitConfig, RateLimitRule},
+    models::{
+        access_token::AccessTokenFilter,
+        authentication_failure::{AuthenticationFailureFilter, AuthenticationSubjectType},
+        environment_key::EnvironmentKeyCreatePayload,
+        service_account_credential::{
+            ServiceAccountCredentialCreatePayload, ServiceAccountCredentialRotatePayload,
+        },
+        service_account_key::ServiceAccountKeyCreatePayload,
+        token::{
+            CLIENT_ASSERTION_TYPE_JWT_BEARER, GRANT_TYPE_TOKEN_EXCHANGE, TOKEN_TYPE_ACCESS_TOKEN,
+            TokenErrorResponse, TokenResponse,
+        },
+    },
+    repositories::{
+        access_token_repository::AccessTokenRepository,
+        authentication_failure_repository::AuthenticationFailureRepository, base::Repository,
+        environment_key_repository::EnvironmentKeyRepository,
+        service_account_credential_repository::ServiceAccountCredentialRepository,
+        service_account_key_repository::ServiceAccountKeyRepository,
+        service_account_repository::ServiceAccountRepository,
+    },
+    routes::token_route,
+    services::token_service::TokenService,
+    utils::{
+        metrics::METRICS,
+        rate_limit::RateLimiter,
+        tokens::key_builder::{ActorClaim, Claims, KeyBuilder},
+    },
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::create_test_app;
+use crate::integration::secrets_manager;
+
+const CLIENT_ID: &str = "30000000-0000-0000-0000-000000000001";
+const DISABLED_CLIENT_ID: &str = "30000000-0000-0000-0000-000000000002";
+const ACTOR_CLIENT_ID: &str = "30000000-0000-0000-0000-000000000003";
+const CLIENT_SECRET: &str = "worker-secret";
+const DEV_ENVIRONMENT_ID: &str = "20000000-0000-0000-0000-000000000001";
+const PROD_ENVIRONMENT_ID: &str = "20000000-0000-0000-0000-000000000002";
+const TOKEN_ENDPOINT: &str = "http://localhost:8080/token";
+
+fn routes() -> fn(&mut actix_web::web::ServiceConfig) {
+    token_route::configure_routes
+}
+
+/// Stores encrypted client secrets and an environment key, returning the signing key
+async fn setup(pool: &PgPool, environment_id: &str, algorithm: Algorithm) -> Vec<u8> {
+    let secrets_manager = secrets_manager();
+    for client_id in [CLIENT_ID, DISABLED_CLIENT_ID, ACTOR_CLIENT_ID] {
+        let client_id = Uuid::parse_str(client_id).unwrap();
+        sqlx::query("UPDATE service_account SET secret = $1 WHERE id = $2")
+            .bind(secrets_manager.encrypt(CLIENT_SECRET, &client_id).unwrap())
+            .bind(client_id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    let repository = EnvironmentKeyRepository::new(Arc::new(pool.clone()), secrets_manager);
+    repository
+        .create(EnvironmentKeyCreatePayload {
+            environment_id: environment_id.to_string(),
+            algorithm: format!("{:?}", algorithm),
+            active: true,
+            rotation_interval_days: None,
+        })
+        .await
+        .unwrap();
+    let (_, key) = repository
+        .get_active_key(Uuid::parse_str(environment_id).unwrap(), Some(algorithm))
+        .await
+        .unwrap();
+    KeyBuilder::signing_key_from_str(algorithm, &key).unwrap()
+}
+
+fn decode_claims(token: &str, key: &[u8], algorithm: Algorithm) -> Claims {
+    let mut validation = Validation::new(algorithm);
+    validation.set_audience(&["payments-api"]);
+    decode::<Claims>(token, &DecodingKey::from_secret(key), &validation)
+        .unwrap()
+        .claims
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_client_credentials_issues_policy_compliant_token(pool: PgPool) {
+    let signing_key = setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool.clone()), secrets_manager()),
+        routes()
+    );
+    let issued = METRICS.tokens_issued_total.get(&["client_credentials"]);
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    assert!(METRICS.tokens_issued_total.get(&["client_credentials"]) > issued);
+    assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+    let token: TokenResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(token.token_type, "Bearer");
+    assert_eq!(token.expires_in, 300);
+    assert_eq!(
+        token.scope,
+        Some("payments:read payments:write".to_string())
+    );
+    assert!(token.refresh_token.is_none());
+
+    let claims = decode_claims(&token.access_token, &signing_key, Algorithm::HS256);
+    assert_eq!(claims.sub, CLIENT_ID);
+    assert_eq!(claims.iss, Some("https://sentinel-guard.test".to_string()));
+    assert_eq!(claims.aud, Some(vec!["payments-api".to_string()]));
+    assert_eq!(claims.exp - claims.iat, 300);
+
+    let stored = AccessTokenRepository::new(Arc::new(pool))
+        .find(AccessTokenFilter::default(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(Some(stored[0].jti.clone()), claims.jti);
+    assert_eq!(stored[0].token_use, "access");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_accepts_basic_client_authentication(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let credentials = STANDARD.encode(format!("{}:{}", CLIENT_ID, CLIENT_SECRET));
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .insert_header(("Authorization", format!("Basic {}", credentials)))
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+            ("scope", "payments:read"),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let token: TokenResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(token.scope, Some("payments:read".to_string()));
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rejects_wrong_secret(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", "wrong-secret"),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_client");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rate_limits_authenticated_clients(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let rate_limiter = RateLimiter::new(RateLimitConfig {
+        token: RateLimitRule {
+            capacity: 1,
+            refill_per_second: 0.01,
+        },
+        ..Default::default()
+    });
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()).with_rate_limiter(Some(rate_limiter)),
+        routes()
+    );
+
+    // Failed authentications do not drain the bucket of the client they claim
+    for _ in 0..2 {
+        let response = actix_web::test::TestRequest::post()
+            .uri("/token")
+            .set_form([
+                ("grant_type", "client_credentials"),
+                ("client_id", CLIENT_ID),
+                ("client_secret", "wrong-secret"),
+                ("environment_id", DEV_ENVIRONMENT_ID),
+            ])
+            .send_request(&app)
+            .await;
+        assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    }
+
+    let response = client_credentials_request(CLIENT_ID)
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+
+    let response = client_credentials_request(CLIENT_ID)
+        .send_request(&app)
+        .await;
+    assert_eq!(
+        response.status(),
+        actix_web::http::StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(response.headers().get("Retry-After").unwrap(), "100");
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "rate_limited");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rejects_disabled_service_account(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", DISABLED_CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rejects_ttl_above_policy_maximum(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+            ("expires_in", "601"),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_request");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rejects_algorithm_outside_policy(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+            ("algorithm", "HS512"),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(
+        error.error_description,
+        Some("Algorithm HS512 is not allowed by the token policy".to_string())
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rejects_scope_not_granted(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+            ("scope", "payments:read payments:admin"),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_scope");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rejects_environment_without_access(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", "20000000-0000-0000-0000-000000000003"),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "unauthorized_client");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rejects_unsupported_grant_type(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "password"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "unsupported_grant_type");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_refresh_token_is_rotated(pool: PgPool) {
+    let signing_key = setup(&pool, PROD_ENVIRONMENT_ID, Algorithm::HS512).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", PROD_ENVIRONMENT_ID),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let token: TokenResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(token.expires_in, 120);
+    let claims = decode_claims(&token.access_token, &signing_key, Algorithm::HS512);
+    assert_eq!(claims.scopes, Some(vec!["payments:read".to_string()]));
+    let refresh_token = token.refresh_token.unwrap();
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "refresh_token"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("refresh_token", refresh_token.as_str()),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let refreshed: TokenResponse = actix_web::test::read_body_json(response).await;
+    assert_ne!(refreshed.refresh_token, Some(refresh_token.clone()));
+
+    // A refresh token can only be used once
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "refresh_token"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("refresh_token", refresh_token.as_str()),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_grant");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rejected_refresh_keeps_refresh_token(pool: PgPool) {
+    setup(&pool, PROD_ENVIRONMENT_ID, Algorithm::HS512).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool.clone()), secrets_manager()),
+        routes()
+    );
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", PROD_ENVIRONMENT_ID),
+        ])
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+    let token: TokenResponse = actix_web::test::read_body_json(response).await;
+    let refresh_token = token.refresh_token.unwrap();
+
+    // A scope outside the grant is refused before the refresh token is used up
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "refresh_token"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("refresh_token", refresh_token.as_str()),
+            ("scope", "payments:admin"),
+        ])
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_scope");
+
+    // So is a refresh once the token policy stopped allowing them
+    sqlx::query("UPDATE token_policies SET allow_refresh_tokens = false WHERE id = '70000000-0000-0000-0000-000000000002'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "refresh_token"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("refresh_token", refresh_token.as_str()),
+        ])
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_grant");
+
+    sqlx::query("UPDATE token_policies SET allow_refresh_tokens = true WHERE id = '70000000-0000-0000-0000-000000000002'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "refresh_token"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("refresh_token", refresh_token.as_str()),
+        ])
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/claim_templates.sql"))]
+async fn test_token_route_renders_claim_templates_into_meta(pool: PgPool) {
+    let signing_key = setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let token: TokenResponse = actix_web::test::read_body_json(response).await;
+    let claims = decode_claims(&token.access_token, &signing_key, Algorithm::HS256);
+    assert_eq!(claims.sub, CLIENT_ID);
+
+    let meta = claims.meta.unwrap();
+    assert_eq!(meta.len(), 3);
+    assert_eq!(meta.get("tenant"), Some(&"payments-dev".to_string()));
+    assert_eq!(meta.get("region"), Some(&"eu-west".to_string()));
+    assert_eq!(meta.get("owner"), Some(&"worker@example.com".to_string()));
+}
+
+/// Builds a client credentials request for the dev environment
+fn client_credentials_request(client_id: &str) -> actix_web::test::TestRequest {
+    actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+        ])
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/token_exchange.sql"))]
+async fn test_token_route_token_exchange_issues_delegated_token(pool: PgPool) {
+    let signing_key = setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+    let response = client_credentials_request(CLIENT_ID)
+        .send_request(&app)
+        .await;
+    let subject: TokenResponse = actix_web::test::read_body_json(response).await;
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", GRANT_TYPE_TOKEN_EXCHANGE),
+            ("client_id", ACTOR_CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+            ("subject_token", subject.access_token.as_str()),
+            ("subject_token_type", TOKEN_TYPE_ACCESS_TOKEN),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let token: TokenResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(
+        token.issued_token_type,
+        Some(TOKEN_TYPE_ACCESS_TOKEN.to_string())
+    );
+    assert_eq!(token.scope, Some("payments:read".to_string()));
+    assert!(token.refresh_token.is_none());
+
+    let subject_claims = decode_claims(&subject.access_token, &signing_key, Algorithm::HS256);
+    let claims = decode_claims(&token.access_token, &signing_key, Algorithm::HS256);
+    assert_eq!(claims.sub, CLIENT_ID);
+    assert_eq!(claims.act, Some(ActorClaim::new(ACTOR_CLIENT_ID, None)));
+    assert!(claims.exp <= subject_claims.exp);
+    assert_ne!(claims.jti, subject_claims.jti);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/token_exchange.sql"))]
+async fn test_token_route_token_exchange_nests_actor_chain(pool: PgPool) {
+    let signing_key = setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+    let response = client_credentials_request(CLIENT_ID)
+        .send_request(&app)
+        .await;
+    let mut token: TokenResponse = actix_web::test::read_body_json(response).await;
+
+    for actor in [ACTOR_CLIENT_ID, CLIENT_ID] {
+        let response = actix_web::test::TestRequest::post()
+            .uri("/token")
+            .set_form([
+                ("grant_type", GRANT_TYPE_TOKEN_EXCHANGE),
+                ("client_id", actor),
+                ("client_secret", CLIENT_SECRET),
+                ("environment_id", DEV_ENVIRONMENT_ID),
+                ("subject_token", token.access_token.as_str()),
+                ("subject_token_type", TOKEN_TYPE_ACCESS_TOKEN),
+            ])
+            .send_request(&app)
+            .await;
+        assert!(response.status().is_success());
+        token = actix_web::test::read_body_json(response).await;
+    }
+
+    let claims = decode_claims(&token.access_token, &signing_key, Algorithm::HS256);
+    assert_eq!(claims.sub, CLIENT_ID);
+    assert_eq!(
+        claims.act,
+        Some(ActorClaim::new(
+            CLIENT_ID,
+            Some(ActorClaim::new(ACTOR_CLIENT_ID, None))
+        ))
+    );
+    assert_eq!(claims.scopes, Some(vec!["payments:read".to_string()]));
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/token_exchange.sql"))]
+async fn test_token_route_token_exchange_rejects_scope_outside_intersection(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+    let response = client_credentials_request(CLIENT_ID)
+        .send_request(&app)
+        .await;
+    let subject: TokenResponse = actix_web::test::read_body_json(response).await;
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", GRANT_TYPE_TOKEN_EXCHANGE),
+            ("client_id", ACTOR_CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+            ("subject_token", subject.access_token.as_str()),
+            ("subject_token_type", TOKEN_TYPE_ACCESS_TOKEN),
+            ("scope", "payments:write"),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_scope");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/token_exchange.sql"))]
+async fn test_token_route_token_exchange_rejects_revoked_subject_token(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool.clone()), secrets_manager()),
+        routes()
+    );
+    let response = client_credentials_request(CLIENT_ID)
+        .send_request(&app)
+        .await;
+    let subject: TokenResponse = actix_web::test::read_body_json(response).await;
+
+    sqlx::query("UPDATE access_tokens SET active = false")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", GRANT_TYPE_TOKEN_EXCHANGE),
+            ("client_id", ACTOR_CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+            ("subject_token", subject.access_token.as_str()),
+            ("subject_token_type", TOKEN_TYPE_ACCESS_TOKEN),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_grant");
+    assert_eq!(
+        error.error_description,
+        Some("Subject token is revoked or unknown".to_string())
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/token_exchange.sql"))]
+async fn test_token_route_token_exchange_rejects_foreign_signature(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let forged_key = KeyBuilder::new().generate_key(Algorithm::HS256).unwrap();
+    let forged = KeyBuilder::new()
+        .create_jwt(
+            &Claims::new(CLIENT_ID, 300)
+                .with_jti(Uuid::new_v4().to_string())
+                .with_scopes(vec!["payments:read".to_string()]),
+            &forged_key.private_key,
+            Algorithm::HS256,
+        )
+        .unwrap();
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", GRANT_TYPE_TOKEN_EXCHANGE),
+            ("client_id", ACTOR_CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+            ("subject_token", forged.as_str()),
+            ("subject_token_type", TOKEN_TYPE_ACCESS_TOKEN),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(
+        error.error_description,
+        Some("Subject token is invalid".to_string())
+    );
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql", "../fixtures/token_exchange.sql"))]
+async fn test_token_route_token_exchange_requires_subject_token_type(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+    let response = client_credentials_request(CLIENT_ID)
+        .send_request(&app)
+        .await;
+    let subject: TokenResponse = actix_web::test::read_body_json(response).await;
+
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", GRANT_TYPE_TOKEN_EXCHANGE),
+            ("client_id", ACTOR_CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+            ("subject_token", subject.access_token.as_str()),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_request");
+}
+
+/// Registers an RS256 key for the client, returning the PEM private key signing its assertions
+async fn register_client_key(pool: &PgPool) -> Vec<u8> {
+    let key_pair = KeyBuilder::new().generate_key(Algorithm::RS256).unwrap();
+    ServiceAccountKeyRepository::new(Arc::new(pool.clone()))
+        .create(ServiceAccountKeyCreatePayload {
+            service_account_id: CLIENT_ID.to_string(),
+            name: "deployer".to_string(),
+            algorithm: "RS256".to_string(),
+            public_key: key_pair.public_key_str.unwrap(),
+            enabled: None,
+        })
+        .await
+        .unwrap();
+    key_pair.private_key
+}
+
+fn client_assertion(private_key: &[u8], audience: &str, jti: &str) -> String {
+    let claims = serde_json::json!({
+        "iss": CLIENT_ID,
+        "sub": CLIENT_ID,
+        "aud": audience,
+        "jti": jti,
+        "exp": Utc::now().timestamp() + 120,
+    });
+    KeyBuilder::new()
+        .create_jwt(&claims, private_key, Algorithm::RS256)
+        .unwrap()
+}
+
+/// Token service accepting client assertions addressed to `TOKEN_ENDPOINT`
+fn assertion_service(pool: PgPool) -> TokenService {
+    TokenService::new(Arc::new(pool), secrets_manager())
+        .with_token_endpoint_url(Some(TOKEN_ENDPOINT.to_string()))
+}
+
+fn client_assertion_request(assertion: &str) -> actix_web::test::TestRequest {
+    actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_assertion_type", CLIENT_ASSERTION_TYPE_JWT_BEARER),
+            ("client_assertion", assertion),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+        ])
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_private_key_jwt_authenticates_client(pool: PgPool) {
+    let signing_key = setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let private_key = register_client_key(&pool).await;
+    let app = create_test_app!(assertion_service(pool), routes());
+
+    let assertion = client_assertion(&private_key, TOKEN_ENDPOINT, "assertion-1");
+    let response = client_assertion_request(&assertion)
+        .send_request(&app)
+        .await;
+
+    assert!(response.status().is_success());
+    let token: TokenResponse = actix_web::test::read_body_json(response).await;
+    let claims = decode_claims(&token.access_token, &signing_key, Algorithm::HS256);
+    assert_eq!(claims.sub, CLIENT_ID);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_private_key_jwt_rejects_replayed_assertion(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let private_key = register_client_key(&pool).await;
+    let app = create_test_app!(assertion_service(pool), routes());
+
+    let assertion = client_assertion(&private_key, TOKEN_ENDPOINT, "assertion-1");
+    let response = client_assertion_request(&assertion)
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+
+    let response = client_assertion_request(&assertion)
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_client");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_private_key_jwt_rejects_wrong_audience(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let private_key = register_client_key(&pool).await;
+    let app = create_test_app!(assertion_service(pool), routes());
+
+    let assertion = client_assertion(&private_key, "https://other.test/token", "assertion-1");
+    let response = client_assertion_request(&assertion)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_private_key_jwt_rejects_unregistered_key(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    register_client_key(&pool).await;
+    let app = create_test_app!(assertion_service(pool), routes());
+
+    let other_key = KeyBuilder::new().generate_key(Algorithm::RS256).unwrap();
+    let assertion = client_assertion(&other_key.private_key, TOKEN_ENDPOINT, "assertion-1");
+    let response = client_assertion_request(&assertion)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_private_key_jwt_rejects_combined_client_secret(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let private_key = register_client_key(&pool).await;
+    let app = create_test_app!(assertion_service(pool), routes());
+
+    let assertion = client_assertion(&private_key, TOKEN_ENDPOINT, "assertion-1");
+    let response = actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_secret", CLIENT_SECRET),
+            ("client_assertion_type", CLIENT_ASSERTION_TYPE_JWT_BEARER),
+            ("client_assertion", assertion.as_str()),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+        ])
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_request");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_private_key_jwt_ignores_request_host(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let private_key = register_client_key(&pool).await;
+    let app = create_test_app!(assertion_service(pool), routes());
+
+    // An assertion minted for another server, replayed with a matching Host header
+    let assertion = client_assertion(&private_key, "https://other.test/token", "assertion-1");
+    let response = client_assertion_request(&assertion)
+        .insert_header(("Host", "other.test"))
+        .insert_header(("X-Forwarded-Proto", "https"))
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_private_key_jwt_requires_token_endpoint_url(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let private_key = register_client_key(&pool).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let assertion = client_assertion(&private_key, TOKEN_ENDPOINT, "assertion-1");
+    let response = client_assertion_request(&assertion)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_request");
+}
+
+fn client_secret_request(client_secret: &str) -> actix_web::test::TestRequest {
+    actix_web::test::TestRequest::post()
+        .uri("/token")
+        .set_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", CLIENT_ID),
+            ("client_secret", client_secret),
+            ("environment_id", DEV_ENVIRONMENT_ID),
+        ])
+}
+
+/// Creates a credential for the client, returning its ID and secret
+async fn create_client_credential(pool: &PgPool) -> (Uuid, String) {
+    let payload = ServiceAccountCredentialCreatePayload {
+        label: "ci".to_string(),
+        expires_at: None,
+    }
+    .with_secret(Uuid::parse_str(CLIENT_ID).unwrap());
+    let secret = payload.secret.clone();
+    let credential = ServiceAccountCredentialRepository::new(Arc::new(pool.clone()))
+        .create(payload)
+        .await
+        .unwrap();
+    (credential.id.unwrap(), secret)
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_accepts_service_account_credential(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let (id, secret) = create_client_credential(&pool).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool.clone()), secrets_manager()),
+        routes()
+    );
+
+    let response = client_secret_request(&secret).send_request(&app).await;
+    assert!(response.status().is_success());
+
+    // The secret of the service account itself keeps working next to its credentials
+    let response = client_secret_request(CLIENT_SECRET)
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+
+    let credential = ServiceAccountCredentialRepository::new(Arc::new(pool))
+        .read(id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(credential.last_used_at.is_some());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_accepts_rotated_credential_until_grace_period_ends(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let (id, old_secret) = create_client_credential(&pool).await;
+    let new_secret = "rotated-secret";
+    ServiceAccountCredentialRepository::new(Arc::new(pool.clone()))
+        .rotate(
+            id,
+            new_secret,
+            ServiceAccountCredentialRotatePayload {
+                grace_period_seconds: Some(3600),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool.clone()), secrets_manager()),
+        routes()
+    );
+
+    for secret in [old_secret.as_str(), new_secret] {
+        let response = client_secret_request(secret).send_request(&app).await;
+        assert!(response.status().is_success());
+    }
+
+    sqlx::query("UPDATE service_account_credentials SET expires_at = now() - interval '1 second' WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let response = client_secret_request(&old_secret).send_request(&app).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    let response = client_secret_request(new_secret).send_request(&app).await;
+    assert!(response.status().is_success());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rejects_credential_of_disabled_service_account(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let (_, secret) = create_client_credential(&pool).await;
+    sqlx::query("UPDATE service_account SET enabled = false WHERE id = $1")
+        .bind(Uuid::parse_str(CLIENT_ID).unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = client_secret_request(&secret).send_request(&app).await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+fn lockout() -> LockoutConfig {
+    LockoutConfig {
+        max_failures: 2,
+        backoff_after_failures: 1,
+        ..Default::default()
+    }
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_locks_out_service_account_after_repeated_failures(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool.clone()), secrets_manager()).with_lockout(lockout()),
+        routes()
+    );
+
+    for _ in 0..2 {
+        let response = client_secret_request("wrong-secret")
+            .send_request(&app)
+            .await;
+        assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    }
+
+    // Locked out clients are rejected even with the right secret
+    let response = client_secret_request(CLIENT_SECRET)
+        .send_request(&app)
+        .await;
+    assert_eq!(
+        response.status(),
+        actix_web::http::StatusCode::TOO_MANY_REQUESTS
+    );
+    let retry_after: i64 = response
+        .headers()
+        .get("retry-after")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after > 0 && retry_after <= 901);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "invalid_client");
+
+    AuthenticationFailureRepository::new(Arc::new(pool))
+        .unlock(AuthenticationSubjectType::ServiceAccount, CLIENT_ID)
+        .await
+        .unwrap();
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_locks_out_source_ip_across_client_ids(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()).with_lockout(lockout()),
+        routes()
+    );
+    let peer_addr = "203.0.113.7:40000".parse().unwrap();
+
+    for _ in 0..2 {
+        let response = client_credentials_request(&Uuid::new_v4().to_string())
+            .peer_addr(peer_addr)
+            .send_request(&app)
+            .await;
+        assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    }
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .peer_addr(peer_addr)
+        .send_request(&app)
+        .await;
+    assert_eq!(
+        response.status(),
+        actix_web::http::StatusCode::TOO_MANY_REQUESTS
+    );
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .peer_addr("198.51.100.1:40000".parse().unwrap())
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_successful_authentication_clears_failures(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool.clone()), secrets_manager()).with_lockout(lockout()),
+        routes()
+    );
+
+    let response = client_secret_request("wrong-secret")
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    let response = client_secret_request(CLIENT_SECRET)
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+
+    let failures = AuthenticationFailureRepository::new(Arc::new(pool))
+        .find(AuthenticationFailureFilter::default(), None)
+        .await
+        .unwrap();
+    assert!(failures.is_empty());
+}
+
+/// Restricts the client and, when given, its dev environment grant to address ranges
+async fn allow_cidrs(pool: &PgPool, service_account: &[&str], project_access: &[&str]) {
+    sqlx::query("UPDATE service_account SET allowed_cidrs = $1 WHERE id = $2")
+        .bind(service_account)
+        .bind(Uuid::parse_str(CLIENT_ID).unwrap())
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE project_access SET allowed_cidrs = $1 WHERE id = $2")
+        .bind(project_access)
+        .bind(Uuid::parse_str("40000000-0000-0000-0000-000000000001").unwrap())
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_enforces_service_account_allowed_cidrs(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    allow_cidrs(&pool, &["10.0.0.0/8"], &[]).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .peer_addr("10.20.30.40:40000".parse().unwrap())
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .peer_addr("192.0.2.1:40000".parse().unwrap())
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "unauthorized_client");
+
+    // Without a known address an allowlist can not be satisfied
+    let response = client_secret_request(CLIENT_SECRET)
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_enforces_project_access_allowed_cidrs(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    allow_cidrs(&pool, &["10.0.0.0/8"], &["10.1.0.0/16"]).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .peer_addr("10.1.2.3:40000".parse().unwrap())
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .peer_addr("10.2.2.3:40000".parse().unwrap())
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let error: TokenErrorResponse = actix_web::test::read_body_json(response).await;
+    assert_eq!(error.error, "unauthorized_client");
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_reads_forwarded_for_from_trusted_proxies_only(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    allow_cidrs(&pool, &["203.0.113.0/24"], &[]).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager())
+            .with_trusted_proxies(vec!["10.0.0.0/8".parse().unwrap()]),
+        routes()
+    );
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .peer_addr("10.0.0.2:40000".parse().unwrap())
+        .insert_header(("X-Forwarded-For", "203.0.113.7"))
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .peer_addr("192.0.2.1:40000".parse().unwrap())
+        .insert_header(("X-Forwarded-For", "203.0.113.7"))
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_rejects_expired_service_account(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    sqlx::query(
+        "UPDATE service_account SET expires_at = now() - interval '1 second' WHERE id = $1",
+    )
+    .bind(Uuid::parse_str(CLIENT_ID).unwrap())
+    .execute(&pool)
+    .await
+    .unwrap();
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool), secrets_manager()),
+        routes()
+    );
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(fixtures("../fixtures/token_policies.sql"))]
+async fn test_token_route_records_last_token_issuance(pool: PgPool) {
+    setup(&pool, DEV_ENVIRONMENT_ID, Algorithm::HS256).await;
+    let app = create_test_app!(
+        TokenService::new(Arc::new(pool.clone()), secrets_manager()),
+        routes()
+    );
+
+    let response = client_secret_request(CLIENT_SECRET)
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+
+    let service_account = ServiceAccountRepository::new(Arc::new(pool), secrets_manager())
+        .read(Uuid::parse_str(CLIENT_ID).unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(service_account.last_token_issued_at.is_some());
+}
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use sentinel_guard::{
+    MIGRATOR,
+    services::migration_service::{MigrationService, MigrationState},
+};
+use sqlx::PgPool;
+
+fn embedded_migrations() -> usize {
+    MIGRATOR
+        .iter()
+        .filter(|migration| migration.migration_type.is_up_migration())
+        .count()
+}
+
+#[sqlx::test(migrations = false)]
+async fn test_migration_service_migrate_applies_pending_migrations(pool: PgPool) {
+    let service = MigrationService::new(Arc::new(pool));
+
+    let statuses = service.status().await.unwrap();
+    assert_eq!(statuses.len(), embedded_migrations());
+    assert!(
+        statuses
+            .iter()
+            .all(|status| status.state == MigrationState::Pending)
+    );
+
+    let applied = service.migrate().await.unwrap();
+    assert_eq!(applied.len(), embedded_migrations());
+    assert!(applied.iter().all(|status| status.installed_on.is_some()));
+
+    let statuses = service.status().await.unwrap();
+    assert!(
+        statuses
+            .iter()
+            .all(|status| { status.state == MigrationState::Applied && !status.checksum_mismatch })
+    );
+    service.ensure_compatible().await.unwrap();
+    service.ensure_migrated().await.unwrap();
+
+    // Nothing is left to apply
+    assert!(service.migrate().await.unwrap().is_empty());
+}
+
+#[sqlx::test(migrations = false)]
+async fn test_migration_service_migrate_runs_once_across_replicas(pool: PgPool) {
+    let pool = Arc::new(pool);
+    let first = MigrationService::new(pool.clone());
+    let second = MigrationService::new(pool.clone());
+    let third = MigrationService::new(pool);
+
+    // Replicas waiting on the lock find nothing left to apply
+    let (first, second, third) = tokio::join!(first.migrate(), second.migrate(), third.migrate());
+    let applied = first.unwrap().len() + second.unwrap().len() + third.unwrap().len();
+    assert_eq!(applied, embedded_migrations());
+}
+
+#[sqlx::test]
+async fn test_migration_service_refuses_schema_ahead_of_binary(pool: PgPool) {
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (29991231000000, 'From the future', true, '\\x00', 0)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let service = MigrationService::new(Arc::new(pool));
+
+    let statuses = service.status().await.unwrap();
+    let future = statuses.last().unwrap();
+    assert_eq!(future.version, 29991231000000);
+    assert_eq!(future.state, MigrationState::Unknown);
+
+    let error = service.ensure_compatible().await.unwrap_err();
+    assert!(error.to_string().contains("ahead"), "{}", error);
+    assert!(error.to_string().contains("29991231000000"), "{}", error);
+    assert!(service.migrate().await.is_err());
+}
+
+#[sqlx::test]
+async fn test_migration_service_ensure_migrated_refuses_pending_migrations(pool: PgPool) {
+    let version = MIGRATOR.iter().last().unwrap().version;
+    sqlx::query("DELETE FROM _sqlx_migrations WHERE version = $1")
+        .bind(version)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let service = MigrationService::new(Arc::new(pool));
+
+    // Pending migrations alone do not make the schema incompatible
+    service.ensure_compatible().await.unwrap();
+    let error = service.ensure_migrated().await.unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Migrations {} are pending, start with --migrate or set database.migrate_on_startup to apply them",
+            version
+        )
+    );
+}
+
+#[sqlx::test]
+async fn test_migration_service_refuses_failed_migration(pool: PgPool) {
+    let version = MIGRATOR.iter().last().unwrap().version;
+    sqlx::query("UPDATE _sqlx_migrations SET success = false WHERE version = $1")
+        .bind(version)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let service = MigrationService::new(Arc::new(pool));
+
+    let error = service.ensure_compatible().await.unwrap_err();
+    assert!(
+        error.to_string().contains(&version.to_string()),
+        "{}",
+        error
+    );
+}